@@ -0,0 +1,111 @@
+//! Benchmarks for the ID generation hot paths.
+//!
+//! Run with `cargo bench`.
+
+use atomic_id::{AtomicId, Encoding, IdGenerator, x64, x256};
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use std::sync::Barrier;
+use std::time::{Duration, Instant};
+
+/// Number of worker threads per width in the mixed-width benchmark.
+const THREADS_PER_WIDTH: usize = 2;
+
+/// Single-threaded generation cost for each timestamp-based width.
+fn single_thread(c: &mut Criterion) {
+    let generator = IdGenerator::new(1, 0);
+    c.bench_function("gen64", |b| b.iter(|| black_box(generator.gen64())));
+    c.bench_function("gen128", |b| b.iter(|| black_box(generator.gen128())));
+    c.bench_function("gen256", |b| b.iter(|| black_box(generator.gen256())));
+}
+
+/// Per-ID latency of a leased block against `gen64`, which does a `fetch_add` on the
+/// shared sequence for every ID. The lease cost includes taking a new block every
+/// 1,024 IDs.
+fn lease(c: &mut Criterion) {
+    let generator = IdGenerator::new(1, 0);
+    c.bench_function("gen64 per id", |b| b.iter(|| black_box(generator.gen64())));
+    let mut lease = AtomicId::<x64>::lease(1024);
+    c.bench_function("lease per id", |b| b.iter(|| black_box(lease.next())));
+}
+
+/// Threads generating 64-bit and 128-bit IDs side by side.
+///
+/// The two widths use independent sequence counters. Without cache-line padding the
+/// counters can share a line, and every `fetch_add` on one invalidates the other;
+/// this benchmark measures that cross-width contention.
+fn mixed_widths(c: &mut Criterion) {
+    c.bench_function("mixed gen64+gen128", |b| {
+        b.iter_custom(|iters| {
+            let barrier = Barrier::new(THREADS_PER_WIDTH * 2);
+            let generator = IdGenerator::new(1, 0);
+            std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..THREADS_PER_WIDTH * 2)
+                    .map(|i| {
+                        let (barrier, generator) = (&barrier, &generator);
+                        scope.spawn(move || {
+                            barrier.wait();
+                            let start = Instant::now();
+                            for _ in 0..iters {
+                                if i % 2 == 0 {
+                                    black_box(generator.gen64());
+                                } else {
+                                    black_box(generator.gen128());
+                                }
+                            }
+                            start.elapsed()
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .map(|w| w.join().unwrap())
+                    .max()
+                    .unwrap_or(Duration::ZERO)
+            })
+        })
+    });
+}
+
+/// `gen128` against the path it replaced, which read the system clock twice per ID:
+/// once for the millisecond timestamp and again for the nanoseconds.
+fn gen128_clock_reads(c: &mut Criterion) {
+    let generator = IdGenerator::new(1, 0);
+    c.bench_function("gen128 two clock reads", |b| b.iter(|| black_box(generator.__gen128_two_reads())));
+    c.bench_function("gen128 one clock read", |b| b.iter(|| black_box(generator.gen128())));
+}
+
+/// Hex encoding against the `format!` path it replaced, which parsed the format
+/// spec and went through `fmt::Write` on every call.
+fn hex(c: &mut Criterion) {
+    let id = IdGenerator::new(1, 0).gen128();
+    c.bench_function("hex128 format!", |b| b.iter(|| format!("{:032x}", black_box(id))));
+    c.bench_function("hex128", |b| b.iter(|| Encoding::Hex.encode(black_box(id), 32)));
+    // Both include generating the ID, which `AtomicId::<x256>::hex` cannot skip.
+    let generator = IdGenerator::new(1, 0);
+    c.bench_function("hex256 format!", |b| {
+        b.iter(|| {
+            let p = generator.gen256();
+            black_box(format!("{:016x}{:016x}{:016x}{:016x}", p[0], p[1], p[2], p[3]))
+        })
+    });
+    c.bench_function("hex256", |b| b.iter(|| black_box(AtomicId::<x256>::hex())));
+}
+
+/// Base36 encoding of a bulk export, one value at a time against `encode_batch`,
+/// which takes the vector path when built with `--features simd` on nightly.
+fn base36_batch(c: &mut Criterion) {
+    let generator = IdGenerator::new(1, 0);
+    let raw: Vec<u64> = (0..10_000).map(|_| generator.gen64()).collect();
+    c.bench_function("base36 10k one by one", |b| {
+        b.iter(|| {
+            let ids: Vec<_> = raw.iter().map(|&n| Encoding::Base36.encode(n as u128, 13)).collect();
+            black_box(ids)
+        })
+    });
+    c.bench_function("base36 10k encode_batch", |b| {
+        b.iter(|| black_box(Encoding::Base36.encode_batch(black_box(&raw), 13)))
+    });
+}
+
+criterion_group!(benches, single_thread, lease, mixed_widths, gen128_clock_reads, hex, base36_batch);
+criterion_main!(benches);
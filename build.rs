@@ -0,0 +1,55 @@
+//! Build script for `atomic-id`.
+//!
+//! With the `const_epoch` feature, bakes the `ATOMIC_ID_EPOCH` environment variable
+//! into the crate as a compile-time constant. With the `simd` feature on a nightly
+//! compiler, sets the `atomic_id_simd` cfg that enables the vector encoder.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Default custom epoch: 2022-05-01 00:00:00 UTC, matching `DEFAULT_EPOCH` in the crate.
+const DEFAULT_EPOCH: u64 = 1651363200000;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=ATOMIC_ID_EPOCH");
+
+    // `std::simd` is unstable, so on a stable compiler the `simd` feature is ignored
+    // instead of failing the build, e.g. under `--all-features`.
+    println!("cargo:rustc-check-cfg=cfg(atomic_id_simd)");
+    if env::var_os("CARGO_FEATURE_SIMD").is_some() && is_nightly() {
+        println!("cargo:rustc-cfg=atomic_id_simd");
+    }
+
+    if env::var_os("CARGO_FEATURE_CONST_EPOCH").is_none() {
+        return;
+    }
+
+    let epoch = match env::var("ATOMIC_ID_EPOCH") {
+        Ok(value) => value.trim().parse::<u64>().unwrap_or_else(|_| {
+            panic!(
+                "ATOMIC_ID_EPOCH must be milliseconds since the UNIX epoch, got {:?}",
+                value
+            )
+        }),
+        Err(_) => DEFAULT_EPOCH,
+    };
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("epoch.rs");
+    fs::write(out, format!("const BUILD_EPOCH: u64 = {};\n", epoch)).unwrap();
+}
+
+/// Whether the compiler building the crate accepts `#![feature]`.
+fn is_nightly() -> bool {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    Command::new(rustc)
+        .arg("--version")
+        .output()
+        .map(|out| {
+            let version = String::from_utf8_lossy(&out.stdout);
+            version.contains("nightly") || version.contains("-dev")
+        })
+        .unwrap_or(false)
+}
@@ -0,0 +1,14 @@
+//! Decoding arbitrary text must never panic, and whatever it accepts must re-encode to
+//! the same text, so each alphabet's reverse lookup agrees with its forward table.
+
+#![no_main]
+
+use atomic_id::Encoding;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Encoding, &str)| {
+    let (encoding, text) = input;
+    if let Ok(value) = encoding.decode(text) {
+        assert_eq!(encoding.encode(value, text.chars().count()), text, "{:?}", encoding);
+    }
+});
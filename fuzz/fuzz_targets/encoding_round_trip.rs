@@ -0,0 +1,13 @@
+//! Every value must decode back from its own encoding, at any padding width.
+
+#![no_main]
+
+use atomic_id::Encoding;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Encoding, u128, u8)| {
+    let (encoding, value, width) = input;
+    let encoded = encoding.encode(value, width as usize);
+    assert!(encoded.len() >= width as usize);
+    assert_eq!(encoding.decode(&encoded), Ok(value), "{:?} {:?}", encoding, encoded);
+});
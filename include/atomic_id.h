@@ -0,0 +1,85 @@
+#ifndef ATOMIC_ID_H
+#define ATOMIC_ID_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit. */
+
+#include <stdint.h>
+#include <stddef.h>
+
+// The call succeeded.
+#define ATOMIC_ID_OK 0
+
+// A required pointer argument was null.
+#define ATOMIC_ID_ERR_NULL -1
+
+// The output buffer is too small for the result and its NUL terminator.
+#define ATOMIC_ID_ERR_BUFFER_TOO_SMALL -2
+
+// The call panicked internally. No output was written.
+#define ATOMIC_ID_ERR_PANIC -3
+
+// The operation is unavailable in this build (e.g. the epoch is fixed by `const_epoch`).
+#define ATOMIC_ID_ERR_UNSUPPORTED -4
+
+// The epoch can no longer change because IDs have already been generated.
+#define ATOMIC_ID_ERR_EPOCH_FROZEN -5
+
+// The ID width is not one of 24, 32, 64, 128, or 256 bits.
+#define ATOMIC_ID_ERR_WIDTH -6
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Generate a raw 64-bit ID from the global generator.
+//
+// Returns 0 if generation panicked; 0 is never a valid ID since the thread ID field
+// is never zero.
+uint64_t atomic_id_new64(void);
+
+// Write a raw 128-bit ID to `out` as 16 big-endian bytes, the same bytes as
+// [`AtomicId::<x128>::uuid_bytes`](crate::AtomicId::uuid_bytes).
+//
+// Returns [`ATOMIC_ID_OK`], [`ATOMIC_ID_ERR_NULL`], or [`ATOMIC_ID_ERR_PANIC`].
+//
+// # Safety
+// `out` must be null or valid for writes of 16 bytes.
+int32_t atomic_id_new128(uint8_t *out);
+
+// Write a new base36 ID of `bits` bits, like
+// [`AtomicId::generate`](crate::AtomicId::generate), NUL-terminated,
+// into `out`, for C code that reads the width from configuration.
+//
+// Returns the string length (excluding the NUL) on success, or
+// [`ATOMIC_ID_ERR_NULL`], [`ATOMIC_ID_ERR_WIDTH`], [`ATOMIC_ID_ERR_UNSUPPORTED`] (24
+// or 32 bits without the `short` feature), [`ATOMIC_ID_ERR_BUFFER_TOO_SMALL`], or
+// [`ATOMIC_ID_ERR_PANIC`]. Nothing is written on error.
+//
+// # Safety
+// `out` must be null or valid for writes of `cap` bytes.
+int32_t atomic_id_generate(size_t bits, char *out, size_t cap);
+
+// Encode a 64-bit ID as the 13-character base36 string used by
+// [`AtomicId::<x64>::new`](crate::AtomicId::new), NUL-terminated, into `out`.
+//
+// Returns the string length (excluding the NUL) on success, or
+// [`ATOMIC_ID_ERR_NULL`], [`ATOMIC_ID_ERR_BUFFER_TOO_SMALL`] (`cap` below 14), or
+// [`ATOMIC_ID_ERR_PANIC`]. Nothing is written on error.
+//
+// # Safety
+// `out` must be null or valid for writes of `cap` bytes.
+int32_t atomic_id_encode_base36(uint64_t value, char *out, size_t cap);
+
+// Set the global epoch, in milliseconds since the UNIX epoch, like
+// [`AtomicOption::try_epoch`]. Call it before generating any ID.
+//
+// Returns [`ATOMIC_ID_OK`], [`ATOMIC_ID_ERR_EPOCH_FROZEN`] once IDs have been
+// generated, or [`ATOMIC_ID_ERR_UNSUPPORTED`] when the crate was built with the
+// `const_epoch` feature.
+int32_t atomic_id_set_epoch(uint64_t ms);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ATOMIC_ID_H */
@@ -0,0 +1,39 @@
+//! A `#![no_std]` library generating IDs with atomic-id's `std` feature off, as
+//! shown in the crate docs. CI builds it for a bare-metal target, where nothing
+//! from `std` can sneak in through a dependency.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use atomic_id::{AtomicId, AtomicOption, TimeSource, x64};
+
+/// Milliseconds since the UNIX epoch from the board's real-time clock.
+fn rtc_ms() -> u64 {
+    1704067200000
+}
+
+/// Nanoseconds since the UNIX epoch from the board's real-time clock.
+fn rtc_ns() -> u64 {
+    rtc_ms() * 1_000_000
+}
+
+/// The core this runs on, to tell cores apart in the thread ID field.
+fn core_id() -> u64 {
+    0
+}
+
+/// Install the clock and thread entropy. Call once at startup, before [`next_id`].
+pub fn init() {
+    AtomicOption::time_source(TimeSource {
+        now_ms: rtc_ms,
+        now_ns: rtc_ns,
+    });
+    AtomicOption::thread_entropy(core_id);
+}
+
+/// Generate a new base36-encoded 64-bit ID.
+pub fn next_id() -> String {
+    AtomicId::<x64>::new()
+}
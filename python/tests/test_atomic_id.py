@@ -0,0 +1,39 @@
+"""Checks for the `python` feature bindings. Run with `maturin develop && pytest python/tests`."""
+
+import atomic_id
+import pytest
+
+
+def test_encodings_match_rust_widths():
+    assert len(atomic_id.new64()) == 13
+    assert len(atomic_id.new128()) == 25
+    assert len(atomic_id.hex64()) == 16
+    assert len(atomic_id.base58_128()) == 22
+
+
+def test_ids_are_unique():
+    ids = {atomic_id.new64() for _ in range(10_000)}
+    assert len(ids) == 10_000
+
+
+def test_decompose64_round_trip():
+    raw = int(atomic_id.hex64(), 16)
+    parts = atomic_id.decompose64(raw)
+    assert parts["node_id"] == 1
+    assert parts["shard_id"] == 0
+    assert 1 <= parts["thread_id"] <= 128
+    assert set(parts) == {"timestamp", "node_id", "shard_id", "thread_id", "sequence"}
+
+
+def test_set_epoch_after_generating_raises():
+    parts = atomic_id.decompose64(int(atomic_id.hex64(), 16))
+    assert parts["timestamp"] >= 0
+    with pytest.raises(RuntimeError, match="frozen"):
+        atomic_id.set_epoch(1704067200000)
+
+
+def test_errors_raise_instead_of_panicking():
+    with pytest.raises(OverflowError):
+        atomic_id.decompose64(-1)
+    with pytest.raises(OverflowError):
+        atomic_id.decompose64(1 << 64)
@@ -0,0 +1,358 @@
+//! Bulk checks over previously exported IDs, such as before an import: that every
+//! ID parses under the expected width and encoding, and that none repeats.
+//!
+//! [`verify_unique`] streams through the IDs once, keeping at most a fixed number
+//! of them in memory. When that many have been read, they are sorted and written to
+//! a run file in the system's temporary directory; at the end the runs are merged,
+//! so duplicates are found however many IDs there are, using disk space for about
+//! 40 bytes per ID. The run files are removed when the audit finishes.
+//!
+//! ```
+//! use atomic_id::audit::verify_unique;
+//! use atomic_id::{Encoding, Width};
+//!
+//! let export = "0000000000001\n0000000000002\nnot an id\n0000000000001\n";
+//! let report = verify_unique(export.lines(), Width::X64, Encoding::Base36)?;
+//! assert_eq!((report.total, report.valid), (4, 3));
+//! assert_eq!(report.duplicates[0].indices, [0, 3]);
+//! assert_eq!(report.parse_failures[0].index, 2);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::{AnyId, Encoding, IdError, Layout64, Width};
+use core::cmp::Reverse;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+/// Number of IDs [`verify_unique`] keeps in memory before spilling them to disk,
+/// about 40 MB.
+pub const DEFAULT_MAX_IN_MEMORY: usize = 1 << 20;
+
+/// An ID's value as four 64-bit parts, most significant first, and its index.
+type Entry = ([u64; 4], u64);
+
+/// Bytes of one spilled [`Entry`]: the value's parts, then the index.
+const ENTRY_BYTES: usize = 40;
+
+/// Distinguishes the run files of audits running at the same time.
+static NEXT_RUN: AtomicU64 = AtomicU64::new(0);
+
+/// What [`verify_unique`] found.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AuditReport {
+    /// Number of strings read.
+    pub total: usize,
+    /// Number of strings that parsed.
+    pub valid: usize,
+    /// Every value that appeared more than once, in increasing order of value.
+    pub duplicates: Vec<Duplicate>,
+    /// Every string that did not parse, in input order.
+    pub parse_failures: Vec<ParseFailure>,
+    /// The earliest timestamp field among the IDs, in milliseconds since the epoch,
+    /// or `None` for widths without a timestamp (24 and 32 bits) or no valid IDs.
+    ///
+    /// The field holds the low bits of the time only, so for 64-bit IDs this is
+    /// meaningful within one wrap period; see [`Layout64::elapsed_ms`].
+    pub min_timestamp_ms: Option<u64>,
+    /// The latest timestamp field among the IDs. See
+    /// [`AuditReport::min_timestamp_ms`].
+    pub max_timestamp_ms: Option<u64>,
+}
+
+impl AuditReport {
+    /// Whether every ID parsed and none repeated.
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.parse_failures.is_empty()
+    }
+}
+
+/// A value that appeared more than once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    /// The repeated ID.
+    pub id: AnyId,
+    /// The positions it appeared at, counting from 0, in increasing order. For the
+    /// lines of a file, line `index + 1`.
+    pub indices: Vec<usize>,
+}
+
+/// A string that did not parse under the expected width and encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFailure {
+    /// The position of the string, counting from 0.
+    pub index: usize,
+    /// Why it did not parse, as from [`AnyId::parse`].
+    pub error: IdError,
+}
+
+/// Check that every string parses as a `width`-bit ID in `encoding`, strictly as
+/// [`AnyId::parse`] reads it, and that no value appears twice. Keeps at most
+/// [`DEFAULT_MAX_IN_MEMORY`] IDs in memory; see [`verify_unique_capped`].
+///
+/// Duplicates and parse failures are collected in full, so an input that is
+/// mostly bad takes memory in proportion to it.
+///
+/// # Errors
+/// Any I/O error from writing or reading the run files.
+pub fn verify_unique<'a>(
+    ids: impl Iterator<Item = &'a str>,
+    width: Width,
+    encoding: Encoding,
+) -> io::Result<AuditReport> {
+    verify_unique_capped(ids, width, encoding, DEFAULT_MAX_IN_MEMORY)
+}
+
+/// [`verify_unique`], keeping at most `max_in_memory` IDs in memory (at least 1)
+/// before spilling them to a sorted run file.
+///
+/// # Errors
+/// Any I/O error from writing or reading the run files.
+pub fn verify_unique_capped<'a>(
+    ids: impl Iterator<Item = &'a str>,
+    width: Width,
+    encoding: Encoding,
+    max_in_memory: usize,
+) -> io::Result<AuditReport> {
+    let max_in_memory = max_in_memory.max(1);
+    let mut report = AuditReport::default();
+    let mut chunk: Vec<Entry> = Vec::new();
+    let mut runs: Vec<Run> = Vec::new();
+    for (index, s) in ids.enumerate() {
+        report.total += 1;
+        let id = match AnyId::parse(s, width, encoding) {
+            Ok(id) => id,
+            Err(error) => {
+                report.parse_failures.push(ParseFailure { index, error });
+                continue;
+            }
+        };
+        report.valid += 1;
+        if let Some(ts) = timestamp_ms(id) {
+            report.min_timestamp_ms = Some(report.min_timestamp_ms.map_or(ts, |min| min.min(ts)));
+            report.max_timestamp_ms = Some(report.max_timestamp_ms.map_or(ts, |max| max.max(ts)));
+        }
+        chunk.push((key(id), index as u64));
+        if chunk.len() == max_in_memory {
+            runs.push(Run::spill(&mut chunk)?);
+        }
+    }
+
+    if runs.is_empty() {
+        chunk.sort_unstable();
+        report.duplicates = duplicates(chunk.into_iter().map(Ok), width)?;
+    } else {
+        if !chunk.is_empty() {
+            runs.push(Run::spill(&mut chunk)?);
+        }
+        report.duplicates = duplicates(Merge::new(&runs)?, width)?;
+    }
+    Ok(report)
+}
+
+/// Collect the values repeated in `sorted`, a stream of entries in increasing
+/// order.
+fn duplicates(sorted: impl Iterator<Item = io::Result<Entry>>, width: Width) -> io::Result<Vec<Duplicate>> {
+    let mut found = Vec::new();
+    let mut current: Option<([u64; 4], Vec<usize>)> = None;
+    for entry in sorted {
+        let (value, index) = entry?;
+        match &mut current {
+            Some((last, indices)) if *last == value => indices.push(index as usize),
+            _ => {
+                if let Some((last, indices)) = current.replace((value, vec![index as usize])) {
+                    push_duplicate(&mut found, last, indices, width);
+                }
+            }
+        }
+    }
+    if let Some((last, indices)) = current {
+        push_duplicate(&mut found, last, indices, width);
+    }
+    Ok(found)
+}
+
+/// Record `value` as a duplicate if it was seen at more than one index.
+fn push_duplicate(found: &mut Vec<Duplicate>, value: [u64; 4], indices: Vec<usize>, width: Width) {
+    if indices.len() > 1 {
+        found.push(Duplicate { id: from_key(value, width), indices });
+    }
+}
+
+/// The value of an ID as four 64-bit parts, most significant first, so IDs of
+/// every width sort and spill alike.
+fn key(id: AnyId) -> [u64; 4] {
+    match id {
+        AnyId::X24(raw) | AnyId::X32(raw) => [0, 0, 0, raw as u64],
+        AnyId::X64(raw) => [0, 0, 0, raw],
+        AnyId::X128(raw) => [0, 0, (raw >> 64) as u64, raw as u64],
+        AnyId::X256(parts) => parts,
+    }
+}
+
+/// Reverse [`key`] for an ID of `width`.
+fn from_key(parts: [u64; 4], width: Width) -> AnyId {
+    match width {
+        Width::X24 => AnyId::X24(parts[3] as u32),
+        Width::X32 => AnyId::X32(parts[3] as u32),
+        Width::X64 => AnyId::X64(parts[3]),
+        Width::X128 => AnyId::X128(((parts[2] as u128) << 64) | parts[3] as u128),
+        Width::X256 => AnyId::X256(parts),
+    }
+}
+
+/// The timestamp field of an ID in milliseconds since the epoch, for the widths
+/// that have one. 256-bit IDs carry the 64-bit layout in their first part.
+fn timestamp_ms(id: AnyId) -> Option<u64> {
+    match id {
+        AnyId::X24(_) | AnyId::X32(_) => None,
+        AnyId::X64(raw) => Some(Layout64::current().elapsed_ms(raw)),
+        AnyId::X128(raw) => Some((raw >> 96) as u64),
+        AnyId::X256(parts) => Some(Layout64::current().elapsed_ms(parts[0])),
+    }
+}
+
+/// A sorted run of entries spilled to a temporary file, removed on drop.
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    /// Sort `chunk`, write it to a new run file, and empty it.
+    fn spill(chunk: &mut Vec<Entry>) -> io::Result<Run> {
+        chunk.sort_unstable();
+        let name = format!(
+            "atomic-id-audit-{}-{}.run",
+            std::process::id(),
+            NEXT_RUN.fetch_add(1, Ordering::Relaxed)
+        );
+        let run = Run { path: std::env::temp_dir().join(name) };
+        let mut out = BufWriter::new(File::create(&run.path)?);
+        for (value, index) in chunk.drain(..) {
+            for part in value {
+                out.write_all(&part.to_le_bytes())?;
+            }
+            out.write_all(&index.to_le_bytes())?;
+        }
+        out.flush()?;
+        Ok(run)
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The entries of several runs, merged into one stream in increasing order.
+struct Merge {
+    readers: Vec<BufReader<File>>,
+    heads: BinaryHeap<Reverse<(Entry, usize)>>,
+}
+
+impl Merge {
+    /// Open every run and read its first entry.
+    fn new(runs: &[Run]) -> io::Result<Merge> {
+        let mut merge = Merge { readers: Vec::with_capacity(runs.len()), heads: BinaryHeap::new() };
+        for (i, run) in runs.iter().enumerate() {
+            merge.readers.push(BufReader::new(File::open(&run.path)?));
+            merge.advance(i)?;
+        }
+        Ok(merge)
+    }
+
+    /// Queue the next entry of run `i`, if it has one.
+    fn advance(&mut self, i: usize) -> io::Result<()> {
+        let mut buf = [0u8; ENTRY_BYTES];
+        match self.readers[i].read_exact(&mut buf) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        let word = |n: usize| u64::from_le_bytes(buf[n * 8..n * 8 + 8].try_into().unwrap());
+        self.heads.push(Reverse((([word(0), word(1), word(2), word(3)], word(4)), i)));
+        Ok(())
+    }
+}
+
+impl Iterator for Merge {
+    type Item = io::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((entry, i)) = self.heads.pop()?;
+        Some(self.advance(i).map(|()| entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestGuard;
+    use crate::{AtomicId, IdGenerator, x64};
+
+    /// Test that spilled and in-memory audits find the same duplicates, parse
+    /// failures, and timestamp range.
+    #[test]
+    fn test_verify_unique() {
+        let _guard = TestGuard::scoped(Default::default());
+        let generator = IdGenerator::new(5, 1);
+        let mut ids: Vec<String> = (0..1000).map(|_| Encoding::Hex.encode(generator.gen64() as u128, 16)).collect();
+        ids.push(ids[10].clone());
+        ids.push(ids[500].clone());
+        ids.push(ids[10].clone());
+        ids.push("not hex!".repeat(2));
+        ids.push("ff".into());
+
+        let in_memory = verify_unique(ids.iter().map(String::as_str), Width::X64, Encoding::Hex).unwrap();
+        for max_in_memory in [1, 7, 100] {
+            let spilled = verify_unique_capped(ids.iter().map(String::as_str), Width::X64, Encoding::Hex, max_in_memory);
+            assert_eq!(spilled.unwrap(), in_memory);
+        }
+
+        assert_eq!((in_memory.total, in_memory.valid), (1005, 1003));
+        assert!(!in_memory.is_clean());
+        let mut duplicates = in_memory.duplicates.clone();
+        duplicates.sort_by_key(|d| d.indices[0]);
+        assert_eq!(duplicates[0].indices, [10, 1000, 1002]);
+        assert_eq!(duplicates[1].indices, [500, 1001]);
+        assert_eq!(duplicates[0].id.encode(Encoding::Hex), ids[10]);
+        assert_eq!(in_memory.parse_failures[0], ParseFailure { index: 1003, error: IdError::InvalidCharacter('n') });
+        assert_eq!(in_memory.parse_failures[1], ParseFailure { index: 1004, error: IdError::InvalidLength(2) });
+
+        let first = IdGenerator::decompose64(u64::from_str_radix(&ids[0], 16).unwrap()).timestamp;
+        assert!(in_memory.min_timestamp_ms <= Some(first));
+        assert!(in_memory.max_timestamp_ms >= Some(first));
+    }
+
+    /// Test audits of every width, including the timestamp-less ones.
+    #[test]
+    fn test_verify_unique_widths() {
+        let ids = ["00001", "00002", "00001"];
+        let report = verify_unique_capped(ids.into_iter(), Width::X24, Encoding::Base36, 2).unwrap();
+        assert_eq!(report.duplicates, [Duplicate { id: AnyId::X24(1), indices: vec![0, 2] }]);
+        assert_eq!((report.min_timestamp_ms, report.max_timestamp_ms), (None, None));
+
+        let wide = AnyId::X256([1, 2, 3, 4]).encode(Encoding::Base58);
+        let report = verify_unique_capped([wide.as_str(); 3].into_iter(), Width::X256, Encoding::Base58, 1).unwrap();
+        assert_eq!(report.duplicates[0].id, AnyId::X256([1, 2, 3, 4]));
+        assert_eq!(report.duplicates[0].indices, [0, 1, 2]);
+
+        let clean = verify_unique(core::iter::empty(), Width::X128, Encoding::Base36).unwrap();
+        assert!(clean.is_clean());
+        assert_eq!(clean.total, 0);
+    }
+
+    /// Test that 10 million generated 64-bit IDs pass the audit clean.
+    #[test]
+    #[ignore] // This test is long-running and should be run manually.
+    fn test_verify_unique_generated() {
+        let _guard = TestGuard::scoped(Default::default());
+        let ids: Vec<String> = (0..10_000_000).map(|_| AtomicId::<x64>::new()).collect();
+        let report = verify_unique(ids.iter().map(String::as_str), Width::X64, Encoding::Base36).unwrap();
+        assert_eq!(report.valid, 10_000_000);
+        assert!(report.is_clean(), "{:?}", &report.duplicates[..report.duplicates.len().min(10)]);
+    }
+}
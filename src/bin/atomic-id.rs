@@ -0,0 +1,529 @@
+//! `atomic-id` command-line tool for generating IDs and inspecting existing ones.
+//!
+//! Built with the `cli` feature:
+//! ```sh
+//! cargo install atomic-id --features cli
+//! atomic-id gen --bits 64 --encoding base58 --count 10
+//! atomic-id decode --bits 64 0n3x8q9a7k2fc
+//! atomic-id range --from 2024-01-01T00:00:00Z --to 2024-01-01T00:05:00Z
+//! ```
+
+use atomic_id::{AnyId, AtomicOption, Encoding, IdGenerator, Layout32, Layout64, Width};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Generate and inspect atomic-id IDs.
+#[derive(Debug, Parser)]
+#[command(name = "atomic-id", version)]
+struct Cli {
+    /// Node ID embedded in generated IDs (0-4095, or 0-65535 with --wide-node).
+    #[arg(long, global = true, default_value_t = 1)]
+    node: u16,
+    /// Shard ID embedded in generated IDs (0-255, or 0-15 with --wide-node).
+    #[arg(long, global = true, default_value_t = 0)]
+    shard: u8,
+    /// Epoch in milliseconds since the UNIX epoch [default: the library default].
+    #[arg(long, global = true)]
+    epoch: Option<u64>,
+    /// Use the wide-node 64-bit layout (16-bit node, 4-bit shard).
+    #[arg(long, global = true)]
+    wide_node: bool,
+    /// Print JSON instead of one value per line.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, PartialEq, Eq, Subcommand)]
+enum Command {
+    /// Generate new IDs.
+    Gen {
+        /// ID width: 24, 32, 64, 128, or 256.
+        #[arg(long, default_value_t = Width::X64, value_parser = parse_width)]
+        bits: Width,
+        /// Output encoding.
+        #[arg(long, value_enum, default_value_t = EncodingArg::Base36)]
+        encoding: EncodingArg,
+        /// Number of IDs to generate.
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+    },
+    /// Print the fields of an ID and when it was created.
+    Decode {
+        /// ID width: 24, 32, 64, 128, or 256.
+        #[arg(long, default_value_t = Width::X64, value_parser = parse_width)]
+        bits: Width,
+        /// Encoding of the ID.
+        #[arg(long, value_enum, default_value_t = EncodingArg::Base36)]
+        encoding: EncodingArg,
+        /// The encoded ID.
+        id: String,
+    },
+    /// Print the smallest and largest IDs minted within a time window.
+    Range {
+        /// Start of the window, e.g. 2024-01-01T00:00:00Z.
+        #[arg(long)]
+        from: String,
+        /// End of the window, inclusive.
+        #[arg(long)]
+        to: String,
+        /// ID width: 64 or 128.
+        #[arg(long, default_value_t = Width::X64, value_parser = parse_width)]
+        bits: Width,
+        /// Output encoding.
+        #[arg(long, value_enum, default_value_t = EncodingArg::Base36)]
+        encoding: EncodingArg,
+    },
+}
+
+/// Command-line names for [`Encoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EncodingArg {
+    Base36,
+    Base58,
+    Base91,
+    Base91Urlsafe,
+    Hex,
+}
+
+impl From<EncodingArg> for Encoding {
+    fn from(arg: EncodingArg) -> Encoding {
+        match arg {
+            EncodingArg::Base36 => Encoding::Base36,
+            EncodingArg::Base58 => Encoding::Base58,
+            EncodingArg::Base91 => Encoding::Base91,
+            EncodingArg::Base91Urlsafe => Encoding::Base91UrlSafe,
+            EncodingArg::Hex => Encoding::Hex,
+        }
+    }
+}
+
+/// A decoded field value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Number(u128),
+    Text(String),
+}
+
+/// Named fields printed by `decode` and `range`.
+type Fields = Vec<(&'static str, Value)>;
+
+/// Parse a `--bits` value into a [`Width`].
+fn parse_width(s: &str) -> Result<Width, String> {
+    let bits: usize = s.parse().map_err(|_| format!("invalid width {:?}", s))?;
+    Width::try_from(bits).map_err(|e| e.to_string())
+}
+
+/// Width of the millisecond timestamp field in each timestamped layout.
+fn timestamp_bits(bits: Width) -> Option<u32> {
+    match bits {
+        Width::X64 | Width::X256 => Some(20),
+        Width::X128 => Some(32),
+        Width::X24 | Width::X32 => None,
+    }
+}
+
+/// Generate `count` IDs of `bits` bits from `generator`.
+fn generate(
+    generator: &IdGenerator,
+    bits: Width,
+    encoding: Encoding,
+    count: usize,
+) -> Result<Vec<String>, String> {
+    let len = encoding.encoded_len(bits.bits()).map_err(|e| e.to_string())?;
+    let one = || match bits {
+        Width::X24 => encoding.encode(generator.gen24() as u128, len),
+        Width::X32 => encoding.encode(generator.gen32() as u128, len),
+        Width::X64 => encoding.encode(generator.gen64() as u128, len),
+        Width::X128 => encoding.encode(generator.gen128(), len),
+        Width::X256 => AnyId::X256(generator.gen256()).encode(encoding),
+    };
+    Ok((0..count).map(|_| one()).collect())
+}
+
+/// The most recent time, in ms since the epoch and no later than `now`, whose low
+/// `bits` bits equal the truncated timestamp `ts`.
+fn resolve_timestamp(ts: u64, bits: u32, now: u64) -> u64 {
+    let period = 1u64 << bits;
+    let candidate = now - now % period + ts;
+    if candidate > now {
+        candidate.checked_sub(period).unwrap_or(ts)
+    } else {
+        candidate
+    }
+}
+
+/// Decode an ID into its fields. `created` is resolved relative to `now_ms`, since
+/// the timestamp field only holds the low bits of the creation time.
+fn decode(
+    bits: Width,
+    encoding: Encoding,
+    id: &str,
+    epoch_ms: u64,
+    now_ms: u64,
+) -> Result<Fields, String> {
+    let len = encoding.encoded_len(bits.bits()).map_err(|e| e.to_string())?;
+    if id.len() != len {
+        return Err(format!(
+            "expected {} characters for a {}-bit {:?} ID, got {}",
+            len,
+            bits,
+            encoding,
+            id.len()
+        ));
+    }
+    let (raw, parts) = match AnyId::parse(id, bits, encoding).map_err(|e| e.to_string())? {
+        AnyId::X24(raw) | AnyId::X32(raw) => (raw as u128, None),
+        AnyId::X64(raw) => (raw as u128, None),
+        AnyId::X128(raw) => (raw, None),
+        // The first of the four 64-bit parts carries the 64-bit layout.
+        AnyId::X256(parts) => (parts[0] as u128, Some(parts)),
+    };
+    let too_large = || format!("value does not fit in {} bits", bits);
+
+    let mut fields: Fields = Vec::new();
+    let ts = match bits {
+        Width::X24 => {
+            let raw = u32::try_from(raw).ok().filter(|&r| r < 1 << 24).ok_or_else(too_large)?;
+            fields.push(("sequence", Value::Number(raw as u128)));
+            None
+        }
+        Width::X32 => {
+            let raw = u32::try_from(raw).map_err(|_| too_large())?;
+            let layout = Layout32::current();
+            let parts = layout.decompose(raw);
+            if layout == Layout32::TimeSalted {
+                fields.push(("timestamp_secs", Value::Number(parts.timestamp as u128)));
+            } else {
+                fields.push(("thread_id", Value::Number(parts.thread_id as u128)));
+            }
+            fields.push(("sequence", Value::Number(parts.sequence as u128)));
+            None
+        }
+        Width::X128 => {
+            let high = (raw >> 64) as u64;
+            fields.push(("timestamp", Value::Number((high >> 32) as u128)));
+            fields.push(("node_id", Value::Number(((high >> 20) & 0xFFF) as u128)));
+            fields.push(("shard_id", Value::Number(((high >> 12) & 0xFF) as u128)));
+            fields.push(("thread_id", Value::Number(((high >> 4) & 0xFF) as u128)));
+            fields.push(("sequence", Value::Number((raw >> 8) & 0xFFFFFF)));
+            Some(high >> 32)
+        }
+        Width::X64 | Width::X256 => {
+            let raw = u64::try_from(raw).map_err(|_| too_large())?;
+            let parts = Layout64::current().decompose(raw);
+            fields.push(("timestamp", Value::Number(parts.timestamp as u128)));
+            fields.push(("node_id", Value::Number(parts.node_id as u128)));
+            fields.push(("shard_id", Value::Number(parts.shard_id as u128)));
+            fields.push(("thread_id", Value::Number(parts.wide_thread_id as u128)));
+            fields.push(("sequence", Value::Number(parts.sequence as u128)));
+            fields.push(("version", Value::Number(parts.version as u128)));
+            Some(parts.timestamp)
+        }
+    };
+    if let Some([_, part1, part2, part3]) = parts {
+        // Part 0 only keeps the low 16 bits of the sequence; parts 1 and 2 keep its
+        // low 40 bits between them. Part 1 also carries the mixed nanosecond clock,
+        // and part 3 further entropy mixed from the time, node, and shard.
+        let sequence = (part1 & 0xFFFF_FFFF) << 8 | (part2 >> 16) & 0xFF;
+        for (name, value) in &mut fields {
+            if *name == "sequence" {
+                *value = Value::Number(sequence as u128);
+            }
+        }
+        fields.push(("nanos_mix", Value::Number((part1 >> 32) as u128)));
+        fields.push(("entropy", Value::Text(format!("{:016x}", part3))));
+    }
+    if let (Some(ts), Some(ts_bits)) = (ts, timestamp_bits(bits)) {
+        let elapsed = resolve_timestamp(ts, ts_bits, now_ms.saturating_sub(epoch_ms));
+        let created = UNIX_EPOCH + Duration::from_millis(epoch_ms + elapsed);
+        fields.push(("created", Value::Text(format_time(created))));
+    }
+    Ok(fields)
+}
+
+/// The smallest and largest raw IDs whose timestamp lies in `from_ms..=to_ms`.
+fn range(bits: Width, from_ms: u64, to_ms: u64, epoch_ms: u64) -> Result<(u128, u128), String> {
+    let ts_bits = match bits {
+        Width::X64 | Width::X128 => timestamp_bits(bits).unwrap(),
+        _ => return Err(format!("range supports 64 and 128-bit IDs, not {}", bits)),
+    };
+    if from_ms > to_ms {
+        return Err("--from is after --to".to_string());
+    }
+    if from_ms < epoch_ms {
+        return Err(format!("--from is before the epoch ({} ms)", epoch_ms));
+    }
+    let (from, to) = (from_ms - epoch_ms, to_ms - epoch_ms);
+    if from >> ts_bits != to >> ts_bits {
+        return Err(format!(
+            "the window crosses a wrap of the {}-bit timestamp field, so IDs are not ordered across it",
+            ts_bits
+        ));
+    }
+    if bits == Width::X64 {
+        let (start, end) = Layout64::current().id_range(from, to);
+        return Ok((start as u128, end as u128));
+    }
+    let shift = bits.bits() as u32 - ts_bits;
+    let mask = (1u128 << ts_bits) - 1;
+    let low = (1u128 << shift) - 1;
+    Ok((((from as u128) & mask) << shift, (((to as u128) & mask) << shift) | low))
+}
+
+/// Parse an RFC 3339 UTC timestamp into milliseconds since the UNIX epoch.
+fn parse_time(s: &str) -> Result<u64, String> {
+    let time = humantime::parse_rfc3339(s).map_err(|e| format!("invalid time {:?}: {}", s, e))?;
+    let since = time.duration_since(UNIX_EPOCH).map_err(|e| e.to_string())?;
+    Ok(since.as_millis() as u64)
+}
+
+/// Format a time as RFC 3339 with millisecond precision.
+fn format_time(time: SystemTime) -> String {
+    humantime::format_rfc3339_millis(time).to_string()
+}
+
+/// Quote a string as a JSON string literal. Base91 IDs can contain `"` and `\`.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Render fields as a JSON object or as `name: value` lines.
+fn render_fields(fields: &Fields, json: bool) -> String {
+    let render = |value: &Value| match value {
+        Value::Number(n) => n.to_string(),
+        Value::Text(s) if json => json_string(s),
+        Value::Text(s) => s.clone(),
+    };
+    if json {
+        let body: Vec<_> = fields
+            .iter()
+            .map(|(name, value)| format!("{}:{}", json_string(name), render(value)))
+            .collect();
+        format!("{{{}}}", body.join(","))
+    } else {
+        fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, render(value)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Build the generator the global flags describe: `--node` and `--shard`, checked
+/// against the layout, and `--epoch` if given.
+fn build_generator(cli: &Cli) -> Result<IdGenerator, String> {
+    let (max_node, max_shard) = if cli.wide_node { (u16::MAX, 15) } else { (4095, u8::MAX) };
+    if cli.node > max_node {
+        return Err(format!("--node {} is out of range (0-{})", cli.node, max_node));
+    }
+    if cli.shard > max_shard {
+        return Err(format!("--shard {} is out of range (0-{})", cli.shard, max_shard));
+    }
+    let builder = IdGenerator::builder().node_id(cli.node).shard_id(cli.shard);
+    Ok(match cli.epoch {
+        Some(epoch) => builder.epoch_ms(epoch),
+        None => builder,
+    }
+    .build())
+}
+
+/// Apply the global flags and run the command, returning the text to print.
+fn run(cli: Cli) -> Result<String, String> {
+    if cli.wide_node {
+        AtomicOption::layout64(Layout64::WideNode);
+    }
+    let generator = build_generator(&cli)?;
+    let epoch_ms = generator.epoch_ms();
+    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+
+    match cli.command {
+        Command::Gen { bits, encoding, count } => {
+            let ids = generate(&generator, bits, encoding.into(), count)?;
+            Ok(if cli.json {
+                let ids: Vec<_> = ids.iter().map(|id| json_string(id)).collect();
+                format!("[{}]", ids.join(","))
+            } else {
+                ids.join("\n")
+            })
+        }
+        Command::Decode { bits, encoding, id } => {
+            let fields = decode(bits, encoding.into(), &id, epoch_ms, now_ms)?;
+            Ok(render_fields(&fields, cli.json))
+        }
+        Command::Range { from, to, bits, encoding } => {
+            let (min, max) = range(bits, parse_time(&from)?, parse_time(&to)?, epoch_ms)?;
+            let encoding = Encoding::from(encoding);
+            let len = encoding.encoded_len(bits.bits()).map_err(|e| e.to_string())?;
+            let (min, max) = (encoding.encode(min, len), encoding.encode(max, len));
+            Ok(if cli.json {
+                render_fields(&vec![("min", Value::Text(min)), ("max", Value::Text(max))], true)
+            } else {
+                format!("{}\n{}", min, max)
+            })
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that subcommands and global flags parse into the expected values.
+    #[test]
+    fn test_parse_args() {
+        let cli = Cli::try_parse_from([
+            "atomic-id", "gen", "--bits", "128", "--encoding", "base58", "--count", "10",
+            "--node", "7", "--json",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Gen { bits: Width::X128, encoding: EncodingArg::Base58, count: 10 }
+        );
+        assert_eq!((cli.node, cli.shard, cli.json), (7, 0, true));
+
+        let cli = Cli::try_parse_from(["atomic-id", "--epoch", "5", "decode", "abc"]).unwrap();
+        assert_eq!(cli.epoch, Some(5));
+        assert_eq!(
+            cli.command,
+            Command::Decode { bits: Width::X64, encoding: EncodingArg::Base36, id: "abc".into() }
+        );
+
+        assert!(Cli::try_parse_from(["atomic-id", "gen", "--encoding", "base64"]).is_err());
+        assert!(Cli::try_parse_from(["atomic-id", "decode", "--bits", "48", "ff"]).is_err());
+        assert!(Cli::try_parse_from(["atomic-id", "gen", "--bits", "x64"]).is_err());
+        assert!(Cli::try_parse_from(["atomic-id", "range", "--from", "x"]).is_err());
+    }
+
+    /// Test that a generated 64-bit ID decodes to its node, shard, and creation time.
+    #[test]
+    fn test_decode_64() {
+        let generator = IdGenerator::new(300, 7);
+        let id = generate(&generator, Width::X64, Encoding::Base58, 1).unwrap().remove(0);
+        let epoch = AtomicOption::get_epoch();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let fields = decode(Width::X64, Encoding::Base58, &id, epoch, now + 1).unwrap();
+
+        let get = |name| fields.iter().find(|(n, _)| *n == name).unwrap().1.clone();
+        assert_eq!(get("node_id"), Value::Number(300));
+        assert_eq!(get("shard_id"), Value::Number(7));
+        let Value::Text(created) = get("created") else { panic!() };
+        let created = parse_time(&created).unwrap();
+        assert!(now - created < 60_000);
+    }
+
+    /// Test decoding every width and rejecting malformed input.
+    #[test]
+    fn test_decode_widths() {
+        let generator = IdGenerator::new(5, 2);
+        for bits in Width::ALL {
+            let id = generate(&generator, bits, Encoding::Hex, 1).unwrap().remove(0);
+            assert!(decode(bits, Encoding::Hex, &id, 0, 1 << 42).is_ok(), "{}", bits);
+        }
+        let id = generate(&generator, Width::X128, Encoding::Base36, 1).unwrap().remove(0);
+        let fields = decode(Width::X128, Encoding::Base36, &id, 0, 1 << 40).unwrap();
+        assert!(fields.contains(&("node_id", Value::Number(5))));
+        assert!(fields.contains(&("shard_id", Value::Number(2))));
+
+        assert!(decode(Width::X64, Encoding::Hex, "xyz", 0, 0).is_err());
+        assert!(decode(Width::X64, Encoding::Hex, "GGGGGGGGGGGGGGGG", 0, 0).is_err());
+        assert!(decode(Width::X24, Encoding::Hex, "ffffff", 0, 0).is_ok());
+    }
+
+    /// Test that a 256-bit ID decodes its 64-bit layout from part 0 and the rest of
+    /// the sequence, the nanosecond mix, and the entropy from parts 1 to 3.
+    #[test]
+    fn test_decode_256() {
+        let seq: u64 = 0x12_3456_789A;
+        let part0 = 9 << 44 | 5 << 32 | 2 << 24 | 3 << 16 | (seq & 0xFFFF);
+        let part1 = 0xABCD << 32 | (seq.rotate_right(8) & 0xFFFF_FFFF);
+        let part2 = 3 << 56 | (seq.rotate_left(16) & 0xFFFF_FFFF);
+        let id = AnyId::X256([part0, part1, part2, 0xFEED]).encode(Encoding::Base36);
+        let fields = decode(Width::X256, Encoding::Base36, &id, 0, 1 << 30).unwrap();
+        let get = |name| fields.iter().find(|(n, _)| *n == name).unwrap().1.clone();
+        assert_eq!(get("timestamp"), Value::Number(9));
+        assert_eq!((get("node_id"), get("shard_id")), (Value::Number(5), Value::Number(2)));
+        assert_eq!(get("sequence"), Value::Number(seq as u128));
+        assert_eq!(get("nanos_mix"), Value::Number(0xABCD));
+        assert_eq!(get("entropy"), Value::Text("000000000000feed".into()));
+    }
+
+    /// Test that the generator is built from the global flags, and that node and
+    /// shard IDs outside the layout are refused instead of truncated.
+    #[test]
+    fn test_generator_flags() {
+        let cli = Cli::try_parse_from(["atomic-id", "--node", "300", "--shard", "7", "--epoch", "5", "gen"]).unwrap();
+        let generator = build_generator(&cli).unwrap();
+        assert_eq!((generator.node_id, generator.shard_id, generator.epoch_ms()), (300, 7, 5));
+
+        let cli = Cli::try_parse_from(["atomic-id", "--node", "5000", "gen"]).unwrap();
+        assert!(build_generator(&cli).is_err());
+        let cli = Cli::try_parse_from(["atomic-id", "--node", "5000", "--wide-node", "gen"]).unwrap();
+        assert_eq!(build_generator(&cli).unwrap().node_id, 5000);
+        let cli = Cli::try_parse_from(["atomic-id", "--shard", "16", "--wide-node", "gen"]).unwrap();
+        assert!(build_generator(&cli).is_err());
+    }
+
+    /// Test that truncated timestamps resolve to the latest matching time.
+    #[test]
+    fn test_resolve_timestamp() {
+        let period = 1 << 20;
+        assert_eq!(resolve_timestamp(5, 20, 10), 5);
+        assert_eq!(resolve_timestamp(5, 20, 3 * period + 10), 3 * period + 5);
+        assert_eq!(resolve_timestamp(20, 20, 3 * period + 10), 2 * period + 20);
+        assert_eq!(resolve_timestamp(20, 20, 10), 20);
+    }
+
+    /// Test the ID bounds for a time window.
+    #[test]
+    fn test_range() {
+        let epoch = 1_704_067_200_000;
+        let (min, max) = range(Width::X64, epoch + 1000, epoch + 2000, epoch).unwrap();
+        assert_eq!(min, 1000 << 44);
+        assert_eq!(max, (2000 << 44) | ((1 << 44) - 1));
+        let (min, _) = range(Width::X128, epoch + 1, epoch + 2, epoch).unwrap();
+        assert_eq!(min, 1 << 96);
+
+        assert!(range(Width::X64, epoch + 2, epoch + 1, epoch).is_err());
+        assert!(range(Width::X64, epoch - 1, epoch, epoch).is_err());
+        assert!(range(Width::X64, epoch, epoch + (1 << 20), epoch).is_err());
+        assert!(range(Width::X32, epoch, epoch, epoch).is_err());
+        assert_eq!(parse_time("2024-01-01T00:00:00Z"), Ok(epoch));
+        assert!(parse_time("2024-13-01T00:00:00Z").is_err());
+    }
+
+    /// Test JSON rendering, including escaping of base91 characters.
+    #[test]
+    fn test_render_json() {
+        let fields = vec![("id", Value::Text("a\"b\\".into())), ("n", Value::Number(3))];
+        assert_eq!(render_fields(&fields, true), r#"{"id":"a\"b\\","n":3}"#);
+        assert_eq!(render_fields(&fields, false), "id: a\"b\\\nn: 3");
+    }
+}
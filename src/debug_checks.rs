@@ -0,0 +1,67 @@
+//! Recently issued 64-bit IDs, checked for repeats, see
+//! [`AtomicOption::debug_duplicate_check`](crate::AtomicOption::debug_duplicate_check).
+//!
+//! The last [`CAPACITY`] IDs are kept twice: in a ring, oldest first, to know which
+//! one to forget next, and in an ordered set, to look them up. Repeats within that
+//! window are always caught; repeats of older IDs never are.
+
+use crate::{IdEvent, emit};
+use alloc::collections::{BTreeSet, VecDeque};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Default number of recent IDs kept.
+pub const DEFAULT_CAPACITY: usize = 65_536;
+
+/// Whether checking is on, so generation skips the lock when it is not.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// IDs kept, set with [`AtomicOption::debug_duplicate_capacity`](crate::AtomicOption::debug_duplicate_capacity).
+pub static CAPACITY: AtomicUsize = AtomicUsize::new(DEFAULT_CAPACITY);
+
+/// The recent IDs, in issue order and sorted.
+static RECENT: Mutex<(VecDeque<u64>, BTreeSet<u64>)> = Mutex::new((VecDeque::new(), BTreeSet::new()));
+
+/// Start or stop checking. Stopping forgets every recorded ID.
+pub fn enable(on: bool) {
+    ENABLED.store(on, Ordering::Relaxed);
+    if !on {
+        let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        *recent = (VecDeque::new(), BTreeSet::new());
+    }
+}
+
+/// Whether checking is on.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Record `id`, reporting [`IdEvent::DuplicateId`] if it is among the recent IDs,
+/// and panicking after the report in builds with debug assertions.
+pub fn check(id: u64) {
+    if !enabled() {
+        return;
+    }
+    let repeated = {
+        let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        let (ring, set) = &mut *recent;
+        let repeated = !set.insert(id);
+        if !repeated {
+            ring.push_back(id);
+            let capacity = CAPACITY.load(Ordering::Relaxed);
+            while ring.len() > capacity {
+                if let Some(oldest) = ring.pop_front() {
+                    set.remove(&oldest);
+                }
+            }
+        }
+        repeated
+    };
+    // The lock is released first: the event hook may generate IDs of its own.
+    if repeated {
+        emit(IdEvent::DuplicateId { width: 64, id: id as u128 });
+        if cfg!(debug_assertions) {
+            panic!("atomic-id: 64-bit ID {:#018x} was issued twice; check the node and shard IDs", id);
+        }
+    }
+}
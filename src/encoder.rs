@@ -0,0 +1,136 @@
+//! Pluggable text encodings for IDs.
+//!
+//! [`Encoder`] is implemented by each built-in encoding, as the unit structs below and
+//! as [`Encoding`] itself, and can be implemented for any other alphabet, such as
+//! Bech32 or z-base-32, without changes to this crate. Pass an encoder to
+//! [`AtomicId::<x64>::encode_with`](crate::AtomicId::<64>::encode_with) or
+//! [`AtomicId::<x128>::encode_with`](crate::AtomicId::<128>::encode_with) to generate
+//! IDs in it.
+//!
+//! ```
+//! use atomic_id::encoder::{Base58, Encoder};
+//! use atomic_id::{AtomicId, IdError, x64};
+//!
+//! /// Octal, as an example of an encoding outside the crate.
+//! struct Octal;
+//!
+//! impl Encoder for Octal {
+//!     fn encode(&self, n: u128, width: usize) -> String {
+//!         format!("{:0width$o}", n, width = width)
+//!     }
+//!
+//!     fn decode(&self, s: &str) -> Result<u128, IdError> {
+//!         match s.chars().find(|c| !c.is_digit(8)) {
+//!             Some(c) => Err(IdError::InvalidCharacter(c)),
+//!             None if s.is_empty() => Err(IdError::Empty),
+//!             None => u128::from_str_radix(s, 8).map_err(|_| IdError::Overflow),
+//!         }
+//!     }
+//! }
+//!
+//! let id = AtomicId::<x64>::encode_with(&Octal);
+//! assert!(Octal.decode(&id).unwrap() <= u64::MAX as u128);
+//! assert_eq!(AtomicId::<x64>::encode_with(&Base58).len(), 11);
+//! ```
+
+use crate::{Encoding, IdError};
+use alloc::string::String;
+
+/// A text encoding for raw ID values.
+pub trait Encoder {
+    /// Encode a raw value, left-padded to at least `width` characters.
+    fn encode(&self, n: u128, width: usize) -> String;
+
+    /// Decode a string produced by [`Encoder::encode`] back into its raw value.
+    ///
+    /// # Errors
+    /// An [`IdError`] describing why `s` is not a valid value in this encoding.
+    fn decode(&self, s: &str) -> Result<u128, IdError>;
+
+    /// Width that IDs of `bits` bits are padded to by `encode_with`, so every ID of a
+    /// width has the same length. Defaults to 0, for no padding.
+    fn width(&self, bits: usize) -> usize {
+        let _ = bits;
+        0
+    }
+}
+
+impl Encoder for Encoding {
+    fn encode(&self, n: u128, width: usize) -> String {
+        Encoding::encode(*self, n, width)
+    }
+
+    fn decode(&self, s: &str) -> Result<u128, IdError> {
+        Encoding::decode(*self, s)
+    }
+
+    fn width(&self, bits: usize) -> usize {
+        self.encoded_len(bits).unwrap_or(0)
+    }
+}
+
+macro_rules! builtin {
+    ($(#[$doc:meta] $name:ident,)*) => {$(
+        #[$doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        pub struct $name;
+
+        impl Encoder for $name {
+            fn encode(&self, n: u128, width: usize) -> String {
+                Encoding::$name.encode(n, width)
+            }
+
+            fn decode(&self, s: &str) -> Result<u128, IdError> {
+                Encoding::$name.decode(s)
+            }
+
+            fn width(&self, bits: usize) -> usize {
+                Encoder::width(&Encoding::$name, bits)
+            }
+        }
+    )*};
+}
+
+builtin! {
+    /// Base36 (`0-9a-z`), the default encoding; see [`Encoding::Base36`].
+    Base36,
+    /// Base58, without the lookalikes `0`, `O`, `I`, and `l`; see [`Encoding::Base58`].
+    Base58,
+    /// Base91, the densest built-in encoding; see [`Encoding::Base91`].
+    Base91,
+    /// Base91 without `%`, `#`, or `?`; see [`Encoding::Base91UrlSafe`].
+    Base91UrlSafe,
+    /// Lowercase hexadecimal; see [`Encoding::Hex`].
+    Hex,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AtomicId, x64, x128};
+
+    /// Test that the built-in encoders agree with [`Encoding`] and that generated IDs
+    /// round-trip through them at the widths of the convenience methods.
+    #[test]
+    fn test_builtin_encoders() {
+        let _guard = crate::testing::TestGuard::scoped(Default::default());
+        let encoders: [(&dyn Encoder, Encoding); 5] = [
+            (&Base36, Encoding::Base36),
+            (&Base58, Encoding::Base58),
+            (&Base91, Encoding::Base91),
+            (&Base91UrlSafe, Encoding::Base91UrlSafe),
+            (&Hex, Encoding::Hex),
+        ];
+        for (encoder, encoding) in encoders {
+            assert_eq!(encoder.encode(12_345, 8), encoding.encode(12_345, 8));
+            assert_eq!(encoder.width(64), encoding.encoded_len(64).unwrap());
+            let id = AtomicId::<x64>::encode_with(encoder);
+            assert_eq!(id.len(), encoding.encoded_len(64).unwrap());
+            assert_eq!(encoder.decode(&id), encoding.decode(&id));
+            let id = AtomicId::<x128>::encode_with(&encoding);
+            assert_eq!(id.len(), encoder.width(128));
+            assert!(encoder.decode(&id).is_ok());
+        }
+        assert_eq!(Base36.decode("!"), Err(IdError::InvalidCharacter('!')));
+    }
+}
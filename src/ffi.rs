@@ -0,0 +1,154 @@
+//! C ABI for generating IDs from C and C++.
+//!
+//! Every function produces exactly the same IDs and encodings as the Rust API, since it
+//! calls straight into it. No function unwinds across the boundary: panics are caught and
+//! reported as [`ATOMIC_ID_ERR_PANIC`]. Note that with `panic = "abort"` (as in this
+//! crate's release profile) a panic aborts the process before it can be caught.
+//!
+//! Build a linkable library with the `ffi` feature:
+//! ```sh
+//! cargo rustc --release --features ffi --crate-type staticlib   # or cdylib
+//! ```
+//!
+//! The header is generated by [cbindgen](https://github.com/mozilla/cbindgen) using the
+//! `cbindgen.toml` at the crate root:
+//! ```sh
+//! cbindgen --config cbindgen.toml --output include/atomic_id.h
+//! ```
+
+use crate::{AtomicId, AtomicOption, Encoding, IdError, Width, encode, xgen};
+use core::ffi::c_char;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// The call succeeded.
+pub const ATOMIC_ID_OK: i32 = 0;
+/// A required pointer argument was null.
+pub const ATOMIC_ID_ERR_NULL: i32 = -1;
+/// The output buffer is too small for the result and its NUL terminator.
+pub const ATOMIC_ID_ERR_BUFFER_TOO_SMALL: i32 = -2;
+/// The call panicked internally. No output was written.
+pub const ATOMIC_ID_ERR_PANIC: i32 = -3;
+/// The operation is unavailable in this build (e.g. the epoch is fixed by `const_epoch`).
+pub const ATOMIC_ID_ERR_UNSUPPORTED: i32 = -4;
+/// The epoch can no longer change because IDs have already been generated.
+pub const ATOMIC_ID_ERR_EPOCH_FROZEN: i32 = -5;
+/// The ID width is not one of 24, 32, 64, 128, or 256 bits.
+pub const ATOMIC_ID_ERR_WIDTH: i32 = -6;
+
+/// Run `f`, turning a panic into [`ATOMIC_ID_ERR_PANIC`].
+fn guarded(f: impl FnOnce() -> i32) -> i32 {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(ATOMIC_ID_ERR_PANIC)
+}
+
+/// Generate a raw 64-bit ID from the global generator.
+///
+/// Returns 0 if generation panicked; 0 is never a valid ID since the thread ID field
+/// is never zero.
+#[unsafe(no_mangle)]
+pub extern "C" fn atomic_id_new64() -> u64 {
+    catch_unwind(|| xgen().gen64()).unwrap_or(0)
+}
+
+/// Write a raw 128-bit ID to `out` as 16 big-endian bytes, the same bytes as
+/// [`AtomicId::<x128>::uuid_bytes`](crate::AtomicId::uuid_bytes).
+///
+/// Returns [`ATOMIC_ID_OK`], [`ATOMIC_ID_ERR_NULL`], or [`ATOMIC_ID_ERR_PANIC`].
+///
+/// # Safety
+/// `out` must be null or valid for writes of 16 bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn atomic_id_new128(out: *mut u8) -> i32 {
+    if out.is_null() {
+        return ATOMIC_ID_ERR_NULL;
+    }
+    guarded(|| {
+        let bytes = xgen().gen128().to_be_bytes();
+        // SAFETY: the caller guarantees `out` is valid for 16 bytes.
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len()) };
+        ATOMIC_ID_OK
+    })
+}
+
+/// Write a new base36 ID of `bits` bits, like
+/// [`AtomicId::generate`](crate::AtomicId::generate), NUL-terminated,
+/// into `out`, for C code that reads the width from configuration.
+///
+/// Returns the string length (excluding the NUL) on success, or
+/// [`ATOMIC_ID_ERR_NULL`], [`ATOMIC_ID_ERR_WIDTH`], [`ATOMIC_ID_ERR_UNSUPPORTED`] (24
+/// or 32 bits without the `short` feature), [`ATOMIC_ID_ERR_BUFFER_TOO_SMALL`], or
+/// [`ATOMIC_ID_ERR_PANIC`]. Nothing is written on error.
+///
+/// # Safety
+/// `out` must be null or valid for writes of `cap` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn atomic_id_generate(bits: usize, out: *mut c_char, cap: usize) -> i32 {
+    if out.is_null() {
+        return ATOMIC_ID_ERR_NULL;
+    }
+    let Ok(width) = Width::try_from(bits) else {
+        return ATOMIC_ID_ERR_WIDTH;
+    };
+    guarded(|| {
+        let encoded = match AtomicId::generate(width, Encoding::Base36) {
+            Ok(encoded) => encoded,
+            Err(IdError::FeatureDisabled(_)) => return ATOMIC_ID_ERR_UNSUPPORTED,
+            Err(_) => return ATOMIC_ID_ERR_WIDTH,
+        };
+        if encoded.len() >= cap {
+            return ATOMIC_ID_ERR_BUFFER_TOO_SMALL;
+        }
+        // SAFETY: the caller guarantees `out` is valid for `cap` bytes, and
+        // `encoded.len() + 1 <= cap`.
+        unsafe {
+            core::ptr::copy_nonoverlapping(encoded.as_ptr(), out.cast::<u8>(), encoded.len());
+            *out.add(encoded.len()) = 0;
+        }
+        encoded.len() as i32
+    })
+}
+
+/// Encode a 64-bit ID as the 13-character base36 string used by
+/// [`AtomicId::<x64>::new`](crate::AtomicId::new), NUL-terminated, into `out`.
+///
+/// Returns the string length (excluding the NUL) on success, or
+/// [`ATOMIC_ID_ERR_NULL`], [`ATOMIC_ID_ERR_BUFFER_TOO_SMALL`] (`cap` below 14), or
+/// [`ATOMIC_ID_ERR_PANIC`]. Nothing is written on error.
+///
+/// # Safety
+/// `out` must be null or valid for writes of `cap` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn atomic_id_encode_base36(value: u64, out: *mut c_char, cap: usize) -> i32 {
+    if out.is_null() {
+        return ATOMIC_ID_ERR_NULL;
+    }
+    guarded(|| {
+        let encoded = encode::base36(value as u128, 13);
+        if encoded.len() >= cap {
+            return ATOMIC_ID_ERR_BUFFER_TOO_SMALL;
+        }
+        // SAFETY: the caller guarantees `out` is valid for `cap` bytes, and
+        // `encoded.len() + 1 <= cap`.
+        unsafe {
+            core::ptr::copy_nonoverlapping(encoded.as_ptr(), out.cast::<u8>(), encoded.len());
+            *out.add(encoded.len()) = 0;
+        }
+        encoded.len() as i32
+    })
+}
+
+/// Set the global epoch, in milliseconds since the UNIX epoch, like
+/// [`AtomicOption::try_epoch`]. Call it before generating any ID.
+///
+/// Returns [`ATOMIC_ID_OK`], [`ATOMIC_ID_ERR_EPOCH_FROZEN`] once IDs have been
+/// generated, or [`ATOMIC_ID_ERR_UNSUPPORTED`] when the crate was built with the
+/// `const_epoch` feature.
+#[unsafe(no_mangle)]
+pub extern "C" fn atomic_id_set_epoch(ms: u64) -> i32 {
+    if cfg!(feature = "const_epoch") {
+        return ATOMIC_ID_ERR_UNSUPPORTED;
+    }
+    guarded(|| match AtomicOption::try_epoch(ms) {
+        Ok(()) => ATOMIC_ID_OK,
+        Err(_) => ATOMIC_ID_ERR_EPOCH_FROZEN,
+    })
+}
@@ -0,0 +1,170 @@
+//! Layouts of snowflake IDs minted by other systems, for reading their creation
+//! times.
+//!
+//! A [`ForeignLayout`] describes a 64-bit ID as a millisecond timestamp, a node
+//! field, and a sequence, stacked from the top down to bit 0. The layouts of
+//! [`DISCORD`], [`TWITTER`], and [`INSTAGRAM`] are provided; describe any other
+//! system with [`ForeignLayout::new`].
+//!
+//! ```
+//! use atomic_id::foreign::{DISCORD, ForeignLayout};
+//!
+//! // The example from Discord's API reference.
+//! let parts = DISCORD.parse(175928847299117063);
+//! assert_eq!((parts.node, parts.sequence), (1 << 5, 7));
+//! assert_eq!(DISCORD.created_at(175928847299117063), 1_462_015_105_796);
+//!
+//! // Any other millisecond snowflake, given its epoch and field widths.
+//! const ORDERS: ForeignLayout = ForeignLayout::new("orders", 1_600_000_000_000, 41, 12, 10);
+//! assert_eq!(ORDERS.created_at(5 << 22), 1_600_000_000_005);
+//! ```
+
+use crate::Layout;
+
+/// Discord's epoch, the first millisecond of 2015 (UTC), in milliseconds since
+/// UNIX_EPOCH.
+pub const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// Twitter's snowflake epoch, 2010-11-04 01:42:54.657 UTC, in milliseconds since
+/// UNIX_EPOCH.
+pub const TWITTER_EPOCH_MS: u64 = 1_288_834_974_657;
+
+/// Instagram's epoch, 2011-08-24 21:07:01.721 UTC, in milliseconds since
+/// UNIX_EPOCH.
+pub const INSTAGRAM_EPOCH_MS: u64 = 1_314_220_021_721;
+
+/// Discord IDs: 42-bit timestamp | 5-bit worker | 5-bit process | 12-bit increment.
+///
+/// The worker and process are read together as the 10-bit node. Discord documents
+/// the timestamp as 42 bits, using the top bit that Twitter leaves unset; the two
+/// layouts read the same for any ID minted before 2084.
+pub const DISCORD: ForeignLayout = ForeignLayout::new("discord", DISCORD_EPOCH_MS, 42, 10, 12);
+
+/// Twitter IDs: unused sign bit | 41-bit timestamp | 10-bit machine | 12-bit sequence.
+pub const TWITTER: ForeignLayout = ForeignLayout::new("twitter", TWITTER_EPOCH_MS, 41, 10, 12);
+
+/// Instagram IDs: 41-bit timestamp | 13-bit logical shard | 10-bit sequence.
+pub const INSTAGRAM: ForeignLayout = ForeignLayout::new("instagram", INSTAGRAM_EPOCH_MS, 41, 13, 10);
+
+/// The layout of a foreign 64-bit snowflake ID: a millisecond timestamp above a
+/// node field above a sequence, with any bits left over at the top unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ForeignLayout {
+    /// Name of the system, for display.
+    pub name: &'static str,
+    /// Milliseconds since UNIX_EPOCH at which the timestamp field reads 0.
+    pub epoch_ms: u64,
+    /// Width of the timestamp field.
+    pub timestamp_bits: u32,
+    /// Width of the node field: worker, machine, or shard, whatever the system
+    /// puts between the timestamp and the sequence.
+    pub node_bits: u32,
+    /// Width of the sequence field, which ends at bit 0.
+    pub sequence_bits: u32,
+}
+
+/// The fields of a foreign ID, as returned by [`ForeignLayout::parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Parts {
+    /// Milliseconds since the layout's epoch.
+    pub timestamp: u64,
+    /// The node field.
+    pub node: u64,
+    /// The sequence field.
+    pub sequence: u64,
+}
+
+impl ForeignLayout {
+    /// Describe a layout.
+    ///
+    /// # Panics
+    /// If the fields add up to more than 64 bits, at compile time when used in a
+    /// `const`.
+    pub const fn new(
+        name: &'static str,
+        epoch_ms: u64,
+        timestamp_bits: u32,
+        node_bits: u32,
+        sequence_bits: u32,
+    ) -> ForeignLayout {
+        assert!(timestamp_bits + node_bits + sequence_bits <= 64, "fields wider than 64 bits");
+        ForeignLayout { name, epoch_ms, timestamp_bits, node_bits, sequence_bits }
+    }
+
+    /// Split `id` into its fields. Bits above the timestamp are ignored.
+    pub const fn parse(&self, id: u64) -> Parts {
+        Parts {
+            timestamp: field(id, self.node_bits + self.sequence_bits, self.timestamp_bits),
+            node: field(id, self.sequence_bits, self.node_bits),
+            sequence: field(id, 0, self.sequence_bits),
+        }
+    }
+
+    /// When `id` was created, in milliseconds since UNIX_EPOCH, saturating at
+    /// `u64::MAX` for timestamps too far past the epoch to count.
+    pub const fn created_at(&self, id: u64) -> u64 {
+        self.epoch_ms.saturating_add(self.parse(id).timestamp)
+    }
+
+    /// The layout as a [`Layout`], with fields `unused`, `timestamp`, `node`, and
+    /// `sequence`.
+    pub fn layout(&self) -> Layout {
+        let unused = 64 - self.timestamp_bits - self.node_bits - self.sequence_bits;
+        Layout::stacked(
+            64,
+            &[
+                ("unused", unused),
+                ("timestamp", self.timestamp_bits),
+                ("node", self.node_bits),
+                ("sequence", self.sequence_bits),
+            ],
+        )
+    }
+}
+
+/// The `width` bits of `id` starting at bit `offset`.
+const fn field(id: u64, offset: u32, width: u32) -> u64 {
+    match width {
+        0 => 0,
+        64 => id,
+        _ => (id >> offset) & ((1 << width) - 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the published layouts against IDs with documented creation times, and a
+    /// user-defined layout against the worked example in Instagram's engineering
+    /// blog post on sharded IDs.
+    #[test]
+    fn test_published_ids() {
+        // Discord's API reference: 2016-04-30 11:18:25.796 UTC, worker 1, process 0,
+        // increment 7.
+        let id = 175928847299117063;
+        assert_eq!(DISCORD.parse(id), Parts { timestamp: 41_944_705_796, node: 1 << 5, sequence: 7 });
+        assert_eq!(DISCORD.created_at(id), 1_462_015_105_796);
+
+        // The Tweet in Twitter's v1.1 API reference, created Wed Oct 10 20:19:24 2018.
+        let id = 1050118621198921728;
+        assert_eq!(TWITTER.created_at(id) / 1000, 1_539_202_764);
+        assert_eq!(TWITTER.parse(id).node, 347);
+
+        // 1387263000ms after 2011-01-01, logical shard 31341 % 2000, sequence
+        // 5001 % 1024.
+        let blog = ForeignLayout::new("instagram-blog", 1_293_840_000_000, 41, 13, 10);
+        let id = 1387263000 << 23 | 1341 << 10 | 905;
+        assert_eq!(blog.parse(id), Parts { timestamp: 1387263000, node: 1341, sequence: 905 });
+        assert_eq!(blog.created_at(id), 1_293_840_000_000 + 1387263000);
+        assert_eq!(INSTAGRAM.parse(id).node, 1341);
+
+        for layout in [DISCORD, TWITTER, INSTAGRAM, blog] {
+            assert!(layout.layout().is_exact(), "{}", layout.name);
+        }
+        assert_eq!(TWITTER.layout().field("unused").unwrap().bit_width, 1);
+        assert_eq!(DISCORD.layout().field("unused"), None);
+        assert_eq!(ForeignLayout::new("all", 0, 64, 0, 0).parse(u64::MAX).timestamp, u64::MAX);
+        assert_eq!(ForeignLayout::new("x", 1, 64, 0, 0).created_at(u64::MAX), u64::MAX);
+    }
+}
@@ -15,6 +15,89 @@
 //!
 //! ## Feature Flags
 //! - `short`: Enables support for 24-bit and 32-bit IDs (`x24`, `x32`). This feature is disabled by default to keep the library lightweight.
+//! - `testing`: Enables the `testing` module for isolating global state in tests.
+//! - `const_epoch`: Fixes the epoch at build time from the `ATOMIC_ID_EPOCH` environment
+//!   variable (milliseconds since the UNIX epoch; defaults to 2022-05-01 when unset).
+//!   This removes an atomic load from every timestamp and makes it impossible to change
+//!   the epoch by accident at runtime, which would silently break ID comparability.
+//!   The cost is flexibility: `AtomicOption::epoch` and `reset_epoch` become deprecated
+//!   no-ops, and changing the epoch requires a rebuild.
+//! - `log`: Provides `log_event`, a hook that forwards [`IdEvent`]s to the `log` crate.
+//! - `ffi`: Exposes a C ABI (`atomic_id_new64`, `atomic_id_encode_base36`, ...) in the
+//!   `ffi` module, for C and C++ services that need the same IDs.
+//! - `python`: Builds a PyO3 extension module named `atomic_id` (see `pyproject.toml`)
+//!   exposing `new64`, `new128`, `hex64`, `base58_128`, `set_epoch`, and `decompose64`.
+//! - `std` (default): Uses the system clock and per-thread caching. Disable it for
+//!   `no_std` targets; see below.
+//! - `wasm`: Reads the clock from `Date.now()` and `performance.now()` on
+//!   `wasm32-unknown-unknown`, where `SystemTime::now()` panics. The browser is
+//!   single-threaded, so every ID gets thread ID `1`, and browsers coarsen
+//!   `performance.now()`, so 128 and 256-bit IDs carry fewer random nanosecond bits than
+//!   on native targets. IDs minted in the same millisecond by different tabs or workers
+//!   are only kept apart by their node and shard IDs, so give each its own.
+//! - `cli`: Builds the `atomic-id` binary for generating IDs, decoding them into their
+//!   fields and creation time, and finding the ID bounds of a time window.
+//! - `arbitrary`: Implements `arbitrary::Arbitrary` for [`Encoding`], for fuzzing the
+//!   encoders and decoders. The targets live in `fuzz/` and run with `cargo fuzz`.
+//! - `rusqlite`: Implements `ToSql` and `FromSql` for [`Id64`] (as `INTEGER`), [`Id128`],
+//!   and [`Id256`] (as big-endian `BLOB`s).
+//! - `redis`: Implements `ToRedisArgs` and `FromRedisValue` for the typed IDs, as their
+//!   base36 strings, and provides `redis::RawId64` to store an [`Id64`] as an integer.
+//! - `schemars`: Implements `JsonSchema` for the typed IDs, as strings with their exact
+//!   length, an alphabet `pattern`, and a `format` such as `atomic-id-64`.
+//! - `proptest`: Implements `proptest::arbitrary::Arbitrary` for [`Id64`] and [`Id128`],
+//!   producing IDs with plausible fields, with strategies in the `proptest` module.
+//! - `otel`: Converts [`Id128`] into `opentelemetry::trace::TraceId` and [`Id64`] into
+//!   `SpanId`, so request IDs can double as trace IDs.
+//! - `zeroize`: Implements `zeroize::Zeroize` for the typed IDs, for IDs used as
+//!   secrets such as API tokens, and wipes the scratch buffers of the encoders.
+//! - `simd`: Encodes large base36 batches ([`Encoding::encode_batch`] and the 64-bit
+//!   `base36_batch`) with portable SIMD, sixteen values at a time. Only takes effect
+//!   on a nightly compiler, since `std::simd` is unstable; stable builds ignore it,
+//!   so `--all-features` still builds there.
+//! - `debug_checks`: Adds `AtomicOption::debug_duplicate_check`, which remembers
+//!   recently issued 64-bit IDs and reports repeats, for catching misconfigured
+//!   node IDs during development.
+//! - `fast_thread_hash`: Derives hashed thread IDs from the `ThreadId` number with the
+//!   SplitMix64 finalizer instead of SipHash. Cheaper on each thread's first ID and
+//!   spreads thread IDs evenly, but assigns different thread IDs than the default.
+//! - `reconfigure`: Adds `AtomicOption::reconfigure`, which swaps the global
+//!   generator's node and shard at runtime, for node IDs assigned after startup.
+//!   Generation then reads the generator through an `arc_swap::ArcSwapOption`.
+//! - `tower`: Provides `tower::RequestIdLayer`, middleware that tags each HTTP request
+//!   with an [`Id64`] from its `x-request-id` header, or a new one if the header is
+//!   missing or malformed, and echoes it in the response.
+//!
+//! ## `no_std`
+//!
+//! With `default-features = false` the crate only needs `core` and `alloc`. There is no
+//! system clock, so a [`TimeSource`] must be installed with [`AtomicOption::time_source`]
+//! before generating timestamp-based IDs; generating one without it panics. Thread IDs
+//! come from an optional [`AtomicOption::thread_entropy`] provider and are `1` without it.
+//! Collision estimates (`collision_probability`) require `std`. The target needs 64-bit
+//! atomics.
+//!
+//! The example below runs as an ordinary doctest, with `std`. The `no_std` directory of
+//! the repository holds the same code as a `#![no_std]` library, which CI builds for the
+//! bare-metal `riscv64gc-unknown-none-elf` target.
+//!
+//! ```rust
+//! use atomic_id::{AtomicId, AtomicOption, TimeSource, x64};
+//!
+//! fn board_ms() -> u64 {
+//!     1704067200000 // read a hardware RTC here
+//! }
+//! fn board_ns() -> u64 {
+//!     board_ms() * 1_000_000
+//! }
+//!
+//! AtomicOption::time_source(TimeSource {
+//!     now_ms: board_ms,
+//!     now_ns: board_ns,
+//! });
+//! let _id = AtomicId::<x64>::new();
+//! # AtomicOption::clear_time_source();
+//! ```
 //!
 //! ## Quick Start
 //!
@@ -47,8 +130,10 @@
 //! For timestamp-based IDs (64, 128, 256-bit), you can set a custom epoch.
 //! ```rust
 //! use atomic_id::AtomicOption;
-//! // Set a custom epoch to `2024-01-01 00:00:00 UTC` in milliseconds.
-//! AtomicOption::epoch(1704067200000);
+//! // Set a custom epoch to `2024-01-01 00:00:00 UTC` in milliseconds, before
+//! // generating any ID.
+//! # #[cfg(not(feature = "const_epoch"))]
+//! AtomicOption::try_epoch(1704067200000).unwrap();
 //! ```
 //!
 //! ### Different Encodings
@@ -62,1003 +147,11696 @@
 //! println!("Hex:    {}", id_hex);
 //! ```
 
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(atomic_id_simd, feature(portable_simd))]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "reconfigure")]
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU8, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use core::time::Duration;
+use core::ops::{Range, RangeInclusive};
+#[cfg(feature = "std")]
+use std::cell::Cell;
+#[cfg(all(feature = "std", not(feature = "fast_thread_hash")))]
 use std::collections::hash_map::DefaultHasher;
+#[cfg(feature = "std")]
 use std::hash::{Hash, Hasher};
-use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "std")]
 use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+use std::{sync::OnceLock, time::Instant};
 
 /// Default custom epoch: 2022-05-01 00:00:00 UTC (milliseconds since UNIX_EPOCH)
 const DEFAULT_EPOCH: u64 = 1651363200000;
 
-/// Global custom epoch (milliseconds since UNIX_EPOCH).
+/// Global custom epoch (milliseconds since UNIX_EPOCH), with [`EPOCH_FROZEN`] in
+/// the top bit.
 /// Used as the reference point for all timestamp-based IDs.
 /// Can be set/reset via [`AtomicOption`].
 static CUSTOM_EPOCH: AtomicU64 = AtomicU64::new(DEFAULT_EPOCH);
 
+/// Set in [`CUSTOM_EPOCH`] once the first timestamp-based ID is generated. From then
+/// on the epoch can only change through [`AtomicOption::force_epoch_unchecked`].
+/// Sharing one word with the epoch lets [`AtomicOption::try_epoch`] check and set it
+/// in one step, so no ID can freeze and read the epoch in between.
+const EPOCH_FROZEN: u64 = 1 << 63;
+
+// With the `const_epoch` feature, the build script writes `BUILD_EPOCH` from the
+// `ATOMIC_ID_EPOCH` environment variable (or `DEFAULT_EPOCH` when unset).
+#[cfg(feature = "const_epoch")]
+include!(concat!(env!("OUT_DIR"), "/epoch.rs"));
+
+/// The epoch that timestamps are measured from, in milliseconds since UNIX_EPOCH.
+///
+/// A compile-time constant with the `const_epoch` feature, otherwise a relaxed load
+/// of [`CUSTOM_EPOCH`].
+#[inline]
+fn current_epoch() -> u64 {
+    #[cfg(feature = "const_epoch")]
+    {
+        BUILD_EPOCH
+    }
+    #[cfg(not(feature = "const_epoch"))]
+    {
+        CUSTOM_EPOCH.load(Ordering::Relaxed) & !EPOCH_FROZEN
+    }
+}
+
+/// Aligns a value to its own 64-byte cache line.
+///
+/// Used for the sequence counters so that threads generating different widths
+/// don't contend on each other's counters through false sharing.
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> core::ops::Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
 /// Global sequence counters for each bit mode.
 /// These ensure atomic, thread-safe, and unique sequence numbers for each ID width.
-static SEQ_24: AtomicU64 = AtomicU64::new(0);
-static SEQ_32: AtomicU64 = AtomicU64::new(0);
-static SEQ_64: AtomicU64 = AtomicU64::new(0);
-static SEQ_128: AtomicU64 = AtomicU64::new(0);
-static SEQ_256: AtomicU64 = AtomicU64::new(0);
+/// Each counter sits on its own cache line.
+///
+/// All counter operations use [`Ordering::Relaxed`]. Uniqueness only depends on the
+/// atomicity of `fetch_add`: every read-modify-write on a single atomic sees the
+/// latest value in that atomic's modification order, so no two calls can ever get
+/// the same value back, whatever the ordering. Stronger orderings would only
+/// order the counter relative to *other* memory, which ID generation never relies on.
+static SEQ_24: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+static SEQ_32: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+static SEQ_64: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+static SEQ_128: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+static SEQ_256: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+
+/// Sequence counter for [`IdGenerator::gen32_timed`]. Kept apart from `SEQ_32` so
+/// untimed 32-bit IDs do not use up its 4096 values per second.
+static SEQ_32_TIMED: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+
+/// Sequence counter and latest timestamp for [`IdGenerator::gen128_monotonic`]. Kept
+/// apart from the counters above so resetting those never breaks monotonicity.
+static SEQ_MONOTONIC_128: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+static TS_MONOTONIC_128: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+
+/// Timestamp and sequence of the last [`IdGenerator::gen64_k_sorted`] ID, packed as
+/// `timestamp << 12 | sequence` so both advance in one atomic update.
+static K_SORTED_64: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+
+/// Sequence counter values at the last [`AtomicOption::reset_stats`] call, indexed
+/// 24, 32, 64, 128, 256, and timed 32. Generated counts are reported relative to these.
+static STATS_BASE: [AtomicU64; 6] = [const { AtomicU64::new(0) }; 6];
+
+/// Most recent wall-clock reading used for a timestamp, in ms since UNIX_EPOCH.
+static LAST_TIMESTAMP: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
+
+/// Number of times the wall clock was observed going backwards.
+static CLOCK_ROLLBACKS: AtomicU64 = AtomicU64::new(0);
+
+/// How the system clock is read, stored as a [`ClockMode`] discriminant.
+static CLOCK_MODE: AtomicU8 = AtomicU8::new(ClockMode::Wall as u8);
+
+/// How often [`ClockMode::MonotonicAnchored`] re-reads the wall clock, in
+/// nanoseconds; 0 for never. Set with [`AtomicOption::clock_reanchor_interval`].
+static CLOCK_REANCHOR_NS: AtomicU64 = AtomicU64::new(0);
+
+/// The instant that monotonic clock readings are measured from.
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+static CLOCK_BASE: OnceLock<Instant> = OnceLock::new();
+
+/// Wall-clock nanoseconds since UNIX_EPOCH at [`CLOCK_BASE`], as of the latest
+/// anchoring. Only ever raised, so anchored readings never go back. 0 until anchored.
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+static CLOCK_ANCHOR_NS: AtomicU64 = AtomicU64::new(0);
+
+/// Nanoseconds after [`CLOCK_BASE`] at which the clock was last anchored.
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+static CLOCK_ANCHORED_AT_NS: AtomicU64 = AtomicU64::new(0);
 
 // Thread-local storage for thread ID.
 // Each thread gets a unique ID (1-128) to add entropy to generated IDs.
+#[cfg(feature = "std")]
 thread_local! {
-    static THREAD_ID: std::cell::Cell<u8> = std::cell::Cell::new(0);
+    // Raw entropy that the current thread's hashed ID is folded from, computed on first
+    // use, and the `THREAD_CACHE_GENERATION` it was computed in.
+    static THREAD_ENTROPY_CACHE: Cell<Option<(u64, u64)>> = const { Cell::new(None) };
+    // The current thread's number in `ThreadIdMode::Sequential`, 0 until assigned, and
+    // the `THREAD_CACHE_GENERATION` it was assigned in.
+    static THREAD_NUMBER: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
 }
 
-/// The core generator struct for producing unique IDs.
-///
-/// This struct holds node and shard identifiers, which are incorporated into
-/// 64, 128, and 256-bit IDs to ensure uniqueness in a distributed environment.
-///
-/// While you can create an `IdGenerator` instance, the library is designed
-/// to be used through the static methods on [`AtomicId`], which manage a global generator.
-pub struct IdGenerator {
-    /// Node identifier (0-4095), used in 64, 128, and 256-bit IDs.
-    pub node_id: u16,
-    /// Shard identifier (0-255), used in 64, 128, and 256-bit IDs.
-    pub shard_id: u8,
-}
+/// Next number handed out in [`ThreadIdMode::Sequential`].
+#[cfg(feature = "std")]
+static NEXT_THREAD_NUMBER: AtomicU64 = AtomicU64::new(1);
 
-impl IdGenerator {
-    /// Create a new generator with the given node and shard IDs.
-    ///
-    /// # Arguments
-    /// * `node_id` - Node identifier (0..=4095).
-    /// * `shard_id` - Shard identifier (0..=255).
-    ///
-    /// # Returns
-    /// A new [`IdGenerator`] instance.
-    pub fn new(node_id: u16, shard_id: u8) -> Self {
-        Self { node_id, shard_id }
-    }
+/// Bumped by [`AtomicOption::__reset_thread_ids`], invalidating the thread-local
+/// thread ID caches of every thread.
+#[cfg(feature = "std")]
+static THREAD_CACHE_GENERATION: AtomicU64 = AtomicU64::new(0);
 
-    /// Get the current timestamp in milliseconds, relative to the global custom epoch.
-    ///
-    /// # Returns
-    /// Milliseconds since the current epoch (see [`AtomicOption`]).
-    fn timestamp(&self) -> u64 {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        now.saturating_sub(CUSTOM_EPOCH.load(Ordering::Relaxed))
-    }
+/// Width of the thread ID field in 64-bit IDs, set with [`AtomicOption::thread_id_bits`].
+static THREAD_ID_BITS: AtomicU8 = AtomicU8::new(DEFAULT_THREAD_ID_BITS);
 
-    /// Get the current timestamp in nanoseconds since the UNIX epoch.
-    ///
-    /// # Returns
-    /// Nanoseconds since UNIX_EPOCH as a `u64`.
-    fn nanos(&self) -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64
-    }
+/// Default width of the 64-bit thread ID field.
+const DEFAULT_THREAD_ID_BITS: u8 = 8;
 
-    /// Get or assign a unique ID for the current thread.
-    ///
-    /// This method provides a thread-local ID from 1 to 128, which is used
-    /// as a source of entropy in ID generation.
-    ///
-    /// # Returns
-    /// A thread-local unique ID in the range 1..=128.
-    fn thread_id(&self) -> u8 {
-        THREAD_ID.with(|id| {
-            let tid = id.get();
-            if tid == 0 {
-                let mut hasher = DefaultHasher::new();
-                std::thread::current().id().hash(&mut hasher);
-                let new_id = ((hasher.finish() & 0x7F) as u8) + 1; // 1-128
-                id.set(new_id);
-                new_id
-            } else {
-                tid
-            }
-        })
-    }
+/// Bits shared by the version, thread ID, and sequence fields in every 64-bit layout.
+const THREAD_SEQ_BITS_64: u32 = 24;
 
-    /// Generate a 24-bit unique ID.
-    ///
-    /// The ID is generated from a single atomic counter that wraps around.
-    /// It is suitable for short-lived, high-throughput scenarios where a small ID is needed.
-    ///
-    /// - **Structure**: 24 bits for the sequence.
-    /// - **Uniqueness**: Guarantees up to 16.7 million (2^24) unique IDs before the counter wraps around.
-    ///
-    /// # Returns
-    /// A 24-bit unique ID as a `u32`.
-    pub fn gen24(&self) -> u32 {
-        let seq = SEQ_24.fetch_add(1, Ordering::Relaxed);
-        (seq & 0xFFFFFF) as u32
-    }
+/// Width of the layout version field at the bottom of 64-bit IDs, and of the
+/// reserved bits 65-64 of 128-bit IDs, when version bits are enabled.
+const VERSION_BITS: u32 = 2;
 
-    /// Generate a 32-bit unique ID.
-    ///
-    /// This ID combines a thread-specific identifier with a sequence number,
-    /// providing better collision resistance in multi-threaded applications than `gen24`.
-    ///
-    /// - **Structure**: 8 bits for the thread ID | 24 bits for the sequence.
-    ///
-    /// # Returns
-    /// A 32-bit unique ID as a `u32`.
-    pub fn gen32(&self) -> u32 {
-        let thread_id = self.thread_id();
-        let seq = SEQ_32.fetch_add(1, Ordering::Relaxed);
+/// The layout version stamped into IDs while [`AtomicOption::version_bits`] is enabled.
+/// Version 0 is the unversioned legacy layout, [`FORMAT_LAYOUT_VERSION`] marks
+/// [`IdGenerator::gen64_versioned`] IDs, and 3 is reserved for future layouts.
+const LAYOUT_VERSION: u8 = 1;
 
-        let thread_bits = ((thread_id as u32) & 0xFF) << 24;
-        let seq_bits = (seq & 0xFFFFFF) as u32;
+/// The layout version of [`IdGenerator::gen64_versioned`] IDs, which record their
+/// [`Layout64`] in the [`FORMAT_BITS_64`] bits above the version.
+const FORMAT_LAYOUT_VERSION: u8 = 2;
 
-        thread_bits | seq_bits
-    }
+/// Whether IDs carry a layout version field, set with [`AtomicOption::version_bits`].
+static VERSION_BITS_ENABLED: AtomicBool = AtomicBool::new(false);
 
-    /// Generate a 64-bit unique ID, inspired by Twitter's Snowflake.
-    ///
-    /// This ID is ideal for distributed systems, as it combines a timestamp,
-    /// node/shard identifiers, and a sequence number to ensure global uniqueness.
-    ///
-    /// - **Structure**: 20-bit timestamp | 12-bit node ID | 8-bit shard ID | 8-bit thread ID | 16-bit sequence.
-    /// - **Timestamp**: Milliseconds since the custom epoch, providing a lifespan of ~34 years (2^20 ms).
-    /// - **Node ID**: Supports up to 4096 nodes (2^12).
-    /// - **Shard ID**: Supports up to 256 shards per node (2^8).
-    /// - **Sequence**: Supports up to 65,536 IDs per millisecond per thread (2^16).
-    ///
-    /// # Returns
-    /// A 64-bit unique ID as a `u64`.
-    pub fn gen64(&self) -> u64 {
-        let ts = self.timestamp();
-        let thread_id = self.thread_id();
-        let seq = SEQ_64.fetch_add(1, Ordering::Relaxed);
+/// Salt mixed into hashed thread IDs, set with [`AtomicOption::thread_id_salt`].
+static THREAD_ID_SALT: AtomicU64 = AtomicU64::new(0);
 
-        let ts_bits = (ts & 0xFFFFF) << 44;
-        let node_bits = ((self.node_id & 0xFFF) as u64) << 32;
-        let shard_bits = ((self.shard_id as u64) & 0xFF) << 24;
-        let thread_bits = ((thread_id as u64) & 0xFF) << 16;
-        let seq_bits = seq & 0xFFFF;
+/// How thread IDs are assigned, stored as a [`ThreadIdMode`] discriminant.
+static THREAD_ID_MODE: AtomicU8 = AtomicU8::new(ThreadIdMode::Hashed as u8);
 
-        ts_bits | node_bits | shard_bits | thread_bits | seq_bits
-    }
+/// The 32-bit layout used by [`IdGenerator::gen32`], stored as a [`Layout32`] discriminant.
+static LAYOUT_32: AtomicU8 = AtomicU8::new(Layout32::Standard as u8);
 
-    /// Generate a 128-bit unique ID with enhanced collision resistance.
-    ///
-    /// This ID uses a two-part structure to maximize entropy, combining a timestamp-based
-    /// part with a high-entropy part derived from nanoseconds and sequence numbers.
-    ///
-    /// - **High 64 bits**: 32-bit timestamp | 12-bit node | 8-bit shard | 8-bit thread | 4-bit reserved.
-    /// - **Low 64 bits**: 32-bit nanoseconds | 24-bit sequence | 8-bit rotated thread ID.
-    ///
-    /// # Returns
-    /// A 128-bit unique ID as a `u128`.
-    pub fn gen128(&self) -> u128 {
-        let ts = self.timestamp();
-        let thread_id = self.thread_id();
-        let nanos = self.nanos();
-        let seq = SEQ_128.fetch_add(1, Ordering::Relaxed);
+/// The 24-bit layout used by [`IdGenerator::gen24`], stored as a [`Layout24`] discriminant.
+static LAYOUT_24: AtomicU8 = AtomicU8::new(Layout24::Standard as u8);
 
-        // First 64 bits: Enhanced timestamp-based entropy
-        // 32-bit timestamp | 12-bit node | 8-bit shard | 8-bit thread | 4-bit reserved
-        let high_part = {
-            let ts_bits = (ts & 0xFFFFFFFF) << 32;
-            let node_bits = ((self.node_id & 0xFFF) as u64) << 20;
-            let shard_bits = ((self.shard_id as u64) & 0xFF) << 12;
-            let thread_bits = ((thread_id as u64) & 0xFF) << 4;
-            let reserved = (nanos.rotate_right(16)) & 0xF;
-            
-            ts_bits | node_bits | shard_bits | thread_bits | reserved
-        };
+/// What [`IdGenerator::try_gen24`] does once the 24-bit sequence is used up, stored as
+/// an [`OverflowPolicy`] discriminant.
+static OVERFLOW_POLICY_24: AtomicU8 = AtomicU8::new(OverflowPolicy::Wrap as u8);
 
-        // Second 64 bits: Maximum entropy mixing
-        // 32-bit nanos | 24-bit sequence | 8-bit thread rotated
-        let low_part = {
-            let nanos_bits = (nanos & 0xFFFFFFFF) << 32;
-            let seq_bits = (seq & 0xFFFFFF) << 8;
-            let thread_rot = thread_id.rotate_left(3) as u64;
-            
-            nanos_bits | seq_bits | thread_rot
-        };
+/// Value of the 24-bit sequence counter at the last [`AtomicOption::reset24`]. The
+/// sequence is used up once the counter is a full field width past it.
+static SEQ_24_RESET_AT: AtomicU64 = AtomicU64::new(0);
 
-        ((high_part as u128) << 64) | (low_part as u128)
-    }
+/// What happens when a timestamp outgrows its field, stored as an [`OverflowMode`]
+/// discriminant.
+static OVERFLOW_MODE: AtomicU8 = AtomicU8::new(OverflowMode::Wrap as u8);
 
-    /// Generate a 256-bit unique ID for maximum entropy and uniqueness.
-    ///
-    /// This ID is constructed from four 64-bit parts, each derived from different
-    /// sources of entropy (timestamps, nanoseconds, node/shard/thread IDs, and sequences).
-    /// It is suitable for applications requiring cryptographic-level uniqueness.
-    ///
-    /// # Returns
-    /// An array of four `u64` values representing the 256-bit ID.
-    pub fn gen256(&self) -> [u64; 4] {
-        let ts = self.timestamp();
-        let thread_id = self.thread_id();
-        let nanos = self.nanos();
-        let seq = SEQ_256.fetch_add(1, Ordering::Relaxed);
+/// Most 64-bit IDs generated per millisecond, set with
+/// [`AtomicOption::max_rate_per_ms`], or 0 for no limit.
+static MAX_RATE_64: AtomicU32 = AtomicU32::new(0);
 
-        // Part 0: Base 64-bit structure (like gen64 but with different sequence)
-        let part0 = {
-            let ts_bits = (ts & 0xFFFFF) << 44;
-            let node_bits = ((self.node_id & 0xFFF) as u64) << 32;
-            let shard_bits = ((self.shard_id as u64) & 0xFF) << 24;
-            let thread_bits = ((thread_id as u64) & 0xFF) << 16;
-            let seq_bits = seq & 0xFFFF;
-            
-            ts_bits | node_bits | shard_bits | thread_bits | seq_bits
-        };
+/// The millisecond the rate limit is counting, truncated to its low 32 bits, in the
+/// top half, and the number of 64-bit IDs generated in it so far in the bottom half.
+/// A count of 0 means no millisecond has been counted yet.
+static RATE_WINDOW_64: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
 
-        // Part 1: Nanosecond precision with entropy mixing
-        let part1 = {
-            let nanos_high = (nanos >> 32) & 0xFFFFFFFF;
-            let nanos_low = nanos & 0xFFFFFFFF;
-            let mixed = nanos_high.rotate_left(16) ^ nanos_low;
-            
-            (mixed << 32) | ((seq.rotate_right(8)) & 0xFFFFFFFF)
-        };
+/// The tick of the latest [`AtomicId::<x64>::lease`](AtomicId::<64>::lease),
+/// truncated to its low 32 bits, in the top half, and the low 32 bits of the first
+/// sequence value leased in that tick in the bottom half. 0 before the first lease.
+static LEASE_WINDOW_64: CachePadded<AtomicU64> = CachePadded(AtomicU64::new(0));
 
-        // Part 2: Thread and sequence entropy
-        let part2 = {
-            let thread_expanded = ((thread_id as u64) << 56) | 
-                                ((thread_id as u64).rotate_left(8) << 48) |
-                                ((thread_id as u64).rotate_left(16) << 40) |
-                                ((thread_id as u64).rotate_left(24) << 32);
-            let seq_mixed = (seq.rotate_left(16)) & 0xFFFFFFFF;
-            
-            thread_expanded | seq_mixed
-        };
+/// A lease takes at most `2^-LEASE_SHARE_BITS` of the values the sequence field
+/// tells apart within one tick.
+const LEASE_SHARE_BITS: u32 = 4;
 
-        // Part 3: Additional entropy sources
-        let part3 = {
-            let ts_rotated = ts.rotate_right(12);
-            let node_expanded = ((self.node_id as u64) << 48) | 
-                              ((self.node_id as u64).rotate_left(4) << 32);
-            let shard_expanded = ((self.shard_id as u64) << 24) |
-                               ((self.shard_id as u64).rotate_left(2) << 16) |
-                               ((self.shard_id as u64).rotate_left(4) << 8) |
-                               (self.shard_id as u64).rotate_left(6);
-            let mixed_entropy = (ts_rotated & 0xFFFF) | node_expanded | shard_expanded;
-            
-            mixed_entropy
-        };
+/// The latest tick of a timestamped sequence field, and the sequence counter at the
+/// first ID of that tick.
+struct TickStart {
+    ts: AtomicU64,
+    seq: AtomicU64,
+}
 
-        [part0, part1, part2, part3]
+impl TickStart {
+    const fn new() -> Self {
+        TickStart { ts: AtomicU64::new(u64::MAX), seq: AtomicU64::new(0) }
     }
 }
 
-/// Encoding utilities for converting numeric IDs to various string representations.
-///
-/// Supported encodings:
-/// - `base36`: `[0-9a-z]`
-/// - `base58`: Bitcoin alphabet (e.g., for short URLs)
-/// - `base91`: ASCII-safe and URL-safe
-/// - `hex`: `[0-9a-f]`
-mod encode {
-    /// Bitcoin-style base58 alphabet (no `0`, `O`, `I`, `l`).
-    const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
-    /// Base91 alphabet (ASCII-safe, URL-safe).
-    const BASE91: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!#$%&()*+,./:;<=>?@[]^_`{|}~\"";
-    /// Base36 alphabet (0-9, a-z).
-    const BASE36: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+/// Where the 64 and 128-bit sequences stood at the start of the current tick. Used to
+/// detect a sequence running out within a single tick. `TICK_START_32` does the same
+/// per second for [`IdGenerator::gen32_timed`].
+static TICK_START_32: TickStart = TickStart::new();
+static TICK_START_64: TickStart = TickStart::new();
+static TICK_START_128: TickStart = TickStart::new();
 
-    /// Convert a number to a string in the given base and alphabet.
-    ///
-    /// # Arguments
-    /// * `n` - The number to encode.
-    /// * `base` - The target base (e.g., 36, 58, 91).
-    /// * `alphabet` - The character set for encoding.
-    /// * `min_width` - The minimum width of the output string, padded with the first alphabet character.
-    ///
-    /// # Returns
-    /// The encoded string.
-    pub fn to_base(mut n: u128, base: usize, alphabet: &[u8], min_width: usize) -> String {
-        if n == 0 {
-            return String::from_utf8(vec![alphabet[0]; min_width]).unwrap();
-        }
+/// Widths of the millisecond timestamp fields in 64 and 128-bit IDs.
+const TS_BITS_64: u32 = 20;
+const TS_BITS_128: u32 = 32;
 
-        let mut chars = Vec::with_capacity(min_width);
-        while n > 0 {
-            chars.push(alphabet[(n % base as u128) as usize]);
-            n /= base as u128;
-        }
+/// Width of the era field above the timestamp in [`Layout64::Era`].
+const ERA_BITS_64: u32 = 3;
 
-        while chars.len() < min_width {
-            chars.push(alphabet[0]);
-        }
+/// Width of the region field in [`Layout64::Regional`].
+const REGION_BITS_64: u32 = 4;
 
-        chars.reverse();
-        String::from_utf8(chars).unwrap()
-    }
+/// Largest region ID that fits the region field.
+const MAX_REGION_ID: u8 = (1 << REGION_BITS_64) - 1;
 
-    /// Encode a number as a base58 string.
-    pub fn base58(n: u128, width: usize) -> String {
-        to_base(n, 58, BASE58, width)
-    }
+/// Width of the layout field between the layout version and the sequence of
+/// [`IdGenerator::gen64_versioned`] IDs.
+const FORMAT_BITS_64: u32 = 2;
 
-    /// Encode a number as a base91 string.
-    pub fn base91(n: u128, width: usize) -> String {
-        to_base(n, 91, BASE91, width)
-    }
+/// How close to a timestamp wrap, as a percentage of the field's period, counts as
+/// near the wrap. Set with [`AtomicOption::wrap_threshold`].
+static WRAP_THRESHOLD_PERCENT: AtomicU8 = AtomicU8::new(DEFAULT_WRAP_THRESHOLD_PERCENT);
 
-    /// Encode a number as a base36 string.
-    pub fn base36(n: u128, width: usize) -> String {
-        to_base(n, 36, BASE36, width)
-    }
+/// Default for [`WRAP_THRESHOLD_PERCENT`].
+const DEFAULT_WRAP_THRESHOLD_PERCENT: u8 = 1;
 
-    /// Encode a number as a hexadecimal string.
-    pub fn hex(n: u128, width: usize) -> String {
-        format!("{:0width$x}", n, width = width)
-    }
-}
+/// The timestamp period (`ts >> Layout64::timestamp_bits`) that the last near-wrap event was
+/// reported for, so it is reported once per period rather than on every ID.
+static WRAP_WARNED_PERIOD_64: AtomicU64 = AtomicU64::new(u64::MAX);
 
-/// Global generator instance, initialized on first use.
-/// Used by all [`AtomicId`] operations.
-static GENERATOR: std::sync::OnceLock<IdGenerator> = std::sync::OnceLock::new();
+/// The 64-bit layout used by [`IdGenerator::gen64`], stored as a [`Layout64`] discriminant.
+static LAYOUT_64: AtomicU8 = AtomicU8::new(Layout64::Standard as u8);
 
-/// Get a reference to the global [`IdGenerator`] instance.
-///
-/// Initializes the generator with default values (node_id=1, shard_id=0) on first call.
-fn xgen() -> &'static IdGenerator {
-    GENERATOR.get_or_init(|| IdGenerator::new(1, 0))
+/// Region of generators built without one, set with [`AtomicOption::region_id`].
+static REGION_ID: AtomicU8 = AtomicU8::new(0);
+
+/// The unit of 64-bit timestamps, stored as a [`TimestampPrecision`] discriminant.
+static TIMESTAMP_PRECISION: AtomicU8 = AtomicU8::new(TimestampPrecision::Millis as u8);
+
+/// The installed event hook, stored as a type-erased `fn(IdEvent)`. Null when unset.
+static EVENT_HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+// Set while the current thread is running the event hook, so events raised
+// from inside the hook are dropped instead of recursing.
+#[cfg(feature = "std")]
+thread_local! {
+    static IN_EVENT_HOOK: Cell<bool> = const { Cell::new(false) };
 }
 
-// Bit mode constants for compile-time selection.
-#[cfg(feature = "short")]
-#[allow(non_upper_case_globals)]
-/// Constant for 24-bit mode (requires the `short` feature).
-pub const x24: usize = 24;
-#[cfg(feature = "short")]
-#[allow(non_upper_case_globals)]
-/// Constant for 32-bit mode (requires the `short` feature).
-pub const x32: usize = 32;
-#[allow(non_upper_case_globals)]
-/// Constant for 64-bit mode.
-pub const x64: usize = 64;
-#[allow(non_upper_case_globals)]
-/// Constant for 128-bit mode.
-pub const x128: usize = 128;
-#[allow(non_upper_case_globals)]
-/// Constant for 256-bit mode.
-pub const x256: usize = 256;
+/// The installed generation hook, stored as a type-erased `fn(usize, u128)`. Null
+/// when unset.
+static GENERATE_HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
 
-/// The main entry point for generating atomic IDs of a specific bit width.
-///
-/// Use the const generic `BITS` parameter to select the desired ID size.
-/// For 24 and 32-bit IDs, the `short` feature must be enabled.
-///
-/// # Examples
-///
-/// ```
-/// use atomic_id::{AtomicId, x64, x128};
-///
-/// // Generate a 64-bit ID
-/// let id64 = AtomicId::<x64>::new();
-///
-/// // Generate a 128-bit ID
-/// let id128 = AtomicId::<x128>::new();
-/// ```
-pub struct AtomicId<const BITS: usize>;
+/// The installed 256-bit generation hook, stored as a type-erased `fn([u64; 4])`.
+/// Null when unset.
+static GENERATE_HOOK_256: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
 
-#[cfg(feature = "short")]
-impl AtomicId<24> {
-    /// Generate a new 24-bit ID, encoded as a 5-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x24};
-    /// let id = AtomicId::<x24>::new();
-    /// assert_eq!(id.len(), 5);
-    /// ```
-    pub fn new() -> String {
-        encode::base36(xgen().gen24() as u128, 5)
+/// The installed [`TimeSource`] callbacks, stored as type-erased `fn() -> u64`s.
+/// Null when unset. Replaced together through [`store_time_source`].
+static TIME_MS: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+static TIME_NS: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Odd while [`TIME_MS`] and [`TIME_NS`] are being replaced, and moved on by 2 for
+/// every replacement, so [`load_time_source`] never pairs the milliseconds of one
+/// [`TimeSource`] with the nanoseconds of another.
+static TIME_SOURCE_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// The installed thread entropy provider, stored as a type-erased `fn() -> u64`.
+/// Null when unset.
+static THREAD_ENTROPY: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Load a callback stored by [`AtomicOption::time_source`] or
+/// [`AtomicOption::thread_entropy`].
+#[inline]
+fn load_callback(slot: &AtomicPtr<()>) -> Option<fn() -> u64> {
+    let ptr = slot.load(Ordering::Acquire);
+    // SAFETY: the callback slots are only ever set from a valid `fn() -> u64`, and
+    // function pointers never dangle.
+    (!ptr.is_null()).then(|| unsafe { core::mem::transmute::<*mut (), fn() -> u64>(ptr) })
+}
+
+/// Replace both [`TimeSource`] callbacks, or clear them with null pointers, as one
+/// change to readers of [`load_time_source`].
+fn store_time_source(now_ms: *mut (), now_ns: *mut ()) {
+    let mut seq = TIME_SOURCE_SEQ.load(Ordering::Relaxed);
+    loop {
+        if seq & 1 == 1 {
+            core::hint::spin_loop();
+            seq = TIME_SOURCE_SEQ.load(Ordering::Relaxed);
+            continue;
+        }
+        match TIME_SOURCE_SEQ.compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => break,
+            Err(current) => seq = current,
+        }
     }
-    /// Generate a new 24-bit ID, encoded as a 5-character base58 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x24};
-    /// let id = AtomicId::<x24>::base58();
-    /// assert_eq!(id.len(), 5);
-    /// ```
-    pub fn base58() -> String {
-        encode::base58(xgen().gen24() as u128, 5)
+    TIME_MS.store(now_ms, Ordering::Release);
+    TIME_NS.store(now_ns, Ordering::Release);
+    TIME_SOURCE_SEQ.store(seq + 2, Ordering::Release);
+}
+
+/// The installed [`TimeSource`], if any, with both callbacks from the same
+/// [`AtomicOption::time_source`] call.
+#[inline]
+fn load_time_source() -> Option<TimeSource> {
+    loop {
+        let seq = TIME_SOURCE_SEQ.load(Ordering::Acquire);
+        if seq & 1 == 0 {
+            let (now_ms, now_ns) = (load_callback(&TIME_MS), load_callback(&TIME_NS));
+            if TIME_SOURCE_SEQ.load(Ordering::Relaxed) == seq {
+                return now_ms.zip(now_ns).map(|(now_ms, now_ns)| TimeSource { now_ms, now_ns });
+            }
+        }
+        core::hint::spin_loop();
     }
-    /// Generate a new 24-bit ID, encoded as a 4-character base91 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x24};
-    /// let id = AtomicId::<x24>::base91();
-    /// assert_eq!(id.len(), 4);
-    /// ```
-    pub fn base91() -> String {
-        encode::base91(xgen().gen24() as u128, 4)
+}
+
+/// Wall-clock milliseconds since UNIX_EPOCH, from the installed [`TimeSource`] or
+/// the system clock.
+fn now_ms() -> u64 {
+    if let Some(source) = load_time_source() {
+        return (source.now_ms)();
     }
-    /// Generate a new 24-bit ID, encoded as a 5-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x24};
-    /// let id = AtomicId::<x24>::base36();
-    /// assert_eq!(id.len(), 5);
-    /// ```
-    pub fn base36() -> String {
-        encode::base36(xgen().gen24() as u128, 5)
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    {
+        wasm::now_ms()
     }
-    /// Generate a new 24-bit ID, encoded as a 6-character hexadecimal string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x24};
-    /// let id = AtomicId::<x24>::hex();
-    /// assert_eq!(id.len(), 6);
-    /// ```
-    pub fn hex() -> String {
-        encode::hex(xgen().gen24() as u128, 6)
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    {
+        system_ns() / 1_000_000
     }
+    #[cfg(not(feature = "std"))]
+    panic!("atomic-id: no TimeSource installed; call AtomicOption::time_source first")
+}
 
-    /// Generate a batch of 24-bit IDs, encoded as base36 strings.
-    ///
-    /// # Arguments
-    /// * `n` - The number of IDs to generate.
-    ///
-    /// # Returns
-    /// A vector of base36-encoded ID strings.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x24};
-    /// let ids = AtomicId::<x24>::batch(3);
-    /// assert_eq!(ids.len(), 3);
-    /// ```
-    pub fn batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::new()).collect()
+/// Wall-clock nanoseconds since UNIX_EPOCH, from the installed [`TimeSource`] or
+/// the system clock.
+fn now_ns() -> u64 {
+    if let Some(source) = load_time_source() {
+        return (source.now_ns)();
     }
-    /// Generate a batch of 24-bit IDs as base58 strings.
-    pub fn base58_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base58()).collect()
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    {
+        wasm::now_ns()
     }
-    /// Generate a batch of 24-bit IDs as base91 strings.
-    pub fn base91_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base91()).collect()
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    {
+        system_ns()
     }
-    /// Generate a batch of 24-bit IDs as base36 strings.
-    pub fn base36_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base36()).collect()
+    #[cfg(not(feature = "std"))]
+    panic!("atomic-id: no TimeSource installed; call AtomicOption::time_source first")
+}
+
+/// Wall-clock milliseconds and nanoseconds since UNIX_EPOCH, as [`now_ms`] and
+/// [`now_ns`] would return them, but from a single reading of the system clock, so
+/// the two always agree about the millisecond. An installed [`TimeSource`] and the
+/// browser clocks are still called once each, both from the same source.
+fn now_ms_ns() -> (u64, u64) {
+    if let Some(source) = load_time_source() {
+        return ((source.now_ms)(), (source.now_ns)());
     }
-    /// Generate a batch of 24-bit IDs as hexadecimal strings.
-    pub fn hex_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::hex()).collect()
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    {
+        let ns = system_ns();
+        (ns / 1_000_000, ns)
     }
+    #[cfg(not(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32")))))]
+    (now_ms(), now_ns())
 }
 
-#[cfg(feature = "short")]
-impl AtomicId<32> {
-    /// Generate a new 32-bit ID, encoded as a 7-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x32};
-    /// let id = AtomicId::<x32>::new();
-    /// assert_eq!(id.len(), 7);
-    /// ```
-    pub fn new() -> String {
-        encode::base36(xgen().gen32() as u128, 7)
+/// Nanoseconds since UNIX_EPOCH from the system clock, read as the [`ClockMode`]
+/// says.
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+fn system_ns() -> u64 {
+    if ClockMode::current() == ClockMode::Wall {
+        return wall_ns();
     }
-    /// Generate a new 32-bit ID, encoded as a 6-character base58 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x32};
-    /// let id = AtomicId::<x32>::base58();
-    /// assert_eq!(id.len(), 6);
-    /// ```
-    pub fn base58() -> String {
-        encode::base58(xgen().gen32() as u128, 6)
+    let elapsed = CLOCK_BASE.get_or_init(Instant::now).elapsed().as_nanos() as u64;
+    let anchor = CLOCK_ANCHOR_NS.load(Ordering::Relaxed);
+    let interval = CLOCK_REANCHOR_NS.load(Ordering::Relaxed);
+    let since_anchoring = elapsed.saturating_sub(CLOCK_ANCHORED_AT_NS.load(Ordering::Relaxed));
+    if anchor == 0 || (interval != 0 && since_anchoring >= interval) {
+        return anchor_clock(elapsed) + elapsed;
     }
-    /// Generate a new 32-bit ID, encoded as a 5-character base91 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x32};
-    /// let id = AtomicId::<x32>::base91();
-    /// assert_eq!(id.len(), 5);
-    /// ```
-    pub fn base91() -> String {
-        encode::base91(xgen().gen32() as u128, 5)
+    anchor + elapsed
+}
+
+/// The wall clock, in nanoseconds since UNIX_EPOCH.
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+fn wall_ns() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64
+}
+
+/// Pair the wall clock with the monotonic clock `elapsed` nanoseconds after
+/// [`CLOCK_BASE`], and return the anchor. The anchor only moves forward: a wall
+/// clock that fell behind the anchored time is not followed back.
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+fn anchor_clock(elapsed: u64) -> u64 {
+    CLOCK_ANCHORED_AT_NS.store(elapsed, Ordering::Relaxed);
+    let anchor = wall_ns().saturating_sub(elapsed);
+    CLOCK_ANCHOR_NS.fetch_max(anchor, Ordering::Relaxed).max(anchor)
+}
+
+/// Forget the monotonic clock anchor, so the next anchored reading follows the wall
+/// clock again, even backwards. Used by the test helpers.
+#[cfg(any(test, feature = "testing"))]
+fn reset_clock_anchor() {
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    {
+        CLOCK_ANCHOR_NS.store(0, Ordering::Relaxed);
+        CLOCK_ANCHORED_AT_NS.store(0, Ordering::Relaxed);
     }
-    /// Generate a new 32-bit ID, encoded as a 7-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x32};
-    /// let id = AtomicId::<x32>::base36();
-    /// assert_eq!(id.len(), 7);
-    /// ```
-    pub fn base36() -> String {
-        encode::base36(xgen().gen32() as u128, 7)
+}
+
+/// Browser clocks for `wasm32-unknown-unknown`, where `SystemTime::now()` panics.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm {
+    use js_sys::{Date, Function, Reflect};
+
+    /// Milliseconds since UNIX_EPOCH from `Date.now()`.
+    pub fn now_ms() -> u64 {
+        Date::now() as u64
     }
-    /// Generate a new 32-bit ID, encoded as an 8-character hexadecimal string.
+
+    /// Nanoseconds since UNIX_EPOCH.
     ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x32};
-    /// let id = AtomicId::<x32>::hex();
-    /// assert_eq!(id.len(), 8);
-    /// ```
-    pub fn hex() -> String {
-        encode::hex(xgen().gen32() as u128, 8)
+    /// `Date.now()` only has millisecond resolution, so the sub-millisecond part is
+    /// taken from `performance.now()` when the host provides it (browsers and
+    /// workers do). Browsers coarsen that clock to between 5µs and 100µs, so the
+    /// low bits carry less entropy than on native targets.
+    pub fn now_ns() -> u64 {
+        let sub_ms = performance_now().map_or(0, |ms| (ms.fract() * 1_000_000.0) as u64);
+        now_ms() * 1_000_000 + sub_ms
     }
 
-    /// Generate a batch of 32-bit IDs, encoded as base36 strings.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x32};
-    /// let ids = AtomicId::<x32>::batch(3);
-    /// assert_eq!(ids.len(), 3);
-    /// ```
-    pub fn batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::new()).collect()
+    /// `performance.now()` on the global object, if present.
+    fn performance_now() -> Option<f64> {
+        let performance = Reflect::get(&js_sys::global(), &"performance".into()).ok()?;
+        let now = Reflect::get(&performance, &"now".into()).ok()?;
+        if !now.is_function() {
+            return None;
+        }
+        Function::from(now).call0(&performance).ok()?.as_f64()
     }
-    /// Generate a batch of 32-bit IDs as base58 strings.
-    pub fn base58_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base58()).collect()
+}
+
+/// The SplitMix64 finalizer: a bijection on `u64` that spreads nearby inputs across
+/// the whole range. Distinct inputs stay distinct, and only 0 maps to 0.
+///
+/// Used to spread [`AtomicOption::thread_entropy`] values (core numbers, task indices)
+/// across the thread ID range, and to scramble trace and span IDs.
+fn mix64(raw: u64) -> u64 {
+    let mut z = raw;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Fold `words` into 64 bits, most significant word first: starting from `seed`,
+/// each word is XORed into the state and the state is run through [`mix64`]. Works on
+/// the integer values, never on their bytes in memory, so the result is the same on
+/// every platform, and it is fixed across releases.
+fn fold_words(seed: u64, words: &[u64]) -> u64 {
+    words.iter().fold(seed, |state, &word| mix64(state ^ word))
+}
+
+/// Seed of the low half of [`Id256::fold128`]: the 64-bit golden ratio, so it differs
+/// from the zero seed of the high half.
+const FOLD128_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Key of [`mix256`]: the first hex digits of the fractional part of pi, so that its
+/// output is unrelated to every other use of [`mix64`] in the crate.
+const MIX256_KEY: [u64; 4] = [
+    0x243F_6A88_85A3_08D3,
+    0x1319_8A2E_0370_7344,
+    0xA409_3822_299F_31D0,
+    0x082E_FA98_EC4E_6C89,
+];
+
+/// Spread every bit of a 256-bit value across all 256 output bits.
+///
+/// Each step replaces one part with [`mix64`] of itself, its neighbour, and a key
+/// word. The neighbour is left unchanged by that step, so each step and the whole
+/// permutation are bijections. After three rounds every output part depends on
+/// every input part.
+fn mix256(mut parts: [u64; 4]) -> [u64; 4] {
+    for _ in 0..3 {
+        for i in 0..4 {
+            parts[i] = mix64(parts[i] ^ parts[(i + 1) % 4] ^ MIX256_KEY[i]);
+        }
     }
-    /// Generate a batch of 32-bit IDs as base91 strings.
-    pub fn base91_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base91()).collect()
+    parts
+}
+
+/// Round keys of [`scatter64`]: the hex digits of pi that follow [`MIX256_KEY`].
+const SCATTER64_KEYS: [u64; 4] = [
+    0x4528_21E6_38D0_1377,
+    0xBE54_66CF_34E9_0C6C,
+    0xC0AC_29B7_C97C_50DD,
+    0x3F84_D5B5_B547_0917,
+];
+
+/// A four-round Feistel network over the two 32-bit halves of `id`, with [`mix64`]
+/// as the round function. Each round swaps the halves and mixes one into the other,
+/// which is undone by [`unscatter64`] whatever the round function, so this is a
+/// bijection; four rounds spread every input bit over every output bit.
+fn scatter64(id: u64) -> u64 {
+    let (mut left, mut right) = ((id >> 32) as u32, id as u32);
+    for key in SCATTER64_KEYS {
+        (left, right) = (right, left ^ mix64(right as u64 ^ key) as u32);
     }
-    /// Generate a batch of 32-bit IDs as base36 strings.
-    pub fn base36_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base36()).collect()
+    ((left as u64) << 32) | right as u64
+}
+
+/// Reverse [`scatter64`], running its rounds backwards.
+fn unscatter64(id: u64) -> u64 {
+    let (mut left, mut right) = ((id >> 32) as u32, id as u32);
+    for key in SCATTER64_KEYS.iter().rev() {
+        (left, right) = (right ^ mix64(left as u64 ^ key) as u32, left);
+    }
+    ((left as u64) << 32) | right as u64
+}
+
+/// Mix the [`AtomicOption::thread_id_salt`] into thread entropy. A salt of 0 leaves
+/// the entropy as it is.
+#[inline]
+fn salt_thread_entropy(entropy: u64) -> u64 {
+    entropy ^ mix64(THREAD_ID_SALT.load(Ordering::Relaxed))
+}
+
+/// Entropy for the current thread's hashed thread ID, from its `ThreadId`: hashed with
+/// SipHash by default, or with the `fast_thread_hash` feature taken as a number and
+/// spread by [`mix64`], which is cheaper and spreads consecutive `ThreadId`s evenly.
+#[cfg(feature = "std")]
+fn thread_id_entropy() -> u64 {
+    #[cfg(feature = "fast_thread_hash")]
+    let mut hasher = hash::MixHasher(0);
+    #[cfg(not(feature = "fast_thread_hash"))]
+    let mut hasher = DefaultHasher::new();
+    std::thread::current().id().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fold thread entropy into a hashed thread ID of `bits` bits, using every value in
+/// `0..2^bits`. With 8 bits this is a full byte, as in [`IdGenerator::gen32`].
+#[inline]
+fn fold_thread_id_full(entropy: u64, bits: u32) -> u16 {
+    (entropy & ((1 << bits) - 1)) as u16
+}
+
+/// Fold thread entropy into a hashed thread ID of `bits` bits, in the range
+/// `1..=2^(bits - 1)`. With 8 bits this is the classic 1..=128.
+#[inline]
+fn fold_thread_id(entropy: u64, bits: u32) -> u16 {
+    ((entropy & ((1 << (bits - 1)) - 1)) + 1) as u16
+}
+
+/// The current 64-bit thread ID width.
+#[inline]
+fn thread_id_bits() -> u32 {
+    THREAD_ID_BITS.load(Ordering::Relaxed) as u32
+}
+
+/// Whether IDs carry a layout version field.
+#[inline]
+fn version_bits() -> bool {
+    VERSION_BITS_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Width of the [`IdGenerator::gen128_tagged`] tag: the reserved bits the layout
+/// version leaves free.
+#[inline]
+fn tag_bits128() -> u32 {
+    if version_bits() { 4 - VERSION_BITS } else { 4 }
+}
+
+/// Width of the 64-bit sequence field for a `thread_bits`-bit thread ID field.
+#[inline]
+fn seq_bits64(thread_bits: u32) -> u32 {
+    let version_bits = if version_bits() { VERSION_BITS } else { 0 };
+    THREAD_SEQ_BITS_64 - version_bits - thread_bits
+}
+
+/// Width of the sequence field of [`IdGenerator::gen64_versioned`] IDs for a
+/// `thread_bits`-bit thread ID field.
+#[inline]
+fn versioned_seq_bits64(thread_bits: u32) -> u32 {
+    THREAD_SEQ_BITS_64 - VERSION_BITS - FORMAT_BITS_64 - thread_bits
+}
+
+/// The [`AtomicOption::max_rate_per_ms`] limit in force, or 0 for none: the limit
+/// set, clamped to the 64-bit sequence values one millisecond holds under the
+/// current [`AtomicOption::thread_id_bits`] and [`TimestampPrecision`].
+fn max_rate64() -> u32 {
+    let limit = MAX_RATE_64.load(Ordering::Relaxed);
+    if limit == 0 {
+        return 0;
+    }
+    let precision = TimestampPrecision::current();
+    let per_tick = 1u64 << precision.sequence_bits_for(seq_bits64(thread_id_bits())).min(63);
+    let per_ms = match precision {
+        TimestampPrecision::Millis => per_tick,
+        TimestampPrecision::Micros => per_tick.saturating_mul(1000),
+        TimestampPrecision::Seconds => (per_tick / 1000).max(1),
+    };
+    (limit as u64).min(per_ms) as u32
+}
+
+/// Bits below the sequence field of a 64-bit ID with the given layout version.
+#[inline]
+fn version_field_bits64(version: u8) -> u32 {
+    match version {
+        LAYOUT_VERSION => VERSION_BITS,
+        FORMAT_LAYOUT_VERSION => VERSION_BITS + FORMAT_BITS_64,
+        _ => 0,
+    }
+}
+
+/// Split a time in `precision` ticks and a sequence value into the value of the
+/// layout's time fields and the value of the [`seq_bits64`]-bit sequence field.
+///
+/// The ticks and the sequence are joined into one number, ticks above the
+/// precision's [`TimestampPrecision::sequence_bits`] of sequence, which is cut at the
+/// field boundary instead: in `Micros` the low tick bits spill into the top of the
+/// sequence field, from where [`raise_ticks64`] moves them above the node fields,
+/// and in `Seconds` the high sequence bits into the bottom of the time fields. In
+/// `Millis` the two line up with the fields.
+#[inline]
+fn pack_ticks64(precision: TimestampPrecision, layout: Layout64, ticks: u64, seq: u64, thread_bits: u32) -> (u64, u64) {
+    pack_ticks64_in(precision, layout, ticks, seq, seq_bits64(thread_bits))
+}
+
+/// [`pack_ticks64`] for a sequence field of `field_bits` bits.
+#[inline]
+fn pack_ticks64_in(precision: TimestampPrecision, layout: Layout64, ticks: u64, seq: u64, field_bits: u32) -> (u64, u64) {
+    let seq_bits = precision.sequence_bits_for(field_bits);
+    let ticks = ticks & ((1 << precision.timestamp_bits_for(layout, field_bits)) - 1);
+    let joined = (ticks << seq_bits) | (seq & ((1 << seq_bits) - 1));
+    (joined >> field_bits, joined & ((1 << field_bits) - 1))
+}
+
+/// Reverse [`pack_ticks64`]: the ticks and sequence from the value of the time fields
+/// and of the `field_bits`-bit sequence field.
+#[inline]
+fn unpack_ticks64(precision: TimestampPrecision, time: u64, seq_field: u64, field_bits: u32) -> (u64, u64) {
+    let seq_bits = precision.sequence_bits_for(field_bits);
+    let joined = (time << field_bits) | seq_field;
+    (joined >> seq_bits, joined & ((1 << seq_bits) - 1))
+}
+
+/// The bits of a packed 64-bit ID that [`raise_ticks64`] rotates, as the offset and
+/// width of the block from the bottom of the low tick bits to the top of the node
+/// fields, and the number of low tick bits. `None` outside `Micros`.
+#[inline]
+fn tick_block64(precision: TimestampPrecision, layout: Layout64, field_bits: u32, low_bits: u32) -> Option<(u32, u32, u32)> {
+    let shift = precision.shift(field_bits);
+    if shift <= 0 {
+        return None;
+    }
+    let offset = low_bits + field_bits - shift as u32;
+    Some((offset, 64 - layout.timestamp_bits() - offset, shift as u32))
+}
+
+/// Move the low tick bits that [`pack_ticks64`] leaves at the top of the
+/// `field_bits`-bit sequence field in `Micros` up to just below the time fields, so
+/// every timestamp bit sits above the region, node, shard, and thread, which move
+/// down to make room. `low_bits` are the bits below the sequence field. IDs in
+/// other precisions are returned unchanged.
+#[inline]
+fn raise_ticks64(precision: TimestampPrecision, layout: Layout64, id: u64, field_bits: u32, low_bits: u32) -> u64 {
+    let Some((offset, width, shift)) = tick_block64(precision, layout, field_bits, low_bits) else {
+        return id;
+    };
+    let mask = (1 << width) - 1;
+    let block = (id >> offset) & mask;
+    let block = (block & ((1 << shift) - 1)) << (width - shift) | block >> shift;
+    (id & !(mask << offset)) | block << offset
+}
+
+/// Reverse [`raise_ticks64`].
+#[inline]
+fn lower_ticks64(precision: TimestampPrecision, layout: Layout64, id: u64, field_bits: u32, low_bits: u32) -> u64 {
+    let Some((offset, width, shift)) = tick_block64(precision, layout, field_bits, low_bits) else {
+        return id;
+    };
+    let mask = (1 << width) - 1;
+    let block = (id >> offset) & mask;
+    let block = block >> (width - shift) | (block & ((1 << (width - shift)) - 1)) << shift;
+    (id & !(mask << offset)) | block << offset
+}
+
+/// Split a 64-bit ID in the current layout, refusing reserved layout versions whose
+/// fields this release cannot know.
+fn known_parts64(id: u64) -> Result<Id64Parts, IdError> {
+    match IdGenerator::layout_version(id) {
+        0 | LAYOUT_VERSION => Ok(Layout64::current().decompose(id)),
+        FORMAT_LAYOUT_VERSION => IdGenerator::decompose_auto(id),
+        version => Err(IdError::UnknownLayoutVersion(version)),
+    }
+}
+
+/// The upper and lower 64 bits of a 128-bit ID as 16-character hexadecimal strings.
+fn hex_halves(id: u128) -> (String, String) {
+    (encode::hex(id >> 64, 16), encode::hex(id as u64 as u128, 16))
+}
+
+/// Decode a base36 string into a raw 64-bit ID.
+fn decode_base36_64(s: &str) -> Result<u64, IdError> {
+    u64::try_from(encode::from_base36(s)?).map_err(|_| IdError::Overflow)
+}
+
+/// Pack a thread ID and sequence into the low 24 bits shared by all 64-bit layouts:
+/// `thread_bits` bits of thread ID above [`seq_bits64`] bits of sequence, followed by
+/// the layout version when version bits are enabled.
+#[inline]
+fn thread_seq64(thread_id: u16, seq: u64, thread_bits: u32) -> u64 {
+    let seq_bits = seq_bits64(thread_bits);
+    let thread_mask = (1u64 << thread_bits) - 1;
+    let thread_seq = (((thread_id as u64) & thread_mask) << seq_bits) | (seq & ((1 << seq_bits) - 1));
+    if version_bits() {
+        (thread_seq << VERSION_BITS) | LAYOUT_VERSION as u64
+    } else {
+        thread_seq
+    }
+}
+
+/// Pass a newly generated ID to the [`AtomicOption::on_generate`] hook, if any.
+#[inline]
+fn notify_generate(bits: usize, raw: u128) {
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    persist::tick();
+    #[cfg(feature = "debug_checks")]
+    if bits == 64 {
+        debug_checks::check(raw as u64);
+    }
+    let ptr = GENERATE_HOOK.load(Ordering::Acquire);
+    if ptr.is_null() {
+        return;
+    }
+    // SAFETY: `GENERATE_HOOK` is only ever set from a valid `fn(usize, u128)` in
+    // `AtomicOption::on_generate`, and function pointers never dangle.
+    let hook = unsafe { core::mem::transmute::<*mut (), fn(usize, u128)>(ptr) };
+    hook(bits, raw);
+}
+
+/// Pass a newly generated 256-bit ID to the [`AtomicOption::on_generate`] hook, with
+/// its first two parts, and whole to the [`AtomicOption::on_generate256`] hook.
+#[inline]
+fn notify_generate256(id: &[u64; 4]) {
+    notify_generate(256, ((id[0] as u128) << 64) | id[1] as u128);
+    let ptr = GENERATE_HOOK_256.load(Ordering::Acquire);
+    if ptr.is_null() {
+        return;
+    }
+    // SAFETY: `GENERATE_HOOK_256` is only ever set from a valid `fn([u64; 4])` in
+    // `AtomicOption::on_generate256`, and function pointers never dangle.
+    let hook = unsafe { core::mem::transmute::<*mut (), fn([u64; 4])>(ptr) };
+    hook(*id);
+}
+
+/// Deliver an event to the installed hook, if any.
+///
+/// This is a single atomic load when no hook is installed. Events raised while the
+/// hook is already running on this thread are dropped. Without `std` there is no
+/// per-thread state, so the hook must not generate IDs that could raise events.
+fn emit(event: IdEvent) {
+    let ptr = EVENT_HOOK.load(Ordering::Acquire);
+    if ptr.is_null() {
+        return;
+    }
+    // SAFETY: `EVENT_HOOK` is only ever set from a valid `fn(IdEvent)` in
+    // `AtomicOption::on_event`, and function pointers never dangle.
+    let hook = unsafe { core::mem::transmute::<*mut (), fn(IdEvent)>(ptr) };
+
+    #[cfg(not(feature = "std"))]
+    hook(event);
+
+    #[cfg(feature = "std")]
+    {
+        struct Reset<'a>(&'a Cell<bool>);
+        impl Drop for Reset<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+
+        let _ = IN_EVENT_HOOK.try_with(|running| {
+            if running.replace(true) {
+                return;
+            }
+            let _reset = Reset(running);
+            hook(event);
+        });
+    }
+}
+
+/// Milliseconds until a `bits`-wide timestamp field holding `ts` wraps to zero.
+#[inline]
+fn ms_until_wrap(ts: u64, bits: u32) -> u64 {
+    (1 << bits) - (ts & ((1 << bits) - 1))
+}
+
+/// Whether `remaining_ms` is within the configured wrap threshold of a `bits`-wide
+/// timestamp field.
+#[inline]
+fn near_wrap(remaining_ms: u64, bits: u32) -> bool {
+    let percent = WRAP_THRESHOLD_PERCENT.load(Ordering::Relaxed) as u64;
+    remaining_ms < ((1 << bits) * percent) / 100
+}
+
+/// Apply the [`OverflowMode`] to a timestamp for a `bits`-wide field. Only
+/// [`OverflowMode::Saturate`] changes it; wrapping is left to the field masks.
+#[inline]
+fn overflow_timestamp(ts: u64, bits: u32) -> u64 {
+    match OverflowMode::current() {
+        OverflowMode::Saturate => ts.min((1 << bits) - 1),
+        OverflowMode::Wrap | OverflowMode::Error => ts,
+    }
+}
+
+/// Report [`IdEvent::TimestampNearWrap`] once per period when a 64-bit timestamp, in
+/// ticks of the [`TimestampPrecision`], is within the wrap threshold of the layout's
+/// time fields. Only fields that wrap are checked: in [`OverflowMode::Wrap`], and in
+/// [`OverflowMode::Error`], which the infallible generators cannot follow.
+#[inline]
+fn check_timestamp_wrap64(layout: Layout64, ticks: u64) {
+    if OverflowMode::current() == OverflowMode::Saturate {
+        return;
+    }
+    let precision = TimestampPrecision::current();
+    let bits = precision.timestamp_bits(layout);
+    let remaining = ms_until_wrap(ticks, bits);
+    if near_wrap(remaining, bits) {
+        let period = ticks >> bits;
+        if WRAP_WARNED_PERIOD_64.swap(period, Ordering::Relaxed) != period {
+            emit(IdEvent::TimestampNearWrap {
+                width: 64,
+                remaining_ms: precision.ticks_to_ms(remaining),
+            });
+        }
+    }
+}
+
+/// Report [`IdEvent::SequenceExhausted`] when a timestamped sequence field has gone
+/// through all `mask + 1` of its values since the first ID of tick `ts`, i.e. `seq`
+/// repeats the sequence field of that ID. Reported once per tick.
+#[inline]
+fn check_sequence_wrap(width: usize, seq: u64, mask: u64, ts: u64, tick: &TickStart) {
+    if tick.ts.load(Ordering::Relaxed) != ts {
+        if tick.ts.swap(ts, Ordering::Relaxed) != ts {
+            tick.seq.store(seq, Ordering::Relaxed);
+        }
+    } else if seq.wrapping_sub(tick.seq.load(Ordering::Relaxed)) == mask + 1 {
+        emit(IdEvent::SequenceExhausted { width });
+    }
+}
+
+/// The core generator struct for producing unique IDs.
+///
+/// This struct holds node and shard identifiers, which are incorporated into
+/// 64, 128, and 256-bit IDs to ensure uniqueness in a distributed environment.
+///
+/// While you can create an `IdGenerator` instance, the library is designed
+/// to be used through the static methods on [`AtomicId`], which manage a global generator.
+///
+/// # Epoch
+///
+/// A generator measures timestamps from the global epoch set with
+/// [`AtomicOption::try_epoch`], unless it was built with its own epoch through
+/// [`IdGenerator::builder`]. That lets one process host tenants with different epoch
+/// conventions. IDs from generators with different epochs share the sequence
+/// counters, so they stay unique, but their timestamps are not comparable: decode
+/// each ID with the epoch of the generator that made it ([`IdGenerator::epoch_ms`]).
+///
+/// # Thread Safety
+///
+/// `IdGenerator` is `Send` and `Sync`, and this is checked at compile time. Every
+/// method takes `&self`, and all mutable state lives in global atomics, so one
+/// instance can be shared freely between threads. The sequence counters use
+/// `Relaxed` ordering, which is enough for uniqueness because each `fetch_add`
+/// returns a distinct value.
+pub struct IdGenerator {
+    /// Node identifier (0-4095), used in 64, 128, and 256-bit IDs.
+    pub node_id: u16,
+    /// Shard identifier (0-255), used in 64, 128, and 256-bit IDs.
+    pub shard_id: u8,
+    /// This generator's epoch in milliseconds since UNIX_EPOCH, or `None` to follow
+    /// the global epoch.
+    epoch: Option<u64>,
+    /// This generator's region (0-15), or `None` to follow the global region.
+    region: Option<u8>,
+    /// This generator's 32-bit layout, or `None` to follow the global layout.
+    layout32: Option<Layout32>,
+}
+
+/// Builder for an [`IdGenerator`] with non-default settings, from [`IdGenerator::builder`].
+///
+/// # Example
+/// ```
+/// use atomic_id::IdGenerator;
+/// let tenant = IdGenerator::builder().node_id(7).epoch_ms(1704067200000).build();
+/// assert_eq!((tenant.node_id, tenant.epoch_ms()), (7, 1704067200000));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdGeneratorBuilder {
+    node_id: u16,
+    shard_id: u8,
+    epoch: Option<u64>,
+    region: Option<u8>,
+    layout32: Option<Layout32>,
+}
+
+impl IdGeneratorBuilder {
+    /// Set the node ID (0..=4095). Defaults to 0.
+    pub const fn node_id(mut self, node_id: u16) -> Self {
+        self.node_id = node_id;
+        self
+    }
+
+    /// Set the shard ID (0..=255). Defaults to 0.
+    pub const fn shard_id(mut self, shard_id: u8) -> Self {
+        self.shard_id = shard_id;
+        self
+    }
+
+    /// Measure this generator's timestamps from `ms` milliseconds since UNIX_EPOCH
+    /// instead of the global epoch.
+    ///
+    /// The global epoch freeze (see [`AtomicOption::try_epoch`]) does not apply: the
+    /// epoch of a generator is fixed when it is built. It also overrides the
+    /// `const_epoch` feature, which only fixes the global epoch.
+    pub const fn epoch_ms(mut self, ms: u64) -> Self {
+        self.epoch = Some(ms);
+        self
+    }
+
+    /// Set the region (0..=15) for [`IdGenerator::gen64_regional`] instead of the
+    /// global region of [`AtomicOption::region_id`].
+    ///
+    /// # Panics
+    /// If `region_id` is greater than 15, at compile time when used in a `const`.
+    pub const fn region_id(mut self, region_id: u8) -> Self {
+        assert!(region_id <= MAX_REGION_ID, "region ID out of range (0..=15)");
+        self.region = Some(region_id);
+        self
+    }
+
+    /// Set the layout of [`IdGenerator::gen32`] IDs instead of the global layout of
+    /// [`AtomicOption::layout32`].
+    pub const fn layout32(mut self, layout: Layout32) -> Self {
+        self.layout32 = Some(layout);
+        self
+    }
+
+    /// Build the generator.
+    pub const fn build(self) -> IdGenerator {
+        IdGenerator {
+            node_id: self.node_id,
+            shard_id: self.shard_id,
+            epoch: self.epoch,
+            region: self.region,
+            layout32: self.layout32,
+        }
+    }
+}
+
+// Fail the build if a future change makes the generator unshareable across threads.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<IdGenerator>();
+    assert_send_sync::<TimeSource>();
+};
+
+impl IdGenerator {
+    /// Create a new generator with the given node and shard IDs.
+    ///
+    /// # Arguments
+    /// * `node_id` - Node identifier (0..=4095).
+    /// * `shard_id` - Shard identifier (0..=255).
+    ///
+    /// # Returns
+    /// A new [`IdGenerator`] instance.
+    pub const fn new(node_id: u16, shard_id: u8) -> Self {
+        Self {
+            node_id,
+            shard_id,
+            epoch: None,
+            region: None,
+            layout32: None,
+        }
+    }
+
+    /// Start building a generator with its own epoch; see [`IdGeneratorBuilder`].
+    pub const fn builder() -> IdGeneratorBuilder {
+        IdGeneratorBuilder {
+            node_id: 0,
+            shard_id: 0,
+            epoch: None,
+            region: None,
+            layout32: None,
+        }
+    }
+
+    /// The epoch this generator measures timestamps from, in milliseconds since
+    /// UNIX_EPOCH: its own, if it was built with one, otherwise the global epoch.
+    pub fn epoch_ms(&self) -> u64 {
+        self.epoch.unwrap_or_else(current_epoch)
+    }
+
+    /// The region this generator writes into [`IdGenerator::gen64_regional`] IDs: its
+    /// own, if it was built with one, otherwise the global [`AtomicOption::region_id`].
+    pub fn region_id(&self) -> u8 {
+        self.region.unwrap_or_else(|| REGION_ID.load(Ordering::Relaxed))
+    }
+
+    /// The layout of this generator's [`IdGenerator::gen32`] IDs: its own, if it was
+    /// built with one, otherwise the global [`AtomicOption::layout32`].
+    pub fn layout32(&self) -> Layout32 {
+        self.layout32.unwrap_or_else(Layout32::current)
+    }
+
+    /// Get the current timestamp in milliseconds, relative to this generator's epoch.
+    ///
+    /// # Returns
+    /// Milliseconds since [`IdGenerator::epoch_ms`].
+    fn timestamp(&self) -> u64 {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(ts) = testing::deterministic_timestamp() {
+            return ts;
+        }
+        self.timestamp_at(now_ms())
+    }
+
+    /// The timestamp [`IdGenerator::timestamp`] would return, without recording it
+    /// as the latest one, reporting a clock rollback, or advancing the deterministic
+    /// clock.
+    fn peek_timestamp(&self) -> u64 {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(ts) = testing::peek_deterministic_timestamp() {
+            return ts;
+        }
+        now_ms().saturating_sub(self.epoch_ms())
+    }
+
+    /// Get the timestamp and the nanoseconds since the UNIX epoch for a new 128 or
+    /// 256-bit ID from one clock reading, freezing the epoch like
+    /// [`IdGenerator::id_timestamp`].
+    ///
+    /// Reading the clock once instead of for each value halves the clock calls of
+    /// these widths; the values are the same as from separate reads in the same
+    /// nanosecond.
+    #[inline]
+    fn id_timestamp_nanos(&self) -> (u64, u64) {
+        self.freeze_epoch();
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(ts) = testing::deterministic_timestamp() {
+            return (ts, self.nanos());
+        }
+        let (now, nanos) = now_ms_ns();
+        (self.timestamp_at(now), nanos)
+    }
+
+    /// Convert `now`, in milliseconds since UNIX_EPOCH, to a timestamp relative to
+    /// this generator's epoch, recording it as the latest clock reading.
+    #[inline]
+    fn timestamp_at(&self, now: u64) -> u64 {
+        // Only write when the millisecond advances, so the common path is a plain load.
+        let last = LAST_TIMESTAMP.load(Ordering::Relaxed);
+        if now > last {
+            LAST_TIMESTAMP.fetch_max(now, Ordering::Relaxed);
+        } else if now < last {
+            CLOCK_ROLLBACKS.fetch_add(1, Ordering::Relaxed);
+            emit(IdEvent::ClockRollback {
+                delta_ms: last - now,
+            });
+        }
+        now.saturating_sub(self.epoch_ms())
+    }
+
+    /// Get the timestamp for a new ID, freezing the epoch.
+    ///
+    /// Every generating method reads its timestamp through here, so that the epoch
+    /// cannot silently change meaning once an ID carries it.
+    #[inline]
+    fn id_timestamp(&self) -> u64 {
+        self.freeze_epoch();
+        self.timestamp()
+    }
+
+    /// Get the timestamp for a new 64-bit ID, as [`IdGenerator::id_timestamp`] does,
+    /// once [`AtomicOption::max_rate_per_ms`] allows another ID in its millisecond.
+    ///
+    /// While the current millisecond is full this spins briefly, then yields, and
+    /// reads the clock again until the millisecond changes. A thread that read the
+    /// clock just before another moved the count on to a newer millisecond reads it
+    /// again instead of reopening the old one; only if it is still behind, because
+    /// the clock stepped back, does counting restart at its millisecond.
+    fn id_timestamp64(&self) -> u64 {
+        self.id_timestamp64_n(1)
+    }
+
+    /// [`IdGenerator::id_timestamp64`] for `n` IDs at once, which all count against
+    /// the limit. A window that is still empty takes them even past the limit.
+    fn id_timestamp64_n(&self, n: u32) -> u64 {
+        let mut ts = self.id_timestamp();
+        let limit = max_rate64();
+        if limit == 0 {
+            return ts;
+        }
+        let (mut spins, mut stale) = (0u32, false);
+        loop {
+            let ms = ts.wrapping_add(self.epoch_ms()) as u32;
+            let claimed = RATE_WINDOW_64.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |window| {
+                let (window_ms, count) = ((window >> 32) as u32, window as u32);
+                let ahead = ms.wrapping_sub(window_ms) as i32;
+                if count == 0 || ahead > 0 || (ahead < 0 && stale) {
+                    Some((ms as u64) << 32 | n as u64)
+                } else if ahead == 0 && count as u64 + n as u64 <= limit as u64 {
+                    Some(window + n as u64)
+                } else {
+                    None
+                }
+            });
+            match claimed {
+                Ok(_) => return ts,
+                Err(window) if (window >> 32) as u32 != ms => stale = true,
+                Err(_) if spins < 64 => {
+                    spins += 1;
+                    core::hint::spin_loop();
+                }
+                Err(_) => {
+                    #[cfg(feature = "std")]
+                    std::thread::yield_now();
+                    #[cfg(not(feature = "std"))]
+                    core::hint::spin_loop();
+                }
+            }
+            ts = self.id_timestamp();
+        }
+    }
+
+    /// Freeze the epoch, as the first timestamp-based ID does.
+    #[inline]
+    fn freeze_epoch(&self) {
+        if CUSTOM_EPOCH.load(Ordering::Relaxed) & EPOCH_FROZEN == 0 {
+            CUSTOM_EPOCH.fetch_or(EPOCH_FROZEN, Ordering::Relaxed);
+        }
+    }
+
+    /// Convert `ms`, a timestamp from [`IdGenerator::timestamp`], to ticks of
+    /// `precision`. Microseconds come from the nanosecond clock, kept within `ms` so
+    /// the two clock reads cannot disagree about the millisecond.
+    fn ticks_at(&self, precision: TimestampPrecision, ms: u64) -> u64 {
+        self.ticks_with(precision, ms, || self.nanos())
+    }
+
+    /// [`IdGenerator::ticks_at`] reading the nanosecond clock through `nanos`, and
+    /// only at microsecond precision.
+    fn ticks_with(&self, precision: TimestampPrecision, ms: u64, nanos: impl FnOnce() -> u64) -> u64 {
+        match precision {
+            TimestampPrecision::Millis => ms,
+            TimestampPrecision::Seconds => ms / 1000,
+            TimestampPrecision::Micros => {
+                let now_ms = ms + self.epoch_ms();
+                let nanos = nanos();
+                let sub_ms = match (nanos / 1_000_000).cmp(&now_ms) {
+                    core::cmp::Ordering::Less => 0,
+                    core::cmp::Ordering::Equal => nanos / 1000 % 1000,
+                    core::cmp::Ordering::Greater => 999,
+                };
+                ms * 1000 + sub_ms
+            }
+        }
+    }
+
+    /// Get the current timestamp in nanoseconds since the UNIX epoch.
+    ///
+    /// # Returns
+    /// Nanoseconds since UNIX_EPOCH as a `u64`.
+    fn nanos(&self) -> u64 {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(nanos) = testing::deterministic_nanos() {
+            return nanos;
+        }
+        now_ns()
+    }
+
+    /// The nanoseconds [`IdGenerator::nanos`] would return, without drawing from the
+    /// deterministic PRNG.
+    fn peek_nanos(&self) -> u64 {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(nanos) = testing::peek_deterministic_nanos() {
+            return nanos;
+        }
+        now_ns()
+    }
+
+    /// Get or assign a unique ID for the current thread.
+    ///
+    /// This method provides a thread-local ID from 1 to 128, which is used
+    /// as a source of entropy in ID generation. It is the 8-bit case of
+    /// [`IdGenerator::thread_id_in`], used by the 128 and 256-bit IDs.
+    ///
+    /// # Returns
+    /// A thread-local unique ID in the range 1..=128.
+    fn thread_id(&self) -> u8 {
+        self.thread_id_in(8) as u8
+    }
+
+    /// Get the current thread's ID as a full byte, `0..=255` when hashed and
+    /// `1..=255` in [`ThreadIdMode::Sequential`]. Used by [`IdGenerator::gen32`].
+    fn thread_byte(&self) -> u8 {
+        self.thread_id_folded(8, fold_thread_id_full) as u8
+    }
+
+    /// Get the current thread's ID for a thread field of `bits` bits.
+    ///
+    /// In [`ThreadIdMode::Hashed`] the ID is folded from per-thread entropy into
+    /// `1..=2^(bits - 1)`. The entropy comes from the installed
+    /// [`AtomicOption::thread_entropy`] provider, or a hash of the thread's ID.
+    /// In [`ThreadIdMode::Sequential`] threads are numbered in the order they first
+    /// generate an ID, cycling through `1..=2^bits - 1`.
+    ///
+    /// Without `std` there is nowhere to cache anything, so the provider is called on
+    /// every use, sequential mode is unavailable, and the ID is `1` when no provider is
+    /// installed. The same fallback of `1` applies with the `wasm` feature on `wasm32`,
+    /// which runs on a single thread.
+    fn thread_id_in(&self, bits: u32) -> u16 {
+        self.thread_id_folded(bits, fold_thread_id)
+    }
+
+    /// [`IdGenerator::thread_id_in`] with hashed IDs folded from the thread's entropy
+    /// by `fold`.
+    fn thread_id_folded(&self, bits: u32, fold: fn(u64, u32) -> u16) -> u16 {
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(entropy) = testing::deterministic_thread_entropy() {
+            return fold(entropy, bits);
+        }
+        #[cfg(feature = "std")]
+        {
+            let generation = THREAD_CACHE_GENERATION.load(Ordering::Relaxed);
+            if ThreadIdMode::current() == ThreadIdMode::Sequential {
+                let number = THREAD_NUMBER.with(|slot| {
+                    let (assigned_in, number) = slot.get();
+                    if number != 0 && assigned_in == generation {
+                        return number;
+                    }
+                    let number = NEXT_THREAD_NUMBER.fetch_add(1, Ordering::Relaxed);
+                    slot.set((generation, number));
+                    number
+                });
+                return ((number - 1) % ((1 << bits) - 1) + 1) as u16;
+            }
+            let entropy = THREAD_ENTROPY_CACHE.with(|cache| {
+                if let Some((computed_in, entropy)) = cache.get()
+                    && computed_in == generation
+                {
+                    return entropy;
+                }
+                let entropy = match load_callback(&THREAD_ENTROPY) {
+                    Some(provider) => salt_thread_entropy(mix64(provider())),
+                    None if cfg!(all(feature = "wasm", target_arch = "wasm32")) => 0,
+                    None => salt_thread_entropy(thread_id_entropy()),
+                };
+                cache.set(Some((generation, entropy)));
+                entropy
+            });
+            fold(entropy, bits)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let entropy = load_callback(&THREAD_ENTROPY).map_or(0, |p| salt_thread_entropy(mix64(p())));
+            fold(entropy, bits)
+        }
+    }
+
+    /// Generate a 24-bit unique ID.
+    ///
+    /// The ID is generated from a single atomic counter that wraps around.
+    /// It is suitable for short-lived, high-throughput scenarios where a small ID is needed.
+    ///
+    /// - **Structure**: 24 bits for the sequence.
+    /// - **Uniqueness**: Guarantees up to 16.7 million (2^24) unique IDs before the counter wraps around.
+    ///
+    /// After the counter wraps, IDs repeat silently and [`IdEvent::SequenceExhausted`]
+    /// is reported. Use [`IdGenerator::try_gen24`] to stop at the wrap instead, or
+    /// [`Layout24::TimeSalted`] to spread repeats out over time.
+    ///
+    /// # Returns
+    /// A 24-bit unique ID as a `u32`.
+    pub fn gen24(&self) -> u32 {
+        let seq = SEQ_24.fetch_add(1, Ordering::Relaxed);
+        self.compose24(seq)
+    }
+
+    /// Generate a 24-bit ID, following the [`AtomicOption::overflow_policy24`] once
+    /// the sequence is used up.
+    ///
+    /// The sequence is used up after [`Layout24::sequence_capacity`] IDs since the last
+    /// [`AtomicOption::reset24`] (or since startup). With [`OverflowPolicy::Wrap`]
+    /// this is [`IdGenerator::gen24`]. With [`OverflowPolicy::Error`] it returns
+    /// [`SequenceExhausted`] without consuming a sequence value, and with
+    /// [`OverflowPolicy::Block`] it waits, spinning, until another thread calls
+    /// [`AtomicOption::reset24`]. IDs from [`IdGenerator::gen24`] count against the
+    /// same sequence. In [`Layout24::TimeSalted`] the count ignores the time bits, so
+    /// it errs on the side of stopping early.
+    ///
+    /// # Errors
+    /// [`SequenceExhausted`] with [`OverflowPolicy::Error`] once the sequence is used up.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdGenerator, OverflowPolicy};
+    /// AtomicOption::overflow_policy24(OverflowPolicy::Error);
+    /// let generator = IdGenerator::new(0, 0);
+    /// match generator.try_gen24() {
+    ///     Ok(id) => assert!(id < 1 << 24),
+    ///     Err(_) => AtomicOption::reset24(),
+    /// }
+    /// ```
+    pub fn try_gen24(&self) -> Result<u32, SequenceExhausted> {
+        let policy = OverflowPolicy::current();
+        if policy == OverflowPolicy::Wrap {
+            return Ok(self.gen24());
+        }
+        loop {
+            let capacity = Layout24::current().sequence_capacity();
+            let claimed = SEQ_24.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |seq| {
+                let used = seq.wrapping_sub(SEQ_24_RESET_AT.load(Ordering::Relaxed));
+                (used < capacity).then_some(seq + 1)
+            });
+            match (claimed, policy) {
+                (Ok(seq), _) => return Ok(self.compose24(seq)),
+                (Err(_), OverflowPolicy::Block) => {
+                    #[cfg(feature = "std")]
+                    std::thread::yield_now();
+                    #[cfg(not(feature = "std"))]
+                    core::hint::spin_loop();
+                }
+                (Err(_), _) => return Err(SequenceExhausted),
+            }
+        }
+    }
+
+    /// Pack a 24-bit ID in the current layout from a sequence value.
+    fn compose24(&self, seq: u64) -> u32 {
+        let layout = Layout24::current();
+        let mask = layout.sequence_capacity() - 1;
+        if seq & mask == 0 && seq != 0 {
+            emit(IdEvent::SequenceExhausted { width: 24 });
+        }
+        let id = Self::pack24(layout, seq, || self.id_timestamp());
+        notify_generate(24, id as u128);
+        id
+    }
+
+    /// Pack a 24-bit ID in `layout`, reading the timestamp from `ts` only if the
+    /// layout has time bits.
+    fn pack24(layout: Layout24, seq: u64, ts: impl FnOnce() -> u64) -> u32 {
+        let mask = layout.sequence_capacity() - 1;
+        let id = match layout {
+            Layout24::Standard => seq & mask,
+            Layout24::TimeSalted => {
+                let secs = ts() / 1000;
+                ((secs & 0xFF) << 16) | (seq & mask)
+            }
+        };
+        id as u32
+    }
+
+    /// Generate a 32-bit unique ID.
+    ///
+    /// This ID combines a thread-specific identifier with a sequence number,
+    /// providing better collision resistance in multi-threaded applications than `gen24`.
+    ///
+    /// - **Structure**: 8 bits for the thread ID | 24 bits for the sequence.
+    ///
+    /// The thread ID uses the whole byte: hashed thread IDs take all 256 values, and
+    /// [`ThreadIdMode::Sequential`] numbers up to 255 threads. In
+    /// [`Layout32::TimeSalted`] the layout is 10 bits of seconds | 22 bits of
+    /// sequence instead; decode either with [`Layout32::decompose`].
+    ///
+    /// # Returns
+    /// A 32-bit unique ID as a `u32`.
+    pub fn gen32(&self) -> u32 {
+        let layout = self.layout32();
+        let seq = SEQ_32.fetch_add(1, Ordering::Relaxed);
+        let mask = layout.sequence_capacity() - 1;
+        if seq & mask == 0 && seq != 0 {
+            emit(IdEvent::SequenceExhausted { width: 32 });
+        }
+
+        let id = self.pack32(layout, seq, || self.id_timestamp());
+        notify_generate(32, id as u128);
+        id
+    }
+
+    /// Pack a 32-bit ID in `layout`, reading the timestamp from `ts` only if the
+    /// layout has time bits.
+    fn pack32(&self, layout: Layout32, seq: u64, ts: impl FnOnce() -> u64) -> u32 {
+        let mask = layout.sequence_capacity() - 1;
+        match layout {
+            Layout32::Standard => {
+                let thread_bits = (self.thread_byte() as u32) << 24;
+                thread_bits | (seq & mask) as u32
+            }
+            Layout32::TimeSalted => {
+                let secs = ts() / 1000;
+                (((secs & 0x3FF) << 22) | (seq & mask)) as u32
+            }
+        }
+    }
+
+    /// Generate a 32-bit ID that is ordered by time, to the second.
+    ///
+    /// Unlike [`IdGenerator::gen32`], these IDs sort by creation time, which makes them
+    /// suitable for short-lived ordered keys such as queue entries or session tokens.
+    ///
+    /// - **Structure**: 20-bit timestamp | 12-bit sequence.
+    /// - **Timestamp**: Seconds since the custom epoch, wrapping every ~12 days (2^20 s).
+    /// - **Sequence**: Up to 4096 IDs per second (2^12), shared by all threads but not
+    ///   with [`IdGenerator::gen32`].
+    ///
+    /// There is no thread or node field, so IDs are only unique within one process,
+    /// and only while fewer than 4096 are generated per second. Beyond that the
+    /// sequence wraps and [`IdEvent::SequenceExhausted`] is reported. IDs from the
+    /// same second are not ordered by the sequence once it wraps.
+    ///
+    /// # Returns
+    /// A 32-bit time-ordered ID as a `u32`.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(0, 0);
+    /// let parts = IdGenerator::decompose32(generator.gen32_timed());
+    /// assert!(parts.sequence < 4096);
+    /// ```
+    pub fn gen32_timed(&self) -> u32 {
+        let secs = overflow_timestamp(self.id_timestamp() / 1000, 20);
+        let seq = SEQ_32_TIMED.fetch_add(1, Ordering::Relaxed);
+        check_sequence_wrap(32, seq, 0xFFF, secs, &TICK_START_32);
+
+        let id = (((secs & 0xFFFFF) as u32) << 12) | (seq & 0xFFF) as u32;
+        notify_generate(32, id as u128);
+        id
+    }
+
+    /// Generate a 64-bit unique ID, inspired by Twitter's Snowflake.
+    ///
+    /// This ID is ideal for distributed systems, as it combines a timestamp,
+    /// node/shard identifiers, and a sequence number to ensure global uniqueness.
+    ///
+    /// - **Structure**: 20-bit timestamp | 12-bit node ID | 8-bit shard ID | 8-bit thread ID | 16-bit sequence.
+    /// - **Timestamp**: Milliseconds since the custom epoch, wrapping every 2^20 ms (about 17.5 minutes; see [`AtomicId::<x64>::time_remaining`](AtomicId::<64>::time_remaining)).
+    /// - **Node ID**: Supports up to 4096 nodes (2^12).
+    /// - **Shard ID**: Supports up to 256 shards per node (2^8).
+    /// - **Sequence**: Supports up to 65,536 IDs per millisecond per thread (2^16).
+    ///
+    /// The thread ID and sequence fields share 24 bits; [`AtomicOption::thread_id_bits`]
+    /// moves the boundary between them. [`AtomicOption::version_bits`] takes 2 of the
+    /// sequence bits for a layout version.
+    ///
+    /// This is the [`Layout64::Standard`] layout. If another layout was selected with
+    /// [`AtomicOption::layout64`], that layout is used instead.
+    ///
+    /// # Returns
+    /// A 64-bit unique ID as a `u64`.
+    pub fn gen64(&self) -> u64 {
+        self.gen64_as(Layout64::current())
+    }
+
+    /// Generate a 64-bit ID whose bits are spread evenly, for placing keys on a hash
+    /// ring or across shards: consecutive IDs land far apart instead of clustering
+    /// in one range.
+    ///
+    /// This is [`IdGenerator::gen64`] run through a fixed bijective permutation (a
+    /// Feistel network), so scattered IDs are as unique as the IDs they come from,
+    /// and [`IdGenerator::unscatter64`] recovers the original ID and its fields. The
+    /// cost is order: scattered IDs do not sort by time, and the timestamp cannot be
+    /// read without unscattering. The permutation is fixed and public, so it hides
+    /// nothing from anyone who wants to reverse it.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(7, 3);
+    /// let id = generator.gen64_scattered();
+    /// assert_eq!(IdGenerator::decompose64(IdGenerator::unscatter64(id)).node_id, 7);
+    /// ```
+    pub fn gen64_scattered(&self) -> u64 {
+        scatter64(self.gen64())
+    }
+
+    /// The ID from [`IdGenerator::gen64`] that [`IdGenerator::gen64_scattered`]
+    /// scattered into `id`.
+    pub fn unscatter64(id: u64) -> u64 {
+        unscatter64(id)
+    }
+
+    /// Generate a 64-bit ID that sorts by time for as long as the epoch lasts, for
+    /// keys that must stay in creation order across days and restarts.
+    ///
+    /// - **Structure**: 1 zero bit | 41-bit millisecond timestamp | 10-bit node ID | 12-bit sequence.
+    /// - **Timestamp**: Milliseconds since the [`IdGenerator::epoch_ms`], about 69
+    ///   years, so it does not wrap like the 20-bit field of [`IdGenerator::gen64`].
+    ///   The [`TimestampPrecision`] does not apply.
+    /// - **Node ID**: The low 10 bits of the node ID; the shard and thread are not
+    ///   stored. Processes need node IDs that differ in those bits.
+    /// - **Sequence**: 4096 IDs per millisecond per process. A burst beyond that
+    ///   borrows the next millisecond instead of waiting, so the timestamp can run
+    ///   slightly ahead of the clock, and a clock that steps back is ignored until
+    ///   it catches up.
+    ///
+    /// Each ID is greater than every ID the same generator returned before its call
+    /// started. The sequence is shared by every generator in the process, so IDs of
+    /// generators with different node IDs interleave by time and node, not by call,
+    /// and generators with different epochs should not be mixed. Across nodes, IDs
+    /// from different milliseconds compare by time and IDs from the same millisecond
+    /// by node, so order is only as good as the clocks.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(7, 3);
+    /// let first = generator.gen64_k_sorted();
+    /// let second = generator.gen64_k_sorted();
+    /// assert!(second > first);
+    /// assert_eq!((first >> 12) & 0x3FF, 7);
+    /// ```
+    pub fn gen64_k_sorted(&self) -> u64 {
+        let now = overflow_timestamp(self.id_timestamp(), 41) << 12;
+        let next = |state: u64| (state + 1).max(now);
+        let prev = K_SORTED_64.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |state| Some(next(state)));
+        let state = next(prev.unwrap_or_else(|state| state));
+        let id = ((state >> 12) & 0x1FF_FFFF_FFFF) << 22 | ((self.node_id & 0x3FF) as u64) << 12 | (state & 0xFFF);
+        notify_generate(64, id as u128);
+        id
+    }
+
+    /// Generate a 64-bit unique ID with a 16-bit node field, for clusters of more than 4096 nodes.
+    ///
+    /// - **Structure**: 20-bit timestamp | 16-bit node ID | 4-bit shard ID | 8-bit thread ID | 16-bit sequence.
+    /// - **Node ID**: Supports up to 65,536 nodes (2^16).
+    /// - **Shard ID**: Supports up to 16 shards per node (2^4); higher shard bits are dropped.
+    ///
+    /// The timestamp, thread, and sequence fields are the same as in [`IdGenerator::gen64`].
+    /// This is the [`Layout64::WideNode`] layout; decode it with [`IdGenerator::decompose64_wide`].
+    ///
+    /// # Returns
+    /// A 64-bit unique ID as a `u64`.
+    pub fn gen64_wide_node(&self) -> u64 {
+        self.gen64_as(Layout64::WideNode)
+    }
+
+    /// Generate a 64-bit unique ID that carries the region that minted it, for routing
+    /// by region straight from the key.
+    ///
+    /// - **Structure**: 20-bit timestamp | 4-bit region | 12-bit node ID | 4-bit shard ID | 8-bit thread ID | 16-bit sequence.
+    /// - **Region**: Up to 16 regions, from [`IdGenerator::region_id`].
+    /// - **Shard ID**: Up to 16 shards per node (2^4), to make room for the region;
+    ///   higher shard bits are dropped.
+    ///
+    /// The timestamp, node, thread, and sequence fields are the same as in
+    /// [`IdGenerator::gen64`]. This is the [`Layout64::Regional`] layout; decode it
+    /// with [`IdGenerator::decompose64_regional`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::builder().node_id(7).shard_id(3).region_id(11).build();
+    /// let parts = IdGenerator::decompose64_regional(generator.gen64_regional());
+    /// assert_eq!((parts.region, parts.node_id, parts.shard_id), (11, 7, 3));
+    /// ```
+    pub fn gen64_regional(&self) -> u64 {
+        self.gen64_as(Layout64::Regional)
+    }
+
+    /// Generate a 64-bit ID in the current [`Layout64`] that records which layout it
+    /// is in, so IDs minted under different layouts can be stored together and each
+    /// decoded correctly with [`IdGenerator::decompose_auto`].
+    ///
+    /// - **Structure**: the layout's fields | 2-bit format version | 2-bit layout version.
+    /// - **Layout version**: always 2 (see [`IdGenerator::layout_version`]).
+    /// - **Format version**: [`Layout64::format_version`] less 1, starting at 0 for
+    ///   [`Layout64::Standard`].
+    ///
+    /// Both fields are taken from the bottom of the sequence, which keeps 4 bits fewer
+    /// than in [`IdGenerator::gen64`]: 4,096 IDs per millisecond and thread with the
+    /// default [`AtomicOption::thread_id_bits`]. The time, node, shard, and thread
+    /// fields are unchanged, so versioned IDs sort by time like any other. Putting the
+    /// version in the top bits instead would take them from the timestamp, so versioned
+    /// IDs would no longer sort by time with the others, and could still not tell
+    /// unversioned IDs apart, which use all 64 bits.
+    ///
+    /// IDs from [`IdGenerator::gen64`] share the sequence with versioned IDs and have
+    /// layout version 1, so the two never collide. Without version bits their low 2
+    /// bits are sequence bits, which read as version 2 for a quarter of them, so this
+    /// requires [`AtomicOption::version_bits`].
+    ///
+    /// # Errors
+    /// [`IdError::VersionBitsDisabled`] while [`AtomicOption::version_bits`] is
+    /// disabled, consuming no sequence value.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdError, IdGenerator, Layout64};
+    /// let generator = IdGenerator::new(7, 3);
+    /// assert_eq!(generator.gen64_versioned(), Err(IdError::VersionBitsDisabled));
+    /// AtomicOption::version_bits(true);
+    /// let id = generator.gen64_versioned()?;
+    /// assert_eq!(IdGenerator::format_version(id), Layout64::Standard.format_version());
+    /// let parts = IdGenerator::decompose_auto(id).unwrap();
+    /// assert_eq!((parts.node_id, parts.shard_id), (7, 3));
+    /// # Ok::<(), IdError>(())
+    /// ```
+    pub fn gen64_versioned(&self) -> Result<u64, IdError> {
+        if !version_bits() {
+            return Err(IdError::VersionBitsDisabled);
+        }
+        let layout = Layout64::current();
+        let precision = TimestampPrecision::current();
+        let ticks = self.ticks_at(precision, self.id_timestamp64());
+        let (ticks, thread_id, seq, thread_bits) = self.next64_in(layout, ticks, versioned_seq_bits64);
+        let field_bits = versioned_seq_bits64(thread_bits);
+        let (time, seq) = pack_ticks64_in(precision, layout, ticks, seq, field_bits);
+        let thread_seq = (((thread_id as u64) & ((1 << thread_bits) - 1)) << field_bits) | seq;
+        let format = ((layout.format_version() - 1) as u64) << VERSION_BITS | FORMAT_LAYOUT_VERSION as u64;
+        let id = self.compose64_as(layout, time, thread_seq << (VERSION_BITS + FORMAT_BITS_64) | format);
+        let id = raise_ticks64(precision, layout, id, field_bits, VERSION_BITS + FORMAT_BITS_64);
+        notify_generate(64, id as u128);
+        Ok(id)
+    }
+
+    /// Generate a 64-bit ID, or fail if the 20-bit timestamp field is about to wrap.
+    ///
+    /// The timestamp field wraps every 2^20 ms (about 17.5 minutes), after which IDs
+    /// start reusing timestamp values from the previous period. In [`Layout64::Era`]
+    /// the era extends this to 2^23 ms. This returns
+    /// [`IdError::TimestampNearWrap`] with the time left while the timestamp is within
+    /// the [`AtomicOption::wrap_threshold`] of wrapping, and consumes no sequence
+    /// value in that case. [`IdGenerator::gen64`] never fails, but reports
+    /// [`IdEvent::TimestampNearWrap`] once per period instead.
+    ///
+    /// This describes the default [`OverflowMode::Wrap`]. Fields that saturate never
+    /// wrap, so in the other modes there is no near-wrap check; in
+    /// [`OverflowMode::Error`] this returns [`IdError::TimestampOverflow`] once the
+    /// time since the epoch no longer fits in the field.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{IdError, IdGenerator};
+    /// let generator = IdGenerator::new(1, 0);
+    /// match generator.gen64_checked() {
+    ///     Ok(id) => println!("{id}"),
+    ///     Err(IdError::TimestampNearWrap(left)) => println!("wraps in {left:?}"),
+    ///     Err(e) => panic!("{e}"),
+    /// }
+    /// ```
+    pub fn gen64_checked(&self) -> Result<u64, IdError> {
+        let layout = Layout64::current();
+        let precision = TimestampPrecision::current();
+        let bits = precision.timestamp_bits(layout);
+        let ticks = self.ticks_at(precision, self.id_timestamp64());
+        match OverflowMode::current() {
+            OverflowMode::Wrap => {}
+            OverflowMode::Error if ticks >> bits != 0 => {
+                return Err(IdError::TimestampOverflow);
+            }
+            OverflowMode::Saturate | OverflowMode::Error => return Ok(self.gen64_ticks(layout, ticks)),
+        }
+        let remaining = ms_until_wrap(ticks, bits);
+        if near_wrap(remaining, bits) {
+            return Err(IdError::TimestampNearWrap(precision.ticks_to_duration(remaining)));
+        }
+        Ok(self.gen64_ticks(layout, ticks))
+    }
+
+    /// Generate a 64-bit ID whose timestamp is `time` rather than now, for backfilling
+    /// historical rows so time-range queries over their IDs keep working.
+    ///
+    /// The ID still takes a fresh sequence value, so IDs generated for the same time
+    /// are distinct. The time is read in the current [`TimestampPrecision`] and must
+    /// fit in the time fields of the current [`Layout64`]: with the default 20-bit
+    /// field, within about 17.5 minutes of the epoch, so backfills usually use a
+    /// generator with its own epoch (see [`IdGenerator::builder`]) or
+    /// [`IdGenerator::gen128_at`].
+    /// Unlike [`IdGenerator::gen64`], nothing wraps or saturates here.
+    ///
+    /// # Errors
+    /// - [`IdError::BeforeEpoch`] if `time` is before the epoch.
+    /// - [`IdError::TimestampOverflow`] if `time` is past the last time the time
+    ///   fields can hold.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdError, IdGenerator};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// let generator = IdGenerator::new(1, 0);
+    /// let epoch = UNIX_EPOCH + Duration::from_millis(AtomicOption::get_epoch());
+    /// let id = generator.gen64_at(epoch + Duration::from_millis(1234)).unwrap();
+    /// assert_eq!(generator.created_at64(id), AtomicOption::get_epoch() + 1234);
+    /// assert_eq!(generator.gen64_at(epoch - Duration::from_millis(1)), Err(IdError::BeforeEpoch));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn gen64_at(&self, time: SystemTime) -> Result<u64, IdError> {
+        let layout = Layout64::current();
+        let precision = TimestampPrecision::current();
+        let ticks = precision.duration_to_ticks(self.elapsed_at(time)?);
+        if ticks >> precision.timestamp_bits(layout) != 0 {
+            return Err(IdError::TimestampOverflow);
+        }
+        Ok(self.gen64_ticks(layout, ticks))
+    }
+
+    /// Generate a 128-bit ID whose timestamp is `time` rather than now. See
+    /// [`IdGenerator::gen64_at`]; the 32-bit millisecond field of 128-bit IDs holds
+    /// times up to about 49.7 days after the epoch.
+    ///
+    /// # Errors
+    /// - [`IdError::BeforeEpoch`] if `time` is before the epoch.
+    /// - [`IdError::TimestampOverflow`] if `time` is 2^32 ms or more after the epoch.
+    #[cfg(feature = "std")]
+    pub fn gen128_at(&self, time: SystemTime) -> Result<u128, IdError> {
+        let ts = self.elapsed_at(time)?.as_millis() as u64;
+        if ts >> 32 != 0 {
+            return Err(IdError::TimestampOverflow);
+        }
+        Ok(self.gen128_from(ts, self.nanos(), None))
+    }
+
+    /// Time from this generator's epoch to `time`, freezing the epoch like any
+    /// timestamp-based ID.
+    #[cfg(feature = "std")]
+    fn elapsed_at(&self, time: SystemTime) -> Result<Duration, IdError> {
+        self.freeze_epoch();
+        time.duration_since(UNIX_EPOCH + Duration::from_millis(self.epoch_ms()))
+            .map_err(|_| IdError::BeforeEpoch)
+    }
+
+    /// Generate a child of a 64-bit ID for correlated ID schemes, such as spans under
+    /// a trace.
+    ///
+    /// The child keeps the parent's timestamp, node, and shard bits, so it decomposes
+    /// to the same time bucket and sorts next to its parent and siblings. Only the
+    /// thread and sequence fields are new: the thread ID of the calling thread and the
+    /// next value of the 64-bit sequence. This works for every [`Layout64`], which all
+    /// keep the thread and sequence fields in the low 24 bits.
+    ///
+    /// Children stay unique through the sequence field, which they share with
+    /// [`IdGenerator::gen64`]. A child therefore differs from its parent, its siblings,
+    /// and every other ID from this process with the same prefix, until the sequence
+    /// field wraps (65,536 IDs with the default [`AtomicOption::thread_id_bits`]). Mint
+    /// children in the process that minted the parent; elsewhere, the child carries the
+    /// other node's bits and may collide with that node's IDs.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(7, 3);
+    /// let parent = generator.gen64();
+    /// let child = generator.gen64_child(parent);
+    /// assert_ne!(child, parent);
+    /// assert_eq!(child >> 24, parent >> 24);
+    /// ```
+    pub fn gen64_child(&self, parent: u64) -> u64 {
+        let (layout, precision) = (Layout64::current(), TimestampPrecision::current());
+        let thread_bits = thread_id_bits();
+        let thread_id = self.thread_id_in(thread_bits);
+        let seq = SEQ_64.fetch_add(1, Ordering::Relaxed);
+        let field_bits = seq_bits64(thread_bits);
+        let low_bits = if version_bits() { VERSION_BITS } else { 0 };
+        // In `Micros` the parent's low tick bits go back to the top of the sequence
+        // field while the child is composed.
+        let parent = lower_ticks64(precision, layout, parent, field_bits, low_bits);
+        let fresh_mask = (1 << precision.sequence_bits_for(field_bits).min(field_bits)) - 1;
+        let parent_seq = (parent >> low_bits) & !fresh_mask;
+        let seq = parent_seq | (seq & fresh_mask);
+        let id = (parent & !0xFFFFFF) | thread_seq64(thread_id, seq, thread_bits);
+        let id = raise_ticks64(precision, layout, id, field_bits, low_bits);
+        notify_generate(64, id as u128);
+        id
+    }
+
+    /// Claim `n` consecutive values of the 64-bit sequence in a single atomic step,
+    /// for bulk inserts that want a contiguous block instead of values interleaved
+    /// with concurrent [`IdGenerator::gen64`] calls. Turn them into IDs with
+    /// [`IdGenerator::gen64_reserved`].
+    ///
+    /// The values are claimed from the counter every 64-bit ID draws from, so no other
+    /// call in this process receives them. A block costs one `fetch_add` whatever its
+    /// size, against one per ID for [`IdGenerator::gen64`]. An empty range is returned
+    /// for `n == 0`.
+    ///
+    /// Unlike [`IdGenerator::gen64`], reserving does not check the block against the
+    /// sequence field: a block that wraps the field within one tick is not refused
+    /// and reports no [`IdEvent::SequenceExhausted`], so keep blocks within
+    /// [`AtomicId::<x64>::capacity`](AtomicId::capacity). The counter itself wraps at
+    /// 2^64; a block claimed across that wrap ends before it starts, and its range is
+    /// empty. Blocks do not count against [`AtomicOption::max_rate_per_ms`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(7, 3);
+    /// let block = generator.reserve64(100);
+    /// assert_eq!(block.end() - block.start(), 99);
+    /// assert_eq!(generator.gen64_reserved(block).count(), 100);
+    /// ```
+    pub fn reserve64(&self, n: u64) -> RangeInclusive<u64> {
+        if n == 0 {
+            return RangeInclusive::new(1, 0);
+        }
+        let start = SEQ_64.fetch_add(n, Ordering::Relaxed);
+        start..=start.wrapping_add(n - 1)
+    }
+
+    /// Generate the 64-bit IDs of a block from [`IdGenerator::reserve64`], in the
+    /// current [`Layout64`]. The clock and thread ID are read once, when this is
+    /// called, and every ID in the block shares them.
+    ///
+    /// The IDs of a block differ only in the sequence field, so they are unique among
+    /// each other while the block fits in it: up to 65,536 values with the default
+    /// [`AtomicOption::thread_id_bits`] (see [`AtomicId::<x64>::capacity`](AtomicId::capacity)).
+    /// Larger blocks repeat, and are better split into several reservations. Against
+    /// other IDs, the block is as unique as the same number of [`IdGenerator::gen64`]
+    /// calls, since no one else holds its sequence values.
+    pub fn gen64_reserved(&self, seqs: RangeInclusive<u64>) -> impl Iterator<Item = u64> + '_ {
+        let ticks = self.ticks_at(TimestampPrecision::current(), self.id_timestamp());
+        self.gen64_reserved_at(ticks, seqs)
+    }
+
+    /// [`IdGenerator::gen64_reserved`] at a time in ticks already read.
+    fn gen64_reserved_at(&self, ticks: u64, seqs: RangeInclusive<u64>) -> impl Iterator<Item = u64> + '_ {
+        let layout = Layout64::current();
+        let precision = TimestampPrecision::current();
+        check_timestamp_wrap64(layout, ticks);
+        let ticks = overflow_timestamp(ticks, precision.timestamp_bits(layout));
+        let thread_bits = thread_id_bits();
+        let thread_id = self.thread_id_in(thread_bits);
+        seqs.map(move |seq| {
+            let id = self.compose64_ticks(layout, ticks, thread_id, seq, thread_bits);
+            notify_generate(64, id as u128);
+            id
+        })
+    }
+
+    /// Claim `n` sequence values for a lease, and the tick to compose them at, once the
+    /// values claimed since the first lease of that tick fit in `capacity`.
+    ///
+    /// Claiming first and checking after keeps the claim a single `fetch_add`. A
+    /// block that does not fit is dropped, and the lease waits for the next tick and
+    /// claims again. As in [`IdGenerator::id_timestamp64`], a lease whose tick is
+    /// behind the window reads the clock again before it restarts the window.
+    fn lease_block(&self, precision: TimestampPrecision, n: u64, capacity: u64) -> (u64, RangeInclusive<u64>) {
+        let mut stale = false;
+        loop {
+            let ticks = self.ticks_at(precision, self.id_timestamp64_n(n as u32));
+            let seqs = self.reserve64(n);
+            let (tick, start) = (ticks as u32, *seqs.start() as u32);
+            let claimed = LEASE_WINDOW_64.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |window| {
+                let (window_tick, first) = ((window >> 32) as u32, window as u32);
+                let ahead = tick.wrapping_sub(window_tick) as i32;
+                if window == 0 || ahead > 0 || (ahead < 0 && stale) {
+                    Some((tick as u64) << 32 | start as u64)
+                } else if ahead == 0 && start.wrapping_sub(first) as u64 + n <= capacity {
+                    Some(window)
+                } else {
+                    None
+                }
+            });
+            match claimed {
+                Ok(_) => return (ticks, seqs),
+                Err(window) if (window >> 32) as u32 != tick => stale = true,
+                Err(_) => self.wait_for_tick(precision, ticks),
+            }
+        }
+    }
+
+    /// Spin briefly, then yield, until the clock has moved past `ticks`.
+    fn wait_for_tick(&self, precision: TimestampPrecision, ticks: u64) {
+        let mut spins = 0u32;
+        while self.ticks_at(precision, self.id_timestamp()) == ticks {
+            if spins < 64 {
+                spins += 1;
+                core::hint::spin_loop();
+            } else {
+                #[cfg(feature = "std")]
+                std::thread::yield_now();
+                #[cfg(not(feature = "std"))]
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Generate a 64-bit ID in the given layout.
+    fn gen64_as(&self, layout: Layout64) -> u64 {
+        let ticks = self.ticks_at(TimestampPrecision::current(), self.id_timestamp64());
+        check_timestamp_wrap64(layout, ticks);
+        self.gen64_ticks(layout, ticks)
+    }
+
+    /// Generate a 64-bit ID in the given layout with an already-read timestamp, in
+    /// ticks of the [`TimestampPrecision`].
+    fn gen64_ticks(&self, layout: Layout64, ticks: u64) -> u64 {
+        let (ticks, thread_id, seq, thread_bits) = self.next64(layout, ticks);
+        let id = self.compose64_ticks(layout, ticks, thread_id, seq, thread_bits);
+        notify_generate(64, id as u128);
+        id
+    }
+
+    /// Claim the fields of a new 64-bit ID: the timestamp in ticks after the
+    /// [`OverflowMode`], this thread's ID, the next sequence value, and the thread
+    /// ID width they were taken at.
+    fn next64(&self, layout: Layout64, ticks: u64) -> (u64, u16, u64, u32) {
+        self.next64_in(layout, ticks, seq_bits64)
+    }
+
+    /// [`IdGenerator::next64`] for IDs whose sequence field is `field_bits(thread_bits)`
+    /// bits wide.
+    fn next64_in(&self, layout: Layout64, ticks: u64, field_bits: fn(u32) -> u32) -> (u64, u16, u64, u32) {
+        let precision = TimestampPrecision::current();
+        let thread_bits = thread_id_bits();
+        let field_bits = field_bits(thread_bits);
+        let ticks = overflow_timestamp(ticks, precision.timestamp_bits_for(layout, field_bits));
+        let thread_id = self.thread_id_in(thread_bits);
+        let seq = SEQ_64.fetch_add(1, Ordering::Relaxed);
+        let seq_mask = (1 << precision.sequence_bits_for(field_bits)) - 1;
+        check_sequence_wrap(64, seq, seq_mask, ticks, &TICK_START_64);
+        (ticks, thread_id, seq, thread_bits)
+    }
+
+    /// Pack a 64-bit ID in the given layout from a timestamp in ticks of the
+    /// [`TimestampPrecision`], a thread ID, and a sequence value.
+    fn compose64_ticks(&self, layout: Layout64, ticks: u64, thread_id: u16, seq: u64, thread_bits: u32) -> u64 {
+        let precision = TimestampPrecision::current();
+        let (time, seq) = pack_ticks64(precision, layout, ticks, seq, thread_bits);
+        let id = self.compose64_as(layout, time, thread_seq64(thread_id, seq, thread_bits));
+        let low_bits = if version_bits() { VERSION_BITS } else { 0 };
+        raise_ticks64(precision, layout, id, seq_bits64(thread_bits), low_bits)
+    }
+
+    /// Generate a 64-bit ID and return its fields instead of the packed value.
+    ///
+    /// The fields are exactly those [`Layout64::decompose`] would return for the ID
+    /// [`IdGenerator::gen64`] would have generated in its place, in the current
+    /// [`Layout64`], and they share its sequence, so they are unique among each other
+    /// and among packed IDs in the same way. Useful for storing the fields in
+    /// separate columns without packing and unpacking them first.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(7, 3);
+    /// let parts = generator.gen64_parts();
+    /// assert_eq!((parts.node_id, parts.shard_id), (7, 3));
+    /// assert_ne!(generator.gen64_parts(), parts);
+    /// ```
+    pub fn gen64_parts(&self) -> Id64Parts {
+        let layout = Layout64::current();
+        let precision = TimestampPrecision::current();
+        let ticks = self.ticks_at(precision, self.id_timestamp64());
+        check_timestamp_wrap64(layout, ticks);
+        let (ticks, thread_id, seq, thread_bits) = self.next64(layout, ticks);
+        let id = self.compose64_ticks(layout, ticks, thread_id, seq, thread_bits);
+        notify_generate(64, id as u128);
+        let field_bits = seq_bits64(thread_bits);
+        let ts_bits = precision.timestamp_bits_for(Layout64::Standard, field_bits);
+        let elapsed = ticks & ((1 << precision.timestamp_bits_for(layout, field_bits)) - 1);
+        let (node_id, shard_id) = match layout {
+            Layout64::Standard => (self.node_id & 0xFFF, self.shard_id),
+            Layout64::WideNode => (self.node_id, self.shard_id & 0xF),
+            Layout64::Era => (self.node_id & 0xFFF, self.shard_id & 0x1F),
+            Layout64::Regional => (self.node_id & 0xFFF, self.shard_id & 0xF),
+        };
+        Id64Parts {
+            era: (elapsed >> ts_bits) as u8,
+            timestamp: elapsed & ((1 << ts_bits) - 1),
+            region: if layout == Layout64::Regional { self.region_id() & MAX_REGION_ID } else { 0 },
+            node_id,
+            shard_id,
+            thread_id: thread_id as u8,
+            sequence: (seq & ((1 << precision.sequence_bits_for(field_bits)) - 1)) as u32,
+            version: if version_bits() { LAYOUT_VERSION } else { 0 },
+            wide_thread_id: thread_id & ((1 << thread_bits) - 1),
+        }
+    }
+
+    /// Pack a 64-bit ID in the given layout, given the low 24 bits from [`thread_seq64`].
+    fn compose64_as(&self, layout: Layout64, ts: u64, thread_seq: u64) -> u64 {
+        match layout {
+            Layout64::Standard => self.compose64(ts, thread_seq),
+            Layout64::WideNode => {
+                let ts_bits = (ts & 0xFFFFF) << 44;
+                let node_bits = (self.node_id as u64) << 28;
+                let shard_bits = ((self.shard_id as u64) & 0xF) << 24;
+
+                ts_bits | node_bits | shard_bits | (thread_seq & 0xFFFFFF)
+            }
+            Layout64::Era => {
+                // The era is simply the next bits of the timestamp.
+                let ts_bits = (ts & 0x7FFFFF) << 41;
+                let node_bits = ((self.node_id & 0xFFF) as u64) << 29;
+                let shard_bits = ((self.shard_id as u64) & 0x1F) << 24;
+
+                ts_bits | node_bits | shard_bits | (thread_seq & 0xFFFFFF)
+            }
+            Layout64::Regional => {
+                let ts_bits = (ts & 0xFFFFF) << 44;
+                let region_bits = ((self.region_id() & MAX_REGION_ID) as u64) << 40;
+                let node_bits = ((self.node_id & 0xFFF) as u64) << 28;
+                let shard_bits = ((self.shard_id as u64) & 0xF) << 24;
+
+                ts_bits | region_bits | node_bits | shard_bits | (thread_seq & 0xFFFFFF)
+            }
+        }
+    }
+
+    /// Pack the standard 64-bit layout from its inputs.
+    fn compose64(&self, ts: u64, thread_seq: u64) -> u64 {
+        let ts_bits = (ts & 0xFFFFF) << 44;
+        let node_bits = ((self.node_id & 0xFFF) as u64) << 32;
+        let shard_bits = ((self.shard_id as u64) & 0xFF) << 24;
+
+        ts_bits | node_bits | shard_bits | (thread_seq & 0xFFFFFF)
+    }
+
+    /// Generate a 128-bit unique ID with enhanced collision resistance.
+    ///
+    /// This ID uses a two-part structure to maximize entropy, combining a timestamp-based
+    /// part with a high-entropy part derived from nanoseconds and sequence numbers.
+    ///
+    /// - **High 64 bits**: 32-bit timestamp | 12-bit node | 8-bit shard | 8-bit thread | 4-bit reserved.
+    ///   The low 2 reserved bits hold the layout version ([`IdGenerator::layout_version128`])
+    ///   while [`AtomicOption::version_bits`] is enabled.
+    /// - **Low 64 bits**: 32-bit nanoseconds | 24-bit sequence | 8-bit rotated thread ID.
+    ///
+    /// The timestamp and the nanoseconds come from a single read of the system clock,
+    /// so the nanoseconds always fall within the timestamp's millisecond.
+    ///
+    /// # Returns
+    /// A 128-bit unique ID as a `u128`.
+    pub fn gen128(&self) -> u128 {
+        let (ts, nanos) = self.id_timestamp_nanos();
+        self.gen128_from(overflow_timestamp(ts, 32), nanos, None)
+    }
+
+    /// [`IdGenerator::gen128`] as it was before it read the clock once: the timestamp
+    /// and the nanoseconds from separate reads, which can fall in different
+    /// milliseconds. Kept only so the benchmarks can compare the two paths.
+    #[doc(hidden)]
+    pub fn __gen128_two_reads(&self) -> u128 {
+        self.gen128_from(overflow_timestamp(self.id_timestamp(), 32), self.nanos(), None)
+    }
+
+    /// Generate a 128-bit ID carrying an application tag, such as a record kind, in
+    /// the reserved field of the [`IdGenerator::gen128`] layout. Read it back with
+    /// [`IdGenerator::tag128`].
+    ///
+    /// The tag takes the reserved bits not used by the layout version: all 4 bits,
+    /// or only the high 2 with [`AtomicOption::version_bits`] enabled, so that
+    /// [`IdGenerator::layout_version128`] still reads the version of tagged IDs.
+    /// Tagged IDs lose the nanosecond bits the tag replaces; the remaining 60 bits of
+    /// timestamp, node, thread, nanoseconds, and sequence keep them as unique as
+    /// untagged ones.
+    ///
+    /// # Errors
+    /// [`IdError::InvalidTag`] if `tag` is greater than 15, or than 3 with version
+    /// bits enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{IdError, IdGenerator};
+    /// let generator = IdGenerator::new(7, 3);
+    /// let id = generator.gen128_tagged(9).unwrap();
+    /// assert_eq!(IdGenerator::tag128(id), 9);
+    /// assert_eq!(generator.gen128_tagged(16), Err(IdError::InvalidTag(16)));
+    /// ```
+    pub fn gen128_tagged(&self, tag: u8) -> Result<u128, IdError> {
+        if tag >> tag_bits128() != 0 {
+            return Err(IdError::InvalidTag(tag));
+        }
+        let (ts, nanos) = self.id_timestamp_nanos();
+        Ok(self.gen128_from(overflow_timestamp(ts, 32), nanos, Some(tag)))
+    }
+
+    /// Generate a 128-bit ID with an already-read timestamp and nanoseconds, and
+    /// `tag` in the reserved field if given.
+    fn gen128_from(&self, ts: u64, nanos: u64, tag: Option<u8>) -> u128 {
+        let thread_id = self.thread_id();
+        let seq = SEQ_128.fetch_add(1, Ordering::Relaxed);
+        check_sequence_wrap(128, seq, 0xFFFFFF, ts, &TICK_START_128);
+        let mut id = self.compose128(ts, thread_id, nanos, seq);
+        if let Some(tag) = tag {
+            let shift = 68 - tag_bits128();
+            id = (id & !(0xF << shift)) | (tag as u128) << shift;
+        }
+        notify_generate(128, id);
+        id
+    }
+
+    /// Pack the 128-bit layout from its inputs.
+    fn compose128(&self, ts: u64, thread_id: u8, nanos: u64, seq: u64) -> u128 {
+        // First 64 bits: Enhanced timestamp-based entropy
+        // 32-bit timestamp | 12-bit node | 8-bit shard | 8-bit thread | 4-bit reserved (incl. version)
+        let high_part = {
+            let ts_bits = (ts & 0xFFFFFFFF) << 32;
+            let node_bits = ((self.node_id & 0xFFF) as u64) << 20;
+            let shard_bits = ((self.shard_id as u64) & 0xFF) << 12;
+            let thread_bits = ((thread_id as u64) & 0xFF) << 4;
+            let reserved = if version_bits() {
+                (nanos.rotate_right(16) & 0xC) | LAYOUT_VERSION as u64
+            } else {
+                nanos.rotate_right(16) & 0xF
+            };
+            
+            ts_bits | node_bits | shard_bits | thread_bits | reserved
+        };
+
+        // Second 64 bits: Maximum entropy mixing
+        // 32-bit nanos | 24-bit sequence | 8-bit thread rotated
+        let low_part = {
+            let nanos_bits = (nanos & 0xFFFFFFFF) << 32;
+            let seq_bits = (seq & 0xFFFFFF) << 8;
+            let thread_rot = thread_id.rotate_left(3) as u64;
+            
+            nanos_bits | seq_bits | thread_rot
+        };
+
+        ((high_part as u128) << 64) | (low_part as u128)
+    }
+
+    /// Generate a 128-bit ID that is strictly increasing within the process.
+    ///
+    /// - **Bits 127-80**: 48-bit timestamp in ms since the epoch (about 8900 years).
+    /// - **Bits 79-64**: 12-bit node | 4-bit shard.
+    /// - **Bits 63-0**: 64-bit sequence, shared by every generator in the process.
+    ///
+    /// The timestamp never goes backwards, even if the clock does, and the sequence
+    /// never repeats, so comparing IDs as `u128` reflects generation order: on one
+    /// thread every ID is greater than the last, and across threads an ID is greater
+    /// than every ID returned before its call started. This makes them usable as log
+    /// offsets. The order only holds between generators with the same node and shard,
+    /// since those bits sit above the sequence; a process normally has just one.
+    /// Unlike [`IdGenerator::gen128`] these IDs carry no randomness, so they are easy
+    /// to guess.
+    ///
+    /// # Returns
+    /// A 128-bit ID as a `u128`.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(7, 3);
+    /// let first = generator.gen128_monotonic();
+    /// let second = generator.gen128_monotonic();
+    /// assert!(second > first);
+    /// assert_eq!((first >> 64) & 0xFFFF, 7 << 4 | 3);
+    /// ```
+    pub fn gen128_monotonic(&self) -> u128 {
+        let now = overflow_timestamp(self.id_timestamp(), 48);
+        let ts = TS_MONOTONIC_128.fetch_max(now, Ordering::Relaxed).max(now);
+        let seq = SEQ_MONOTONIC_128.fetch_add(1, Ordering::Relaxed);
+        let node_shard = ((self.node_id & 0xFFF) << 4) | (self.shard_id & 0xF) as u16;
+        let id = ((ts & 0xFFFF_FFFF_FFFF) as u128) << 80 | (node_shard as u128) << 64 | seq as u128;
+        notify_generate(128, id);
+        id
+    }
+
+    /// Generate a 256-bit unique ID for maximum entropy and uniqueness.
+    ///
+    /// This ID is constructed from four 64-bit parts, each derived from different
+    /// sources of entropy (timestamps, nanoseconds, node/shard/thread IDs, and sequences).
+    /// It is suitable for applications requiring cryptographic-level uniqueness.
+    ///
+    /// # Returns
+    /// An array of four `u64` values representing the 256-bit ID.
+    pub fn gen256(&self) -> [u64; 4] {
+        let (ts, nanos) = self.id_timestamp_nanos();
+        let ts = overflow_timestamp(ts, 20);
+        let thread_id = self.thread_id();
+        let seq = SEQ_256.fetch_add(1, Ordering::Relaxed);
+        let id = self.compose256(ts, thread_id, nanos, seq);
+        notify_generate256(&id);
+        id
+    }
+
+    /// Generate a 256-bit ID as 32 uniformly distributed bytes, for hash-table keys
+    /// and content addressing.
+    ///
+    /// The parts of [`IdGenerator::gen256`] repeat their inputs in fixed positions, so
+    /// many of their bits are constant or correlated. This runs the parts through a
+    /// keyed permutation that spreads every input bit over every output byte. The
+    /// permutation is a bijection, so distinct IDs still give distinct bytes, but
+    /// it is not meant to be reversed: no inverse is provided, and the timestamp and
+    /// other fields cannot be read back. The key is fixed, so the mixing hides
+    /// structure from hash tables, not from attackers; the bytes are not a secret.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(1, 1);
+    /// assert_ne!(generator.gen256_bytes(), generator.gen256_bytes());
+    /// ```
+    pub fn gen256_bytes(&self) -> [u8; 32] {
+        Id256(mix256(self.gen256())).to_be_bytes()
+    }
+
+    /// Pack the 256-bit layout from its inputs.
+    fn compose256(&self, ts: u64, thread_id: u8, nanos: u64, seq: u64) -> [u64; 4] {
+        // Part 0: Base 64-bit structure (like gen64 but with different sequence)
+        let part0 = {
+            let ts_bits = (ts & 0xFFFFF) << 44;
+            let node_bits = ((self.node_id & 0xFFF) as u64) << 32;
+            let shard_bits = ((self.shard_id as u64) & 0xFF) << 24;
+            let thread_bits = ((thread_id as u64) & 0xFF) << 16;
+            let seq_bits = seq & 0xFFFF;
+            
+            ts_bits | node_bits | shard_bits | thread_bits | seq_bits
+        };
+
+        // Part 1: Nanosecond precision with entropy mixing
+        let part1 = {
+            let nanos_high = (nanos >> 32) & 0xFFFFFFFF;
+            let nanos_low = nanos & 0xFFFFFFFF;
+            let mixed = nanos_high.rotate_left(16) ^ nanos_low;
+            
+            (mixed << 32) | ((seq.rotate_right(8)) & 0xFFFFFFFF)
+        };
+
+        // Part 2: Thread and sequence entropy
+        let part2 = {
+            let thread_expanded = ((thread_id as u64) << 56) | 
+                                ((thread_id as u64).rotate_left(8) << 48) |
+                                ((thread_id as u64).rotate_left(16) << 40) |
+                                ((thread_id as u64).rotate_left(24) << 32);
+            let seq_mixed = (seq.rotate_left(16)) & 0xFFFFFFFF;
+            
+            thread_expanded | seq_mixed
+        };
+
+        // Part 3: Additional entropy sources
+        let part3 = {
+            let ts_rotated = ts.rotate_right(12);
+            let node_expanded = ((self.node_id as u64) << 48) | 
+                              ((self.node_id as u64).rotate_left(4) << 32);
+            let shard_expanded = ((self.shard_id as u64) << 24) |
+                               ((self.shard_id as u64).rotate_left(2) << 16) |
+                               ((self.shard_id as u64).rotate_left(4) << 8) |
+                               (self.shard_id as u64).rotate_left(6);
+            (ts_rotated & 0xFFFF) | node_expanded | shard_expanded
+        };
+
+        [part0, part1, part2, part3]
+    }
+
+    /// Predict the next 24-bit ID without consuming a sequence value.
+    ///
+    /// This reads the sequence counter instead of incrementing it, so the prediction
+    /// is only accurate if no other thread generates a 24-bit ID in between. Use it
+    /// in tests or as an advisory hint, never to reserve an ID.
+    ///
+    /// Peeking has no side effects on the generator: the clock is read without
+    /// recording the timestamp, reporting a rollback, freezing the epoch, or, in
+    /// [`AtomicOption::deterministic`] mode, advancing the logical clock and PRNG, and
+    /// no [`IdEvent`] or generation hook fires.
+    pub fn peek24(&self) -> u32 {
+        Self::pack24(Layout24::current(), SEQ_24.load(Ordering::Relaxed), || self.peek_timestamp())
+    }
+
+    /// Predict the next 32-bit ID without consuming a sequence value.
+    ///
+    /// Advisory only; see [`IdGenerator::peek24`].
+    pub fn peek32(&self) -> u32 {
+        self.pack32(self.layout32(), SEQ_32.load(Ordering::Relaxed), || self.peek_timestamp())
+    }
+
+    /// Predict the next 64-bit ID without consuming a sequence value.
+    ///
+    /// The result is what [`IdGenerator::gen64`] would return right now on this thread.
+    /// It is racy by nature: another thread may take the sequence value, and the
+    /// timestamp may tick, before the next real `gen64` call, and a full
+    /// [`AtomicOption::max_rate_per_ms`] window makes `gen64` wait for a later
+    /// millisecond. Use it to assert layout properties in tests without permanently
+    /// advancing the global counter. Like [`IdGenerator::peek24`] it has no side
+    /// effects.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(7, 3);
+    /// let next = generator.peek64();
+    /// assert_eq!((next >> 32) & 0xFFF, 7);
+    /// assert_eq!((next >> 24) & 0xFF, 3);
+    /// ```
+    pub fn peek64(&self) -> u64 {
+        let layout = Layout64::current();
+        let precision = TimestampPrecision::current();
+        let thread_bits = thread_id_bits();
+        let ticks = self.ticks_with(precision, self.peek_timestamp(), || self.peek_nanos());
+        let ticks = overflow_timestamp(ticks, precision.timestamp_bits_for(layout, seq_bits64(thread_bits)));
+        let seq = SEQ_64.load(Ordering::Relaxed);
+        self.compose64_ticks(layout, ticks, self.thread_id_in(thread_bits), seq, thread_bits)
+    }
+
+    /// Predict the next 128-bit ID without consuming a sequence value.
+    ///
+    /// Advisory only; see [`IdGenerator::peek64`]. The nanosecond bits will almost
+    /// certainly differ from the next real ID, except in deterministic mode.
+    pub fn peek128(&self) -> u128 {
+        let ts = overflow_timestamp(self.peek_timestamp(), 32);
+        let seq = SEQ_128.load(Ordering::Relaxed);
+        self.compose128(ts, self.thread_id(), self.peek_nanos(), seq)
+    }
+
+    /// Predict the next 256-bit ID without consuming a sequence value.
+    ///
+    /// Advisory only; see [`IdGenerator::peek128`].
+    pub fn peek256(&self) -> [u64; 4] {
+        let ts = overflow_timestamp(self.peek_timestamp(), 20);
+        let seq = SEQ_256.load(Ordering::Relaxed);
+        self.compose256(ts, self.thread_id(), self.peek_nanos(), seq)
+    }
+
+    /// Split a 64-bit ID in the standard layout into its fields, at the current
+    /// [`AtomicOption::thread_id_bits`] setting (see
+    /// [`Layout64::decompose_with_thread_bits`] for others).
+    ///
+    /// The reader must share the writer's [`AtomicOption::version_bits`] setting. A
+    /// reader without it takes a versioned ID's 2 version bits for the bottom of the
+    /// sequence, and a reader with it takes the bottom 2 sequence bits of an
+    /// unversioned ID for a version.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(300, 7);
+    /// let parts = IdGenerator::decompose64(generator.gen64());
+    /// assert_eq!((parts.node_id, parts.shard_id), (300, 7));
+    /// ```
+    pub fn decompose64(id: u64) -> Id64Parts {
+        Layout64::Standard.decompose(id)
+    }
+
+    /// Split a 64-bit ID in the wide-node layout ([`IdGenerator::gen64_wide_node`]) into its fields.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(5000, 3);
+    /// let parts = IdGenerator::decompose64_wide(generator.gen64_wide_node());
+    /// assert_eq!((parts.node_id, parts.shard_id), (5000, 3));
+    /// ```
+    pub fn decompose64_wide(id: u64) -> Id64Parts {
+        Layout64::WideNode.decompose(id)
+    }
+
+    /// Split a 64-bit ID in the regional layout ([`IdGenerator::gen64_regional`]) into
+    /// its fields, including the region.
+    pub fn decompose64_regional(id: u64) -> Id64Parts {
+        Layout64::Regional.decompose(id)
+    }
+
+    /// The format version of an ID from [`IdGenerator::gen64_versioned`], or 0 for an
+    /// ID whose layout version is not 2 and so records no layout. See
+    /// [`Layout64::from_format_version`] for the layout it names.
+    ///
+    /// The layout version is read whether or not [`AtomicOption::version_bits`] is
+    /// enabled.
+    pub const fn format_version(id: u64) -> u8 {
+        if id as u8 & ((1 << VERSION_BITS) - 1) == FORMAT_LAYOUT_VERSION {
+            (id >> VERSION_BITS) as u8 % (1 << FORMAT_BITS_64) + 1
+        } else {
+            0
+        }
+    }
+
+    /// Split an ID from [`IdGenerator::gen64_versioned`] into its fields, in the layout
+    /// named by its format version rather than the current [`Layout64`].
+    ///
+    /// # Errors
+    /// [`IdError::UnknownFormatVersion`] with version 0 for IDs that record no layout:
+    /// IDs with layout version 1, which are in whatever layout their generator was
+    /// configured with, and IDs generated without version bits, which cannot be told
+    /// apart from other layouts and must be decoded with the layout they were minted
+    /// in. An unversioned ID whose low 2 bits happen to read as layout version 2 is
+    /// indistinguishable from a versioned one; see [`IdGenerator::gen64_versioned`].
+    ///
+    /// Unlike [`IdGenerator::layout_version`], this reads the version whether or not
+    /// [`AtomicOption::version_bits`] is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdGenerator, Layout64};
+    /// let generator = IdGenerator::new(5000, 3);
+    /// AtomicOption::version_bits(true);
+    /// AtomicOption::layout64(Layout64::WideNode);
+    /// let id = generator.gen64_versioned().unwrap();
+    /// AtomicOption::layout64(Layout64::Standard);
+    /// AtomicOption::version_bits(false);
+    /// assert_eq!(IdGenerator::decompose_auto(id).unwrap().node_id, 5000);
+    /// ```
+    pub fn decompose_auto(id: u64) -> Result<Id64Parts, IdError> {
+        let version = Self::format_version(id);
+        let layout = Layout64::from_format_version(version).ok_or(IdError::UnknownFormatVersion(version))?;
+        Ok(layout.decompose_version(id, FORMAT_LAYOUT_VERSION, thread_id_bits()))
+    }
+
+    /// The layout version of a 64-bit ID: 1 for IDs in the current layouts while
+    /// [`AtomicOption::version_bits`] is enabled, 2 for IDs that also record their
+    /// layout ([`IdGenerator::gen64_versioned`]), or 0 for unversioned IDs, the
+    /// default. 3 is reserved for future layouts.
+    ///
+    /// The version is only read while version bits are enabled; otherwise every ID,
+    /// including a versioned one, reads as version 0 and is split in the current
+    /// layout. Decode versioned IDs with [`IdGenerator::decompose_auto`], which does
+    /// not depend on the setting.
+    ///
+    /// [`Layout64::decompose`] reads the thread and sequence fields according to the
+    /// version. The version bits sit below the sequence, so the time fields, and
+    /// therefore [`IdGenerator::created_at64`], read the same in every version.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdGenerator};
+    /// AtomicOption::version_bits(true);
+    /// let id = IdGenerator::new(7, 3).gen64();
+    /// assert_eq!(IdGenerator::layout_version(id), 1);
+    /// assert_eq!(IdGenerator::decompose64(id).version, 1);
+    /// ```
+    pub fn layout_version(id: u64) -> u8 {
+        if version_bits() { (id & ((1 << VERSION_BITS) - 1)) as u8 } else { 0 }
+    }
+
+    /// Split a 128-bit ID in the [`IdGenerator::gen128`] layout into its fields.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let generator = IdGenerator::new(300, 7);
+    /// let parts = IdGenerator::decompose128(generator.gen128());
+    /// assert_eq!((parts.node_id, parts.shard_id), (300, 7));
+    /// ```
+    pub fn decompose128(id: u128) -> Id128Parts {
+        let (high, low) = ((id >> 64) as u64, id as u64);
+        Id128Parts {
+            timestamp: high >> 32,
+            node_id: ((high >> 20) & 0xFFF) as u16,
+            shard_id: (high >> 12) as u8,
+            thread_id: (high >> 4) as u8,
+            reserved: (high & 0xF) as u8,
+            nanos: (low >> 32) as u32,
+            sequence: ((low >> 8) & 0xFF_FFFF) as u32,
+        }
+    }
+
+    /// The tag of an [`IdGenerator::gen128_tagged`] ID: the reserved bits above the
+    /// layout version. For untagged IDs this reads nanosecond bits.
+    pub fn tag128(id: u128) -> u8 {
+        ((id >> 64) & 0xF) as u8 >> (4 - tag_bits128())
+    }
+
+    /// The layout version of a 128-bit ID, from bits 65-64. See
+    /// [`IdGenerator::layout_version`].
+    pub fn layout_version128(id: u128) -> u8 {
+        if version_bits() { ((id >> 64) & ((1 << VERSION_BITS) - 1)) as u8 } else { 0 }
+    }
+
+    /// When a 64-bit ID from this generator was created, in milliseconds since
+    /// UNIX_EPOCH: this generator's epoch plus the time recorded in the ID, read in
+    /// the current [`Layout64`].
+    ///
+    /// The time fields wrap (see [`Layout64::timestamp_bits`]), so the result is only
+    /// correct for IDs created within one wrap period of the epoch. In
+    /// [`Layout64::Era`] the era extends that period eightfold.
+    ///
+    /// As with [`IdGenerator::decompose64`], the reader must share the writer's
+    /// [`AtomicOption::version_bits`] setting. The time fields sit above the version
+    /// bits, so this reads the same either way today, but layout versions 2 and 3 are
+    /// only recognised with the setting enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdGenerator, Layout64};
+    /// AtomicOption::layout64(Layout64::Era);
+    /// let generator = IdGenerator::builder().epoch_ms(1_000_000).build();
+    /// // Era 2, 5 ms into the period.
+    /// let id = ((2u64 << 20) | 5) << 41;
+    /// assert_eq!(IdGenerator::decompose64(id).era, 0);
+    /// assert_eq!(Layout64::Era.decompose(id).era, 2);
+    /// assert_eq!(generator.created_at64(id), 1_000_000 + (2 << 20) + 5);
+    /// ```
+    pub fn created_at64(&self, id: u64) -> u64 {
+        self.epoch_ms() + Layout64::current().elapsed_ms(id)
+    }
+
+    /// Floor the timestamp of a raw 64-bit ID to a multiple of `bucket`, for grouping
+    /// IDs by time without decoding strings or allocating.
+    ///
+    /// The result is the start of the bucket, in milliseconds since the epoch, so IDs
+    /// from the same bucket share a key and keys sort in time order. The ID is read in
+    /// the current [`Layout64`], including the era in [`Layout64::Era`].
+    ///
+    /// The time fields wrap every 2^20 ms (about 17.5 minutes), or 2^23 ms with an
+    /// era (see [`AtomicId::<x64>::time_remaining`](AtomicId::<64>::time_remaining)),
+    /// so keys are only meaningful for buckets shorter than that, within one wrap
+    /// period. For longer spans such as hourly buckets, use IDs from
+    /// [`IdGenerator::gen128_monotonic`], whose 48-bit timestamp lasts about 8900
+    /// years; the 32-bit timestamp of [`IdGenerator::gen128`] wraps after about 49.7
+    /// days.
+    ///
+    /// # Panics
+    /// If `bucket` is shorter than one millisecond.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// use std::time::Duration;
+    /// let id = 12_345u64 << 44;
+    /// assert_eq!(IdGenerator::time_bucket64(id, Duration::from_secs(1)), 12_000);
+    /// ```
+    pub fn time_bucket64(id: u64, bucket: Duration) -> u64 {
+        let bucket_ms = bucket.as_millis() as u64;
+        assert!(bucket_ms > 0, "time buckets must be at least 1 ms");
+        let ts = Layout64::current().elapsed_ms(id);
+        ts - ts % bucket_ms
+    }
+
+    /// Like [`IdGenerator::time_bucket64`], for a 64-bit ID encoded as base36, the
+    /// default encoding of [`AtomicId::<x64>::new`](AtomicId::<64>::new).
+    ///
+    /// # Errors
+    /// - [`IdError::Empty`] or [`IdError::InvalidCharacter`] if `id` is not base36.
+    /// - [`IdError::Overflow`] if the value does not fit in 64 bits.
+    ///
+    /// # Panics
+    /// If `bucket` is shorter than one millisecond.
+    pub fn time_bucket64_from_str(id: &str, bucket: Duration) -> Result<u64, IdError> {
+        Ok(Self::time_bucket64(decode_base36_64(id)?, bucket))
+    }
+
+    /// Split a time-ordered 32-bit ID ([`IdGenerator::gen32_timed`]) into its fields.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::IdGenerator;
+    /// let parts = IdGenerator::decompose32((5 << 12) | 9);
+    /// assert_eq!((parts.timestamp, parts.sequence), (5, 9));
+    /// ```
+    pub fn decompose32(id: u32) -> Id32Parts {
+        Id32Parts {
+            timestamp: id >> 12,
+            sequence: (id & 0xFFF) as u16,
+        }
+    }
+
+    /// Derive a deterministic 64-bit ID from input data, optionally scoped by a namespace.
+    ///
+    /// The same `namespace` and `data` always produce the same ID, on every platform
+    /// and across crate versions, which makes derived IDs suitable as idempotency keys.
+    /// The value is the 64-bit FNV-1a hash of:
+    ///
+    /// `namespace length (u64, little-endian) | namespace bytes | data`
+    ///
+    /// The length prefix keeps namespaces from bleeding into the data. An empty
+    /// namespace is the "no namespace" case used by [`AtomicId::derive`].
+    ///
+    /// Derived IDs carry no timestamp, node, or sequence, so they are not guaranteed
+    /// to be distinct from generated IDs or from each other for different inputs.
+    ///
+    /// # Returns
+    /// A 64-bit derived ID as a `u64`.
+    pub fn derive64(namespace: &str, data: &[u8]) -> u64 {
+        let len = (namespace.len() as u64).to_le_bytes();
+        hash::fnv1a64(&[&len, namespace.as_bytes(), data])
+    }
+
+    /// Derive a deterministic 128-bit ID from input data, optionally scoped by a namespace.
+    ///
+    /// Identical to [`IdGenerator::derive64`], but uses the 128-bit FNV-1a hash.
+    ///
+    /// # Returns
+    /// A 128-bit derived ID as a `u128`.
+    pub fn derive128(namespace: &str, data: &[u8]) -> u128 {
+        let len = (namespace.len() as u64).to_le_bytes();
+        hash::fnv1a128(&[&len, namespace.as_bytes(), data])
+    }
+}
+
+/// Encoding utilities for converting numeric IDs to various string representations.
+///
+/// Supported encodings:
+/// - `base36`: `[0-9a-z]`
+/// - `base58`: Bitcoin alphabet (e.g., for short URLs)
+/// - `base91`: dense printable ASCII, not URL-safe
+/// - `base91_urlsafe`: base91 without `%`, `#`, or `?` (but with `/`)
+/// - `hex`: `[0-9a-f]`
+mod encode {
+    use crate::IdError;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use alloc::format;
+
+    /// Bitcoin-style base58 alphabet (no `0`, `O`, `I`, `l`).
+    pub const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    /// Base91 alphabet (printable ASCII). Contains `%`, `#`, `?`, `/`, and `"`, so it is
+    /// not URL-safe; see [`BASE91_URLSAFE`].
+    pub const BASE91: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!#$%&()*+,./:;<=>?@[]^_`{|}~\"";
+    /// Base91 alphabet for URLs: the 66 RFC 3986 unreserved characters, the 14 other
+    /// characters that are legal unencoded in a query string or fragment
+    /// (`!$&'()*+,;=:@/`), and 11 that strict URL parsers need percent-encoded
+    /// (`` "<>[\]^`{|} ``). No alphabet of 91 printable ASCII characters can avoid the
+    /// last group; this one leaves out `%`, `#`, and `?` instead, so an unencoded ID
+    /// never starts an escape, a fragment, or a query.
+    ///
+    /// `/` is the one character left that splits a URL path, so percent-encode IDs
+    /// placed in a path segment, or use base58 there. It cannot be swapped for an
+    /// unreserved character: all 66 are already in the alphabet, and the only
+    /// printable ASCII characters outside it are space, `%`, `#`, and `?`.
+    pub const BASE91_URLSAFE: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~!$&'()*+,;=:@/\"<>[\\]^`{|}";
+    /// Base36 alphabet (0-9, a-z).
+    pub const BASE36: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    /// Check that `alphabet` can serve as the digits of a base: 2 to 256 symbols, and,
+    /// in debug builds, no symbol twice.
+    ///
+    /// The base is always `alphabet.len()`, so the two can never disagree. Each byte
+    /// is one symbol, read as the Unicode code point of the same value (Latin-1), so
+    /// an alphabet of 256 bytes yields 256 distinct characters.
+    #[inline]
+    fn check_alphabet(alphabet: &[u8]) {
+        assert!(
+            (2..=256).contains(&alphabet.len()),
+            "an encoding alphabet needs 2 to 256 symbols, got {}",
+            alphabet.len()
+        );
+        let mut seen = [false; 256];
+        debug_assert!(
+            alphabet.iter().all(|&a| !core::mem::replace(&mut seen[a as usize], true)),
+            "encoding alphabet contains a repeated symbol"
+        );
+    }
+
+    /// Convert a number to a string in the base given by the alphabet's length.
+    ///
+    /// # Arguments
+    /// * `n` - The number to encode.
+    /// * `alphabet` - The character set for encoding; its length (2 to 256) is the base.
+    ///   Bytes from `0x80` up stand for the Latin-1 characters `U+0080..=U+00FF`.
+    /// * `min_width` - The minimum width of the output string, padded with the first alphabet character.
+    ///
+    /// # Returns
+    /// The encoded string.
+    ///
+    /// # Panics
+    /// If `alphabet` has fewer than 2 or more than 256 symbols.
+    pub fn to_base(mut n: u128, alphabet: &[u8], min_width: usize) -> String {
+        check_alphabet(alphabet);
+        let base = alphabet.len() as u128;
+
+        // Digits are written from the back, most significant last, so the filled
+        // suffix is already in order. Base 2 needs the most: one digit per bit.
+        let mut buf = [0u8; u128::BITS as usize];
+        let mut start = buf.len();
+        loop {
+            start -= 1;
+            buf[start] = alphabet[(n % base) as usize];
+            n /= base;
+            if n == 0 {
+                break;
+            }
+        }
+
+        let digits = &buf[start..];
+        let pad = min_width.saturating_sub(digits.len());
+        let mut s = String::with_capacity(pad + digits.len());
+        s.extend(core::iter::repeat_n(alphabet[0] as char, pad));
+        s.extend(digits.iter().map(|&b| char::from(b)));
+        wipe(&mut buf);
+        s
+    }
+
+    /// Encode a number as a base58 string.
+    pub fn base58(n: u128, width: usize) -> String {
+        to_base(n, BASE58, width)
+    }
+
+    /// Encode a number as a base91 string.
+    pub fn base91(n: u128, width: usize) -> String {
+        to_base(n, BASE91, width)
+    }
+
+    /// Encode a number as a URL-safe base91 string.
+    pub fn base91_urlsafe(n: u128, width: usize) -> String {
+        to_base(n, BASE91_URLSAFE, width)
+    }
+
+    /// Encode a number as a base36 string.
+    pub fn base36(n: u128, width: usize) -> String {
+        to_base(n, BASE36, width)
+    }
+
+    /// Batches smaller than this are encoded one value at a time, since the vector path
+    /// only pays off once its setup is spread over a few chunks.
+    #[cfg(any(test, atomic_id_simd))]
+    pub const SIMD_MIN_BATCH: usize = 32;
+
+    /// Encode 64-bit values as base36 strings, each left-padded to at least `width`
+    /// characters, with the `simd` feature's vector path for large batches.
+    pub fn base36_batch(values: &[u64], width: usize) -> Vec<String> {
+        #[cfg(atomic_id_simd)]
+        if values.len() >= SIMD_MIN_BATCH {
+            return base36_batch_simd(values, width);
+        }
+        values.iter().map(|&n| base36(n as u128, width)).collect()
+    }
+
+    /// Encode 64-bit values as base36 strings, extracting the digits of sixteen values
+    /// at a time in vector registers. The output is identical to [`base36`].
+    ///
+    /// Division by 36 has no vector instruction for 64-bit lanes, so each value is
+    /// first split into a top digit and two 6-digit halves below 36^6 < 2^32; the
+    /// halves are then divided in 32-bit lanes, which compile to multiply-and-shift.
+    #[cfg(atomic_id_simd)]
+    pub fn base36_batch_simd(values: &[u64], width: usize) -> Vec<String> {
+        use core::simd::prelude::*;
+
+        const LANES: usize = 16;
+        const DIGITS: usize = 13;
+        const POW6: u64 = 36u64.pow(6);
+
+        let mut out = Vec::with_capacity(values.len());
+        // Digit `i` of every lane, most significant first.
+        let mut columns = [[0u8; LANES]; DIGITS];
+        let mut row = [0u8; DIGITS];
+        for chunk in values.chunks(LANES) {
+            let (mut top, mut high, mut low) = ([0u32; LANES], [0u32; LANES], [0u32; LANES]);
+            for (lane, &n) in chunk.iter().enumerate() {
+                low[lane] = (n % POW6) as u32;
+                high[lane] = (n / POW6 % POW6) as u32;
+                top[lane] = (n / POW6 / POW6) as u32;
+            }
+            let mut extract = |mut x: Simd<u32, LANES>, digits: core::ops::Range<usize>| {
+                for i in digits.rev() {
+                    let q = x / Simd::splat(36);
+                    let d = x - q * Simd::splat(36);
+                    let letter = d.simd_gt(Simd::splat(9)).select(Simd::splat(b'a' as u32 - b'0' as u32 - 10), Simd::splat(0));
+                    columns[i] = (d + letter + Simd::splat(b'0' as u32)).cast::<u8>().to_array();
+                    x = q;
+                }
+            };
+            extract(Simd::from_array(top), 0..1);
+            extract(Simd::from_array(high), 1..7);
+            extract(Simd::from_array(low), 7..DIGITS);
+            for lane in 0..chunk.len() {
+                for (digit, column) in row.iter_mut().zip(&columns) {
+                    *digit = column[lane];
+                }
+                let zeros = row[..DIGITS - 1].iter().take_while(|&&d| d == b'0').count();
+                let start = zeros.min(DIGITS.saturating_sub(width));
+                let mut s = String::with_capacity(width.max(DIGITS - start));
+                s.extend(core::iter::repeat_n('0', width.saturating_sub(DIGITS)));
+                s.push_str(core::str::from_utf8(&row[start..]).unwrap_or_default());
+                out.push(s);
+            }
+        }
+        wipe(&mut columns);
+        wipe(&mut row);
+        out
+    }
+
+    /// Encode a number as a hexadecimal string, the same text as
+    /// `format!("{:0width$x}", n)`.
+    pub fn hex(n: u128, width: usize) -> String {
+        let mut s = String::with_capacity(width.max(32));
+        push_hex(&mut s, n, width);
+        s
+    }
+
+    /// Append the lowercase hex digits of `n`, left-padded with zeros to at least
+    /// `width` characters, to `out`. Reads each nibble from [`HEX`] directly rather
+    /// than going through the formatting machinery.
+    pub fn push_hex(out: &mut String, n: u128, width: usize) {
+        let digits = ((128 - n.leading_zeros() as usize).div_ceil(4)).max(1);
+        let mut buf = [0u8; 32];
+        for (i, b) in buf.iter_mut().rev().enumerate() {
+            *b = HEX[(n >> (i * 4)) as usize & 0xF];
+        }
+        out.extend(core::iter::repeat_n('0', width.saturating_sub(digits)));
+        // Every byte is from `HEX`, so this is ASCII.
+        out.push_str(core::str::from_utf8(&buf[32 - digits..]).unwrap_or_default());
+        wipe(&mut buf);
+    }
+
+    /// Overwrite a scratch buffer that held the digits of an ID, with the `zeroize`
+    /// feature, so the digits of secret IDs don't outlive the call.
+    #[cfg(feature = "zeroize")]
+    pub fn wipe<T: zeroize::Zeroize>(buf: &mut T) {
+        buf.zeroize();
+    }
+
+    /// Without the `zeroize` feature scratch buffers are left as they are.
+    #[cfg(not(feature = "zeroize"))]
+    pub fn wipe<T>(_buf: &mut T) {}
+
+    /// Insert `sep` between every `size` characters of `s`, counting from the left.
+    /// A `size` of zero leaves `s` as it is.
+    pub fn group(s: &str, size: usize, sep: char) -> String {
+        if size == 0 {
+            return s.into();
+        }
+        let mut out = String::with_capacity(s.len() + s.len() / size);
+        for (i, c) in s.chars().enumerate() {
+            if i > 0 && i % size == 0 {
+                out.push(sep);
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    /// Remove every `sep` from `s`, reversing [`group`].
+    pub fn ungroup(s: &str, sep: char) -> String {
+        s.chars().filter(|&c| c != sep).collect()
+    }
+
+    /// Lowercase hexadecimal alphabet.
+    pub const HEX: &[u8] = b"0123456789abcdef";
+
+    /// Decimal digits, for decoding.
+    const DECIMAL: &[u8] = b"0123456789";
+
+    /// Encode a number as a decimal string, without padding.
+    pub fn decimal(n: u128) -> String {
+        format!("{}", n)
+    }
+
+    /// Decode a decimal string. Leading zeros are accepted; signs and whitespace are not.
+    pub fn from_decimal(s: &str) -> Result<u128, IdError> {
+        from_base(s, DECIMAL)
+    }
+
+    /// Parse a string produced by [`to_base`] back into a number.
+    ///
+    /// Leading padding characters are accepted, so any `min_width` round-trips.
+    ///
+    /// # Errors
+    /// - [`IdError::Empty`] for an empty string.
+    /// - [`IdError::InvalidCharacter`] for a character outside `alphabet`.
+    /// - [`IdError::Overflow`] if the value does not fit in 128 bits.
+    ///
+    /// # Panics
+    /// If `alphabet` has fewer than 2 or more than 256 symbols.
+    pub fn from_base(s: &str, alphabet: &[u8]) -> Result<u128, IdError> {
+        check_alphabet(alphabet);
+        if s.is_empty() {
+            return Err(IdError::Empty);
+        }
+        let base = alphabet.len() as u128;
+        s.chars().try_fold(0u128, |n, c| {
+            let digit = alphabet
+                .iter()
+                .position(|&a| a as char == c)
+                .ok_or(IdError::InvalidCharacter(c))?;
+            n.checked_mul(base)
+                .and_then(|n| n.checked_add(digit as u128))
+                .ok_or(IdError::Overflow)
+        })
+    }
+
+    /// Encode a 256-bit value, given as four parts with the most significant first, as
+    /// a single number in the base given by the alphabet's length, left-padded to at
+    /// least `min_width` characters.
+    ///
+    /// # Panics
+    /// If `alphabet` has fewer than 2 or more than 256 symbols.
+    pub fn to_base_256(parts: [u64; 4], alphabet: &[u8], min_width: usize) -> String {
+        check_alphabet(alphabet);
+        let base = alphabet.len() as u128;
+        let mut n = parts;
+        let mut chars = Vec::new();
+        loop {
+            // Long division of the whole value by the base, one 64-bit part at a time.
+            let mut rem = 0u128;
+            for part in n.iter_mut() {
+                let cur = (rem << 64) | *part as u128;
+                *part = (cur / base) as u64;
+                rem = cur % base;
+            }
+            chars.push(alphabet[rem as usize]);
+            if n == [0; 4] {
+                break;
+            }
+        }
+        while chars.len() < min_width {
+            chars.push(alphabet[0]);
+        }
+        let s = chars.iter().rev().map(|&b| char::from(b)).collect();
+        wipe(&mut chars);
+        s
+    }
+
+    /// Reverse [`to_base_256`].
+    pub fn from_base_256(s: &str, alphabet: &[u8]) -> Result<[u64; 4], IdError> {
+        check_alphabet(alphabet);
+        if s.is_empty() {
+            return Err(IdError::Empty);
+        }
+        let base = alphabet.len() as u128;
+        s.chars().try_fold([0u64; 4], |mut n, c| {
+            let digit = alphabet
+                .iter()
+                .position(|&a| a as char == c)
+                .ok_or(IdError::InvalidCharacter(c))?;
+            let mut carry = digit as u128;
+            for part in n.iter_mut().rev() {
+                let cur = *part as u128 * base + carry;
+                *part = cur as u64;
+                carry = cur >> 64;
+            }
+            if carry != 0 {
+                return Err(IdError::Overflow);
+            }
+            Ok(n)
+        })
+    }
+
+    /// Decode a base58 string.
+    pub fn from_base58(s: &str) -> Result<u128, IdError> {
+        from_base(s, BASE58)
+    }
+
+    /// Decode a base91 string.
+    pub fn from_base91(s: &str) -> Result<u128, IdError> {
+        from_base(s, BASE91)
+    }
+
+    /// Decode a URL-safe base91 string.
+    pub fn from_base91_urlsafe(s: &str) -> Result<u128, IdError> {
+        from_base(s, BASE91_URLSAFE)
+    }
+
+    /// Decode a base36 string.
+    pub fn from_base36(s: &str) -> Result<u128, IdError> {
+        from_base(s, BASE36)
+    }
+
+    /// Decode a lowercase hexadecimal string.
+    pub fn from_hex(s: &str) -> Result<u128, IdError> {
+        from_base(s, HEX)
+    }
+}
+
+/// One field of an ID [`Layout`]: `bit_width` bits starting `bit_offset` bits above
+/// the least significant bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldSpec {
+    /// The field's name, such as `"timestamp"` or `"node"`.
+    pub name: &'static str,
+    /// Position of the field's least significant bit.
+    pub bit_offset: u32,
+    /// Width of the field in bits.
+    pub bit_width: u32,
+}
+
+impl FieldSpec {
+    /// The field's bits set, in place.
+    pub const fn mask(self) -> u128 {
+        ((1u128 << self.bit_width) - 1) << self.bit_offset
+    }
+
+    /// The value of this field in a raw ID.
+    pub const fn extract(self, id: u128) -> u128 {
+        (id & self.mask()) >> self.bit_offset
+    }
+}
+
+/// The fields of one ID width, most significant first, as the generator packs them
+/// with the current settings. Returned by `layout()` on [`AtomicId`], such as
+/// [`AtomicId::<x64>::layout`](AtomicId::<64>::layout).
+///
+/// The 64-bit layout depends on [`Layout64`], [`TimestampPrecision`],
+/// [`AtomicOption::thread_id_bits`], and [`AtomicOption::version_bits`], so it is
+/// built on each call rather than kept in a static. `Display` writes one field per
+/// line, for generated documentation.
+///
+/// # Example
+/// ```
+/// use atomic_id::{AtomicId, x64};
+/// let layout = AtomicId::<x64>::layout();
+/// assert!(layout.is_exact());
+/// let node = layout.field("node").unwrap();
+/// assert_eq!((node.bit_offset, node.bit_width), (32, 12));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Layout {
+    bits: u32,
+    fields: [FieldSpec; Layout::MAX_FIELDS],
+    len: usize,
+}
+
+impl Layout {
+    const MAX_FIELDS: usize = 10;
+
+    /// Lay out `fields`, given most significant first with their widths, each
+    /// directly below the previous one from the top of a `bits`-bit ID. Fields of
+    /// width 0 are left out.
+    fn stacked(bits: u32, fields: &[(&'static str, u32)]) -> Layout {
+        let empty = FieldSpec { name: "", bit_offset: 0, bit_width: 0 };
+        let mut layout = Layout { bits, fields: [empty; Layout::MAX_FIELDS], len: 0 };
+        let mut top = bits;
+        for &(name, bit_width) in fields.iter().filter(|(_, width)| *width > 0) {
+            top -= bit_width;
+            layout.fields[layout.len] = FieldSpec { name, bit_offset: top, bit_width };
+            layout.len += 1;
+        }
+        layout
+    }
+
+    /// Width of the ID in bits.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// The fields, most significant first.
+    pub fn fields(&self) -> &[FieldSpec] {
+        &self.fields[..self.len]
+    }
+
+    /// The field called `name`, if the layout has one.
+    pub fn field(&self, name: &str) -> Option<FieldSpec> {
+        self.fields().iter().copied().find(|field| field.name == name)
+    }
+
+    /// Whether the fields are pairwise disjoint and together cover exactly
+    /// [`Layout::bits`] bits.
+    pub fn is_exact(&self) -> bool {
+        let mut covered = 0u128;
+        let mut total = 0;
+        for field in self.fields() {
+            if field.bit_offset + field.bit_width > self.bits || covered & field.mask() != 0 {
+                return false;
+            }
+            covered |= field.mask();
+            total += field.bit_width;
+        }
+        total == self.bits
+    }
+}
+
+impl core::fmt::Display for Layout {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for field in self.fields() {
+            let (low, high) = (field.bit_offset, field.bit_offset + field.bit_width - 1);
+            writeln!(f, "{:>3}..={:<3} {:>2} bits  {}", high, low, field.bit_width, field.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// The effective 64-bit layout for the given settings.
+///
+/// The time fields count ticks of `precision`. In `Micros` the low tick bits sit
+/// right below them, as `timestamp_low`, above the fields that moved down to make
+/// room; in `Seconds` the high sequence bits sit at the bottom of the time fields,
+/// as `sequence_high`.
+fn layout64(layout: Layout64, precision: TimestampPrecision, thread_bits: u32, version: bool) -> Layout {
+    let version_bits = if version { VERSION_BITS } else { 0 };
+    let field_bits = THREAD_SEQ_BITS_64 - version_bits - thread_bits;
+    let shift = precision.shift(field_bits);
+    let (timestamp_low, sequence_high) = (shift.max(0) as u32, (-shift).max(0) as u32);
+    let (era, region, node, shard) = match layout {
+        Layout64::Standard => (0, 0, 12, 8),
+        Layout64::WideNode => (0, 0, 16, 4),
+        Layout64::Era => (ERA_BITS_64, 0, 12, 5),
+        Layout64::Regional => (0, REGION_BITS_64, 12, 4),
+    };
+    Layout::stacked(64, &[
+        ("era", era),
+        ("timestamp", TS_BITS_64 - sequence_high),
+        ("sequence_high", sequence_high),
+        ("timestamp_low", timestamp_low),
+        ("region", region),
+        ("node", node),
+        ("shard", shard),
+        ("thread", thread_bits),
+        ("sequence", field_bits - timestamp_low),
+        ("version", version_bits),
+    ])
+}
+
+/// Bit layouts for 64-bit IDs. Select the global layout with [`AtomicOption::layout64`].
+///
+/// | Layout      | Era | Timestamp | Node | Shard | Thread | Sequence |
+/// |-------------|-----|-----------|------|-------|--------|----------|
+/// | `Standard`  | -   | 20        | 12   | 8     | 8      | 16       |
+/// | `WideNode`  | -   | 20        | 16   | 4     | 8      | 16       |
+/// | `Era`       | 3   | 20        | 12   | 5     | 8      | 16       |
+/// | `Regional`  | -   | 20        | 12   | 4     | 8      | 16       |
+///
+/// `Regional` spends the 4 shard bits it gives up on a region field between the
+/// timestamp and the node; see [`IdGenerator::gen64_regional`].
+///
+/// The thread and sequence widths shown are the defaults. In every layout the two
+/// fields share the low 24 bits, split by [`AtomicOption::thread_id_bits`], with a
+/// 2-bit layout version below them while [`AtomicOption::version_bits`] is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum Layout64 {
+    /// The default layout, with up to 4096 nodes and 256 shards.
+    #[default]
+    Standard = 0,
+    /// Up to 65,536 nodes and 16 shards, for large fleets.
+    WideNode = 1,
+    /// Up to 4096 nodes and 32 shards, with a 3-bit era above the timestamp.
+    ///
+    /// When the 20-bit timestamp field rolls over (every 2^20 ms, about 17.5
+    /// minutes), the era advances instead of the IDs silently wrapping, so IDs keep
+    /// sorting in generation order across the rollover. Together the era and the
+    /// timestamp count 2^23 ms (about 2 hours 20 minutes) before the era itself wraps.
+    /// [`IdGenerator::created_at64`] reconstructs the absolute time within that span.
+    Era = 2,
+    /// Up to 16 regions, 4096 nodes, and 16 shards, with the region of
+    /// [`IdGenerator::region_id`] above the node.
+    Regional = 3,
+}
+
+impl Layout64 {
+    /// The layout currently selected with [`AtomicOption::layout64`].
+    pub fn current() -> Layout64 {
+        Self::from_u8(LAYOUT_64.load(Ordering::Relaxed))
+    }
+
+    fn from_u8(value: u8) -> Layout64 {
+        match value {
+            1 => Layout64::WideNode,
+            2 => Layout64::Era,
+            3 => Layout64::Regional,
+            _ => Layout64::Standard,
+        }
+    }
+
+    /// The format version [`IdGenerator::gen64_versioned`] records for this layout:
+    /// 1 for `Standard`, 2 for `WideNode`, 3 for `Era`, and 4 for `Regional`. Existing
+    /// numbers never change meaning; the 2-bit field has no room for more, so a new
+    /// layout will need a new layout version.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Layout64;
+    /// assert_eq!(Layout64::Standard.format_version(), 1);
+    /// assert_eq!(Layout64::from_format_version(4), Some(Layout64::Regional));
+    /// ```
+    pub const fn format_version(self) -> u8 {
+        self as u8 + 1
+    }
+
+    /// The layout with the given [`Layout64::format_version`], or `None` for 0 and
+    /// the unassigned versions.
+    pub const fn from_format_version(version: u8) -> Option<Layout64> {
+        match version {
+            1 => Some(Layout64::Standard),
+            2 => Some(Layout64::WideNode),
+            3 => Some(Layout64::Era),
+            4 => Some(Layout64::Regional),
+            _ => None,
+        }
+    }
+
+    /// Width in bits of the time fields (the timestamp, plus the era if any), which
+    /// together wrap every `2^timestamp_bits()` ms in the default
+    /// [`TimestampPrecision`]. See [`TimestampPrecision::timestamp_bits`] for the
+    /// other precisions.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Layout64;
+    /// assert_eq!(Layout64::Standard.timestamp_bits(), 20);
+    /// assert_eq!(Layout64::Era.timestamp_bits(), 23);
+    /// ```
+    pub const fn timestamp_bits(self) -> u32 {
+        match self {
+            Layout64::Standard | Layout64::WideNode | Layout64::Regional => TS_BITS_64,
+            Layout64::Era => TS_BITS_64 + ERA_BITS_64,
+        }
+    }
+
+    /// Milliseconds since the epoch recorded in an ID in this layout, combining the
+    /// era and the timestamp. Like the fields, it wraps every
+    /// [`TimestampPrecision::timestamp_bits`] ticks; in `Micros` the sub-millisecond
+    /// part is dropped.
+    pub fn elapsed_ms(self, id: u64) -> u64 {
+        let precision = TimestampPrecision::current();
+        precision.ticks_to_ms(self.ticks_and_sequence(precision, id, IdGenerator::layout_version(id), thread_id_bits()).0)
+    }
+
+    /// The smallest and largest 64-bit IDs in this layout whose time fields can hold
+    /// a time from `from_ms` to `to_ms` milliseconds since the epoch, inclusive, for
+    /// range scans over IDs stored as integers. Every bit below the time fields is 0
+    /// in the first and 1 in the second.
+    ///
+    /// The bounds follow the current [`TimestampPrecision`]. In `Seconds` they widen
+    /// to whole seconds. Times are truncated to the time fields, so the range is only
+    /// meaningful within one wrap period.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Layout64;
+    /// let (start, end) = Layout64::Standard.id_range(5, 7);
+    /// assert_eq!((start, end), (5 << 44, (8 << 44) - 1));
+    /// // 6 ms, node 300, shard 7.
+    /// let id = (6 << 44) | (300 << 32) | (7 << 24) | 1;
+    /// assert!((start..=end).contains(&id));
+    /// ```
+    pub fn id_range(self, from_ms: u64, to_ms: u64) -> (u64, u64) {
+        let precision = TimestampPrecision::current();
+        let thread_bits = thread_id_bits();
+        let (field_bits, low_bits) = (seq_bits64(thread_bits), if version_bits() { VERSION_BITS } else { 0 });
+        let last_tick = precision.last_tick_of_ms(to_ms);
+        let (start, start_seq) = pack_ticks64(precision, self, precision.ms_to_ticks(from_ms), 0, thread_bits);
+        let (end, end_seq) = pack_ticks64(precision, self, last_tick, u64::MAX, thread_bits);
+        let shift = 64 - self.timestamp_bits();
+        let seq_mask = ((1 << field_bits) - 1) << low_bits;
+        let start = start << shift | start_seq << low_bits;
+        let end = end << shift | (((1 << shift) - 1) & !seq_mask) | end_seq << low_bits;
+        let raise = |id| raise_ticks64(precision, self, id, field_bits, low_bits);
+        (raise(start), raise(end))
+    }
+
+    /// The time in ticks of `precision` and the sequence recorded in an ID in this
+    /// layout, with the sequence field split according to the layout version and a
+    /// `thread_bits`-bit thread ID field.
+    fn ticks_and_sequence(self, precision: TimestampPrecision, id: u64, version: u8, thread_bits: u32) -> (u64, u64) {
+        let low_bits = version_field_bits64(version);
+        let field_bits = THREAD_SEQ_BITS_64 - low_bits - thread_bits;
+        let id = lower_ticks64(precision, self, id, field_bits, low_bits);
+        let thread_seq = (id & 0xFFFFFF) >> low_bits;
+        let time = id >> (64 - self.timestamp_bits());
+        unpack_ticks64(precision, time, thread_seq & ((1 << field_bits) - 1), field_bits)
+    }
+
+    /// Split a 64-bit ID in this layout into its fields.
+    ///
+    /// The thread and sequence fields are split at the current
+    /// [`AtomicOption::thread_id_bits`] setting, after the layout version
+    /// ([`IdGenerator::layout_version`]) if it is one that has a version field. To
+    /// read IDs generated with another setting, use
+    /// [`Layout64::decompose_with_thread_bits`].
+    pub fn decompose(self, id: u64) -> Id64Parts {
+        self.decompose_version(id, IdGenerator::layout_version(id), thread_id_bits())
+    }
+
+    /// Split a 64-bit ID in this layout into its fields, with the thread and
+    /// sequence fields split at `thread_bits` instead of the current
+    /// [`AtomicOption::thread_id_bits`] setting. `thread_bits` is clamped to 1..=16
+    /// like the setting.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdGenerator, Layout64};
+    /// AtomicOption::thread_id_bits(10);
+    /// let id = IdGenerator::new(7, 3).gen64();
+    /// let parts = IdGenerator::decompose64(id);
+    /// AtomicOption::thread_id_bits(8);
+    /// assert_eq!(Layout64::Standard.decompose_with_thread_bits(id, 10), parts);
+    /// ```
+    pub fn decompose_with_thread_bits(self, id: u64, thread_bits: u8) -> Id64Parts {
+        self.decompose_version(id, IdGenerator::layout_version(id), thread_bits.clamp(1, 16) as u32)
+    }
+
+    /// [`Layout64::decompose`] for an ID with the given layout version and thread ID
+    /// width.
+    fn decompose_version(self, id: u64, version: u8, thread_bits: u32) -> Id64Parts {
+        let low_bits = version_field_bits64(version);
+        let seq_bits = THREAD_SEQ_BITS_64 - low_bits - thread_bits;
+        let precision = TimestampPrecision::current();
+        let thread_seq = (lower_ticks64(precision, self, id, seq_bits, low_bits) & 0xFFFFFF) >> low_bits;
+        let fields = layout64(self, precision, thread_bits, version == LAYOUT_VERSION);
+        let field = |name| fields.field(name).map_or(0, |field| field.extract(id as u128));
+        let (region, node_id, shard_id) = (field("region") as u8, field("node") as u16, field("shard") as u8);
+        let (ticks, sequence) = self.ticks_and_sequence(precision, id, version, thread_bits);
+        let ts_bits = precision.timestamp_bits_for(Layout64::Standard, seq_bits);
+        let thread_id = (thread_seq >> seq_bits) as u16;
+        Id64Parts {
+            era: (ticks >> ts_bits) as u8,
+            timestamp: ticks & ((1 << ts_bits) - 1),
+            region,
+            node_id,
+            shard_id,
+            thread_id: thread_id as u8,
+            sequence: sequence as u32,
+            version,
+            wide_thread_id: thread_id,
+        }
+    }
+}
+
+/// The unit of the timestamp in 64-bit IDs. Select the precision with
+/// [`AtomicOption::timestamp_precision`].
+///
+/// The time fields and the sequence field trade bits: each step finer moves 10 bits
+/// from the sequence to the timestamp, so the time fields wrap after about the same
+/// span (see [`Layout64::timestamp_bits`]) at every precision. With the default
+/// 8-bit thread IDs:
+///
+/// | Precision | Timestamp | Sequence | IDs per thread and tick |
+/// |-----------|-----------|----------|-------------------------|
+/// | `Micros`  | 30        | 6        | 64                      |
+/// | `Millis`  | 20        | 16       | 65,536                  |
+/// | `Seconds` | 10        | 26       | 67,108,864              |
+///
+/// In `Micros` the time fields grow downwards into the space the sequence gives up,
+/// and the region, node, shard, and thread fields move down by as many bits, so
+/// every timestamp bit sits above them and IDs sort by the microsecond first. In
+/// `Seconds` the wider sequence takes the bottom of the time fields, which keep
+/// their place. The shift is clamped to leave at least one sequence bit and at most
+/// 32.
+/// IDs are read back with the precision they were generated in, so every process
+/// reading the IDs must use the same setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum TimestampPrecision {
+    /// Milliseconds since the epoch, the timestamp of every layout in the table of
+    /// [`Layout64`].
+    #[default]
+    Millis = 0,
+    /// Microseconds since the epoch, for ordering IDs generated within the same
+    /// millisecond on different nodes, at the cost of a 16-value sequence per tick.
+    Micros = 1,
+    /// Seconds since the epoch, for generators that mint more IDs per second than a
+    /// millisecond sequence can hold.
+    Seconds = 2,
+}
+
+impl TimestampPrecision {
+    /// The precision currently selected with [`AtomicOption::timestamp_precision`].
+    pub fn current() -> TimestampPrecision {
+        match TIMESTAMP_PRECISION.load(Ordering::Relaxed) {
+            1 => TimestampPrecision::Micros,
+            2 => TimestampPrecision::Seconds,
+            _ => TimestampPrecision::Millis,
+        }
+    }
+
+    /// Width in bits of the time fields of `layout` at this precision and the current
+    /// [`AtomicOption::thread_id_bits`] setting. The time fields count ticks of this
+    /// precision and wrap every `2^timestamp_bits` ticks.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{Layout64, TimestampPrecision};
+    /// assert_eq!(TimestampPrecision::Millis.timestamp_bits(Layout64::Standard), 20);
+    /// assert_eq!(TimestampPrecision::Micros.timestamp_bits(Layout64::Standard), 30);
+    /// assert_eq!(TimestampPrecision::Seconds.timestamp_bits(Layout64::Era), 13);
+    /// ```
+    pub fn timestamp_bits(self, layout: Layout64) -> u32 {
+        self.timestamp_bits_for(layout, seq_bits64(thread_id_bits()))
+    }
+
+    /// Width in bits of the sequence at this precision and the current
+    /// [`AtomicOption::thread_id_bits`] setting: how many IDs a thread can generate per
+    /// tick before the sequence wraps.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::TimestampPrecision;
+    /// assert_eq!(TimestampPrecision::Micros.sequence_bits(), 6);
+    /// assert_eq!(TimestampPrecision::Seconds.sequence_bits(), 26);
+    /// ```
+    pub fn sequence_bits(self) -> u32 {
+        self.sequence_bits_for(seq_bits64(thread_id_bits()))
+    }
+
+    /// Bits moved from the sequence to the time fields, for a sequence field of
+    /// `field_bits` bits. Negative in `Seconds`, which moves them the other way.
+    fn shift(self, field_bits: u32) -> i32 {
+        match self {
+            TimestampPrecision::Millis => 0,
+            TimestampPrecision::Micros => 10.min(field_bits as i32 - 1),
+            TimestampPrecision::Seconds => -(10.min(32 - field_bits as i32)),
+        }
+    }
+
+    fn timestamp_bits_for(self, layout: Layout64, field_bits: u32) -> u32 {
+        (layout.timestamp_bits() as i32 + self.shift(field_bits)) as u32
+    }
+
+    fn sequence_bits_for(self, field_bits: u32) -> u32 {
+        (field_bits as i32 - self.shift(field_bits)) as u32
+    }
+
+    /// The tick of this precision containing millisecond `ms`.
+    fn ms_to_ticks(self, ms: u64) -> u64 {
+        match self {
+            TimestampPrecision::Millis => ms,
+            TimestampPrecision::Micros => ms * 1000,
+            TimestampPrecision::Seconds => ms / 1000,
+        }
+    }
+
+    /// The last tick of this precision within millisecond `ms`.
+    fn last_tick_of_ms(self, ms: u64) -> u64 {
+        match self {
+            TimestampPrecision::Micros => ms * 1000 + 999,
+            TimestampPrecision::Millis | TimestampPrecision::Seconds => self.ms_to_ticks(ms),
+        }
+    }
+
+    /// The start of tick `ticks`, in milliseconds.
+    fn ticks_to_ms(self, ticks: u64) -> u64 {
+        match self {
+            TimestampPrecision::Millis => ticks,
+            TimestampPrecision::Micros => ticks / 1000,
+            TimestampPrecision::Seconds => ticks * 1000,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn duration_to_ticks(self, elapsed: Duration) -> u64 {
+        match self {
+            TimestampPrecision::Millis => elapsed.as_millis() as u64,
+            TimestampPrecision::Micros => elapsed.as_micros() as u64,
+            TimestampPrecision::Seconds => elapsed.as_secs(),
+        }
+    }
+
+    fn ticks_to_duration(self, ticks: u64) -> Duration {
+        match self {
+            TimestampPrecision::Millis => Duration::from_millis(ticks),
+            TimestampPrecision::Micros => Duration::from_micros(ticks),
+            TimestampPrecision::Seconds => Duration::from_secs(ticks),
+        }
+    }
+}
+
+/// How thread IDs are assigned. Select the mode with [`AtomicOption::thread_id_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum ThreadIdMode {
+    /// Hash each thread's identity into the thread field. Needs no coordination, but
+    /// distinct threads can share an ID.
+    #[default]
+    Hashed = 0,
+    /// Number threads `1, 2, 3, ...` in the order they first generate an ID, so up to
+    /// `2^bits - 1` threads get distinct IDs. Requires the `std` feature.
+    Sequential = 1,
+}
+
+impl ThreadIdMode {
+    /// The mode currently selected with [`AtomicOption::thread_id_mode`].
+    pub fn current() -> ThreadIdMode {
+        match THREAD_ID_MODE.load(Ordering::Relaxed) {
+            1 => ThreadIdMode::Sequential,
+            _ => ThreadIdMode::Hashed,
+        }
+    }
+}
+
+/// Bit layouts for 24-bit IDs. Select the global layout with [`AtomicOption::layout24`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum Layout24 {
+    /// A 24-bit sequence. IDs repeat after 16.7 million (2^24) IDs, however long
+    /// that takes.
+    #[default]
+    Standard = 0,
+    /// 8 bits of seconds since the epoch, modulo 256, above a 16-bit sequence. An ID
+    /// only repeats if the sequence wraps within the same second modulo 256, so
+    /// reuse is spread over time: up to 65,536 IDs per second stay unique for
+    /// about 4 minutes. Bursts of more than 65,536 IDs in one second repeat sooner
+    /// than in `Standard`. IDs no longer sort in generation order.
+    TimeSalted = 1,
+}
+
+impl Layout24 {
+    /// The layout currently selected with [`AtomicOption::layout24`].
+    pub fn current() -> Layout24 {
+        match LAYOUT_24.load(Ordering::Relaxed) {
+            1 => Layout24::TimeSalted,
+            _ => Layout24::Standard,
+        }
+    }
+
+    /// Number of distinct sequence values in this layout: 2^24 in `Standard`, 2^16 in
+    /// `TimeSalted`.
+    pub const fn sequence_capacity(self) -> u64 {
+        match self {
+            Layout24::Standard => 1 << 24,
+            Layout24::TimeSalted => 1 << 16,
+        }
+    }
+}
+
+/// What [`IdGenerator::try_gen24`] and `AtomicId::<x24>::try_new` do once the 24-bit
+/// sequence is used up. Select the policy with [`AtomicOption::overflow_policy24`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum OverflowPolicy {
+    /// Wrap around and repeat earlier IDs, like [`IdGenerator::gen24`].
+    #[default]
+    Wrap = 0,
+    /// Return [`SequenceExhausted`] until [`AtomicOption::reset24`] is called.
+    Error = 1,
+    /// Wait until another thread calls [`AtomicOption::reset24`]. Never returns an
+    /// error, but waits forever if nothing resets the sequence.
+    Block = 2,
+}
+
+impl OverflowPolicy {
+    /// The policy currently selected with [`AtomicOption::overflow_policy24`].
+    pub fn current() -> OverflowPolicy {
+        match OVERFLOW_POLICY_24.load(Ordering::Relaxed) {
+            1 => OverflowPolicy::Error,
+            2 => OverflowPolicy::Block,
+            _ => OverflowPolicy::Wrap,
+        }
+    }
+}
+
+/// How timestamps are read from the system clock. Select the mode with
+/// [`AtomicOption::clock_mode`]; an installed [`TimeSource`] is always used as is.
+///
+/// Only used with `std` on native targets; the `wasm` clocks and `no_std` ignore it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum ClockMode {
+    /// Read `SystemTime::now()` for every timestamp. IDs carry the true wall-clock
+    /// time, but NTP steps and manual changes can move it backwards, and IDs minted
+    /// after a step back sort before IDs minted before it.
+    #[default]
+    Wall = 0,
+    /// Pair the wall clock with the monotonic clock (`Instant`) when the mode is
+    /// selected, and derive every later timestamp as the anchored wall time plus the
+    /// monotonic time elapsed since. Timestamps advance with the monotonic clock, and
+    /// do not go back while this mode is selected, whatever happens to the wall clock.
+    ///
+    /// The trade-off is that timestamps stop following the wall clock. Steps of the
+    /// wall clock, from manual changes or NTP correcting a clock that was far off, are
+    /// ignored, and where the monotonic clock is not slewed along with the wall clock
+    /// by NTP (it is on Linux), the two drift apart by the clock's frequency error. Set
+    /// [`AtomicOption::clock_reanchor_interval`] to pair them again periodically. The
+    /// anchor only moves forward: a wall clock that got ahead of the timestamps is
+    /// caught up with at the next re-anchoring, while one that fell behind is not
+    /// followed back, and the timestamps stay ahead of it until it moves forward
+    /// again.
+    MonotonicAnchored = 1,
+}
+
+impl ClockMode {
+    /// The mode currently selected with [`AtomicOption::clock_mode`].
+    pub fn current() -> ClockMode {
+        match CLOCK_MODE.load(Ordering::Relaxed) {
+            1 => ClockMode::MonotonicAnchored,
+            _ => ClockMode::Wall,
+        }
+    }
+}
+
+/// What happens when the time since the epoch no longer fits in an ID's timestamp
+/// field. Select the mode with [`AtomicOption::on_overflow`]; it applies to the
+/// timestamp field of every width: 20 bits of seconds in
+/// [`IdGenerator::gen32_timed`], the layout's time fields in 64-bit IDs, 32 bits in
+/// [`IdGenerator::gen128`], 48 bits in [`IdGenerator::gen128_monotonic`], and 20 bits
+/// in [`IdGenerator::gen256`].
+///
+/// The narrow fields overflow quickly: 64-bit IDs 17.5 minutes after the epoch and
+/// 128-bit IDs after about 49.7 days. With the default epoch both have long since
+/// overflowed, so the modes other than `Wrap` are meant for epochs set close to the
+/// start of the process, such as with [`IdGenerator::builder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum OverflowMode {
+    /// Keep the low bits of the timestamp, so the field wraps to zero and starts
+    /// over. IDs stay unique as long as their sequence and thread fields differ
+    /// from IDs minted one or more periods earlier, but they stop sorting by time
+    /// across a wrap.
+    #[default]
+    Wrap = 0,
+    /// Clamp the timestamp to the field's maximum. IDs keep sorting by time, but all
+    /// IDs minted after the overflow share one timestamp, so they are only unique
+    /// until the sequence field wraps once; after that they repeat.
+    Saturate = 1,
+    /// Make [`IdGenerator::gen64_checked`] return [`IdError::TimestampOverflow`],
+    /// without consuming a sequence value. This is the only generator the mode
+    /// changes: every other one, of every width, returns an ID rather than a
+    /// `Result`, so it wraps as in `Wrap`, and the 64-bit ones report
+    /// [`IdEvent::TimestampNearWrap`] before they do. Use `gen64_checked` to never hand
+    /// out an overflowed ID.
+    Error = 2,
+}
+
+impl OverflowMode {
+    /// The mode currently selected with [`AtomicOption::on_overflow`].
+    pub fn current() -> OverflowMode {
+        match OVERFLOW_MODE.load(Ordering::Relaxed) {
+            1 => OverflowMode::Saturate,
+            2 => OverflowMode::Error,
+            _ => OverflowMode::Wrap,
+        }
+    }
+}
+
+/// Bit layouts for [`IdGenerator::gen32`]. Select the global layout with
+/// [`AtomicOption::layout32`].
+///
+/// | Layout       | Seconds | Thread | Sequence |
+/// |--------------|---------|--------|----------|
+/// | `Standard`   | -       | 8      | 24       |
+/// | `TimeSalted` | 10      | -      | 22       |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum Layout32 {
+    /// A thread byte above a 24-bit sequence. IDs repeat once the shared sequence
+    /// wraps, after 16.7 million IDs, unless the thread byte differs.
+    #[default]
+    Standard = 0,
+    /// Seconds since the epoch, modulo 1024, above a 22-bit sequence. An ID only
+    /// repeats if the sequence wraps within the same second modulo 1024, so up to
+    /// 4 million IDs per second stay unique for about 17 minutes, however long the
+    /// service runs. There are no thread bits, so a burst of more than 2^22 IDs in
+    /// one second repeats.
+    TimeSalted = 1,
+}
+
+impl Layout32 {
+    /// The layout currently selected with [`AtomicOption::layout32`].
+    pub fn current() -> Layout32 {
+        match LAYOUT_32.load(Ordering::Relaxed) {
+            1 => Layout32::TimeSalted,
+            _ => Layout32::Standard,
+        }
+    }
+
+    /// Number of distinct sequence values in this layout: 2^24 in `Standard`, 2^22 in
+    /// `TimeSalted`.
+    pub const fn sequence_capacity(self) -> u64 {
+        match self {
+            Layout32::Standard => 1 << 24,
+            Layout32::TimeSalted => 1 << 22,
+        }
+    }
+
+    /// Split an ID from [`IdGenerator::gen32`] in this layout into its fields. Fields
+    /// the layout does not have are 0.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Layout32;
+    /// let parts = Layout32::Standard.decompose(0xFF00_0009);
+    /// assert_eq!((parts.thread_id, parts.sequence), (255, 9));
+    /// let parts = Layout32::TimeSalted.decompose((5 << 22) | 9);
+    /// assert_eq!((parts.timestamp, parts.sequence), (5, 9));
+    /// ```
+    pub const fn decompose(self, id: u32) -> Layout32Parts {
+        match self {
+            Layout32::Standard => Layout32Parts {
+                timestamp: 0,
+                thread_id: (id >> 24) as u8,
+                sequence: id & 0xFFFFFF,
+            },
+            Layout32::TimeSalted => Layout32Parts {
+                timestamp: id >> 22,
+                thread_id: 0,
+                sequence: id & 0x3FFFFF,
+            },
+        }
+    }
+}
+
+/// The fields of a time-ordered 32-bit ID, as returned by [`IdGenerator::decompose32`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Id32Parts {
+    /// Seconds since the epoch, truncated to 20 bits.
+    pub timestamp: u32,
+    /// Sequence number.
+    pub sequence: u16,
+}
+
+/// The fields of a 32-bit ID from [`IdGenerator::gen32`], as returned by
+/// [`Layout32::decompose`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Layout32Parts {
+    /// Seconds since the epoch, modulo 1024. 0 if the layout has no timestamp.
+    pub timestamp: u32,
+    /// Thread identifier. 0 if the layout has no thread field.
+    pub thread_id: u8,
+    /// Sequence number.
+    pub sequence: u32,
+}
+
+/// The fields of a 64-bit ID, as returned by [`IdGenerator::decompose64`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Id64Parts {
+    /// Number of times the timestamp field has rolled over, in [`Layout64::Era`]
+    /// (modulo 8). Always 0 in layouts without an era.
+    pub era: u8,
+    /// Time since the epoch in ticks of the [`TimestampPrecision`] (milliseconds by
+    /// default), truncated to the layout's timestamp width.
+    pub timestamp: u64,
+    /// Region identifier, in [`Layout64::Regional`]. Always 0 in layouts without a
+    /// region.
+    pub region: u8,
+    /// Node identifier.
+    pub node_id: u16,
+    /// Shard identifier.
+    pub shard_id: u8,
+    /// Thread identifier, truncated to its low 8 bits. With a thread ID field of the
+    /// default 8 bits this is the whole of it; see [`Id64Parts::wide_thread_id`] for
+    /// wider fields.
+    pub thread_id: u8,
+    /// Sequence number.
+    pub sequence: u32,
+    /// Layout version, as read by [`IdGenerator::layout_version`].
+    pub version: u8,
+    /// Thread identifier, all of the up to 16 bits set with
+    /// [`AtomicOption::thread_id_bits`].
+    pub wide_thread_id: u16,
+}
+
+/// The fields of a 128-bit ID, as returned by [`IdGenerator::decompose128`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Id128Parts {
+    /// Time since the epoch in milliseconds, truncated to 32 bits.
+    pub timestamp: u64,
+    /// Node identifier.
+    pub node_id: u16,
+    /// Shard identifier.
+    pub shard_id: u8,
+    /// Thread identifier.
+    pub thread_id: u8,
+    /// The 4-bit reserved field: nanosecond bits, or the tag of an
+    /// [`IdGenerator::gen128_tagged`] ID, with the layout version in the low 2 bits
+    /// when [`AtomicOption::version_bits`] is enabled.
+    pub reserved: u8,
+    /// Low 32 bits of the nanosecond clock.
+    pub nanos: u32,
+    /// Sequence number, truncated to 24 bits.
+    pub sequence: u32,
+}
+
+/// A 64-bit ID kept as its raw value, for storing and passing IDs without encoding
+/// them to strings. Displays as the 13-character base36 form of [`AtomicId::<x64>::new`](AtomicId::new).
+///
+/// # Example
+/// ```
+/// use atomic_id::Id64;
+/// let id = Id64::new();
+/// assert_eq!(id.to_string().len(), 13);
+/// assert_eq!(Id64::from_i64(id.to_i64()), id);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id64(pub u64);
+
+impl Id64 {
+    /// Generate a new ID from the global generator.
+    pub fn new() -> Self {
+        Id64(xgen().gen64())
+    }
+
+    /// Generate a new ID with the top bit cleared, so it is non-negative as an `i64`.
+    ///
+    /// The top bit is the high bit of the timestamp, so these IDs wrap twice as often
+    /// (every 2^19 ms, about 8.7 minutes). Use them for signed 64-bit columns that must
+    /// sort in generation order.
+    pub fn new_i64_safe() -> Self {
+        Id64(AtomicId::<64>::raw_positive())
+    }
+
+    /// Reinterpret the bits as an `i64`, for signed 64-bit storage such as SQLite
+    /// `INTEGER`. IDs with the top bit set become negative and sort before the rest;
+    /// see [`Id64::new_i64_safe`].
+    pub const fn to_i64(self) -> i64 {
+        self.0 as i64
+    }
+
+    /// Reverse [`Id64::to_i64`].
+    pub const fn from_i64(value: i64) -> Self {
+        Id64(value as u64)
+    }
+
+    /// The 16 hex digits of the ID in groups of 8, such as `0a1b2c3d-4e5f6071`.
+    pub fn to_grouped_hex(self) -> String {
+        format_grouped(&encode::hex(self.0 as u128, 16), 8, '-')
+    }
+
+    /// Parse hex with any grouping, case, or surrounding whitespace, such as the
+    /// output of [`Id64::to_grouped_hex`].
+    ///
+    /// # Errors
+    /// The errors of [`Encoding::parse_lenient`], and [`IdError::Overflow`] if the
+    /// value does not fit in 64 bits.
+    pub fn from_grouped_hex(s: &str) -> Result<Self, IdError> {
+        let raw = Encoding::Hex.parse_lenient(s, &['-'])?;
+        u64::try_from(raw).map(Id64).map_err(|_| IdError::Overflow)
+    }
+
+    /// The smallest ID the global generator can mint at `time`, with every bit below
+    /// the time fields 0, as the lower bound of a keyset pagination cursor.
+    ///
+    /// The bounds follow the current [`Layout64`], [`TimestampPrecision`],
+    /// [`OverflowMode`], and the global generator's epoch, widening like
+    /// [`Layout64::id_range`] to the whole millisecond, or second in `Seconds`, that
+    /// `time` falls in. Every ID generated then lies within
+    /// `min_for(time)..=max_for(time)`. Past the last time the time fields can hold,
+    /// the bounds wrap with the fields in [`OverflowMode::Wrap`], the default, and
+    /// cover the clamped IDs in [`OverflowMode::Saturate`].
+    ///
+    /// # Errors
+    /// - [`IdError::BeforeEpoch`] if `time` is before the epoch.
+    /// - [`IdError::TimestampOverflow`] if `time` is past the last time the time
+    ///   fields can hold, in [`OverflowMode::Error`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, Id64};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// let time = UNIX_EPOCH + Duration::from_millis(AtomicOption::get_epoch() + 5);
+    /// let (min, max) = (Id64::min_for(time).unwrap(), Id64::max_for(time).unwrap());
+    /// assert_eq!((min.0 >> 44, max.0 >> 44), (5, 5));
+    /// assert_eq!(max.0 - min.0, (1 << 44) - 1);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn min_for(time: SystemTime) -> Result<Self, IdError> {
+        Self::bounds_for(time).map(|(min, _)| min)
+    }
+
+    /// The largest ID the global generator can mint at `time`, with every bit below
+    /// the time fields 1, as the upper bound of a keyset pagination cursor. See
+    /// [`Id64::min_for`].
+    ///
+    /// # Errors
+    /// As for [`Id64::min_for`].
+    #[cfg(feature = "std")]
+    pub fn max_for(time: SystemTime) -> Result<Self, IdError> {
+        Self::bounds_for(time).map(|(_, max)| max)
+    }
+
+    /// [`Id64::min_for`] as a fixed-width string in `encoding`, for cursors compared
+    /// against encoded IDs. Compare them as strings only in an encoding where
+    /// [`Encoding::is_sort_preserving`] holds.
+    ///
+    /// # Errors
+    /// As for [`Id64::min_for`].
+    #[cfg(feature = "std")]
+    pub fn min_for_encoded(time: SystemTime, encoding: Encoding) -> Result<String, IdError> {
+        Self::min_for(time).map(|id| AtomicId::<64>::encode_raw(id.0, encoding))
+    }
+
+    /// [`Id64::max_for`] as a fixed-width string in `encoding`. See
+    /// [`Id64::min_for_encoded`].
+    ///
+    /// # Errors
+    /// As for [`Id64::min_for`].
+    #[cfg(feature = "std")]
+    pub fn max_for_encoded(time: SystemTime, encoding: Encoding) -> Result<String, IdError> {
+        Self::max_for(time).map(|id| AtomicId::<64>::encode_raw(id.0, encoding))
+    }
+
+    /// The bounds of [`Id64::min_for`] and [`Id64::max_for`]. Reading them does not
+    /// freeze the epoch, since no ID is minted.
+    #[cfg(feature = "std")]
+    fn bounds_for(time: SystemTime) -> Result<(Self, Self), IdError> {
+        let layout = Layout64::current();
+        let precision = TimestampPrecision::current();
+        let epoch = UNIX_EPOCH + Duration::from_millis(xgen_read().epoch_ms());
+        let elapsed = time.duration_since(epoch).map_err(|_| IdError::BeforeEpoch)?;
+        let bits = precision.timestamp_bits(layout);
+        let ticks = precision.duration_to_ticks(elapsed);
+        let overflow = ticks >> bits != 0;
+        match OverflowMode::current() {
+            OverflowMode::Error if overflow => Err(IdError::TimestampOverflow),
+            OverflowMode::Saturate if overflow => {
+                // The clamped IDs sit in the last tick, which may end before its
+                // millisecond does, so the bound is the top of the time fields.
+                let ms = precision.ticks_to_ms((1 << bits) - 1);
+                Ok((Id64(layout.id_range(ms, ms).0), Id64(u64::MAX)))
+            }
+            _ => {
+                let ms = elapsed.as_millis() as u64;
+                let (min, max) = layout.id_range(ms, ms);
+                Ok((Id64(min), Id64(max)))
+            }
+        }
+    }
+}
+
+impl Default for Id64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Display for Id64 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&encode::base36(self.0 as u128, 13))
+    }
+}
+
+impl core::str::FromStr for Id64 {
+    type Err = IdError;
+
+    /// Parse the 13-character base36 form written by `Display`, or as a fallback
+    /// the 16-character lowercase hex of [`AtomicId::<x64>::hex`](AtomicId::<64>::hex).
+    /// The two are told apart by length.
+    ///
+    /// # Errors
+    /// - [`IdError::InvalidLength`] for any other length.
+    /// - [`IdError::InvalidCharacter`] for a character outside the detected encoding.
+    /// - [`IdError::Overflow`] if the value does not fit in 64 bits.
+    fn from_str(s: &str) -> Result<Self, IdError> {
+        parse_parts(s).map(|[part]| Id64(part))
+    }
+}
+
+impl TryFrom<&str> for Id64 {
+    type Error = IdError;
+
+    fn try_from(s: &str) -> Result<Self, IdError> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Id64 {
+    type Error = IdError;
+
+    fn try_from(s: String) -> Result<Self, IdError> {
+        s.parse()
+    }
+}
+
+impl From<u64> for Id64 {
+    fn from(raw: u64) -> Self {
+        Id64(raw)
+    }
+}
+
+impl From<Id64> for u64 {
+    fn from(id: Id64) -> Self {
+        id.0
+    }
+}
+
+/// A 128-bit ID kept as its raw value. Displays as the 25-character base36 form of
+/// [`AtomicId::<x128>::new`](AtomicId::new).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id128(pub u128);
+
+impl Id128 {
+    /// Generate a new ID from the global generator.
+    pub fn new() -> Self {
+        Id128(xgen().gen128())
+    }
+
+    /// The ID as 16 big-endian bytes, which sort in the same order as the IDs.
+    pub const fn to_be_bytes(self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Reverse [`Id128::to_be_bytes`].
+    pub const fn from_be_bytes(bytes: [u8; 16]) -> Self {
+        Id128(u128::from_be_bytes(bytes))
+    }
+
+    /// A 64-bit fingerprint of the ID, for Bloom filters and hash-sharded caches.
+    ///
+    /// **Not an ID**: distinct IDs can share a fingerprint. With `n` IDs, about
+    /// `n² / 2^65` pairs collide, so a collision somewhere becomes likely past 5
+    /// billion IDs; IDs that differ only in the high or only in the low 64 bits never
+    /// collide. The algorithm is fixed across releases and platforms:
+    ///
+    /// `mix64(mix64(high) ^ low)`, where `mix64` is the SplitMix64 finalizer and
+    /// `high` and `low` are the two halves of the ID as integers.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Id128;
+    /// assert_eq!(Id128(1 << 64).fold64(), 0x7ab4_0e09_0f36_3a7d);
+    /// ```
+    pub fn fold64(self) -> u64 {
+        fold_words(0, &[(self.0 >> 64) as u64, self.0 as u64])
+    }
+
+    /// Compare two IDs in constant time, for IDs used as secrets.
+    /// See [`Id256::ct_eq`] for what this does and does not guarantee.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        let diff = self.0 ^ other.0;
+        core::hint::black_box((diff >> 64) as u64 | diff as u64) == 0
+    }
+
+    /// The 32 hex digits of the ID in groups of 8, such as
+    /// `0a1b2c3d-4e5f6071-8293a4b5-c6d7e8f9`.
+    pub fn to_grouped_hex(self) -> String {
+        format_grouped(&encode::hex(self.0, 32), 8, '-')
+    }
+
+    /// Parse hex with any grouping, case, or surrounding whitespace, such as the
+    /// output of [`Id128::to_grouped_hex`].
+    ///
+    /// # Errors
+    /// The errors of [`Encoding::parse_lenient`].
+    pub fn from_grouped_hex(s: &str) -> Result<Self, IdError> {
+        Encoding::Hex.parse_lenient(s, &['-']).map(Id128)
+    }
+}
+
+impl Default for Id128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Display for Id128 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&encode::base36(self.0, 25))
+    }
+}
+
+impl core::str::FromStr for Id128 {
+    type Err = IdError;
+
+    /// Parse the 25-character base36 form written by `Display`, or as a fallback
+    /// the 32-character lowercase hex of [`AtomicId::<x128>::hex`](AtomicId::<128>::hex).
+    /// The two are told apart by length.
+    ///
+    /// # Errors
+    /// - [`IdError::InvalidLength`] for any other length.
+    /// - [`IdError::InvalidCharacter`] for a character outside the detected encoding.
+    /// - [`IdError::Overflow`] if the value does not fit in 128 bits.
+    fn from_str(s: &str) -> Result<Self, IdError> {
+        match s.len() {
+            25 => Encoding::Base36.decode(s).map(Id128),
+            32 => Encoding::Hex.decode(s).map(Id128),
+            len => Err(IdError::InvalidLength(len)),
+        }
+    }
+}
+
+impl TryFrom<&str> for Id128 {
+    type Error = IdError;
+
+    fn try_from(s: &str) -> Result<Self, IdError> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Id128 {
+    type Error = IdError;
+
+    fn try_from(s: String) -> Result<Self, IdError> {
+        s.parse()
+    }
+}
+
+impl From<u128> for Id128 {
+    fn from(raw: u128) -> Self {
+        Id128(raw)
+    }
+}
+
+impl From<Id128> for u128 {
+    fn from(id: Id128) -> Self {
+        id.0
+    }
+}
+
+/// A 256-bit ID kept as its four raw 64-bit parts. Displays as the 50-character
+/// base36 form of [`AtomicId::<x256>::new`](AtomicId::new).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id256(pub [u64; 4]);
+
+impl Id256 {
+    /// Generate a new ID from the global generator.
+    pub fn new() -> Self {
+        Id256(xgen().gen256())
+    }
+
+    /// The ID as 32 big-endian bytes: each part big-endian, in order.
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (chunk, part) in bytes.chunks_exact_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&part.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Reverse [`Id256::to_be_bytes`].
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut parts = [0u64; 4];
+        for (part, chunk) in parts.iter_mut().zip(bytes.chunks_exact(8)) {
+            *part = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Id256(parts)
+    }
+
+    /// A 64-bit fingerprint of the ID, for Bloom filters and hash-sharded caches.
+    ///
+    /// **Not an ID**; see [`Id128::fold64`] for the collision rate, which is the
+    /// same. Each part, in order, is XORed into a state that starts at 0 and is
+    /// mixed with the SplitMix64 finalizer after each part:
+    ///
+    /// `mix64(mix64(mix64(mix64(p0) ^ p1) ^ p2) ^ p3)`
+    ///
+    /// IDs that differ in a single part never collide. Fixed across releases and
+    /// platforms.
+    pub fn fold64(self) -> u64 {
+        fold_words(0, &self.0)
+    }
+
+    /// A 128-bit fingerprint of the ID, where 64 bits are too few: a collision
+    /// becomes likely only past about 2^64 IDs. Still **not an ID**.
+    ///
+    /// The high half is [`Id256::fold64`]; the low half is the same fold started
+    /// from `0x9E3779B97F4A7C15` instead of 0. Fixed across releases and platforms.
+    pub fn fold128(self) -> u128 {
+        (fold_words(0, &self.0) as u128) << 64 | fold_words(FOLD128_SEED, &self.0) as u128
+    }
+
+    /// Compare two IDs in constant time, for IDs used as secrets such as API tokens,
+    /// where `==` would stop at the first differing part and leak how much of a
+    /// guess was right through its timing.
+    ///
+    /// Every part is compared, the differences are accumulated with bitwise OR with
+    /// no branch on the data, and the result goes through [`core::hint::black_box`]
+    /// so the optimizer has a harder time adding an early exit back. This is a best
+    /// effort, like any constant-time code in Rust: neither the compiler nor the CPU
+    /// promises constant timing. Only this method is covered; `==`, `Ord`, `Hash`,
+    /// and the encoders and decoders are not constant-time, so compare tokens as
+    /// decoded IDs with this method rather than as strings.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Id256;
+    /// let token = Id256::new();
+    /// assert!(token.ct_eq(&token));
+    /// assert!(!token.ct_eq(&Id256([0; 4])));
+    /// ```
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        let diff = self.0.iter().zip(other.0).fold(0, |diff, (a, b)| diff | (a ^ b));
+        core::hint::black_box(diff) == 0
+    }
+
+    /// The 64 hex digits of the ID, the parts in order, in groups of 8 such as
+    /// `0a1b2c3d-4e5f6071-...`. Far easier to read back from a log or a support
+    /// ticket than the 50-character base36 form.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Id256;
+    /// let id = Id256::new();
+    /// let grouped = id.to_grouped_hex();
+    /// assert_eq!(grouped.len(), 64 + 7);
+    /// assert_eq!(Id256::from_grouped_hex(&grouped), Ok(id));
+    /// ```
+    pub fn to_grouped_hex(self) -> String {
+        let mut hex = String::with_capacity(64);
+        for part in self.0 {
+            encode::push_hex(&mut hex, part as u128, 16);
+        }
+        let grouped = format_grouped(&hex, 8, '-');
+        encode::wipe(&mut hex);
+        grouped
+    }
+
+    /// Parse hex with any grouping, case, or surrounding whitespace, such as the
+    /// output of [`Id256::to_grouped_hex`]. Fewer than 64 digits are read as if
+    /// left-padded with zeros.
+    ///
+    /// # Errors
+    /// - The errors of [`Encoding::parse_lenient`].
+    /// - [`IdError::Overflow`] for more than 64 digits.
+    pub fn from_grouped_hex(s: &str) -> Result<Self, IdError> {
+        let mut buf: String = s.chars().filter(|&c| c != '-').collect();
+        let parsed = Self::from_hex_digits(buf.trim_matches(|c: char| c.is_ascii_whitespace()));
+        encode::wipe(&mut buf);
+        parsed
+    }
+
+    /// Parse up to 64 hex digits in either case, as if left-padded with zeros.
+    fn from_hex_digits(digits: &str) -> Result<Self, IdError> {
+        if let Some(c) = digits.chars().find(|c| !c.is_ascii()) {
+            return Err(IdError::InvalidCharacter(c));
+        }
+        if digits.len() > 64 {
+            return Err(IdError::Overflow);
+        }
+        let high_len = digits.len().saturating_sub(32);
+        let high = match high_len {
+            0 => 0,
+            _ => Encoding::Hex.parse_lenient(&digits[..high_len], &[])?,
+        };
+        let low = Encoding::Hex.parse_lenient(&digits[high_len..], &[])?;
+        Ok(Id256([(high >> 64) as u64, high as u64, (low >> 64) as u64, low as u64]))
+    }
+}
+
+impl Default for Id256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Display for Id256 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut digits = AtomicId::<256>::to_canonical(self.0, Encoding::Base36);
+        let written = f.write_str(&digits);
+        encode::wipe(&mut digits);
+        written
+    }
+}
+
+impl core::str::FromStr for Id256 {
+    type Err = IdError;
+
+    /// Parse the 50-character base36 form written by `Display`, or as a fallback
+    /// the 64-character lowercase hex of [`AtomicId::<x256>::hex`](AtomicId::<256>::hex)
+    /// or the 52-character legacy word format of
+    /// [`AtomicId::<x256>::base36_words`](AtomicId::<256>::base36_words), which
+    /// `Display` wrote before. The three are told apart by length.
+    ///
+    /// # Errors
+    /// - [`IdError::InvalidLength`] for any other length.
+    /// - [`IdError::InvalidCharacter`] for a character outside the detected encoding.
+    /// - [`IdError::Overflow`] if the value does not fit in 256 bits.
+    fn from_str(s: &str) -> Result<Self, IdError> {
+        match s.len() {
+            50 => AtomicId::<256>::from_canonical(s, Encoding::Base36),
+            _ => parse_parts(s),
+        }
+        .map(Id256)
+    }
+}
+
+impl TryFrom<&str> for Id256 {
+    type Error = IdError;
+
+    fn try_from(s: &str) -> Result<Self, IdError> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for Id256 {
+    type Error = IdError;
+
+    fn try_from(s: String) -> Result<Self, IdError> {
+        s.parse()
+    }
+}
+
+impl From<[u64; 4]> for Id256 {
+    fn from(raw: [u64; 4]) -> Self {
+        Id256(raw)
+    }
+}
+
+impl From<Id256> for [u64; 4] {
+    fn from(id: Id256) -> Self {
+        id.0
+    }
+}
+
+/// Parse `N` 64-bit parts written back to back, each as 13 base36 or 16 lowercase hex
+/// digits, the encoding told apart by the total length.
+fn parse_parts<const N: usize>(s: &str) -> Result<[u64; N], IdError> {
+    let (encoding, width) = match s.len() {
+        len if len == 13 * N => (Encoding::Base36, 13),
+        len if len == 16 * N => (Encoding::Hex, 16),
+        len => return Err(IdError::InvalidLength(len)),
+    };
+    // A non-ASCII character could straddle a part boundary.
+    if let Some(c) = s.chars().find(|c| !c.is_ascii()) {
+        return Err(IdError::InvalidCharacter(c));
+    }
+    let mut parts = [0u64; N];
+    for (i, part) in parts.iter_mut().enumerate() {
+        let raw = encoding.decode(&s[i * width..(i + 1) * width])?;
+        *part = u64::try_from(raw).map_err(|_| IdError::Overflow)?;
+    }
+    Ok(parts)
+}
+
+/// Stable hash functions used for content-addressed (derived) IDs.
+///
+/// This is the 64-bit and 128-bit FNV-1a hash as published at
+/// <http://www.isthe.com/chongo/tech/comp/fnv/>. Unlike `DefaultHasher`, the output
+/// is fixed by the specification, so derived IDs never change between Rust releases.
+mod hash {
+    const FNV64_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV64_PRIME: u64 = 0x00000100000001b3;
+    const FNV128_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const FNV128_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    /// Hash a sequence of byte chunks with 64-bit FNV-1a, as if they were concatenated.
+    pub fn fnv1a64(chunks: &[&[u8]]) -> u64 {
+        let mut h = FNV64_OFFSET;
+        for &byte in chunks.iter().flat_map(|c| c.iter()) {
+            h ^= byte as u64;
+            h = h.wrapping_mul(FNV64_PRIME);
+        }
+        h
+    }
+
+    /// A `Hasher` for values that hash as a few integers, such as `ThreadId`, which
+    /// hashes as its `u64` number: each word is folded in with [`mix64`](crate::mix64).
+    /// Not for untrusted input, since nothing is keyed.
+    #[cfg(feature = "fast_thread_hash")]
+    pub struct MixHasher(pub u64);
+
+    #[cfg(feature = "fast_thread_hash")]
+    impl core::hash::Hasher for MixHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            for chunk in bytes.chunks(8) {
+                let mut word = [0; 8];
+                word[..chunk.len()].copy_from_slice(chunk);
+                self.write_u64(u64::from_le_bytes(word));
+            }
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.0 = crate::mix64(self.0 ^ n);
+        }
+
+        fn finish(&self) -> u64 {
+            self.0
+        }
+    }
+
+    /// Hash a sequence of byte chunks with 128-bit FNV-1a, as if they were concatenated.
+    pub fn fnv1a128(chunks: &[&[u8]]) -> u128 {
+        let mut h = FNV128_OFFSET;
+        for &byte in chunks.iter().flat_map(|c| c.iter()) {
+            h ^= byte as u128;
+            h = h.wrapping_mul(FNV128_PRIME);
+        }
+        h
+    }
+}
+
+pub mod encoder;
+
+pub mod foreign;
+
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+pub mod audit;
+
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+mod persist;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+#[cfg(feature = "debug_checks")]
+mod debug_checks;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(feature = "rusqlite")]
+mod sqlite;
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[cfg(feature = "schemars")]
+mod schema;
+
+#[cfg(feature = "proptest")]
+pub mod proptest;
+
+#[cfg(feature = "otel")]
+mod otel;
+
+#[cfg(feature = "zeroize")]
+mod zeroize;
+
+#[cfg(feature = "tower")]
+pub mod tower;
+
+/// Global generator instance (node_id=1, shard_id=0).
+/// Used by all [`AtomicId`] operations unless [`AtomicOption::init_global`] came first.
+static GENERATOR: IdGenerator = IdGenerator::new(1, 0);
+
+/// The global generator: leaked from the `Box` of [`AtomicOption::init_global`], or
+/// [`GENERATOR`] once the global generator was used without it. Null until then.
+static GLOBAL: AtomicPtr<IdGenerator> = AtomicPtr::new(core::ptr::null_mut());
+
+/// The generator installed by [`AtomicOption::reconfigure`], used in place of
+/// [`GENERATOR`] once set.
+#[cfg(feature = "reconfigure")]
+static RECONFIGURED: arc_swap::ArcSwapOption<IdGenerator> = arc_swap::ArcSwapOption::const_empty();
+
+/// The global generator, as returned by [`xgen`].
+enum GlobalGenerator {
+    /// A generator that lives for the whole process.
+    Static(&'static IdGenerator),
+    /// The generator installed by [`AtomicOption::reconfigure`], kept alive while in
+    /// use even if it is replaced in the meantime.
+    #[cfg(feature = "reconfigure")]
+    Reconfigured(arc_swap::Guard<Option<Arc<IdGenerator>>>),
+    /// The generator installed by a [`testing::TestGuard`], kept alive while in use
+    /// even if the guard is dropped in the meantime.
+    #[cfg(any(test, feature = "testing"))]
+    Override(alloc::sync::Arc<IdGenerator>),
+}
+
+impl core::ops::Deref for GlobalGenerator {
+    type Target = IdGenerator;
+
+    #[inline]
+    fn deref(&self) -> &IdGenerator {
+        match self {
+            GlobalGenerator::Static(generator) => generator,
+            #[cfg(feature = "reconfigure")]
+            GlobalGenerator::Reconfigured(generator) => generator.as_deref().unwrap_or(&GENERATOR),
+            #[cfg(any(test, feature = "testing"))]
+            GlobalGenerator::Override(generator) => generator,
+        }
+    }
+}
+
+/// Get the global [`IdGenerator`] instance to generate IDs with.
+#[inline]
+fn xgen() -> GlobalGenerator {
+    current_generator(global)
+}
+
+/// Get the global [`IdGenerator`] instance to read its settings, such as the epoch.
+/// Unlike [`xgen`], it does not lock in [`GENERATOR`], since no ID is generated.
+#[inline]
+fn xgen_read() -> GlobalGenerator {
+    current_generator(|| {
+        let ptr = GLOBAL.load(Ordering::Acquire);
+        // SAFETY: as in `global`.
+        if ptr.is_null() { &GENERATOR } else { unsafe { &*ptr } }
+    })
+}
+
+/// The test override, the reconfigured generator, or else `fallback`.
+#[inline]
+fn current_generator(fallback: fn() -> &'static IdGenerator) -> GlobalGenerator {
+    #[cfg(any(test, feature = "testing"))]
+    if let Some(generator) = testing::generator_override() {
+        return GlobalGenerator::Override(generator);
+    }
+    #[cfg(feature = "reconfigure")]
+    {
+        let generator = RECONFIGURED.load();
+        if generator.is_some() {
+            return GlobalGenerator::Reconfigured(generator);
+        }
+    }
+    GlobalGenerator::Static(fallback())
+}
+
+/// The generator fixed by [`AtomicOption::init_global`], or [`GENERATOR`], locked in
+/// as the global generator if nothing was yet.
+#[inline]
+fn global() -> &'static IdGenerator {
+    let ptr = GLOBAL.load(Ordering::Acquire);
+    if ptr.is_null() {
+        return init_default();
+    }
+    // SAFETY: non-null values are `GENERATOR` or leaked by `AtomicOption::init_global`,
+    // so they live for the rest of the process.
+    unsafe { &*ptr }
+}
+
+/// Lock in [`GENERATOR`] as the global generator on its first use, unless another
+/// thread got there first, and warn about it in debug builds.
+#[cold]
+fn init_default() -> &'static IdGenerator {
+    let default = &GENERATOR as *const IdGenerator as *mut IdGenerator;
+    match GLOBAL.compare_exchange(core::ptr::null_mut(), default, Ordering::AcqRel, Ordering::Acquire) {
+        Ok(_) => {
+            #[cfg(debug_assertions)]
+            emit(IdEvent::ImplicitInit {
+                node_id: GENERATOR.node_id,
+                shard_id: GENERATOR.shard_id,
+            });
+            &GENERATOR
+        }
+        // SAFETY: as in `global`.
+        Err(ptr) => unsafe { &*ptr },
+    }
+}
+
+/// Birthday-bound collision probability for `n` IDs.
+///
+/// Models the worst case where every ID shares the same timestamp, node, and shard,
+/// so only the sequence and the random bits can tell them apart. The first
+/// `2^seq_bits` IDs are distinct by construction; after that, IDs landing on the same
+/// sequence value collide unless their `random_bits` differ.
+#[cfg(feature = "std")]
+fn birthday_probability(n: u64, seq_bits: u32, random_bits: u32) -> f64 {
+    let slots = 1u128 << seq_bits;
+    let n = n as u128;
+    if n <= slots {
+        return 0.0;
+    }
+    if random_bits == 0 {
+        return 1.0;
+    }
+    // Count the exact number of pairs sharing a sequence value.
+    let (q, rem) = (n / slots, n % slots);
+    let pairs = rem * (q + 1) * q / 2 + (slots - rem) * q * (q - 1) / 2;
+    let x = pairs as f64 / (1u128 << random_bits) as f64;
+    -(-x).exp_m1()
+}
+
+/// Draw IDs from `generate` until one is not in `seen`, insert it, and return it.
+#[cfg(feature = "std")]
+fn new_unique_with<T: Copy + Eq + Hash>(
+    seen: &mut std::collections::HashSet<T>,
+    max_retries: usize,
+    mut generate: impl FnMut() -> T,
+) -> Result<T, IdError> {
+    for _ in 0..=max_retries {
+        let id = generate();
+        if seen.insert(id) {
+            return Ok(id);
+        }
+    }
+    Err(IdError::RetriesExhausted(max_retries.saturating_add(1)))
+}
+
+// Bit mode constants for compile-time selection.
+#[cfg(feature = "short")]
+#[allow(non_upper_case_globals)]
+/// Constant for 24-bit mode (requires the `short` feature).
+pub const x24: usize = 24;
+#[cfg(feature = "short")]
+#[allow(non_upper_case_globals)]
+/// Constant for 32-bit mode (requires the `short` feature).
+pub const x32: usize = 32;
+#[allow(non_upper_case_globals)]
+/// Constant for 64-bit mode.
+pub const x64: usize = 64;
+#[allow(non_upper_case_globals)]
+/// Constant for 128-bit mode.
+pub const x128: usize = 128;
+#[allow(non_upper_case_globals)]
+/// Constant for 256-bit mode.
+pub const x256: usize = 256;
+
+/// Output encodings supported by the generator.
+///
+/// Used to select an encoding at runtime, e.g. with [`AtomicId::generate`]. For
+/// encodings outside this set, implement [`encoder::Encoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
+pub enum Encoding {
+    /// `[0-9a-z]`, the default encoding.
+    Base36,
+    /// Bitcoin alphabet (no `0`, `O`, `I`, `l`).
+    Base58,
+    /// Dense 91-symbol ASCII alphabet. Not URL-safe: it contains `%`, `#`, and `?`.
+    Base91,
+    /// Base91 with an alphabet that leaves out `%`, `#`, and `?`, for IDs in URLs.
+    /// Same length as [`Encoding::Base91`]; 11 of its characters (`` "<>[\]^`{|} ``)
+    /// still need percent-encoding for strict RFC 3986 parsers, and `/` does in a
+    /// path segment.
+    Base91UrlSafe,
+    /// Lowercase hexadecimal `[0-9a-f]`.
+    Hex,
+}
+
+impl Encoding {
+    /// Number of characters an ID of `bits` bits encodes to, as produced by the
+    /// methods on `AtomicId<BITS>`.
+    ///
+    /// For 256 bits this is the length of the canonical form, the whole value as one
+    /// number; the legacy word format of
+    /// [`AtomicId::<x256>::base36_words`](AtomicId::<256>::base36_words) is four times
+    /// the 64-bit length instead.
+    ///
+    /// # Errors
+    /// [`IdError::UnsupportedWidth`] if `bits` is not 24, 32, 64, 128, or 256.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Encoding;
+    /// assert_eq!(Encoding::Base58.encoded_len(64), Ok(11));
+    /// assert_eq!(Encoding::Hex.encoded_len(256), Ok(64));
+    /// ```
+    pub fn encoded_len(self, bits: usize) -> Result<usize, IdError> {
+        let lens = match bits {
+            24 => [5, 5, 4, 6],
+            32 => [7, 6, 5, 8],
+            64 => [13, 11, 10, 16],
+            128 => [25, 22, 20, 32],
+            256 => [50, 44, 40, 64],
+            _ => return Err(IdError::UnsupportedWidth(bits)),
+        };
+        Ok(match self {
+            Encoding::Base36 => lens[0],
+            Encoding::Base58 => lens[1],
+            Encoding::Base91 | Encoding::Base91UrlSafe => lens[2],
+            Encoding::Hex => lens[3],
+        })
+    }
+
+    /// Whether fixed-width strings in this encoding compare in the same order as the
+    /// values they encode, so a byte-wise sort of IDs is a numeric sort.
+    ///
+    /// True for [`Encoding::Base36`], [`Encoding::Base58`], and [`Encoding::Hex`],
+    /// whose digits are in ASCII order. The base91 alphabets put `A-Z` before `0-9`
+    /// and punctuation, so their strings do not sort. Only strings of the same
+    /// length compare correctly; every `AtomicId` method pads to a fixed width.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Encoding;
+    /// assert!(Encoding::Base36.is_sort_preserving());
+    /// assert!(!Encoding::Base91.is_sort_preserving());
+    /// ```
+    pub fn is_sort_preserving(self) -> bool {
+        self.alphabet().windows(2).all(|pair| pair[0] < pair[1])
+    }
+
+    /// Encode a raw value, left-padded to at least `width` characters.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Encoding;
+    /// assert_eq!(Encoding::Base36.encode(35, 3), "00z");
+    /// ```
+    pub fn encode(self, n: u128, width: usize) -> String {
+        match self {
+            Encoding::Base36 => encode::base36(n, width),
+            Encoding::Base58 => encode::base58(n, width),
+            Encoding::Base91 => encode::base91(n, width),
+            Encoding::Base91UrlSafe => encode::base91_urlsafe(n, width),
+            Encoding::Hex => encode::hex(n, width),
+        }
+    }
+
+    /// Encode many raw 64-bit values at once, each left-padded to at least `width`
+    /// characters, for bulk exports. The output is the same as calling
+    /// [`Encoding::encode`] on each value; with the `simd` feature, large base36
+    /// batches take a vectorized path.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Encoding;
+    /// let ids = Encoding::Base36.encode_batch(&[0, 35, u64::MAX], 13);
+    /// assert_eq!(ids, ["0000000000000", "000000000000z", "3w5e11264sgsf"]);
+    /// ```
+    pub fn encode_batch(self, values: &[u64], width: usize) -> Vec<String> {
+        match self {
+            Encoding::Base36 => encode::base36_batch(values, width),
+            _ => values.iter().map(|&n| self.encode(n as u128, width)).collect(),
+        }
+    }
+
+    /// Decode a string in this encoding back into its raw value.
+    ///
+    /// Decoding is strict: the string must use exactly this encoding's alphabet, with
+    /// no whitespace, and hex must be lowercase. See [`Encoding::parse_lenient`] for
+    /// IDs that were copied or reformatted along the way.
+    ///
+    /// # Errors
+    /// - [`IdError::Empty`] for an empty string.
+    /// - [`IdError::InvalidCharacter`] for a character outside the alphabet.
+    /// - [`IdError::Overflow`] if the value does not fit in 128 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x64};
+    /// let (raw, id) = AtomicId::<x64>::base58_with_raw();
+    /// assert_eq!(Encoding::Base58.decode(&id), Ok(raw as u128));
+    /// ```
+    pub fn decode(self, s: &str) -> Result<u128, IdError> {
+        match self {
+            Encoding::Base36 => encode::from_base36(s),
+            Encoding::Base58 => encode::from_base58(s),
+            Encoding::Base91 => encode::from_base91(s),
+            Encoding::Base91UrlSafe => encode::from_base91_urlsafe(s),
+            Encoding::Hex => encode::from_hex(s),
+        }
+    }
+
+    /// Decode a string in this encoding leniently, for IDs that were pasted from
+    /// emails or reformatted by other systems.
+    ///
+    /// Before decoding as [`Encoding::decode`] does, this trims ASCII whitespace from
+    /// both ends, removes every character in `separators` (such as the `-` added by
+    /// [`Encoding::format_grouped`]), and for the case-insensitive
+    /// [`Encoding::Base36`] and [`Encoding::Hex`] lowercases ASCII letters. Base58 and
+    /// base91 tell letters of different case apart, so they are left as they are.
+    ///
+    /// # Errors
+    /// - [`IdError::SeparatorInAlphabet`] if a separator is a digit of this encoding,
+    ///   such as `-` in [`Encoding::Base91UrlSafe`]; stripping it would change the value.
+    /// - The errors of [`Encoding::decode`], for the cleaned-up string.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Encoding;
+    /// let strict = Encoding::Hex.decode("00ff00ff").unwrap();
+    /// assert_eq!(Encoding::Hex.parse_lenient(" 00FF-00FF\n", &['-']), Ok(strict));
+    /// ```
+    pub fn parse_lenient(self, s: &str, separators: &[char]) -> Result<u128, IdError> {
+        self.lenient(s, separators, |cleaned| self.decode(cleaned))
+    }
+
+    /// Whether two strings in this encoding hold the same value, for checking an ID
+    /// a user typed or pasted against a stored one, where `==` on the strings gives
+    /// false negatives.
+    ///
+    /// Both sides are read like [`Encoding::parse_lenient`] without separators, so
+    /// surrounding whitespace, leading zeros, and case in [`Encoding::Base36`] and
+    /// [`Encoding::Hex`] make no difference. Values of up to 256 bits are compared.
+    ///
+    /// This is not a constant-time comparison: decoding takes time that depends on
+    /// the strings. Compare IDs used as secrets with [`constant_eq`].
+    ///
+    /// # Errors
+    /// The errors of [`Encoding::decode`] for either side, such as
+    /// [`IdError::InvalidCharacter`] for a string in another encoding, and
+    /// [`IdError::Overflow`] for a value above 256 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{Encoding, IdError};
+    /// assert_eq!(Encoding::Hex.encoded_eq("00ff00ff", " FF00FF "), Ok(true));
+    /// assert_eq!(Encoding::Base36.encoded_eq("00z", "y"), Ok(false));
+    /// assert_eq!(Encoding::Base58.encoded_eq("0abc", "abc"), Err(IdError::InvalidCharacter('0')));
+    /// ```
+    pub fn encoded_eq(self, a: &str, b: &str) -> Result<bool, IdError> {
+        let decode = |s| self.lenient(s, &[], |cleaned| encode::from_base_256(cleaned, self.alphabet()));
+        Ok(decode(a)? == decode(b)?)
+    }
+
+    /// Re-emit a string in this encoding in the fixed-width form the generators
+    /// produce for `bits`-bit IDs: left-padded to [`Encoding::encoded_len`], with
+    /// lowercase base36 and hex. The input is read like [`Encoding::encoded_eq`], so
+    /// compact, padded, and differently cased forms of one ID canonicalize to the
+    /// same string. 256-bit IDs take the canonical single-number form of
+    /// [`AtomicId::<x256>::to_canonical`](AtomicId::<256>::to_canonical); convert
+    /// the legacy word format with
+    /// [`AtomicId::<x256>::words_to_canonical`](AtomicId::<256>::words_to_canonical)
+    /// first.
+    ///
+    /// # Errors
+    /// - [`IdError::UnsupportedWidth`] if `bits` is not 24, 32, 64, 128, or 256.
+    /// - [`IdError::Overflow`] if the value does not fit in `bits` bits.
+    /// - The errors of [`Encoding::decode`], as for [`Encoding::encoded_eq`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{Encoding, IdError};
+    /// assert_eq!(Encoding::Hex.canonicalize(" FF ", 32), Ok("000000ff".to_string()));
+    /// assert_eq!(Encoding::Hex.canonicalize("1ffffff", 24), Err(IdError::Overflow));
+    /// ```
+    pub fn canonicalize(self, s: &str, bits: usize) -> Result<String, IdError> {
+        let width = self.encoded_len(bits)?;
+        let parts = self.lenient(s, &[], |cleaned| encode::from_base_256(cleaned, self.alphabet()))?;
+        let value = ((parts[2] as u128) << 64) | parts[3] as u128;
+        match bits {
+            256 => Ok(encode::to_base_256(parts, self.alphabet(), width)),
+            _ if parts[..2] != [0, 0] || (bits < 128 && value >> bits != 0) => Err(IdError::Overflow),
+            _ => Ok(self.encode(value, width)),
+        }
+    }
+
+    /// Clean up `s` as [`Encoding::parse_lenient`] describes and pass it to `decode`,
+    /// wiping the cleaned copy afterwards.
+    fn lenient<T>(
+        self,
+        s: &str,
+        separators: &[char],
+        decode: impl FnOnce(&str) -> Result<T, IdError>,
+    ) -> Result<T, IdError> {
+        if let Some(&sep) = separators.iter().find(|&&c| self.contains(c)) {
+            return Err(IdError::SeparatorInAlphabet(sep));
+        }
+        let fold_case = matches!(self, Encoding::Base36 | Encoding::Hex);
+        let mut cleaned: String = s
+            .trim_matches(|c: char| c.is_ascii_whitespace())
+            .chars()
+            .filter(|c| !separators.contains(c))
+            .map(|c| if fold_case { c.to_ascii_lowercase() } else { c })
+            .collect();
+        let decoded = decode(&cleaned);
+        encode::wipe(&mut cleaned);
+        decoded
+    }
+
+    /// Split an ID encoded with this encoding into groups of `group` characters joined
+    /// by `sep`, for logs and support tickets. See [`format_grouped`] for the layout;
+    /// [`Encoding::parse_lenient`] with the same separator reverses it.
+    ///
+    /// # Errors
+    /// [`IdError::SeparatorInAlphabet`] if `sep` is a digit of this encoding, where
+    /// the grouped string could not be told apart from a different ID.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{Encoding, IdError};
+    /// let id = Encoding::Hex.encode(0xdead_beef_cafe, 16);
+    /// let grouped = Encoding::Hex.format_grouped(&id, 4, '-').unwrap();
+    /// assert_eq!(grouped, "0000-dead-beef-cafe");
+    /// assert_eq!(Encoding::Hex.parse_lenient(&grouped, &['-']), Ok(0xdead_beef_cafe));
+    /// assert_eq!(
+    ///     Encoding::Base91UrlSafe.format_grouped("abc", 1, '-'),
+    ///     Err(IdError::SeparatorInAlphabet('-'))
+    /// );
+    /// ```
+    pub fn format_grouped(self, s: &str, group: usize, sep: char) -> Result<String, IdError> {
+        if self.contains(sep) {
+            return Err(IdError::SeparatorInAlphabet(sep));
+        }
+        Ok(format_grouped(s, group, sep))
+    }
+
+    /// Whether `c` is a digit of this encoding, in either case for the
+    /// case-insensitive encodings.
+    fn contains(self, c: char) -> bool {
+        let alphabet = self.alphabet();
+        let c = match self {
+            Encoding::Base36 | Encoding::Hex => c.to_ascii_lowercase(),
+            _ => c,
+        };
+        c.is_ascii() && alphabet.contains(&(c as u8))
+    }
+
+    /// The digits of this encoding, lowest first.
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            Encoding::Base36 => encode::BASE36,
+            Encoding::Base58 => encode::BASE58,
+            Encoding::Base91 => encode::BASE91,
+            Encoding::Base91UrlSafe => encode::BASE91_URLSAFE,
+            Encoding::Hex => encode::HEX,
+        }
+    }
+}
+
+/// Insert `sep` between every `group` characters of `s`, counting from the left, so
+/// `"0123456789"` in groups of 4 becomes `"0123-4567-89"`. A `group` of zero returns
+/// `s` unchanged.
+///
+/// This does not check `sep` against any alphabet; use [`Encoding::format_grouped`]
+/// to refuse separators that would be mistaken for digits.
+///
+/// # Example
+/// ```
+/// assert_eq!(atomic_id::format_grouped("0123456789", 4, '-'), "0123-4567-89");
+/// ```
+pub fn format_grouped(s: &str, group: usize, sep: char) -> String {
+    encode::group(s, group, sep)
+}
+
+/// Remove every `sep` from `s`, reversing [`format_grouped`], so the digits can be
+/// decoded. Whitespace and other separators are left in place; use
+/// [`Encoding::parse_lenient`] to strip those too.
+///
+/// # Example
+/// ```
+/// assert_eq!(atomic_id::ungroup("0123-4567-89", '-'), "0123456789");
+/// ```
+pub fn ungroup(s: &str, sep: char) -> String {
+    encode::ungroup(s, sep)
+}
+
+/// Convert an ID of `width` from one encoding to another in a single call, e.g.
+/// for serving base36 IDs stored in a database as hex. The ID is read strictly, as
+/// [`AnyId::parse`] reads it, and written as [`AnyId::encode`] writes it, in the
+/// generators' fixed-width form of [`Encoding::encoded_len`].
+///
+/// # Errors
+/// The errors of [`AnyId::parse`]:
+/// - [`IdError::InvalidLength`] unless `s` has the encoded length of `width` in `from`.
+/// - [`IdError::InvalidCharacter`] for a character outside `from`'s alphabet, e.g.
+///   when `s` is actually in another encoding.
+/// - [`IdError::Overflow`] if the value does not fit in `width`.
+///
+/// # Example
+/// ```
+/// use atomic_id::{Encoding, IdError, Width, reencode};
+/// let id = Encoding::Base36.encode(0xFFFF, 13);
+/// assert_eq!(reencode(&id, Encoding::Base36, Encoding::Hex, Width::X64), Ok("000000000000ffff".to_string()));
+/// assert_eq!(reencode("zz", Encoding::Base36, Encoding::Hex, Width::X64), Err(IdError::InvalidLength(2)));
+/// ```
+pub fn reencode(s: &str, from: Encoding, to: Encoding, width: Width) -> Result<String, IdError> {
+    AnyId::parse(s, width, from).map(|id| id.encode(to))
+}
+
+/// Compare two encoded IDs in constant time, for IDs used as secrets such as
+/// bearer tokens.
+///
+/// `==` on strings returns at the first differing byte, so its timing tells an
+/// attacker how much of a guessed token was right. This compares every byte of
+/// `a`, accumulating the differences with bitwise OR and no branch on the data,
+/// so its running time depends only on the length of `a`. Pass the presented
+/// token as `a` and the stored one as `b`: the presented length is already known
+/// to whoever sent it, and a different length simply compares unequal.
+///
+/// Only use this for secret IDs; ordinary IDs are not secret and `==` is faster.
+/// Like any constant-time code in Rust it is a best effort, as neither the
+/// compiler nor the CPU promises constant timing. To compare decoded IDs, use
+/// [`Id128::ct_eq`] or [`Id256::ct_eq`].
+///
+/// # Example
+/// ```
+/// use atomic_id::{AtomicId, constant_eq, x256};
+/// let token = AtomicId::<x256>::base58();
+/// assert!(constant_eq(&token, &token.clone()));
+/// assert!(!constant_eq(&token[1..], &token));
+/// ```
+pub fn constant_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut diff = (a.len() ^ b.len()) as u64;
+    for (i, &byte) in a.iter().enumerate() {
+        diff |= (byte ^ b.get(i).copied().unwrap_or(0)) as u64;
+    }
+    core::hint::black_box(diff) == 0
+}
+
+/// Errors returned by fallible `atomic-id` operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdError {
+    /// The requested bit width is not one of 24, 32, 64, 128, or 256.
+    UnsupportedWidth(usize),
+    /// The requested bit width exists but its cargo feature is not enabled.
+    FeatureDisabled(&'static str),
+    /// The timestamp field is within the wrap threshold; it wraps after this long.
+    TimestampNearWrap(Duration),
+    /// A string being decoded contains a character outside the encoding's alphabet.
+    InvalidCharacter(char),
+    /// A decoded value does not fit in the target integer type.
+    Overflow,
+    /// The string being decoded is empty.
+    Empty,
+    /// No unused ID was found within this many attempts, saturating at
+    /// `usize::MAX`.
+    RetriesExhausted(usize),
+    /// The epoch can no longer change: timestamp-based IDs were already generated,
+    /// or it is fixed at build time by the `const_epoch` feature.
+    EpochFrozen,
+    /// The time since the epoch no longer fits in the timestamp field, in
+    /// [`OverflowMode::Error`].
+    TimestampOverflow,
+    /// A grouping separator is also a digit of the encoding, so grouped IDs would be
+    /// ambiguous.
+    SeparatorInAlphabet(char),
+    /// A typed ID string has none of the lengths of its encodings; this many bytes.
+    InvalidLength(usize),
+    /// A time given for an ID, e.g. to [`IdGenerator::gen64_at`], is before the epoch.
+    BeforeEpoch,
+    /// An ID has a layout version this release cannot read (see
+    /// [`IdGenerator::layout_version`]).
+    UnknownLayoutVersion(u8),
+    /// A [`Sequence`] is already past the requested start; this is its next value.
+    SequenceAhead(u64),
+    /// A tag for [`IdGenerator::gen128_tagged`] does not fit in the reserved bits the
+    /// layout version leaves free.
+    InvalidTag(u8),
+    /// A region ID for [`AtomicOption::region_id`] is greater than 15.
+    InvalidRegion(u8),
+    /// An ID has a format version (see [`IdGenerator::format_version`]) that names no
+    /// layout: 0 for IDs that record no layout.
+    UnknownFormatVersion(u8),
+    /// [`IdGenerator::gen64_versioned`] was called while [`AtomicOption::version_bits`]
+    /// is disabled.
+    VersionBitsDisabled,
+}
+
+impl core::fmt::Display for IdError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IdError::UnsupportedWidth(bits) => write!(f, "unsupported ID width: {} bits", bits),
+            IdError::FeatureDisabled(feature) => {
+                write!(f, "this ID width requires the `{}` feature", feature)
+            }
+            IdError::TimestampNearWrap(remaining) => {
+                write!(f, "timestamp field wraps in {} ms", remaining.as_millis())
+            }
+            IdError::InvalidCharacter(c) => write!(f, "invalid character {:?} in ID", c),
+            IdError::Overflow => write!(f, "decoded ID does not fit in the target type"),
+            IdError::Empty => write!(f, "empty ID"),
+            IdError::RetriesExhausted(attempts) => {
+                write!(f, "no unused ID found after {} attempts", attempts)
+            }
+            IdError::EpochFrozen => write!(f, "the epoch is frozen"),
+            IdError::TimestampOverflow => write!(f, "timestamp does not fit in the ID's timestamp field"),
+            IdError::SeparatorInAlphabet(c) => {
+                write!(f, "separator {:?} is a digit of the encoding", c)
+            }
+            IdError::InvalidLength(len) => write!(f, "wrong length for an ID: {} bytes", len),
+            IdError::BeforeEpoch => write!(f, "time is before the epoch"),
+            IdError::UnknownLayoutVersion(version) => write!(f, "unknown ID layout version {}", version),
+            IdError::SequenceAhead(next) => write!(f, "sequence is already at {}", next),
+            IdError::InvalidTag(tag) => write!(f, "tag {} does not fit in the tag bits", tag),
+            IdError::InvalidRegion(region) => write!(f, "region ID {} is out of range (0..=15)", region),
+            IdError::UnknownFormatVersion(version) => write!(f, "unknown 64-bit ID format version {}", version),
+            IdError::VersionBitsDisabled => write!(f, "versioned IDs require version bits"),
+        }
+    }
+}
+
+impl core::error::Error for IdError {}
+
+/// Errors returned when setting the epoch from a timestamp, e.g. with
+/// [`AtomicOption::epoch_rfc3339`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EpochParseError {
+    /// The string is not an RFC 3339 timestamp such as `2024-01-01T00:00:00Z`.
+    Syntax,
+    /// A date or time field is out of range, e.g. month 13 or February 30.
+    FieldOutOfRange,
+    /// The timestamp is before 1970-01-01T00:00:00Z.
+    BeforeUnixEpoch,
+    /// The timestamp is in the future, so the first IDs would carry a timestamp of 0.
+    InFuture,
+    /// The epoch can no longer change; see [`IdError::EpochFrozen`].
+    Frozen,
+}
+
+impl core::fmt::Display for EpochParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EpochParseError::Syntax => write!(f, "not an RFC 3339 timestamp"),
+            EpochParseError::FieldOutOfRange => write!(f, "date or time field out of range"),
+            EpochParseError::BeforeUnixEpoch => write!(f, "epoch is before the UNIX epoch"),
+            EpochParseError::InFuture => write!(f, "epoch is in the future"),
+            EpochParseError::Frozen => write!(f, "the epoch is frozen"),
+        }
+    }
+}
+
+impl core::error::Error for EpochParseError {}
+
+/// Error returned by [`AtomicOption::init_global`] when the global generator is
+/// already fixed, naming the node and shard it was fixed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadyInitialized {
+    /// Node ID of the global generator in place.
+    pub node_id: u16,
+    /// Shard ID of the global generator in place.
+    pub shard_id: u8,
+    /// Whether the generator in place is the default one, locked in when the global
+    /// generator first generated an ID, rather than one from an earlier
+    /// [`AtomicOption::init_global`] or [`AtomicOption::reconfigure`].
+    pub implicit: bool,
+}
+
+impl core::fmt::Display for AlreadyInitialized {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.implicit {
+            write!(
+                f,
+                "the global generator was already initialized with the defaults node_id={}, shard_id={} \
+                 by the first ID generated; call AtomicOption::init_global before generating any ID",
+                self.node_id, self.shard_id
+            )
+        } else {
+            write!(
+                f,
+                "the global generator was already set to node_id={}, shard_id={}",
+                self.node_id, self.shard_id
+            )
+        }
+    }
+}
+
+impl core::error::Error for AlreadyInitialized {}
+
+/// Error returned by [`IdGenerator::try_gen24`] when the 24-bit sequence is used up
+/// under [`OverflowPolicy::Error`]. Call [`AtomicOption::reset24`] once earlier IDs
+/// may be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceExhausted;
+
+impl core::fmt::Display for SequenceExhausted {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "the 24-bit sequence is used up")
+    }
+}
+
+impl core::error::Error for SequenceExhausted {}
+
+/// Days from 1970-01-01 to the given proleptic Gregorian date (Howard Hinnant's
+/// `days_from_civil`). Negative before 1970.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Parse an RFC 3339 timestamp into milliseconds since UNIX_EPOCH.
+///
+/// Accepts `YYYY-MM-DDTHH:MM:SS`, optional fractional seconds (truncated to
+/// milliseconds), and a `Z` or `+HH:MM` / `-HH:MM` offset. `T` and `Z` may be
+/// lowercase, as RFC 3339 allows. A leap second (`:60`) counts as the next second.
+fn parse_rfc3339(s: &str) -> Result<u64, EpochParseError> {
+    let b = s.as_bytes();
+    let digits = |range: core::ops::Range<usize>| -> Result<u32, EpochParseError> {
+        let field = b.get(range).ok_or(EpochParseError::Syntax)?;
+        field.iter().try_fold(0u32, |n, &c| match c {
+            b'0'..=b'9' => Ok(n * 10 + (c - b'0') as u32),
+            _ => Err(EpochParseError::Syntax),
+        })
+    };
+    let expect = |i: usize, allowed: &[u8]| match b.get(i) {
+        Some(c) if allowed.contains(c) => Ok(()),
+        _ => Err(EpochParseError::Syntax),
+    };
+    expect(4, b"-")?;
+    expect(7, b"-")?;
+    expect(10, b"Tt")?;
+    expect(13, b":")?;
+    expect(16, b":")?;
+    let (year, month, day) = (digits(0..4)?, digits(5..7)?, digits(8..10)?);
+    let (hour, minute, second) = (digits(11..13)?, digits(14..16)?, digits(17..19)?);
+
+    let mut i = 19;
+    let mut millis = 0;
+    if b.get(i) == Some(&b'.') {
+        let start = i + 1;
+        i = start;
+        while b.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        if i == start {
+            return Err(EpochParseError::Syntax);
+        }
+        let frac = &b[start..i.min(start + 3)];
+        millis = frac.iter().fold(0, |n, &c| n * 10 + (c - b'0') as u64);
+        millis *= 10u64.pow(3 - frac.len() as u32);
+    }
+    let offset_minutes = match b.get(i) {
+        Some(b'Z' | b'z') if b.len() == i + 1 => 0,
+        Some(&sign @ (b'+' | b'-')) if b.len() == i + 6 => {
+            expect(i + 3, b":")?;
+            let (oh, om) = (digits(i + 1..i + 3)?, digits(i + 4..i + 6)?);
+            if oh > 23 || om > 59 {
+                return Err(EpochParseError::FieldOutOfRange);
+            }
+            let minutes = (oh * 60 + om) as i64;
+            if sign == b'-' { -minutes } else { minutes }
+        }
+        _ => return Err(EpochParseError::Syntax),
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(EpochParseError::FieldOutOfRange),
+    };
+    if day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 60 {
+        return Err(EpochParseError::FieldOutOfRange);
+    }
+
+    let days = days_from_civil(year as i64, month, day);
+    let secs = days * 86_400 + (hour * 3600 + minute * 60 + second) as i64 - offset_minutes * 60;
+    let secs = u64::try_from(secs).map_err(|_| EpochParseError::BeforeUnixEpoch)?;
+    Ok(secs * 1000 + millis)
+}
+
+/// The main entry point for generating atomic IDs of a specific bit width.
+///
+/// Use the const generic `BITS` parameter to select the desired ID size.
+/// For 24 and 32-bit IDs, the `short` feature must be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use atomic_id::{AtomicId, x64, x128};
+///
+/// // Generate a 64-bit ID
+/// let id64 = AtomicId::<x64>::new();
+///
+/// // Generate a 128-bit ID
+/// let id128 = AtomicId::<x128>::new();
+/// ```
+pub struct AtomicId<const BITS: usize>;
+
+/// Seals [`BitWidth`]: the layouts are only defined for the widths below.
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A width that [`AtomicId`] generates IDs of: `AtomicId<24>` and `AtomicId<32>` with
+/// the `short` feature, `AtomicId<64>`, `AtomicId<128>`, and `AtomicId<256>`.
+///
+/// The methods every width has, such as [`AtomicId::base58`] and
+/// [`AtomicId::batch_map`], are written once over this trait, which carries the raw
+/// type, how to generate it, and how to encode it. The trait is sealed.
+pub trait BitWidth: sealed::Sealed {
+    /// The raw ID: `u32` for 24 and 32 bits, `u64`, `u128`, or four 64-bit parts,
+    /// most significant first.
+    type Raw: Copy + Eq + core::hash::Hash;
+
+    /// Generate a raw ID from `generator`, as [`IdGenerator::gen64`] and the like do.
+    fn generate_raw(generator: &IdGenerator) -> Self::Raw;
+
+    /// Encode a raw ID in `encoding`, left-padded to [`Encoding::encoded_len`] of
+    /// this width.
+    fn encode_raw(raw: Self::Raw, encoding: Encoding) -> String;
+
+    /// Encode a raw ID the way `new()` does. Base36, except for the word format of
+    /// 256-bit IDs.
+    fn encode_default(raw: Self::Raw) -> String {
+        Self::encode_raw(raw, Encoding::Base36)
+    }
+
+    /// Generate `n` IDs from `generator`, encoded in `encoding`.
+    fn encode_batch(generator: &IdGenerator, n: usize, encoding: Encoding) -> Vec<String> {
+        (0..n).map(|_| Self::encode_raw(Self::generate_raw(generator), encoding)).collect()
+    }
+}
+
+macro_rules! bit_width {
+    ($($(#[$attr:meta])* $bits:literal => $raw:ty, $generate:ident;)*) => {$(
+        $(#[$attr])*
+        impl sealed::Sealed for AtomicId<$bits> {}
+
+        $(#[$attr])*
+        impl BitWidth for AtomicId<$bits> {
+            type Raw = $raw;
+
+            fn generate_raw(generator: &IdGenerator) -> $raw {
+                generator.$generate()
+            }
+
+            fn encode_raw(raw: $raw, encoding: Encoding) -> String {
+                encoding.encode(raw as u128, encoding.encoded_len($bits).unwrap_or(0))
+            }
+        }
+    )*};
+}
+
+bit_width! {
+    #[cfg(feature = "short")]
+    24 => u32, gen24;
+    #[cfg(feature = "short")]
+    32 => u32, gen32;
+    128 => u128, gen128;
+}
+
+impl sealed::Sealed for AtomicId<64> {}
+
+impl BitWidth for AtomicId<64> {
+    type Raw = u64;
+
+    fn generate_raw(generator: &IdGenerator) -> u64 {
+        generator.gen64()
+    }
+
+    fn encode_raw(raw: u64, encoding: Encoding) -> String {
+        encoding.encode(raw as u128, encoding.encoded_len(64).unwrap_or(0))
+    }
+
+    /// Generates the raw IDs first and encodes them together, so base36 batches take
+    /// the vector path of [`Encoding::encode_batch`] with the `simd` feature.
+    fn encode_batch(generator: &IdGenerator, n: usize, encoding: Encoding) -> Vec<String> {
+        let raw: Vec<u64> = (0..n).map(|_| generator.gen64()).collect();
+        encoding.encode_batch(&raw, encoding.encoded_len(64).unwrap_or(0))
+    }
+}
+
+impl sealed::Sealed for AtomicId<256> {}
+
+impl BitWidth for AtomicId<256> {
+    type Raw = [u64; 4];
+
+    fn generate_raw(generator: &IdGenerator) -> [u64; 4] {
+        generator.gen256()
+    }
+
+    /// The canonical form; hex, whose digits fall on part boundaries, skips the
+    /// long division.
+    fn encode_raw(raw: [u64; 4], encoding: Encoding) -> String {
+        match encoding {
+            Encoding::Hex => {
+                let mut s = String::with_capacity(64);
+                for part in raw {
+                    encode::push_hex(&mut s, part as u128, 16);
+                }
+                s
+            }
+            _ => AtomicId::<256>::to_canonical(raw, encoding),
+        }
+    }
+
+    fn encode_default(raw: [u64; 4]) -> String {
+        AtomicId::<256>::to_canonical(raw, Encoding::Base36)
+    }
+}
+
+/// Generation and encoding shared by every [`BitWidth`].
+///
+/// Every string is left-padded to [`Encoding::encoded_len`] of the width, so IDs of
+/// one width and encoding all have the same length:
+///
+/// | Width  | base36 | base58 | base91 | hex |
+/// |--------|--------|--------|--------|-----|
+/// | `x24`  | 5      | 5      | 4      | 6   |
+/// | `x32`  | 7      | 6      | 5      | 8   |
+/// | `x64`  | 13     | 11     | 10     | 16  |
+/// | `x128` | 25     | 22     | 20     | 32  |
+/// | `x256` | 50     | 44     | 40     | 64  |
+///
+/// 256-bit IDs encode the whole value as one number in every method. Base36 IDs
+/// were once the 52-character word format, which
+/// [`AtomicId::<x256>::base36_words`](AtomicId::<256>::base36_words) still produces
+/// for matching IDs already stored.
+impl<const BITS: usize> AtomicId<BITS>
+where
+    Self: BitWidth,
+{
+    /// Generate a new ID, encoded as a base36 string.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64, x128, x256};
+    /// assert_eq!(AtomicId::<x64>::new().len(), 13);
+    /// assert_eq!(AtomicId::<x128>::new().len(), 25);
+    /// assert_eq!(AtomicId::<x256>::new().len(), 50);
+    /// ```
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> String {
+        Self::encode_default(Self::generate_raw(&xgen()))
+    }
+    /// Generate a new ID, encoded as a base58 string.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id = AtomicId::<x64>::base58();
+    /// assert_eq!(id.len(), 11);
+    /// ```
+    pub fn base58() -> String {
+        Self::with_encoding(Encoding::Base58)
+    }
+    /// Generate a new ID, encoded as a base91 string.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id = AtomicId::<x64>::base91();
+    /// assert_eq!(id.len(), 10);
+    /// ```
+    pub fn base91() -> String {
+        Self::with_encoding(Encoding::Base91)
+    }
+    /// Generate a new ID, encoded as a base36 string.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id = AtomicId::<x64>::base36();
+    /// assert_eq!(id.len(), 13);
+    /// ```
+    pub fn base36() -> String {
+        Self::with_encoding(Encoding::Base36)
+    }
+    /// Generate a new ID, encoded as a lowercase hexadecimal string.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id = AtomicId::<x64>::hex();
+    /// assert_eq!(id.len(), 16);
+    /// ```
+    pub fn hex() -> String {
+        Self::with_encoding(Encoding::Hex)
+    }
+    /// Generate a new ID in `encoding`, chosen at runtime.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x128};
+    /// let id = AtomicId::<x128>::with_encoding(Encoding::Base91UrlSafe);
+    /// assert_eq!(id.len(), 20);
+    /// ```
+    pub fn with_encoding(encoding: Encoding) -> String {
+        Self::encode_raw(Self::generate_raw(&xgen()), encoding)
+    }
+
+    /// Generate a new ID and return both its raw value and its string in `encoding`,
+    /// e.g. the integer for a primary key column and the string for an API response.
+    /// Both describe the same ID; generating them separately would give two.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x128};
+    /// let (raw, id) = AtomicId::<x128>::new_pair(Encoding::Base58);
+    /// assert_eq!(Encoding::Base58.decode(&id), Ok(raw));
+    /// ```
+    pub fn new_pair(encoding: Encoding) -> (<Self as BitWidth>::Raw, String) {
+        let raw = Self::generate_raw(&xgen());
+        (raw, Self::encode_raw(raw, encoding))
+    }
+
+    /// Generate a new ID, encoded as `new()` encodes it, with `sep` inserted every
+    /// `group` characters, e.g. `"abcd-efgh-..."`, for IDs read aloud or copied by
+    /// hand. [`ungroup`] removes the separators again before decoding.
+    ///
+    /// # Panics
+    /// If `sep` is a base36 digit, which [`ungroup`] could not tell apart from the ID.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x256};
+    /// let id = AtomicId::<x256>::grouped(4, '-');
+    /// assert_eq!(id.len(), 50 + 12);
+    /// assert_eq!(atomic_id::ungroup(&id, '-').len(), 50);
+    /// ```
+    pub fn grouped(group: usize, sep: char) -> String {
+        assert!(!Encoding::Base36.contains(sep), "separator {:?} is a base36 digit", sep);
+        format_grouped(&Self::new(), group, sep)
+    }
+
+    /// Generate a new ID in `encoding`, as [`AtomicId::with_encoding`] encodes it,
+    /// with `sep` inserted every `group` characters. See [`Encoding::format_grouped`].
+    ///
+    /// # Errors
+    /// [`IdError::SeparatorInAlphabet`] if `sep` is a digit of `encoding`.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AnyId, AtomicId, Encoding, Width, x128};
+    /// let id = AtomicId::<x128>::grouped_with(Encoding::Hex, 8, ' ').unwrap();
+    /// assert_eq!(id.len(), 32 + 3);
+    /// let raw = AnyId::parse(&atomic_id::ungroup(&id, ' '), Width::X128, Encoding::Hex);
+    /// assert!(raw.is_ok());
+    /// ```
+    pub fn grouped_with(encoding: Encoding, group: usize, sep: char) -> Result<String, IdError> {
+        if encoding.contains(sep) {
+            return Err(IdError::SeparatorInAlphabet(sep));
+        }
+        Ok(format_grouped(&Self::with_encoding(encoding), group, sep))
+    }
+
+    /// Generate a raw ID that is not already in `seen`, and insert it.
+    ///
+    /// For merging ID spaces: an ID found in `seen` is discarded and another is
+    /// generated, up to `max_retries` times after the first attempt. Collisions with
+    /// freshly generated IDs are rare, so retries usually only happen when `seen`
+    /// holds IDs minted elsewhere with the same node and shard.
+    ///
+    /// # Errors
+    /// [`IdError::RetriesExhausted`] if every attempt was already in `seen`.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// use std::collections::HashSet;
+    /// let mut seen: HashSet<u64> = HashSet::new();
+    /// let id = AtomicId::<x64>::new_unique(&mut seen, 3).unwrap();
+    /// assert!(seen.contains(&id));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn new_unique(
+        seen: &mut std::collections::HashSet<<Self as BitWidth>::Raw>,
+        max_retries: usize,
+    ) -> Result<<Self as BitWidth>::Raw, IdError> {
+        new_unique_with(seen, max_retries, || Self::generate_raw(&xgen()))
+    }
+
+    /// Generate a batch of IDs, encoded as `new()` encodes them.
+    ///
+    /// # Arguments
+    /// * `n` - The number of IDs to generate.
+    ///
+    /// # Returns
+    /// A vector of base36-encoded ID strings.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let ids = AtomicId::<x64>::batch(3);
+    /// assert_eq!(ids.len(), 3);
+    /// ```
+    pub fn batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::new()).collect()
+    }
+    /// Generate a batch of IDs as base58 strings.
+    pub fn base58_batch(n: usize) -> Vec<String> {
+        Self::encode_batch(&xgen(), n, Encoding::Base58)
+    }
+    /// Generate a batch of IDs as base91 strings.
+    pub fn base91_batch(n: usize) -> Vec<String> {
+        Self::encode_batch(&xgen(), n, Encoding::Base91)
+    }
+    /// Generate a batch of IDs as base36 strings. 64-bit IDs are generated first and
+    /// encoded together, with the `simd` feature's vector path when enabled.
+    pub fn base36_batch(n: usize) -> Vec<String> {
+        Self::encode_batch(&xgen(), n, Encoding::Base36)
+    }
+    /// Generate a batch of IDs as hexadecimal strings.
+    pub fn hex_batch(n: usize) -> Vec<String> {
+        Self::encode_batch(&xgen(), n, Encoding::Hex)
+    }
+    /// Append `n` new IDs, encoded as `new()` encodes them, to `out`, reserving room
+    /// for all of them first, so a pool of vectors can be reused across batches.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let mut ids = Vec::with_capacity(3);
+    /// AtomicId::<x64>::batch_extend(3, &mut ids);
+    /// ids.clear();
+    /// AtomicId::<x64>::batch_extend(2, &mut ids);
+    /// assert_eq!(ids.len(), 2);
+    /// ```
+    pub fn batch_extend(n: usize, out: &mut Vec<String>) {
+        out.reserve(n);
+        out.extend((0..n).map(|_| Self::new()));
+    }
+    /// Append `n` new raw IDs, as from [`IdGenerator::gen64`] and the like, to `out`,
+    /// reserving room for all of them first.
+    pub fn batch_raw_extend(n: usize, out: &mut Vec<<Self as BitWidth>::Raw>) {
+        out.reserve(n);
+        out.extend((0..n).map(|_| Self::generate_raw(&xgen())));
+    }
+    /// Generate `n` raw IDs and convert each with `f`, for building other
+    /// representations without encoding a string first.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64, x256};
+    /// let ids = AtomicId::<x64>::batch_map(3, |id| id.to_be_bytes());
+    /// assert_eq!(ids.len(), 3);
+    /// let ids = AtomicId::<x256>::batch_map(3, |id| id[0]);
+    /// assert_eq!(ids.len(), 3);
+    /// ```
+    pub fn batch_map<T>(n: usize, f: impl FnMut(<Self as BitWidth>::Raw) -> T) -> Vec<T> {
+        (0..n).map(|_| Self::generate_raw(&xgen())).map(f).collect()
+    }
+    /// Generate `n` IDs, each as its raw value and its string in `encoding`. See
+    /// [`AtomicId::new_pair`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x64};
+    /// for (raw, id) in AtomicId::<x64>::batch_pairs(3, Encoding::Hex) {
+    ///     assert_eq!(u64::from_str_radix(&id, 16), Ok(raw));
+    /// }
+    /// ```
+    pub fn batch_pairs(n: usize, encoding: Encoding) -> Vec<(<Self as BitWidth>::Raw, String)> {
+        Self::batch_map(n, |raw| (raw, Self::encode_raw(raw, encoding)))
+    }
+}
+
+#[cfg(feature = "short")]
+impl AtomicId<24> {
+    /// The fields of 24-bit IDs in the current [`Layout24`]. See [`Layout`].
+    pub fn layout() -> Layout {
+        match Layout24::current() {
+            Layout24::Standard => Layout::stacked(24, &[("sequence", 24)]),
+            Layout24::TimeSalted => Layout::stacked(24, &[("seconds", 8), ("sequence", 16)]),
+        }
+    }
+
+    /// Total number of distinct 24-bit IDs the layout can produce (2^24).
+    pub fn capacity() -> u128 {
+        1 << 24
+    }
+
+    /// Probability that `n` 24-bit IDs contain at least one duplicate.
+    /// Requires the `std` feature.
+    ///
+    /// 24-bit IDs are a pure counter with no random bits, so this is `0.0` up to
+    /// 2^24 IDs and `1.0` beyond, when the counter wraps.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x24};
+    /// assert_eq!(AtomicId::<x24>::collision_probability(1 << 24), 0.0);
+    /// assert_eq!(AtomicId::<x24>::collision_probability((1 << 24) + 1), 1.0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn collision_probability(n: u64) -> f64 {
+        birthday_probability(n, 24, 0)
+    }
+
+    /// Generate a new 24-bit ID as a 5-character base36 string, or fail once the
+    /// sequence is used up. See [`IdGenerator::try_gen24`] for how the
+    /// [`AtomicOption::overflow_policy24`] applies.
+    ///
+    /// # Errors
+    /// [`SequenceExhausted`] with [`OverflowPolicy::Error`] once the sequence is used up.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x24};
+    /// let id = AtomicId::<x24>::try_new().unwrap();
+    /// assert_eq!(id.len(), 5);
+    /// ```
+    pub fn try_new() -> Result<String, SequenceExhausted> {
+        xgen().try_gen24().map(|id| encode::base36(id as u128, 5))
+    }
+
+    /// Number of IDs that can still be generated before the 24-bit sequence wraps
+    /// around and starts repeating earlier IDs, in the current [`Layout24`].
+    ///
+    /// With [`OverflowPolicy::Wrap`] this counts down to the next wrap and starts over.
+    /// With the other policies it counts down to the point where
+    /// [`AtomicId::<x24>::try_new`](AtomicId::try_new) stops, and stays at 0 until
+    /// [`AtomicOption::reset24`].
+    pub fn remaining_before_wrap() -> u64 {
+        let seq = SEQ_24.load(Ordering::Relaxed);
+        let capacity = Layout24::current().sequence_capacity();
+        match OverflowPolicy::current() {
+            OverflowPolicy::Wrap => capacity - (seq & (capacity - 1)),
+            OverflowPolicy::Error | OverflowPolicy::Block => {
+                capacity.saturating_sub(seq.wrapping_sub(SEQ_24_RESET_AT.load(Ordering::Relaxed)))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "short")]
+impl AtomicId<32> {
+    /// Generate a new time-ordered 32-bit ID, encoded as a 7-character base36 string.
+    /// See [`IdGenerator::gen32_timed`] for the layout and its limits.
+    ///
+    /// Base36 and hex strings sort in the same order as the raw IDs.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x32};
+    /// let id = AtomicId::<x32>::timed();
+    /// assert_eq!(id.len(), 7);
+    /// ```
+    pub fn timed() -> String {
+        encode::base36(xgen().gen32_timed() as u128, 7)
+    }
+    /// Generate a new time-ordered 32-bit ID in the given encoding.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x32};
+    /// let id = AtomicId::<x32>::timed_with(Encoding::Hex);
+    /// assert_eq!(id.len(), 8);
+    /// ```
+    pub fn timed_with(encoding: Encoding) -> String {
+        let width = encoding.encoded_len(32).unwrap_or(8);
+        encoding.encode(xgen().gen32_timed() as u128, width)
+    }
+
+    /// The fields of 32-bit IDs in the global generator's [`Layout32`]. See [`Layout`].
+    pub fn layout() -> Layout {
+        match xgen_read().layout32() {
+            Layout32::Standard => Layout::stacked(32, &[("thread", 8), ("sequence", 24)]),
+            Layout32::TimeSalted => Layout::stacked(32, &[("seconds", 10), ("sequence", 22)]),
+        }
+    }
+
+    /// Total number of distinct 32-bit IDs the layout can produce.
+    ///
+    /// Every bit is used in both [`Layout32`] variants, so this is 2^32.
+    pub fn capacity() -> u128 {
+        1 << 32
+    }
+
+    /// Probability that `n` 32-bit IDs contain at least one duplicate, in the global
+    /// generator's [`Layout32`]. Requires the `std` feature.
+    ///
+    /// In [`Layout32::Standard`] the 24-bit sequence keeps the first 2^24 IDs distinct;
+    /// beyond that, IDs that share a sequence value only differ by the 8 bits of hashed
+    /// thread ID. In [`Layout32::TimeSalted`] this is the worst case of all `n` IDs
+    /// falling in one second: distinct up to 2^22, then certain to repeat.
+    #[cfg(feature = "std")]
+    pub fn collision_probability(n: u64) -> f64 {
+        match xgen_read().layout32() {
+            Layout32::Standard => birthday_probability(n, 24, 8),
+            Layout32::TimeSalted => birthday_probability(n, 22, 0),
+        }
+    }
+}
+
+impl AtomicId<64> {
+    /// Generate a new 64-bit ID, encoded as a 10-character URL-safe base91 string
+    /// ([`Encoding::Base91UrlSafe`]).
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id = AtomicId::<x64>::base91_urlsafe();
+    /// assert_eq!(id.len(), 10);
+    /// assert!(!id.contains(['%', '#', '?']));
+    /// ```
+    pub fn base91_urlsafe() -> String {
+        encode::base91_urlsafe(xgen().gen64() as u128, 10)
+    }
+    /// Generate a new 64-bit ID, encoded as base36 and left-padded with zeros to
+    /// `width` characters, for aligning IDs in columns.
+    ///
+    /// Widths below 13 are raised to 13, the length of the largest 64-bit value,
+    /// so no ID is ever truncated. IDs of every width decode with
+    /// [`Encoding::decode`], since leading zeros do not change the value.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// assert_eq!(AtomicId::<x64>::base36_width(16).len(), 16);
+    /// assert_eq!(AtomicId::<x64>::base36_width(4).len(), 13);
+    /// ```
+    pub fn base36_width(width: usize) -> String {
+        encode::base36(xgen().gen64() as u128, width.max(13))
+    }
+    /// Generate a new 64-bit ID, encoded with `encoder` and padded to its
+    /// [`Encoder::width`](encoder::Encoder::width) for 64 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::encoder::Hex;
+    /// use atomic_id::{AtomicId, x64};
+    /// assert_eq!(AtomicId::<x64>::encode_with(&Hex).len(), 16);
+    /// ```
+    pub fn encode_with<E: encoder::Encoder + ?Sized>(encoder: &E) -> String {
+        encoder.encode(xgen().gen64() as u128, encoder.width(64))
+    }
+
+    /// Generate a new 64-bit ID as the decimal digits of its raw value, e.g.
+    /// `"1234567890123456789"`.
+    ///
+    /// For consumers that cannot hold 64-bit integers exactly, such as JavaScript,
+    /// but need no particular encoding: as a string the value survives JSON intact.
+    /// The length varies, up to 20 digits, so unlike the other encodings these
+    /// strings do not sort in ID order. Decode with `s.parse::<u64>()` or
+    /// [`AtomicId::<x64>::from_decimal`](AtomicId::<64>::from_decimal).
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id = AtomicId::<x64>::decimal();
+    /// assert!(id.len() <= 20);
+    /// let raw: u64 = id.parse().unwrap();
+    /// assert_eq!(raw.to_string(), id);
+    /// ```
+    pub fn decimal() -> String {
+        encode::decimal(xgen().gen64() as u128)
+    }
+    /// Generate a batch of 64-bit IDs as decimal strings.
+    pub fn decimal_batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::decimal()).collect()
+    }
+    /// Decode a decimal string from [`AtomicId::<x64>::decimal`](AtomicId::<64>::decimal)
+    /// into the raw ID. Like `s.parse::<u64>()`, but with the same errors as
+    /// [`Encoding::decode`].
+    ///
+    /// # Errors
+    /// - [`IdError::Empty`] for an empty string.
+    /// - [`IdError::InvalidCharacter`] for anything but the digits `0-9`.
+    /// - [`IdError::Overflow`] if the value does not fit in 64 bits.
+    pub fn from_decimal(s: &str) -> Result<u64, IdError> {
+        u64::try_from(encode::from_decimal(s)?).map_err(|_| IdError::Overflow)
+    }
+
+    /// Generate a new 64-bit ID and return both the raw value and its 13-character
+    /// base36 encoding.
+    ///
+    /// Both halves describe the same ID, e.g. the integer for a database column and
+    /// the string for an API response. Calling [`IdGenerator::gen64`] and
+    /// [`AtomicId::<x64>::new`] separately would produce two unrelated IDs.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let (raw, id) = AtomicId::<x64>::new_with_raw();
+    /// assert_eq!(u64::from_str_radix(&id, 36).unwrap(), raw);
+    /// ```
+    pub fn new_with_raw() -> (u64, String) {
+        let raw = xgen().gen64();
+        (raw, encode::base36(raw as u128, 13))
+    }
+
+    /// Generate a new raw 64-bit ID that is never equal to `prev`, e.g. a fresh
+    /// idempotency key for a retry after `prev` failed.
+    ///
+    /// A new ID equals an earlier one only if the sequence wrapped back to the same
+    /// value within one tick on the same thread, so this almost always returns the
+    /// first ID generated. If that ID is `prev`, another is generated, which takes the
+    /// next sequence value and so differs, and so on until one differs from `prev`.
+    /// Only `prev` is excluded: the result can still repeat other earlier IDs in the
+    /// same way any ID can.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let (first, _) = AtomicId::<x64>::new_with_raw();
+    /// assert_ne!(AtomicId::<x64>::new_distinct_from(first), first);
+    /// ```
+    pub fn new_distinct_from(prev: u64) -> u64 {
+        let generator = xgen();
+        loop {
+            let id = generator.gen64();
+            if id != prev {
+                return id;
+            }
+        }
+    }
+    /// Generate a new raw 64-bit ID with the top bit cleared, so it fits a signed
+    /// 64-bit column such as Postgres `bigint` without turning negative.
+    ///
+    /// The top bit is the high bit of the time fields, so the timestamp span is one
+    /// bit shorter: in the default layout, IDs wrap every 2^19 ms (about 8.7 minutes)
+    /// instead of 2^20 ms, and in [`Layout64::Era`] the era counts 4 periods instead
+    /// of 8. Within that span the IDs sort in generation order as signed integers.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// assert_eq!(AtomicId::<x64>::raw_positive() >> 63, 0);
+    /// ```
+    pub fn raw_positive() -> u64 {
+        xgen().gen64() & (u64::MAX >> 1)
+    }
+    /// Generate a new 64-bit ID as a non-negative `i64`. See
+    /// [`AtomicId::<x64>::raw_positive`](AtomicId::<64>::raw_positive) for the shorter
+    /// timestamp span.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// assert!(AtomicId::<x64>::positive() >= 0);
+    /// ```
+    pub fn positive() -> i64 {
+        Self::raw_positive() as i64
+    }
+    /// Generate a new 64-bit ID with its 11-character base58 encoding.
+    pub fn base58_with_raw() -> (u64, String) {
+        let raw = xgen().gen64();
+        (raw, encode::base58(raw as u128, 11))
+    }
+    /// Generate a new 64-bit ID with its 10-character base91 encoding.
+    pub fn base91_with_raw() -> (u64, String) {
+        let raw = xgen().gen64();
+        (raw, encode::base91(raw as u128, 10))
+    }
+    /// Generate a new 64-bit ID with its 13-character base36 encoding.
+    pub fn base36_with_raw() -> (u64, String) {
+        Self::new_with_raw()
+    }
+    /// Generate a new 64-bit ID with its 16-character hexadecimal encoding.
+    pub fn hex_with_raw() -> (u64, String) {
+        let raw = xgen().gen64();
+        (raw, encode::hex(raw as u128, 16))
+    }
+    /// Generate a new 64-bit ID with the timestamp `time` instead of now, encoded as
+    /// a 13-character base36 string. See [`IdGenerator::gen64_at`] for the
+    /// accepted times.
+    ///
+    /// # Errors
+    /// [`IdError::BeforeEpoch`] or [`IdError::TimestampOverflow`] if `time` does not
+    /// fit in the ID's timestamp field.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, AtomicOption, x64};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// let time = UNIX_EPOCH + Duration::from_millis(AtomicOption::get_epoch() + 60_000);
+    /// assert_eq!(AtomicId::<x64>::new_at(time).unwrap().len(), 13);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn new_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen64_at(time).map(|id| encode::base36(id as u128, 13))
+    }
+    /// Like [`AtomicId::<x64>::new_at`](AtomicId::<64>::new_at), with its 11-character base58 encoding.
+    #[cfg(feature = "std")]
+    pub fn base58_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen64_at(time).map(|id| encode::base58(id as u128, 11))
+    }
+    /// Like [`AtomicId::<x64>::new_at`](AtomicId::<64>::new_at), with its 10-character base91 encoding.
+    #[cfg(feature = "std")]
+    pub fn base91_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen64_at(time).map(|id| encode::base91(id as u128, 10))
+    }
+    /// Like [`AtomicId::<x64>::new_at`](AtomicId::<64>::new_at), with its 13-character base36 encoding.
+    #[cfg(feature = "std")]
+    pub fn base36_at(time: SystemTime) -> Result<String, IdError> {
+        Self::new_at(time)
+    }
+    /// Like [`AtomicId::<x64>::new_at`](AtomicId::<64>::new_at), with its 16-character hexadecimal encoding.
+    #[cfg(feature = "std")]
+    pub fn hex_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen64_at(time).map(|id| encode::hex(id as u128, 16))
+    }
+
+    /// The fields of 64-bit IDs as [`IdGenerator::gen64`] packs them now, in the
+    /// current [`Layout64`], [`TimestampPrecision`], [`AtomicOption::thread_id_bits`],
+    /// and [`AtomicOption::version_bits`] settings.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let layout = AtomicId::<x64>::layout();
+    /// let names: Vec<_> = layout.fields().iter().map(|field| field.name).collect();
+    /// assert_eq!(names, ["timestamp", "node", "shard", "thread", "sequence"]);
+    /// assert_eq!(layout.field("timestamp").unwrap().bit_offset, 44);
+    /// ```
+    pub fn layout() -> Layout {
+        layout64(Layout64::current(), TimestampPrecision::current(), thread_id_bits(), version_bits())
+    }
+
+    /// Total number of distinct 64-bit IDs the layout can produce.
+    ///
+    /// Every field is fully used except the thread ID, which takes 128 distinct
+    /// values, so this is 2^63, or 2^61 while [`AtomicOption::version_bits`] fixes
+    /// the layout version.
+    pub fn capacity() -> u128 {
+        if version_bits() { 1 << 61 } else { 1 << 63 }
+    }
+
+    /// Probability that `n` 64-bit IDs contain at least one duplicate.
+    /// Requires the `std` feature.
+    ///
+    /// This is a worst-case estimate for IDs minted within a single millisecond on one
+    /// node and shard: the 16-bit sequence keeps the first 65,536 IDs distinct, after
+    /// which IDs sharing a sequence value only differ by the 7 bits of hashed thread ID.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// assert_eq!(AtomicId::<x64>::collision_probability(65_536), 0.0);
+    /// assert!(AtomicId::<x64>::collision_probability(65_536 + 16) > 0.1);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn collision_probability(n: u64) -> f64 {
+        birthday_probability(n, seq_bits64(DEFAULT_THREAD_ID_BITS as u32), 7)
+    }
+
+    /// Generate `ids` raw 64-bit IDs from the global generator on `threads` threads
+    /// and count the duplicates, to check in CI that the node, shard, thread, and
+    /// layout settings in use stay unique at the expected load.
+    ///
+    /// Each thread generates its share of the IDs, `ids / threads` with the remainder
+    /// spread over the first threads, into a vector of its own, so generation runs as
+    /// concurrently as in production; the IDs are merged and checked once every
+    /// thread is done. A diagnostic, not for production paths: it holds all `ids` IDs
+    /// in memory, and the IDs it generates are used up. A `threads` of zero runs on
+    /// one thread.
+    ///
+    /// Run it with the thread count of production, on comparable hardware: with more
+    /// threads than cores, a thread descheduled between reading the clock and taking
+    /// its sequence value resumes with a stale timestamp, and repeats one of its own
+    /// IDs if the other threads wrapped the sequence in the meantime. The self-test
+    /// reports those duplicates like any other.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let report = AtomicId::<x64>::self_test(10_000, 4);
+    /// assert_eq!(report.generated, 10_000);
+    /// assert_eq!(report.duplicates, 0);
+    /// ```
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    pub fn self_test(ids: usize, threads: usize) -> SelfTestReport {
+        let threads = threads.max(1);
+        let start = Instant::now();
+        let mut all: Vec<u64> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|worker| {
+                    let share = ids / threads + usize::from(worker < ids % threads);
+                    scope.spawn(move || {
+                        let generator = xgen();
+                        (0..share).map(|_| generator.gen64()).collect::<Vec<u64>>()
+                    })
+                })
+                .collect();
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        });
+        let duration = start.elapsed();
+        all.sort_unstable();
+        all.dedup();
+        SelfTestReport { generated: ids, unique: all.len(), duplicates: ids - all.len(), duration }
+    }
+
+    /// Time left until the 20-bit timestamp field wraps to zero.
+    ///
+    /// The field counts milliseconds since the epoch modulo 2^20, so it wraps every
+    /// 17 minutes 28.576 seconds. IDs minted after a wrap reuse the timestamps of the
+    /// previous period. In [`Layout64::Era`] the era absorbs the rollover, and this is
+    /// the time until the era wraps too. In another [`TimestampPrecision`] the field
+    /// counts its ticks instead, with the width of
+    /// [`TimestampPrecision::timestamp_bits`]. See [`IdGenerator::gen64_checked`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// use std::time::Duration;
+    /// assert!(AtomicId::<x64>::time_remaining() <= Duration::from_millis(1 << 20));
+    /// ```
+    pub fn time_remaining() -> Duration {
+        let precision = TimestampPrecision::current();
+        let generator = xgen_read();
+        let ticks = generator.ticks_at(precision, generator.timestamp());
+        precision.ticks_to_duration(ms_until_wrap(ticks, precision.timestamp_bits(Layout64::current())))
+    }
+
+    /// Derive a deterministic 64-bit ID from `data` in `encoding`, padded to
+    /// [`Encoding::encoded_len`] of 64 bits.
+    ///
+    /// The same input always yields the same ID. See [`IdGenerator::derive64`] for the
+    /// hash definition and the raw `u64` value.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x64};
+    /// let a = AtomicId::<x64>::derive(b"order-1234", Encoding::Base36);
+    /// let b = AtomicId::<x64>::derive(b"order-1234", Encoding::Base36);
+    /// assert_eq!(a, b);
+    /// assert_eq!(a.len(), 13);
+    /// ```
+    pub fn derive(data: &[u8], encoding: Encoding) -> String {
+        Self::derive_in("", data, encoding)
+    }
+
+    /// Derive a deterministic 64-bit ID from `data` within `namespace`, in `encoding`.
+    ///
+    /// Like UUIDv5, the same data in different namespaces produces unrelated IDs.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x64};
+    /// let orders = AtomicId::<x64>::derive_in("orders", b"1234", Encoding::Hex);
+    /// let users = AtomicId::<x64>::derive_in("users", b"1234", Encoding::Hex);
+    /// assert_ne!(orders, users);
+    /// ```
+    pub fn derive_in(namespace: &str, data: &[u8], encoding: Encoding) -> String {
+        let id = IdGenerator::derive64(namespace, data) as u128;
+        encoding.encode(id, encoding.encoded_len(64).unwrap_or(0))
+    }
+
+    /// Assign a raw 64-bit ID to one of `partitions` partitions, e.g. a Kafka partition.
+    ///
+    /// The ID is scrambled with the SplitMix64 finalizer before taking the remainder,
+    /// so the timestamp-heavy high bits and the sequence-heavy low bits both spread
+    /// evenly. The result is `splitmix64(id) % partitions` and never changes between
+    /// releases, so every service computes the same partition for the same ID.
+    ///
+    /// Adding a partition moves almost every ID; use [`AtomicId::<x64>::bucket_of`](AtomicId::<64>::bucket_of)
+    /// when the count changes over time.
+    ///
+    /// # Panics
+    /// If `partitions` is 0.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, IdGenerator, x64};
+    /// let id = IdGenerator::new(1, 0).gen64();
+    /// let partition = AtomicId::<x64>::partition_of(id, 12);
+    /// assert!(partition < 12);
+    /// assert_eq!(partition, AtomicId::<x64>::partition_of(id, 12));
+    /// ```
+    pub fn partition_of(id: u64, partitions: u32) -> u32 {
+        (mix64(id) % partitions as u64) as u32
+    }
+
+    /// Assign an encoded ID to one of `partitions` partitions.
+    ///
+    /// The string's bytes are hashed with 64-bit FNV-1a and then placed like
+    /// [`AtomicId::<x64>::partition_of`](AtomicId::<64>::partition_of), so any
+    /// encoding, width, or foreign key string works. The same ID in two encodings
+    /// generally lands in different partitions; pick one form per topic.
+    ///
+    /// # Panics
+    /// If `partitions` is 0.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id = AtomicId::<x64>::new();
+    /// assert!(AtomicId::<x64>::partition_of_str(&id, 12) < 12);
+    /// ```
+    pub fn partition_of_str(id: &str, partitions: u32) -> u32 {
+        Self::partition_of(hash::fnv1a64(&[id.as_bytes()]), partitions)
+    }
+
+    /// Assign a raw 64-bit ID to one of `buckets` buckets with jump consistent hashing
+    /// (Lamping and Veach, 2014).
+    ///
+    /// When the bucket count grows from `n` to `n + 1`, only about `1 / (n + 1)` of
+    /// the IDs move, and all of them move to the new bucket `n`. This suits resharding,
+    /// where [`AtomicId::<x64>::partition_of`](AtomicId::<64>::partition_of) would
+    /// reshuffle nearly everything. Buckets are numbered, not named, so they can only
+    /// be added or removed at the end.
+    ///
+    /// # Panics
+    /// If `buckets` is 0.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, IdGenerator, x64};
+    /// let id = IdGenerator::new(1, 0).gen64();
+    /// let before = AtomicId::<x64>::bucket_of(id, 10);
+    /// let after = AtomicId::<x64>::bucket_of(id, 11);
+    /// assert!(after == before || after == 10);
+    /// ```
+    pub fn bucket_of(id: u64, buckets: u32) -> u32 {
+        assert!(buckets > 0, "bucket_of requires at least one bucket");
+        let mut key = mix64(id);
+        let (mut bucket, mut next) = (0i64, 0i64);
+        while next < buckets as i64 {
+            bucket = next;
+            key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+            next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+        }
+        bucket as u32
+    }
+
+    /// Whether two raw 64-bit IDs were generated on the same node, read in the current
+    /// [`Layout64`].
+    ///
+    /// The reader must share the writer's [`AtomicOption::version_bits`] setting:
+    /// without it, IDs from [`IdGenerator::gen64_versioned`] are read in the current
+    /// layout rather than their own, and reserved versions are not refused.
+    ///
+    /// # Errors
+    /// [`IdError::UnknownLayoutVersion`] if either ID has a reserved layout version,
+    /// which IDs only carry while [`AtomicOption::version_bits`] is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, IdGenerator, x64};
+    /// let (a, b) = (IdGenerator::new(7, 1).gen64(), IdGenerator::new(7, 2).gen64());
+    /// assert_eq!(AtomicId::<x64>::same_node(a, b), Ok(true));
+    /// assert_eq!(AtomicId::<x64>::same_shard(a, b), Ok(false));
+    /// ```
+    pub fn same_node(a: u64, b: u64) -> Result<bool, IdError> {
+        Ok(known_parts64(a)?.node_id == known_parts64(b)?.node_id)
+    }
+
+    /// Whether two raw 64-bit IDs were generated on the same node and shard, read in
+    /// the current [`Layout64`]. Shard numbers are only meaningful within a node. As
+    /// with [`AtomicId::same_node`], the reader must share the writer's
+    /// [`AtomicOption::version_bits`] setting.
+    ///
+    /// # Errors
+    /// [`IdError::UnknownLayoutVersion`] if either ID has a reserved layout version,
+    /// which IDs only carry while [`AtomicOption::version_bits`] is enabled.
+    pub fn same_shard(a: u64, b: u64) -> Result<bool, IdError> {
+        let (a, b) = (known_parts64(a)?, known_parts64(b)?);
+        Ok((a.node_id, a.shard_id) == (b.node_id, b.shard_id))
+    }
+
+    /// How far apart two raw 64-bit IDs were generated, whichever came first, at the
+    /// current [`TimestampPrecision`] and read in the current [`Layout64`].
+    ///
+    /// Both IDs must come from the same wrap period of the time fields (see
+    /// [`AtomicId::<x64>::time_remaining`](AtomicId::<64>::time_remaining)); across a
+    /// wrap the result is meaningless.
+    ///
+    /// # Errors
+    /// [`IdError::UnknownLayoutVersion`] if either ID has a reserved layout version,
+    /// which IDs only carry while [`AtomicOption::version_bits`] is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// use std::time::Duration;
+    /// let (a, b) = ((5u64 << 44) | 1, (1250u64 << 44) | 1);
+    /// assert_eq!(AtomicId::<x64>::time_between(b, a), Ok(Duration::from_millis(1245)));
+    /// ```
+    pub fn time_between(a: u64, b: u64) -> Result<Duration, IdError> {
+        known_parts64(a)?;
+        known_parts64(b)?;
+        let precision = TimestampPrecision::current();
+        let ticks = |id| {
+            let version = IdGenerator::layout_version(id);
+            let layout = match version {
+                FORMAT_LAYOUT_VERSION => Layout64::from_format_version(IdGenerator::format_version(id)).unwrap_or_default(),
+                _ => Layout64::current(),
+            };
+            layout.ticks_and_sequence(precision, id, version, thread_id_bits()).0
+        };
+        Ok(precision.ticks_to_duration(ticks(a).abs_diff(ticks(b))))
+    }
+
+    /// Like [`AtomicId::<x64>::same_node`](AtomicId::<64>::same_node), for IDs encoded
+    /// as base36, the default encoding of [`AtomicId::<x64>::new`](AtomicId::<64>::new).
+    ///
+    /// # Errors
+    /// - [`IdError::Empty`] or [`IdError::InvalidCharacter`] if an ID is not base36.
+    /// - [`IdError::Overflow`] if a value does not fit in 64 bits.
+    /// - [`IdError::UnknownLayoutVersion`] as for `same_node`.
+    pub fn same_node_str(a: &str, b: &str) -> Result<bool, IdError> {
+        Self::same_node(decode_base36_64(a)?, decode_base36_64(b)?)
+    }
+
+    /// Like [`AtomicId::<x64>::same_shard`](AtomicId::<64>::same_shard), for IDs
+    /// encoded as base36. See [`AtomicId::<x64>::same_node_str`](AtomicId::<64>::same_node_str)
+    /// for the errors.
+    pub fn same_shard_str(a: &str, b: &str) -> Result<bool, IdError> {
+        Self::same_shard(decode_base36_64(a)?, decode_base36_64(b)?)
+    }
+
+    /// Like [`AtomicId::<x64>::time_between`](AtomicId::<64>::time_between), for IDs
+    /// encoded as base36. See [`AtomicId::<x64>::same_node_str`](AtomicId::<64>::same_node_str)
+    /// for the errors.
+    pub fn time_between_str(a: &str, b: &str) -> Result<Duration, IdError> {
+        Self::time_between(decode_base36_64(a)?, decode_base36_64(b)?)
+    }
+
+    /// Generate a 64-bit ID whose strings sort in creation order, encoded as a
+    /// 13-character base36 string. See [`IdGenerator::gen64_k_sorted`] for the layout
+    /// and how far the order holds across processes.
+    ///
+    /// # Sort order
+    /// Strings sort by time when the layout puts the timestamp on top, the encoding
+    /// [preserves order](Encoding::is_sort_preserving), and the width is fixed.
+    /// Among the other methods:
+    ///
+    /// - [`AtomicId::<x64>::new`](AtomicId::<64>::new), `base36`, `base58`, and `hex`
+    ///   sort only until the 20-bit timestamp wraps, about 17 minutes in milliseconds
+    ///   (see [`IdGenerator::gen64`]), and only per thread within a tick.
+    /// - [`AtomicId::<x128>::new`](AtomicId::<128>::new) and the other order-preserving
+    ///   encodings sort until the 32-bit timestamp wraps, about 49 days.
+    /// - [`AtomicId::<x64>::sequential`](AtomicId::<64>::sequential) sorts in counter
+    ///   order within one process, not by time.
+    /// - `AtomicId::<x32>::timed` sorts until its 20-bit seconds wrap, about 12 days.
+    /// - The `base91` methods, [`IdGenerator::gen64_scattered`], and the counter- and
+    ///   thread-prefixed 24- and 32-bit IDs do not sort by time.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let first = AtomicId::<x64>::k_sorted();
+    /// let second = AtomicId::<x64>::k_sorted();
+    /// assert_eq!(first.len(), 13);
+    /// assert!(second > first);
+    /// ```
+    pub fn k_sorted() -> String {
+        encode::base36(xgen().gen64_k_sorted() as u128, 13)
+    }
+
+    /// Generate a sequential 64-bit ID as a base36 string.
+    ///
+    /// This method uses a simple atomic counter, making the IDs sequential but not
+    /// time-sortable. It is useful for scenarios where strict ordering is more
+    /// important than distributed uniqueness. Every `sequential*` method takes its
+    /// values from [`Sequence::global`], so move them all with
+    /// [`Sequence::set_start`], or use a [`Sequence`] of your own per table.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let id1 = AtomicId::<x64>::sequential();
+    /// let id2 = AtomicId::<x64>::sequential();
+    /// // id2 will be lexicographically greater than id1
+    /// assert!(id2 > id1);
+    /// ```
+    pub fn sequential() -> String {
+        Sequence::global().next_base36()
+    }
+
+    /// Generate a sequential 64-bit ID as a base58 string.
+    pub fn sequential_base58() -> String {
+        Sequence::global().next_encoded(Encoding::Base58)
+    }
+
+    /// Generate a sequential 64-bit ID as a base91 string.
+    pub fn sequential_base91() -> String {
+        Sequence::global().next_encoded(Encoding::Base91)
+    }
+
+    /// Generate a sequential 64-bit ID as a base36 string.
+    pub fn sequential_base36() -> String {
+        Sequence::global().next_base36()
+    }
+
+    /// Generate a sequential 64-bit ID as a hexadecimal string.
+    pub fn sequential_hex() -> String {
+        Sequence::global().next_encoded(Encoding::Hex)
+    }
+
+    /// Generate a batch of sequential 64-bit IDs as base36 strings.
+    pub fn sequential_batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::sequential()).collect()
+    }
+    /// Generate a batch of sequential 64-bit IDs as base58 strings.
+    pub fn sequential_base58_batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::sequential_base58()).collect()
+    }
+    /// Generate a batch of sequential 64-bit IDs as base91 strings.
+    pub fn sequential_base91_batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::sequential_base91()).collect()
+    }
+    /// Generate a batch of sequential 64-bit IDs as base36 strings.
+    pub fn sequential_base36_batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::sequential_base36()).collect()
+    }
+    /// Generate a batch of sequential 64-bit IDs as hexadecimal strings.
+    pub fn sequential_hex_batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::sequential_hex()).collect()
+    }
+
+    /// Lease a block of `n` raw 64-bit IDs for this thread, for inner loops that
+    /// must not touch shared state per ID. See [`IdLease`].
+    ///
+    /// `n` is raised to 1 and capped at a sixteenth of the values the sequence field
+    /// can tell apart within one tick, 4,096 with the default
+    /// [`AtomicOption::thread_id_bits`] and [`TimestampPrecision::Millis`], and at the
+    /// [`AtomicOption::max_rate_per_ms`] limit if there is one, against which the
+    /// whole block counts.
+    ///
+    /// Leases taken within one tick, on any thread, share its sequence values. Once
+    /// the values claimed since the first lease of the tick would no longer fit in
+    /// the sequence field, a lease waits for the next tick, so back-to-back leases
+    /// never repeat each other. With a [`TimeSource`] whose time stands still, that
+    /// wait never ends. Calls to [`IdGenerator::gen64`] between leases in the same tick
+    /// use up sequence values as well, but they are not counted before the first.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x64};
+    /// let mut lease = AtomicId::<x64>::lease(256);
+    /// let a = lease.next().unwrap();
+    /// let b = lease.next().unwrap();
+    /// assert_ne!(a, b);
+    /// assert_eq!(lease.remaining(), 254);
+    /// ```
+    pub fn lease(n: usize) -> IdLease {
+        let generator = xgen();
+        let precision = TimestampPrecision::current();
+        let seq_bits = precision.sequence_bits_for(seq_bits64(thread_id_bits())).min(usize::BITS - 1);
+        let mut n = n.clamp(1, 1 << seq_bits.saturating_sub(LEASE_SHARE_BITS));
+        let limit = max_rate64();
+        if limit != 0 {
+            n = n.min(limit as usize);
+        }
+        let (ticks, seqs) = generator.lease_block(precision, n as u64, 1 << seq_bits);
+        let ids: Vec<u64> = generator.gen64_reserved_at(ticks, seqs).collect();
+        IdLease { ids: ids.into_iter(), size: n }
+    }
+}
+
+/// A block of raw 64-bit IDs leased by [`AtomicId::<x64>::lease`](AtomicId::<64>::lease),
+/// handed out one at a time without any atomic operation.
+///
+/// Taking the lease reads the clock once and claims the block's sequence values
+/// with a single `fetch_add`, through [`IdGenerator::reserve64`], waiting for the
+/// next tick first if earlier leases used up this one; the IDs are composed there
+/// and then, so
+/// [`AtomicOption::on_generate`] sees the whole block at lease time. When the block
+/// runs out, the next call takes a new lease of the same size with a fresh
+/// timestamp, so the iterator never ends.
+///
+/// All IDs of a block carry the timestamp of the lease, not of the call that
+/// returns them, so a lease held for long hands out IDs that sort before those
+/// generated since. IDs left over when the lease is dropped are never handed out
+/// anywhere else: that part of the sequence is simply wasted, which costs nothing
+/// but ID space. A lease is `Send`, so it can be taken on one thread and used on
+/// another, but it is not meant to be shared.
+#[derive(Debug)]
+pub struct IdLease {
+    ids: alloc::vec::IntoIter<u64>,
+    size: usize,
+}
+
+impl IdLease {
+    /// The number of IDs left in the current block before the next lease.
+    pub fn remaining(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// The number of IDs in each block.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl Iterator for IdLease {
+    type Item = u64;
+
+    /// The next ID of the block, taking a new lease first if the block is used up.
+    /// Never returns `None`.
+    fn next(&mut self) -> Option<u64> {
+        if self.ids.len() == 0 {
+            *self = AtomicId::<64>::lease(self.size);
+        }
+        self.ids.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+/// A thread-safe counter handing out sequential IDs, for tables that need plain
+/// increasing numbers rather than timestamp-based IDs.
+///
+/// Each sequence counts on its own from its start. [`Sequence::new`] makes one to
+/// keep in a `static` or a struct; [`Sequence::named`] looks one up by name, so
+/// separate tables can share a sequence per table without passing it around. The
+/// `sequential*` methods of [`AtomicId::<x64>`](AtomicId::<64>::sequential) draw
+/// from [`Sequence::global`].
+///
+/// # Example
+/// ```
+/// use atomic_id::Sequence;
+/// static ORDERS: Sequence = Sequence::new(1_000_000);
+/// assert_eq!(ORDERS.next(), 1_000_000);
+/// assert_eq!(ORDERS.reserve(3), 1_000_001..1_000_004);
+/// assert_eq!(ORDERS.peek(), 1_000_004);
+/// assert_eq!(ORDERS.next_base36(), "000000000lflw");
+/// ```
+#[derive(Debug)]
+pub struct Sequence {
+    name: Option<&'static str>,
+    next: AtomicU64,
+}
+
+/// The sequence behind the `sequential*` methods.
+static GLOBAL_SEQUENCE: Sequence = Sequence::new(0);
+
+/// Sequences created by [`Sequence::named`].
+#[cfg(feature = "std")]
+static NAMED_SEQUENCES: std::sync::Mutex<Vec<&'static Sequence>> = std::sync::Mutex::new(Vec::new());
+
+impl Sequence {
+    /// Create a sequence whose first value is `start`.
+    pub const fn new(start: u64) -> Sequence {
+        Sequence { name: None, next: AtomicU64::new(start) }
+    }
+
+    /// The sequence called `name`, created starting at `start` on first use. Later
+    /// calls with the same name return the same sequence and ignore `start`; use
+    /// [`Sequence::set_start`] to move it. Requires the `std` feature.
+    ///
+    /// Named sequences live for the rest of the process.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::Sequence;
+    /// let orders = Sequence::named("orders", 500);
+    /// assert_eq!(orders.next(), 500);
+    /// assert_eq!(Sequence::named("orders", 0).next(), 501);
+    /// assert_eq!(Sequence::named("invoices", 0).next(), 0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn named(name: &str, start: u64) -> &'static Sequence {
+        let mut named = NAMED_SEQUENCES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(sequence) = named.iter().find(|sequence| sequence.name == Some(name)) {
+            return sequence;
+        }
+        let name: &'static str = Box::leak(name.into());
+        let sequence: &'static Sequence = Box::leak(Box::new(Sequence { name: Some(name), next: AtomicU64::new(start) }));
+        named.push(sequence);
+        sequence
+    }
+
+    /// The sequence behind [`AtomicId::<x64>::sequential`](AtomicId::<64>::sequential)
+    /// and the other `sequential*` methods, starting at 0.
+    pub fn global() -> &'static Sequence {
+        &GLOBAL_SEQUENCE
+    }
+
+    /// The name given to [`Sequence::named`], if the sequence has one.
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    /// Take the next value. Wraps to 0 after `u64::MAX`.
+    pub fn next(&self) -> u64 {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Take the next value as a 13-character base36 string, which sorts like the
+    /// values.
+    pub fn next_base36(&self) -> String {
+        self.next_encoded(Encoding::Base36)
+    }
+
+    /// Take the next value in `encoding`, padded to [`Encoding::encoded_len`] of 64
+    /// bits.
+    pub fn next_encoded(&self, encoding: Encoding) -> String {
+        encoding.encode(self.next() as u128, encoding.encoded_len(64).unwrap_or(0))
+    }
+
+    /// The value [`Sequence::next`] would return, without taking it. Another thread
+    /// may take it first.
+    pub fn peek(&self) -> u64 {
+        self.next.load(Ordering::Relaxed)
+    }
+
+    /// Take `n` consecutive values at once, for handing a block to a bulk insert.
+    /// Wraps like [`Sequence::next`].
+    pub fn reserve(&self, n: u64) -> Range<u64> {
+        let start = self.next.fetch_add(n, Ordering::Relaxed);
+        start..start.wrapping_add(n)
+    }
+
+    /// Move the sequence forward so its next value is `start`, e.g. past the
+    /// highest ID copied over in a data migration.
+    ///
+    /// # Errors
+    /// [`IdError::SequenceAhead`] with the next value if the sequence is already
+    /// past `start`, since moving it back would hand out values again.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{IdError, Sequence};
+    /// let sequence = Sequence::new(0);
+    /// sequence.set_start(1_000_000).unwrap();
+    /// assert_eq!(sequence.next(), 1_000_000);
+    /// assert_eq!(sequence.set_start(10), Err(IdError::SequenceAhead(1_000_001)));
+    /// ```
+    pub fn set_start(&self, start: u64) -> Result<(), IdError> {
+        self.next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |next| (next <= start).then_some(start))
+            .map(|_| ())
+            .map_err(IdError::SequenceAhead)
+    }
+}
+
+impl AtomicId<128> {
+    /// Generate a new 128-bit ID, encoded as base36 and left-padded with zeros to
+    /// `width` characters, for aligning IDs in columns.
+    ///
+    /// Widths below 25 are raised to 25, the length of the largest 128-bit value,
+    /// so no ID is ever truncated. IDs of every width decode with
+    /// [`Encoding::decode`], since leading zeros do not change the value.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x128};
+    /// assert_eq!(AtomicId::<x128>::base36_width(28).len(), 28);
+    /// assert_eq!(AtomicId::<x128>::base36_width(4).len(), 25);
+    /// ```
+    pub fn base36_width(width: usize) -> String {
+        encode::base36(xgen().gen128(), width.max(25))
+    }
+    /// Generate a new 128-bit ID, encoded with `encoder` and padded to its
+    /// [`Encoder::width`](encoder::Encoder::width) for 128 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::encoder::Hex;
+    /// use atomic_id::{AtomicId, x128};
+    /// assert_eq!(AtomicId::<x128>::encode_with(&Hex).len(), 32);
+    /// ```
+    pub fn encode_with<E: encoder::Encoder + ?Sized>(encoder: &E) -> String {
+        encoder.encode(xgen().gen128(), encoder.width(128))
+    }
+    /// Generate a new 128-bit ID as two 16-character hexadecimal strings,
+    /// `(high, low)`, for systems that store the halves in separate columns.
+    ///
+    /// `high` is the upper 64 bits of the value from [`IdGenerator::gen128`], the
+    /// half holding the timestamp, node, shard, and thread ID; `low` is the lower 64
+    /// bits, holding the nanoseconds and sequence. `high + low` is the string
+    /// [`AtomicId::<x128>::hex`](AtomicId::<128>::hex) would return for the same ID.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x128};
+    /// let (high, low) = AtomicId::<x128>::hex_halves();
+    /// let id = u128::from_str_radix(&(high.clone() + &low), 16).unwrap();
+    /// assert_eq!(u64::from_str_radix(&high, 16).unwrap(), (id >> 64) as u64);
+    /// ```
+    pub fn hex_halves() -> (String, String) {
+        hex_halves(xgen().gen128())
+    }
+    /// Generate a new 128-bit ID with the timestamp `time` instead of now, encoded as
+    /// a 25-character base36 string. See [`IdGenerator::gen128_at`] for the
+    /// accepted times.
+    ///
+    /// # Errors
+    /// [`IdError::BeforeEpoch`] or [`IdError::TimestampOverflow`] if `time` does not
+    /// fit in the ID's timestamp field.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, AtomicOption, x128};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// let time = UNIX_EPOCH + Duration::from_millis(AtomicOption::get_epoch() + 60_000);
+    /// assert_eq!(AtomicId::<x128>::new_at(time).unwrap().len(), 25);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn new_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen128_at(time).map(|id| encode::base36(id, 25))
+    }
+    /// Like [`AtomicId::<x128>::new_at`](AtomicId::<128>::new_at), with its 22-character base58 encoding.
+    #[cfg(feature = "std")]
+    pub fn base58_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen128_at(time).map(|id| encode::base58(id, 22))
+    }
+    /// Like [`AtomicId::<x128>::new_at`](AtomicId::<128>::new_at), with its 20-character base91 encoding.
+    #[cfg(feature = "std")]
+    pub fn base91_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen128_at(time).map(|id| encode::base91(id, 20))
+    }
+    /// Like [`AtomicId::<x128>::new_at`](AtomicId::<128>::new_at), with its 25-character base36 encoding.
+    #[cfg(feature = "std")]
+    pub fn base36_at(time: SystemTime) -> Result<String, IdError> {
+        Self::new_at(time)
+    }
+    /// Like [`AtomicId::<x128>::new_at`](AtomicId::<128>::new_at), with its 32-character hexadecimal encoding.
+    #[cfg(feature = "std")]
+    pub fn hex_at(time: SystemTime) -> Result<String, IdError> {
+        xgen().gen128_at(time).map(|id| encode::hex(id, 32))
+    }
+
+    /// The fields of 128-bit IDs as [`IdGenerator::gen128`] packs them, with the
+    /// current [`AtomicOption::version_bits`] setting. `thread_rotated` repeats the
+    /// thread ID, rotated left by 3.
+    pub fn layout() -> Layout {
+        let version = if version_bits() { VERSION_BITS } else { 0 };
+        Layout::stacked(128, &[
+            ("timestamp", TS_BITS_128),
+            ("node", 12),
+            ("shard", 8),
+            ("thread", 8),
+            ("reserved", 4 - version),
+            ("version", version),
+            ("nanos", 32),
+            ("sequence", 24),
+            ("thread_rotated", 8),
+        ])
+    }
+
+    /// Total number of distinct 128-bit IDs the layout can produce.
+    ///
+    /// The rotated thread ID in the low part adds no new values and the thread ID
+    /// itself takes 128 values, leaving 119 effective bits (2^119), or 117 while
+    /// [`AtomicOption::version_bits`] fixes two of the reserved bits.
+    pub fn capacity() -> u128 {
+        if version_bits() { 1 << 117 } else { 1 << 119 }
+    }
+
+    /// Probability that `n` 128-bit IDs contain at least one duplicate.
+    /// Requires the `std` feature.
+    ///
+    /// Worst-case estimate for IDs sharing a timestamp: the 24-bit sequence keeps the
+    /// first 2^24 IDs distinct, after which only the 7 bits of hashed thread ID remain.
+    /// Nanoseconds are treated as time, not randomness.
+    #[cfg(feature = "std")]
+    pub fn collision_probability(n: u64) -> f64 {
+        birthday_probability(n, 24, 7)
+    }
+
+    /// Time left until the 32-bit timestamp field wraps to zero, which happens every
+    /// 2^32 ms (about 49.7 days).
+    pub fn time_remaining() -> Duration {
+        Duration::from_millis(ms_until_wrap(xgen_read().timestamp(), TS_BITS_128))
+    }
+
+    /// Generate a new 128-bit ID as 16 bytes in canonical UUID (big-endian) byte order.
+    ///
+    /// The most significant byte comes first, so the timestamp-bearing high part of
+    /// [`IdGenerator::gen128`] leads. Stored in a `uuid` column (e.g. Postgres), the ID
+    /// displays with its timestamp first and sorts by creation time.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x128};
+    /// let bytes = AtomicId::<x128>::uuid_bytes();
+    /// let raw = AtomicId::<x128>::from_uuid_bytes(bytes);
+    /// assert_eq!(raw.to_be_bytes(), bytes);
+    /// ```
+    pub fn uuid_bytes() -> [u8; 16] {
+        xgen().gen128().to_be_bytes()
+    }
+
+    /// Convert bytes produced by [`AtomicId::<x128>::uuid_bytes`] back to the raw 128-bit value.
+    pub fn from_uuid_bytes(bytes: [u8; 16]) -> u128 {
+        u128::from_be_bytes(bytes)
+    }
+
+    /// Encode an existing 128-bit value, such as a legacy UUID, as the 25-character
+    /// base36 string of [`AtomicId::<x128>::new`](AtomicId::<128>::new), without
+    /// generating anything.
+    ///
+    /// Adopted values bypass every guarantee of the generator: they are unique only
+    /// if whatever produced them made them so, they may collide with generated IDs,
+    /// and their "timestamp" and other fields are meaningless. They only share the
+    /// string format, so legacy and new IDs can be stored and parsed the same way.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x128};
+    /// let id = AtomicId::<x128>::from_u128(42);
+    /// assert_eq!(id.len(), 25);
+    /// assert_eq!(Encoding::Base36.decode(&id), Ok(42));
+    /// ```
+    pub fn from_u128(n: u128) -> String {
+        encode::base36(n, 25)
+    }
+
+    /// Encode an existing UUID, given as its 16 bytes in canonical (big-endian) order,
+    /// like [`AtomicId::<x128>::from_u128`](AtomicId::<128>::from_u128). The same
+    /// caveats apply: adopted IDs are not checked for uniqueness in any way.
+    ///
+    /// For the raw value instead of the string, use
+    /// [`AtomicId::<x128>::from_uuid_bytes`](AtomicId::<128>::from_uuid_bytes).
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x128};
+    /// // 67e55044-10b1-426f-9247-bb680e5fe0c8
+    /// let uuid = 0x67e5504410b1426f9247bb680e5fe0c8u128.to_be_bytes();
+    /// assert_eq!(AtomicId::<x128>::adopt_uuid_bytes(uuid), AtomicId::<x128>::from_u128(u128::from_be_bytes(uuid)));
+    /// ```
+    pub fn adopt_uuid_bytes(bytes: [u8; 16]) -> String {
+        Self::from_u128(u128::from_be_bytes(bytes))
+    }
+
+    /// Derive a deterministic 128-bit ID from `data` in `encoding`, padded to
+    /// [`Encoding::encoded_len`] of 128 bits.
+    ///
+    /// The same input always yields the same ID. See [`IdGenerator::derive128`] for the
+    /// hash definition and the raw `u128` value.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x128};
+    /// let id = AtomicId::<x128>::derive(b"order-1234", Encoding::Base36);
+    /// assert_eq!(id, AtomicId::<x128>::derive(b"order-1234", Encoding::Base36));
+    /// assert_eq!(id.len(), 25);
+    /// ```
+    pub fn derive(data: &[u8], encoding: Encoding) -> String {
+        Self::derive_in("", data, encoding)
+    }
+
+    /// Derive a deterministic 128-bit ID from `data` within `namespace`, in `encoding`.
+    pub fn derive_in(namespace: &str, data: &[u8], encoding: Encoding) -> String {
+        let id = IdGenerator::derive128(namespace, data);
+        encoding.encode(id, encoding.encoded_len(128).unwrap_or(0))
+    }
+}
+
+impl AtomicId<256> {
+    /// Generate a new 256-bit ID in the legacy word format: each 64-bit part encoded
+    /// separately as 13 base36 characters, and the four joined, for 52 characters.
+    ///
+    /// This was the base36 form of `new()`, `base36()`, and `Id256`'s `Display`
+    /// before they moved to the 50-character canonical form; use it only where IDs
+    /// must match ones already stored. It is not the base36 number of the 256-bit
+    /// value, so big-integer decoders misread it; convert with
+    /// [`AtomicId::<x256>::words_to_canonical`](AtomicId::<256>::words_to_canonical).
+    /// Decode with [`AtomicId::<x256>::from_words`](AtomicId::<256>::from_words).
+    pub fn base36_words() -> String {
+        Self::to_words(xgen().gen256(), Encoding::Base36)
+    }
+
+    /// Generate a new 256-bit ID in the legacy word format, with 11 base58 characters
+    /// per part, for 44 characters. See [`AtomicId::<x256>::base36_words`](AtomicId::<256>::base36_words).
+    pub fn base58_words() -> String {
+        Self::to_words(xgen().gen256(), Encoding::Base58)
+    }
+
+    /// Generate a new 256-bit ID in the legacy word format, with 10 base91 characters
+    /// per part, for 40 characters. See [`AtomicId::<x256>::base36_words`](AtomicId::<256>::base36_words).
+    pub fn base91_words() -> String {
+        Self::to_words(xgen().gen256(), Encoding::Base91)
+    }
+
+    /// Encode raw 256-bit parts, most significant first, as one number in
+    /// `encoding`, left-padded to [`Encoding::encoded_len`] of 256 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x256};
+    /// let id = AtomicId::<x256>::to_canonical([0, 0, 0, 57], Encoding::Base58);
+    /// assert_eq!(id, format!("{}z", "1".repeat(43)));
+    /// ```
+    pub fn to_canonical(parts: [u64; 4], encoding: Encoding) -> String {
+        encode::to_base_256(parts, encoding.alphabet(), Self::canonical_len(encoding))
+    }
+
+    /// Decode the output of [`AtomicId::<x256>::to_canonical`](AtomicId::<256>::to_canonical)
+    /// or of the canonical generators such as [`AtomicId::<x256>::base58`](AtomicId::<256>::base58)
+    /// into the four raw 64-bit parts, most significant first.
+    ///
+    /// # Errors
+    /// - [`IdError::InvalidLength`] unless `s` has the length `to_canonical` pads to.
+    /// - [`IdError::InvalidCharacter`] for a character outside the encoding.
+    /// - [`IdError::Overflow`] if the value does not fit in 256 bits.
+    pub fn from_canonical(s: &str, encoding: Encoding) -> Result<[u64; 4], IdError> {
+        if s.len() != Self::canonical_len(encoding) {
+            return Err(IdError::InvalidLength(s.len()));
+        }
+        encode::from_base_256(s, encoding.alphabet())
+    }
+
+    /// Encode raw 256-bit parts in the legacy word format: each part separately,
+    /// padded to [`Encoding::encoded_len`] of 64 bits, and the four joined.
+    pub fn to_words(parts: [u64; 4], encoding: Encoding) -> String {
+        let width = Self::word_len(encoding);
+        let mut s = String::with_capacity(width * 4);
+        for part in parts {
+            let mut word = encoding.encode(part as u128, width);
+            s.push_str(&word);
+            encode::wipe(&mut word);
+        }
+        s
+    }
+
+    /// Decode the output of [`AtomicId::<x256>::to_words`](AtomicId::<256>::to_words)
+    /// into the four raw 64-bit parts.
+    ///
+    /// # Errors
+    /// - [`IdError::InvalidLength`] unless `s` is four words long.
+    /// - [`IdError::InvalidCharacter`] for a character outside the encoding.
+    /// - [`IdError::Overflow`] if a word does not fit in 64 bits.
+    pub fn from_words(s: &str, encoding: Encoding) -> Result<[u64; 4], IdError> {
+        let width = Self::word_len(encoding);
+        if s.len() != width * 4 {
+            return Err(IdError::InvalidLength(s.len()));
+        }
+        // A non-ASCII character could straddle a word boundary.
+        if let Some(c) = s.chars().find(|c| !c.is_ascii()) {
+            return Err(IdError::InvalidCharacter(c));
+        }
+        let mut parts = [0u64; 4];
+        for (i, part) in parts.iter_mut().enumerate() {
+            let raw = encoding.decode(&s[i * width..(i + 1) * width])?;
+            *part = u64::try_from(raw).map_err(|_| IdError::Overflow)?;
+        }
+        Ok(parts)
+    }
+
+    /// Convert an ID in the legacy word format, such as the output of
+    /// [`AtomicId::<x256>::base36_words`](AtomicId::<256>::base36_words), to the
+    /// canonical form of the same value.
+    ///
+    /// # Errors
+    /// The errors of [`AtomicId::<x256>::from_words`](AtomicId::<256>::from_words).
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, x256};
+    /// let legacy = AtomicId::<x256>::base36_words();
+    /// let canonical = AtomicId::<x256>::words_to_canonical(&legacy, Encoding::Base36).unwrap();
+    /// assert_eq!(canonical.len(), 50);
+    /// assert_eq!(AtomicId::<x256>::canonical_to_words(&canonical, Encoding::Base36), Ok(legacy));
+    /// ```
+    pub fn words_to_canonical(s: &str, encoding: Encoding) -> Result<String, IdError> {
+        Self::from_words(s, encoding).map(|parts| Self::to_canonical(parts, encoding))
+    }
+
+    /// Convert a canonical ID to the legacy word format of the same value.
+    ///
+    /// # Errors
+    /// The errors of [`AtomicId::<x256>::from_canonical`](AtomicId::<256>::from_canonical).
+    pub fn canonical_to_words(s: &str, encoding: Encoding) -> Result<String, IdError> {
+        Self::from_canonical(s, encoding).map(|parts| Self::to_words(parts, encoding))
+    }
+
+    /// Length of a canonical 256-bit ID in `encoding`: [`Encoding::encoded_len`] of
+    /// 256 bits.
+    fn canonical_len(encoding: Encoding) -> usize {
+        encoding.encoded_len(256).unwrap_or(64)
+    }
+
+    /// Length of one 64-bit word of the legacy format in `encoding`.
+    fn word_len(encoding: Encoding) -> usize {
+        encoding.encoded_len(64).unwrap_or(16)
+    }
+
+    /// Generate a new 256-bit ID, encoded as one variable-length base58 number of the
+    /// whole 256-bit value, with no per-part padding.
+    ///
+    /// Leading zero bits cost no characters, so the result is at most 44 characters
+    /// and shorter whenever the high part is small, which helps in QR codes and other
+    /// size-limited places. Because the length varies, these strings do not sort in ID
+    /// order, and no fixed width can be assumed; use [`AtomicId::<x256>::base58`](AtomicId::<256>::base58)
+    /// where either matters. Decode with [`AtomicId::<x256>::from_compact`](AtomicId::<256>::from_compact).
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, x256};
+    /// let id = AtomicId::<x256>::compact();
+    /// assert!(id.len() <= 44);
+    /// assert!(AtomicId::<x256>::from_compact(&id).is_ok());
+    /// ```
+    pub fn compact() -> String {
+        encode::to_base_256(xgen().gen256(), encode::BASE58, 0)
+    }
+
+    /// Decode the output of [`AtomicId::<x256>::compact`](AtomicId::<256>::compact)
+    /// into the four raw 64-bit parts, most significant first.
+    ///
+    /// # Errors
+    /// - [`IdError::Empty`] for an empty string.
+    /// - [`IdError::InvalidCharacter`] for a character outside the base58 alphabet.
+    /// - [`IdError::Overflow`] if the value does not fit in 256 bits.
+    pub fn from_compact(s: &str) -> Result<[u64; 4], IdError> {
+        encode::from_base_256(s, encode::BASE58)
+    }
+
+    /// Generate a batch of 256-bit IDs as compact base58 strings.
+    pub fn compact_batch(n: usize) -> Vec<String> {
+        (0..n).map(|_| Self::compact()).collect()
+    }
+
+    /// Total number of distinct 256-bit IDs the layout can produce.
+    ///
+    /// Many of the 256 bits repeat information from other parts. The independent
+    /// inputs are 28 timestamp bits, 12 node bits, 8 shard bits, 7 effective thread
+    /// bits, 40 sequence bits, and 32 mixed nanosecond bits, for 2^127 in total.
+    pub fn capacity() -> u128 {
+        1 << 127
+    }
+
+    /// Probability that `n` 256-bit IDs contain at least one duplicate.
+    /// Requires the `std` feature.
+    ///
+    /// Worst-case estimate for IDs sharing a timestamp: the sequence contributes 40
+    /// distinct bits across the parts, after which only the 7 bits of hashed thread ID remain.
+    #[cfg(feature = "std")]
+    pub fn collision_probability(n: u64) -> f64 {
+        birthday_probability(n, 40, 7)
+    }
+}
+
+/// Build a trace ID from a 128-bit ID: the high half as is, the low half mixed.
+fn trace_id_from(raw: u128) -> [u8; 16] {
+    let high = (raw >> 64) as u64;
+    let low = mix64(raw as u64);
+    (((high as u128) << 64) | low as u128).to_be_bytes()
+}
+
+/// Build a span ID from a 64-bit ID by mixing it.
+fn span_id_from(raw: u64) -> [u8; 8] {
+    mix64(raw).to_be_bytes()
+}
+
+/// Generate a [W3C trace context](https://www.w3.org/TR/trace-context/) trace ID:
+/// 16 bytes that are never all zero.
+///
+/// The first 8 bytes are the high half of a 128-bit ID (timestamp, node, shard,
+/// and thread), which is never zero because the thread ID field never is. The last
+/// 8 bytes are the low half (nanoseconds and sequence) scrambled with a bijective
+/// mix, so they vary like the random bytes the spec recommends there, and distinct
+/// IDs stay distinct. The mix is not cryptographic: trace IDs are unique, not
+/// unguessable.
+///
+/// # Example
+/// ```
+/// assert_ne!(atomic_id::trace_id(), [0; 16]);
+/// ```
+pub fn trace_id() -> [u8; 16] {
+    trace_id_from(xgen().gen128())
+}
+
+/// Generate a W3C trace context span ID: 8 bytes that are never all zero.
+///
+/// This is a 64-bit ID scrambled with the same bijective mix as [`trace_id`].
+/// 64-bit IDs are never zero and the mix only maps zero to zero, so span IDs never
+/// are either.
+///
+/// # Example
+/// ```
+/// assert_ne!(atomic_id::span_id(), [0; 8]);
+/// ```
+pub fn span_id() -> [u8; 8] {
+    span_id_from(xgen().gen64())
+}
+
+/// Generate a trace ID ([`trace_id`]) as 32 lowercase hex characters,
+/// as it appears in a `traceparent` header.
+pub fn trace_id_hex() -> String {
+    encode::hex(u128::from_be_bytes(trace_id()), 32)
+}
+
+/// Generate a span ID ([`span_id`]) as 16 lowercase hex characters, as
+/// it appears in a `traceparent` header.
+pub fn span_id_hex() -> String {
+    encode::hex(u64::from_be_bytes(span_id()) as u128, 16)
+}
+
+/// An ID width, for choosing the width at runtime, e.g. from a configuration file,
+/// with [`AtomicId::generate`].
+///
+/// Every width is defined in every build, so code that names them compiles whatever
+/// the features; using a width that is compiled out is an error at runtime instead,
+/// [`IdError::FeatureDisabled`]. [`Width::is_enabled`] tells which are available.
+///
+/// # Example
+/// ```
+/// use atomic_id::{AtomicId, Encoding, IdError, Width};
+/// let width = Width::try_from(128).unwrap();
+/// assert_eq!(AtomicId::generate(width, Encoding::Hex).unwrap().len(), 32);
+/// assert_eq!(width.to_string(), "128");
+/// assert_eq!(Width::try_from(48), Err(IdError::UnsupportedWidth(48)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum Width {
+    /// 24-bit IDs, `AtomicId<x24>`. Requires the `short` feature.
+    X24 = 24,
+    /// 32-bit IDs, `AtomicId<x32>`. Requires the `short` feature.
+    X32 = 32,
+    /// 64-bit IDs, [`AtomicId<x64>`](AtomicId).
+    X64 = 64,
+    /// 128-bit IDs, [`AtomicId<x128>`](AtomicId).
+    X128 = 128,
+    /// 256-bit IDs, [`AtomicId<x256>`](AtomicId).
+    X256 = 256,
+}
+
+impl Width {
+    /// Every width, narrowest first, including those compiled out.
+    pub const ALL: [Width; 5] = [Width::X24, Width::X32, Width::X64, Width::X128, Width::X256];
+
+    /// The number of bits.
+    pub const fn bits(self) -> usize {
+        self as usize
+    }
+
+    /// Whether IDs of this width can be generated in this build: false for 24 and 32
+    /// bits without the `short` feature.
+    pub const fn is_enabled(self) -> bool {
+        match self {
+            Width::X24 | Width::X32 => cfg!(feature = "short"),
+            Width::X64 | Width::X128 | Width::X256 => true,
+        }
+    }
+}
+
+impl TryFrom<usize> for Width {
+    type Error = IdError;
+
+    /// The width of `bits` bits.
+    ///
+    /// # Errors
+    /// [`IdError::UnsupportedWidth`] if `bits` is not 24, 32, 64, 128, or 256. Widths
+    /// that are compiled out convert successfully.
+    fn try_from(bits: usize) -> Result<Width, IdError> {
+        Width::ALL.into_iter().find(|width| width.bits() == bits).ok_or(IdError::UnsupportedWidth(bits))
+    }
+}
+
+impl core::fmt::Display for Width {
+    /// The number of bits, e.g. `64`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.bits())
+    }
+}
+
+/// A raw ID of any width, keeping its width alongside the value, for code that
+/// stores IDs of several widths together. Returned by [`AtomicId::generate_any`].
+///
+/// `Display` writes the base36 form of [`AnyId::encode`], whose length differs for
+/// each width; [`AnyId::try_from`] with the width reads it back.
+///
+/// # Example
+/// ```
+/// use atomic_id::{AnyId, AtomicId, Encoding, Width};
+/// let id = AtomicId::generate_any(Width::X128).unwrap();
+/// assert_eq!(id.width(), 128);
+/// assert_eq!(id.encode(Encoding::Hex).len(), 32);
+/// assert_eq!(AnyId::try_from((id.to_string().as_str(), 128)), Ok(id));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnyId {
+    /// A 24-bit ID, in the low bits.
+    X24(u32),
+    /// A 32-bit ID.
+    X32(u32),
+    /// A 64-bit ID.
+    X64(u64),
+    /// A 128-bit ID.
+    X128(u128),
+    /// A 256-bit ID as four 64-bit parts, most significant first.
+    X256([u64; 4]),
+}
+
+impl AnyId {
+    /// The width of the ID in bits.
+    pub const fn width(&self) -> usize {
+        match self {
+            AnyId::X24(_) => 24,
+            AnyId::X32(_) => 32,
+            AnyId::X64(_) => 64,
+            AnyId::X128(_) => 128,
+            AnyId::X256(_) => 256,
+        }
+    }
+
+    /// Encode the ID as [`AtomicId::with_encoding`] does for its width: left-padded
+    /// to [`Encoding::encoded_len`], and for 256 bits the canonical single-number
+    /// form of [`AtomicId::<x256>::to_canonical`](AtomicId::<256>::to_canonical).
+    pub fn encode(&self, encoding: Encoding) -> String {
+        let len = encoding.encoded_len(self.width()).unwrap_or(0);
+        match *self {
+            AnyId::X24(raw) | AnyId::X32(raw) => encoding.encode(raw as u128, len),
+            AnyId::X64(raw) => encoding.encode(raw as u128, len),
+            AnyId::X128(raw) => encoding.encode(raw, len),
+            AnyId::X256(parts) => AtomicId::<256>::encode_raw(parts, encoding),
+        }
+    }
+
+    /// Parse the output of [`AnyId::encode`] for an ID of `width`, strictly: the
+    /// string must be exactly [`Encoding::encoded_len`] long, as [`Encoding::decode`]
+    /// accepts it.
+    ///
+    /// # Errors
+    /// - [`IdError::InvalidLength`] unless the string has the encoded length.
+    /// - [`IdError::InvalidCharacter`] for a character outside the encoding.
+    /// - [`IdError::Overflow`] if the value does not fit in the width.
+    pub fn parse(s: &str, width: Width, encoding: Encoding) -> Result<AnyId, IdError> {
+        let bits = width.bits();
+        if s.len() != encoding.encoded_len(bits)? {
+            return Err(IdError::InvalidLength(s.len()));
+        }
+        if width == Width::X256 {
+            return AtomicId::<256>::from_canonical(s, encoding).map(AnyId::X256);
+        }
+        let raw = encoding.decode(s)?;
+        if bits < 128 && raw >> bits != 0 {
+            return Err(IdError::Overflow);
+        }
+        Ok(match width {
+            Width::X24 => AnyId::X24(raw as u32),
+            Width::X32 => AnyId::X32(raw as u32),
+            Width::X64 => AnyId::X64(raw as u64),
+            _ => AnyId::X128(raw),
+        })
+    }
+}
+
+impl core::fmt::Display for AnyId {
+    /// The base36 form, such as 13 characters for 64 bits.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.encode(Encoding::Base36))
+    }
+}
+
+impl TryFrom<(&str, usize)> for AnyId {
+    type Error = IdError;
+
+    /// Parse the base36 `Display` form of an ID of the given width in bits.
+    ///
+    /// # Errors
+    /// - [`IdError::UnsupportedWidth`] if the width is not 24, 32, 64, 128, or 256.
+    /// - [`IdError::InvalidLength`] unless the string is [`Encoding::encoded_len`]
+    ///   of that width long.
+    /// - [`IdError::InvalidCharacter`] for a character outside base36.
+    /// - [`IdError::Overflow`] if the value does not fit in the width.
+    fn try_from((s, bits): (&str, usize)) -> Result<AnyId, IdError> {
+        AnyId::parse(s, Width::try_from(bits)?, Encoding::Base36)
+    }
+}
+
+/// Runtime-dispatched generation, for when the width is only known at runtime.
+///
+/// `AtomicId<0>` is not a real width; it only hosts [`AtomicId::generate`] and
+/// [`AtomicId::generate_any`], which take the width as a [`Width`], so that they can
+/// be called without a turbofish.
+impl AtomicId<0> {
+    /// Generate an ID of the given [`Width`] and encoding, chosen at runtime.
+    ///
+    /// The output is identical to calling the matching method on `AtomicId<BITS>`,
+    /// e.g. `generate(Width::X64, Encoding::Base58)` is `AtomicId::<x64>::base58()`.
+    /// For a width given as a number of bits, convert it with [`Width::try_from`].
+    ///
+    /// # Errors
+    /// [`IdError::FeatureDisabled`] for [`Width::X24`] and [`Width::X32`] without the
+    /// `short` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, Encoding, IdError, Width};
+    /// let id = AtomicId::generate(Width::X64, Encoding::Base58).unwrap();
+    /// assert_eq!(id.len(), 11);
+    /// assert_eq!(Width::try_from(48), Err(IdError::UnsupportedWidth(48)));
+    /// ```
+    pub fn generate(width: Width, encoding: Encoding) -> Result<String, IdError> {
+        let id = match width {
+            #[cfg(feature = "short")]
+            Width::X24 => AtomicId::<24>::with_encoding(encoding),
+            #[cfg(feature = "short")]
+            Width::X32 => AtomicId::<32>::with_encoding(encoding),
+            #[cfg(not(feature = "short"))]
+            Width::X24 | Width::X32 => return Err(IdError::FeatureDisabled("short")),
+            Width::X64 => AtomicId::<64>::with_encoding(encoding),
+            Width::X128 => AtomicId::<128>::with_encoding(encoding),
+            Width::X256 => AtomicId::<256>::with_encoding(encoding),
+        };
+        Ok(id)
+    }
+
+    /// Generate a raw ID of the given [`Width`] as an [`AnyId`], which keeps the
+    /// width with the value.
+    ///
+    /// # Errors
+    /// [`IdError::FeatureDisabled`] for [`Width::X24`] and [`Width::X32`] without the
+    /// `short` feature.
+    pub fn generate_any(width: Width) -> Result<AnyId, IdError> {
+        let generator = xgen();
+        let id = match width {
+            #[cfg(feature = "short")]
+            Width::X24 => AnyId::X24(generator.gen24()),
+            #[cfg(feature = "short")]
+            Width::X32 => AnyId::X32(generator.gen32()),
+            #[cfg(not(feature = "short"))]
+            Width::X24 | Width::X32 => return Err(IdError::FeatureDisabled("short")),
+            Width::X64 => AnyId::X64(generator.gen64()),
+            Width::X128 => AnyId::X128(generator.gen128()),
+            Width::X256 => AnyId::X256(generator.gen256()),
+        };
+        Ok(id)
+    }
+
+}
+
+/// Anomalous events reported to the hook installed with [`AtomicOption::on_event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdEvent {
+    /// The wall clock moved backwards by `delta_ms` milliseconds.
+    ClockRollback {
+        /// How far the clock moved back, in milliseconds.
+        delta_ms: u64,
+    },
+    /// The sequence counter for `width`-bit IDs ran out and started repeating.
+    ///
+    /// For 24 and 32-bit IDs this fires every time the counter wraps. For 64 and
+    /// 128-bit IDs, and time-ordered 32-bit IDs, it fires once a single tick has
+    /// used every value of the sequence field, at the first ID that repeats one.
+    SequenceExhausted {
+        /// The bit width whose sequence was exhausted.
+        width: usize,
+    },
+    /// The global epoch was changed from `old` to `new` (milliseconds since UNIX_EPOCH).
+    EpochChanged {
+        /// The previous epoch.
+        old: u64,
+        /// The new epoch.
+        new: u64,
+    },
+    /// A deprecated [`AtomicOption::epoch`] or [`AtomicOption::reset_epoch`] call was
+    /// ignored because the epoch is frozen (see [`AtomicOption::try_epoch`]).
+    EpochChangeRejected {
+        /// The epoch that stays in effect.
+        current: u64,
+        /// The epoch that was requested.
+        requested: u64,
+    },
+    /// The timestamp field of `width`-bit IDs is within the
+    /// [`AtomicOption::wrap_threshold`] of wrapping. Reported once per wrap period.
+    TimestampNearWrap {
+        /// The bit width whose timestamp is about to wrap.
+        width: usize,
+        /// Milliseconds left until the wrap.
+        remaining_ms: u64,
+    },
+    /// Writing the sequence watermarks set up with [`AtomicOption::persist_sequence`]
+    /// failed. Reported from the thread that writes them, which retries the write,
+    /// and generation waits once the reserve already written is used up.
+    SequencePersistFailed,
+    /// A `width`-bit ID was issued twice within the window of recent IDs kept by
+    /// `AtomicOption::debug_duplicate_check` (`debug_checks` feature).
+    DuplicateId {
+        /// The bit width of the ID.
+        width: usize,
+        /// The repeated ID.
+        id: u128,
+    },
+    /// The global generator was first used before [`AtomicOption::init_global`], which
+    /// fixed it to the default `node_id` and `shard_id`. Only reported in builds with
+    /// debug assertions.
+    ImplicitInit {
+        /// Node ID of the default generator.
+        node_id: u16,
+        /// Shard ID of the default generator.
+        shard_id: u8,
+    },
+}
+
+impl core::fmt::Display for IdEvent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IdEvent::ClockRollback { delta_ms } => {
+                write!(f, "clock moved backwards by {} ms", delta_ms)
+            }
+            IdEvent::SequenceExhausted { width } => {
+                write!(f, "{}-bit sequence exhausted, IDs may repeat", width)
+            }
+            IdEvent::EpochChanged { old, new } => {
+                write!(f, "epoch changed from {} to {}", old, new)
+            }
+            IdEvent::EpochChangeRejected { current, requested } => write!(
+                f,
+                "epoch change from {} to {} ignored: the epoch is frozen",
+                current, requested
+            ),
+            IdEvent::TimestampNearWrap { width, remaining_ms } => {
+                write!(f, "{}-bit timestamp wraps in {} ms", width, remaining_ms)
+            }
+            IdEvent::SequencePersistFailed => write!(f, "failed to persist sequence watermarks"),
+            IdEvent::DuplicateId { width, id } => write!(f, "{}-bit ID {:#x} was issued twice", width, id),
+            IdEvent::ImplicitInit { node_id, shard_id } => write!(
+                f,
+                "global generator initialized implicitly with node_id={}, shard_id={}; call AtomicOption::init_global first",
+                node_id, shard_id
+            ),
+        }
+    }
+}
+
+/// Forward an [`IdEvent`] to the `log` crate.
+///
+/// Epoch changes are logged at `info` level, everything else (clock rollbacks,
+/// sequence exhaustion, rejected epoch changes) at `warn`. Install it with `AtomicOption::on_event(atomic_id::log_event)`.
+/// Requires the `log` feature.
+#[cfg(feature = "log")]
+pub fn log_event(event: IdEvent) {
+    match event {
+        IdEvent::EpochChanged { .. } => log::info!(target: "atomic_id", "{}", event),
+        _ => log::warn!(target: "atomic_id", "{}", event),
+    }
+}
+
+/// The outcome of [`AtomicId::<x64>::self_test`](AtomicId::<64>::self_test).
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// IDs generated.
+    pub generated: usize,
+    /// Distinct IDs among them.
+    pub unique: usize,
+    /// IDs that repeated an earlier one: `generated - unique`.
+    pub duplicates: usize,
+    /// Time spent generating, from spawning the threads to joining them.
+    pub duration: Duration,
+}
+
+/// A snapshot of generation statistics, returned by [`AtomicOption::stats`].
+///
+/// All counters are read with `Relaxed` ordering while other threads may still be
+/// generating, so the fields are not a consistent point-in-time view of each other.
+/// They are intended for capacity planning and monitoring, not for exact accounting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IdStats {
+    /// 24-bit IDs generated since the last reset.
+    pub generated_24: u64,
+    /// 32-bit IDs generated since the last reset.
+    pub generated_32: u64,
+    /// 64-bit IDs generated since the last reset.
+    pub generated_64: u64,
+    /// 128-bit IDs generated since the last reset.
+    pub generated_128: u64,
+    /// 256-bit IDs generated since the last reset.
+    pub generated_256: u64,
+    /// The most recent wall-clock time used for a timestamp, in milliseconds since the
+    /// UNIX epoch, or 0 if no timestamp-based ID has been generated since the last reset.
+    pub last_timestamp_ms: u64,
+    /// How many times the wall clock was observed moving backwards.
+    ///
+    /// Threads that read the clock concurrently at a millisecond boundary can
+    /// occasionally be counted here as well, so treat small values as noise.
+    pub clock_rollbacks: u64,
+}
+
+/// Clock callbacks used in place of the system clock.
+///
+/// Both callbacks must report wall-clock time since the UNIX epoch (or on any timeline
+/// consistent with the configured epoch) and should never go backwards. They are
+/// called on every timestamp-based ID, so keep them cheap. Install one with
+/// [`AtomicOption::time_source`]; it is required without the `std` feature.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeSource {
+    /// Current time in milliseconds.
+    pub now_ms: fn() -> u64,
+    /// Current time in nanoseconds. Only used as entropy, and for the microseconds of
+    /// [`TimestampPrecision::Micros`] timestamps, so it may otherwise be coarse.
+    pub now_ns: fn() -> u64,
+}
+
+/// Provides methods for configuring global settings for `atomic-id`.
+///
+/// Use this struct to manage the global epoch for timestamp-based ID generation.
+pub struct AtomicOption;
+
+impl AtomicOption {
+    /// Set the global custom epoch for timestamp-based IDs.
+    ///
+    /// The epoch is the point in time from which the timestamp portion of an ID is measured.
+    /// Setting a more recent epoch can extend the lifespan of the generator.
+    ///
+    /// Set the epoch once at startup, before generating any ID. Once a timestamp-based
+    /// ID (64, 128, or 256-bit, or [`IdGenerator::gen32_timed`]) has been generated,
+    /// the epoch is frozen: changing it would shift the meaning of every timestamp
+    /// already issued and break sorting between old and new IDs.
+    ///
+    /// # Arguments
+    /// * `ms` - The epoch timestamp in milliseconds since the UNIX epoch.
+    ///
+    /// # Errors
+    /// [`IdError::EpochFrozen`] if an ID has already been generated, or always with
+    /// the `const_epoch` feature, which fixes the epoch at build time.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, AtomicOption, IdError, x64};
+    /// # #[cfg(not(feature = "const_epoch"))]
+    /// # {
+    /// // Set the epoch to January 1, 2024
+    /// AtomicOption::try_epoch(1704067200000).unwrap();
+    /// let id = AtomicId::<x64>::new();
+    /// assert_eq!(AtomicOption::try_epoch(0), Err(IdError::EpochFrozen));
+    /// # }
+    /// ```
+    pub fn try_epoch(ms: u64) -> Result<(), IdError> {
+        if cfg!(feature = "const_epoch") {
+            return Err(IdError::EpochFrozen);
+        }
+        let ms = ms & !EPOCH_FROZEN;
+        let old = CUSTOM_EPOCH
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |word| (word & EPOCH_FROZEN == 0).then_some(ms))
+            .map_err(|_| IdError::EpochFrozen)?;
+        if old != ms {
+            emit(IdEvent::EpochChanged { old, new: ms });
+        }
+        Ok(())
+    }
+
+    /// Set the global custom epoch, even after IDs have been generated.
+    ///
+    /// Only use this when every ID issued so far is discarded or kept apart from the
+    /// new ones, e.g. between independent runs of a benchmark: timestamps before and
+    /// after the change are measured from different epochs and do not sort together.
+    /// Prefer [`AtomicOption::try_epoch`].
+    ///
+    /// With the `const_epoch` feature the epoch is fixed at build time and this is a no-op.
+    #[cfg_attr(
+        feature = "const_epoch",
+        deprecated(note = "the epoch is fixed at build time by the `const_epoch` feature; this call does nothing")
+    )]
+    pub fn force_epoch_unchecked(ms: u64) {
+        #[cfg(not(feature = "const_epoch"))]
+        {
+            let ms = ms & !EPOCH_FROZEN;
+            let old = CUSTOM_EPOCH
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |word| Some(word & EPOCH_FROZEN | ms))
+                .unwrap_or_else(|word| word)
+                & !EPOCH_FROZEN;
+            if old != ms {
+                emit(IdEvent::EpochChanged { old, new: ms });
+            }
+        }
+        #[cfg(feature = "const_epoch")]
+        let _ = ms;
+    }
+
+    /// Set the global custom epoch, ignoring the call if the epoch is frozen.
+    ///
+    /// Like [`AtomicOption::try_epoch`], but an ignored call is only reported through
+    /// the [`AtomicOption::on_event`] hook, as [`IdEvent::EpochChangeRejected`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::AtomicOption;
+    /// // Set the epoch to January 1, 2024
+    /// # #[allow(deprecated)]
+    /// AtomicOption::epoch(1704067200000);
+    /// ```
+    #[deprecated(note = "use `AtomicOption::try_epoch`, which reports a frozen epoch as an error")]
+    pub fn epoch(ms: u64) {
+        if Self::try_epoch(ms).is_err() {
+            emit(IdEvent::EpochChangeRejected {
+                current: current_epoch(),
+                requested: ms,
+            });
+        }
+    }
+
+    /// Set the global custom epoch from a [`SystemTime`]. Requires the `std` feature.
+    ///
+    /// Sub-millisecond precision is truncated. See [`AtomicOption::try_epoch`].
+    ///
+    /// # Errors
+    /// - [`EpochParseError::BeforeUnixEpoch`] if `t` is before 1970.
+    /// - [`EpochParseError::InFuture`] if `t` is later than the current time.
+    /// - [`EpochParseError::Frozen`] if the epoch can no longer change.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::AtomicOption;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// // January 1, 2024
+    /// # #[cfg(not(feature = "const_epoch"))]
+    /// AtomicOption::epoch_time(UNIX_EPOCH + Duration::from_secs(1704067200)).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn epoch_time(t: SystemTime) -> Result<(), EpochParseError> {
+        let since = t
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| EpochParseError::BeforeUnixEpoch)?;
+        Self::set_epoch_checked(since.as_millis() as u64)
+    }
+
+    /// Set the global custom epoch from an RFC 3339 timestamp such as
+    /// `2024-01-01T00:00:00Z`.
+    ///
+    /// The parser is built in and accepts a date, a time with optional fractional
+    /// seconds (truncated to milliseconds), and a `Z` or `±HH:MM` offset. See
+    /// [`AtomicOption::try_epoch`].
+    ///
+    /// # Errors
+    /// - [`EpochParseError::Syntax`] if the string is not in that form.
+    /// - [`EpochParseError::FieldOutOfRange`] for an impossible date or time.
+    /// - [`EpochParseError::BeforeUnixEpoch`] if the timestamp is before 1970.
+    /// - [`EpochParseError::InFuture`] if the timestamp is later than the current time.
+    /// - [`EpochParseError::Frozen`] if the epoch can no longer change.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, EpochParseError};
+    /// # #[cfg(not(feature = "const_epoch"))]
+    /// # {
+    /// AtomicOption::epoch_rfc3339("2024-01-01T00:00:00Z").unwrap();
+    /// assert_eq!(AtomicOption::get_epoch(), 1704067200000);
+    /// # }
+    /// assert_eq!(AtomicOption::epoch_rfc3339("2024-02-30T00:00:00Z"), Err(EpochParseError::FieldOutOfRange));
+    /// ```
+    pub fn epoch_rfc3339(s: &str) -> Result<(), EpochParseError> {
+        Self::set_epoch_checked(parse_rfc3339(s)?)
+    }
+
+    /// Set the epoch after checking that it is not in the future.
+    fn set_epoch_checked(ms: u64) -> Result<(), EpochParseError> {
+        if ms > now_ms() {
+            return Err(EpochParseError::InFuture);
+        }
+        Self::try_epoch(ms).map_err(|_| EpochParseError::Frozen)
+    }
+
+    /// Get the current global epoch value.
+    ///
+    /// # Returns
+    /// The current epoch in milliseconds since the UNIX epoch.
+    pub fn get_epoch() -> u64 {
+        current_epoch()
+    }
+
+    /// Get the current global epoch as a [`SystemTime`]. Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn get_epoch_time() -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(current_epoch())
+    }
+
+    /// Reset the global epoch to its default value (`2022-05-01 00:00:00 UTC`).
+    ///
+    /// Like the deprecated [`AtomicOption::epoch`], this is ignored and reported as
+    /// [`IdEvent::EpochChangeRejected`] once the epoch is frozen. With the
+    /// `const_epoch` feature the epoch is fixed at build time and this is a no-op.
+    #[cfg_attr(
+        feature = "const_epoch",
+        deprecated(note = "the epoch is fixed at build time by the `const_epoch` feature; this call does nothing")
+    )]
+    pub fn reset_epoch() {
+        #[allow(deprecated)]
+        Self::epoch(DEFAULT_EPOCH);
+    }
+
+    /// Install a hook that is called on anomalous events, replacing any previous hook.
+    ///
+    /// The hook may be called from any thread that generates IDs or changes settings,
+    /// so it must be cheap and must not block. Events raised from inside the hook
+    /// itself (for example by generating an ID) are dropped rather than delivered
+    /// re-entrantly (this needs the `std` feature). With the `log` feature, `log_event` is a ready-made hook.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdEvent};
+    ///
+    /// fn report(event: IdEvent) {
+    ///     eprintln!("atomic-id: {}", event);
+    /// }
+    /// AtomicOption::on_event(report);
+    /// ```
+    pub fn on_event(hook: fn(IdEvent)) {
+        EVENT_HOOK.store(hook as *mut (), Ordering::Release);
+    }
+
+    /// Remove the hook installed with [`AtomicOption::on_event`].
+    pub fn clear_on_event() {
+        EVENT_HOOK.store(core::ptr::null_mut(), Ordering::Release);
+    }
+
+    /// Install a hook that is called with every generated ID, replacing any previous
+    /// hook. Use it to sample or count minted IDs without wrapping every call site.
+    ///
+    /// The hook receives the width and the raw value of each ID from the `gen*`
+    /// methods of [`IdGenerator`], and so from every `AtomicId` method built on them.
+    /// A 256-bit ID does not fit in `raw`, which holds only its first two 64-bit
+    /// parts; install [`AtomicOption::on_generate256`] to see all four. It is a plain
+    /// function pointer, so installing it allocates nothing.
+    ///
+    /// The hook runs on the hot path, on the generating thread, once per ID: keep it
+    /// to a few atomic operations. Anything slower (locks, I/O, logging every ID)
+    /// caps the generation rate. IDs generated inside the hook call it again, so it
+    /// must not generate IDs itself.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, AtomicOption, x64};
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// static MINTED_64: AtomicU64 = AtomicU64::new(0);
+    /// fn count(bits: usize, _raw: u128) {
+    ///     if bits == 64 {
+    ///         MINTED_64.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    /// AtomicOption::on_generate(count);
+    /// AtomicId::<x64>::new();
+    /// assert!(MINTED_64.load(Ordering::Relaxed) >= 1);
+    /// # AtomicOption::clear_on_generate();
+    /// ```
+    pub fn on_generate(hook: fn(bits: usize, raw: u128)) {
+        GENERATE_HOOK.store(hook as *mut (), Ordering::Release);
+    }
+
+    /// Remove the hook installed with [`AtomicOption::on_generate`].
+    pub fn clear_on_generate() {
+        GENERATE_HOOK.store(core::ptr::null_mut(), Ordering::Release);
+    }
+
+    /// Install a hook that is called with all four parts of every generated 256-bit
+    /// ID, replacing any previous one. It runs after the [`AtomicOption::on_generate`]
+    /// hook, which only sees the first two parts, and has the same hot-path costs.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdGenerator};
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// static LAST_ENTROPY: AtomicU64 = AtomicU64::new(0);
+    /// fn record(id: [u64; 4]) {
+    ///     LAST_ENTROPY.store(id[3], Ordering::Relaxed);
+    /// }
+    /// AtomicOption::on_generate256(record);
+    /// let id = IdGenerator::new(1, 1).gen256();
+    /// assert_eq!(LAST_ENTROPY.load(Ordering::Relaxed), id[3]);
+    /// # AtomicOption::clear_on_generate256();
+    /// ```
+    pub fn on_generate256(hook: fn(id: [u64; 4])) {
+        GENERATE_HOOK_256.store(hook as *mut (), Ordering::Release);
+    }
+
+    /// Remove the hook installed with [`AtomicOption::on_generate256`].
+    pub fn clear_on_generate256() {
+        GENERATE_HOOK_256.store(core::ptr::null_mut(), Ordering::Release);
+    }
+
+    /// Read time from `source` instead of the system clock.
+    ///
+    /// Required before generating timestamp-based IDs without the `std` feature.
+    /// With `std` it replaces [`std::time::SystemTime`], e.g. to use a
+    /// clock synchronized by other means.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, TimeSource};
+    ///
+    /// fn rtc_ms() -> u64 {
+    ///     1704067200000
+    /// }
+    /// fn rtc_ns() -> u64 {
+    ///     rtc_ms() * 1_000_000
+    /// }
+    /// AtomicOption::time_source(TimeSource { now_ms: rtc_ms, now_ns: rtc_ns });
+    /// # AtomicOption::clear_time_source();
+    /// ```
+    pub fn time_source(source: TimeSource) {
+        store_time_source(source.now_ms as *mut (), source.now_ns as *mut ());
+    }
+
+    /// Remove the installed [`TimeSource`] and go back to the system clock.
+    pub fn clear_time_source() {
+        store_time_source(core::ptr::null_mut(), core::ptr::null_mut());
+    }
+
+    /// Select how timestamps are read from the system clock. Switching to
+    /// [`ClockMode::MonotonicAnchored`] anchors the clock right away; select it at
+    /// startup, before the wall clock has a chance to jump.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, ClockMode};
+    /// AtomicOption::clock_mode(ClockMode::MonotonicAnchored);
+    /// assert_eq!(AtomicOption::get_clock_mode(), ClockMode::MonotonicAnchored);
+    /// # AtomicOption::clock_mode(ClockMode::Wall);
+    /// ```
+    pub fn clock_mode(mode: ClockMode) {
+        CLOCK_MODE.store(mode as u8, Ordering::Relaxed);
+        #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+        if mode == ClockMode::MonotonicAnchored {
+            anchor_clock(CLOCK_BASE.get_or_init(Instant::now).elapsed().as_nanos() as u64);
+        }
+    }
+
+    /// Get the current clock mode.
+    pub fn get_clock_mode() -> ClockMode {
+        ClockMode::current()
+    }
+
+    /// Re-read the wall clock every `interval` in [`ClockMode::MonotonicAnchored`],
+    /// to limit how far the monotonic clock drifts from it. [`Duration::ZERO`], the
+    /// default, anchors only once.
+    pub fn clock_reanchor_interval(interval: Duration) {
+        let nanos = u64::try_from(interval.as_nanos()).unwrap_or(u64::MAX);
+        CLOCK_REANCHOR_NS.store(nanos, Ordering::Relaxed);
+    }
+
+    /// Get the re-anchoring interval of [`ClockMode::MonotonicAnchored`].
+    pub fn get_clock_reanchor_interval() -> Duration {
+        Duration::from_nanos(CLOCK_REANCHOR_NS.load(Ordering::Relaxed))
+    }
+
+    /// Derive thread IDs from `provider` instead of hashing the thread's ID.
+    ///
+    /// The provider should return a value that differs between concurrent execution
+    /// contexts, such as a core number or task index; it is mixed before use, so small
+    /// consecutive values are fine. With `std` it is called once per thread and cached,
+    /// so threads that already generated an ID keep their thread ID. Without `std` it
+    /// is called on every ID.
+    pub fn thread_entropy(provider: fn() -> u64) {
+        THREAD_ENTROPY.store(provider as *mut (), Ordering::Release);
+    }
+
+    /// Remove the installed thread entropy provider.
+    pub fn clear_thread_entropy() {
+        THREAD_ENTROPY.store(core::ptr::null_mut(), Ordering::Release);
+    }
+
+    /// Get a snapshot of generation statistics.
+    ///
+    /// The generated counts are derived from the sequence counters, so they cover
+    /// every ID of that width, whether minted through [`AtomicId`] or an
+    /// [`IdGenerator`]. Sequential IDs use separate counters and are not included.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, AtomicId, x64};
+    /// AtomicOption::reset_stats();
+    /// let _ = AtomicId::<x64>::new();
+    /// assert!(AtomicOption::stats().generated_64 >= 1);
+    /// ```
+    pub fn stats() -> IdStats {
+        let generated = |seq: &AtomicU64, i: usize| {
+            seq.load(Ordering::Relaxed)
+                .wrapping_sub(STATS_BASE[i].load(Ordering::Relaxed))
+        };
+        IdStats {
+            generated_24: generated(&SEQ_24, 0),
+            generated_32: generated(&SEQ_32, 1).wrapping_add(generated(&SEQ_32_TIMED, 5)),
+            generated_64: generated(&SEQ_64, 2),
+            generated_128: generated(&SEQ_128, 3),
+            generated_256: generated(&SEQ_256, 4),
+            last_timestamp_ms: LAST_TIMESTAMP.load(Ordering::Relaxed),
+            clock_rollbacks: CLOCK_ROLLBACKS.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Switch to deterministic mode for reproducible fixtures and snapshot tests.
+    ///
+    /// The wall clock is replaced by a logical clock that starts `seed` milliseconds
+    /// after the epoch and advances by one on every read, thread IDs and nanosecond
+    /// entropy come from a PRNG seeded with `seed`, and the sequence counters are reset
+    /// to zero. The same sequence of calls after the same `seed` then yields exactly the
+    /// same IDs, on every run and platform. Thread IDs are assigned in the order threads
+    /// first generate an ID, so only single-threaded call sequences are reproducible.
+    ///
+    /// Because it resets the counters, this mode would break uniqueness in production.
+    /// It is only available in tests and with the `testing` feature, and it is best
+    /// enabled through [`TestConfig::deterministic`](testing::TestConfig::deterministic)
+    /// so the guard switches it off again when dropped.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "testing")] {
+    /// use atomic_id::testing::{TestConfig, TestGuard};
+    /// use atomic_id::{AtomicId, x64};
+    ///
+    /// let run = || {
+    ///     let _guard = TestGuard::scoped(TestConfig {
+    ///         deterministic: Some(42),
+    ///         ..TestConfig::default()
+    ///     });
+    ///     AtomicId::<x64>::batch(3)
+    /// };
+    /// assert_eq!(run(), run());
+    /// # }
+    /// ```
+    #[cfg(any(test, feature = "testing"))]
+    pub fn deterministic(seed: u64) {
+        testing::enable_deterministic(seed);
+    }
+
+    /// Reseed the PRNG behind deterministic mode without leaving it.
+    ///
+    /// Nanosecond entropy drawn after the call comes from a stream seeded with `seed`,
+    /// and every thread draws fresh thread entropy the next time it generates an ID.
+    /// Unlike [`AtomicOption::deterministic`], the logical clock and the sequence
+    /// counters keep running, so IDs stay unique across the reseed. Outside
+    /// deterministic mode nothing is drawn from the PRNG and this has no effect on
+    /// generated IDs; entering the mode sets its own seed.
+    ///
+    /// The PRNG state is a single atomic word, replaced by one store. Each draw is
+    /// an atomic read-modify-write on that word, so a draw racing with the reseed
+    /// returns a value from either the old stream or the new one, never a mix, and
+    /// never panics. Draws on the same thread after `reseed` returns, or on threads
+    /// that synchronize with it (e.g. by joining it), always see the new stream.
+    /// Other concurrent threads switch at some point during the race.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "testing")] {
+    /// use atomic_id::testing::{TestConfig, TestGuard};
+    /// use atomic_id::{AtomicId, AtomicOption, x128};
+    ///
+    /// let run = |seed| {
+    ///     let _guard = TestGuard::scoped(TestConfig {
+    ///         deterministic: Some(1),
+    ///         ..TestConfig::default()
+    ///     });
+    ///     AtomicOption::reseed(seed);
+    ///     AtomicId::<x128>::batch(3)
+    /// };
+    /// assert_eq!(run(7), run(7));
+    /// assert_ne!(run(7), run(8));
+    /// # }
+    /// ```
+    #[cfg(any(test, feature = "testing"))]
+    pub fn reseed(seed: u64) {
+        testing::reseed(seed);
+    }
+
+    /// Forget every thread's assigned thread ID. Test-only.
+    ///
+    /// Restarts the [`ThreadIdMode::Sequential`] numbering at 1 and drops the cached
+    /// hashed thread entropy, so each thread, including ones that already generated
+    /// IDs, is assigned afresh on its next ID. Tests that spawn many threads can call
+    /// this first so their thread numbers don't depend on earlier tests in the same
+    /// binary. [`testing::reset_all`] calls it too.
+    ///
+    /// Threads still generating during the reset may keep their old number or share
+    /// one with a thread numbered after it, so only call this while no other thread
+    /// is generating IDs.
+    #[doc(hidden)]
+    #[cfg(all(feature = "std", any(test, feature = "testing")))]
+    pub fn __reset_thread_ids() {
+        NEXT_THREAD_NUMBER.store(1, Ordering::Relaxed);
+        THREAD_CACHE_GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Select the bit layout used for all 64-bit IDs generated from now on.
+    ///
+    /// This affects [`AtomicId<64>`] and [`IdGenerator::gen64`]. IDs in different
+    /// layouts are not comparable, so pick the layout once at startup and decode
+    /// stored IDs with the matching [`Layout64::decompose`].
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, Layout64};
+    /// AtomicOption::layout64(Layout64::WideNode);
+    /// assert_eq!(AtomicOption::get_layout64(), Layout64::WideNode);
+    /// ```
+    pub fn layout64(layout: Layout64) {
+        LAYOUT_64.store(layout as u8, Ordering::Relaxed);
+    }
+
+    /// Get the currently selected 64-bit layout.
+    pub fn get_layout64() -> Layout64 {
+        Layout64::current()
+    }
+
+    /// Set the region (0..=15) that [`IdGenerator::gen64_regional`] and
+    /// [`Layout64::Regional`] write into IDs, for generators built without a region
+    /// of their own, including the global one. Defaults to 0.
+    ///
+    /// # Errors
+    /// [`IdError::InvalidRegion`] if `region_id` is greater than 15; the region is
+    /// unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdError};
+    /// AtomicOption::region_id(4)?;
+    /// assert_eq!(AtomicOption::get_region_id(), 4);
+    /// assert_eq!(AtomicOption::region_id(16), Err(IdError::InvalidRegion(16)));
+    /// # AtomicOption::region_id(0)?;
+    /// # Ok::<(), IdError>(())
+    /// ```
+    pub fn region_id(region_id: u8) -> Result<(), IdError> {
+        if region_id > MAX_REGION_ID {
+            return Err(IdError::InvalidRegion(region_id));
+        }
+        REGION_ID.store(region_id, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Get the global region ID.
+    pub fn get_region_id() -> u8 {
+        REGION_ID.load(Ordering::Relaxed)
+    }
+
+    /// Fix the global generator used by [`AtomicId`] to `generator`, e.g. one for the
+    /// node and shard from the application's configuration. Call it at startup,
+    /// before anything generates an ID.
+    ///
+    /// Otherwise the first ID generated, which may come from a library dependency
+    /// before the configuration is read, fixes the global generator to the default
+    /// node 1, shard 0. In builds with debug assertions that is reported as
+    /// [`IdEvent::ImplicitInit`], so the mistake shows up in development. Calls that
+    /// only read the generator's settings, such as [`Id64::min_for`] or
+    /// `time_remaining`, leave it unfixed. [`AtomicOption::reconfigure`] can still
+    /// replace the generator later.
+    ///
+    /// # Errors
+    /// [`AlreadyInitialized`] with the node and shard in place if the global
+    /// generator was already fixed, by the first ID generated, an earlier call, or
+    /// [`AtomicOption::reconfigure`]. `generator` is dropped and nothing changes.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AlreadyInitialized, AtomicId, AtomicOption, IdGenerator, x64};
+    /// assert_eq!(AtomicOption::init_global(IdGenerator::new(42, 7)), Ok(()));
+    /// assert!(AtomicOption::is_initialized());
+    /// let (id, _) = AtomicId::<x64>::new_with_raw();
+    /// assert_eq!(IdGenerator::decompose64(id).node_id, 42);
+    /// assert_eq!(
+    ///     AtomicOption::init_global(IdGenerator::new(1, 0)),
+    ///     Err(AlreadyInitialized { node_id: 42, shard_id: 7, implicit: false })
+    /// );
+    /// ```
+    pub fn init_global(generator: IdGenerator) -> Result<(), AlreadyInitialized> {
+        // A reconfigured generator takes precedence, so this one would go unused.
+        #[cfg(feature = "reconfigure")]
+        if let Some(current) = RECONFIGURED.load().as_deref() {
+            return Err(AlreadyInitialized {
+                node_id: current.node_id,
+                shard_id: current.shard_id,
+                implicit: false,
+            });
+        }
+        let ptr = alloc::boxed::Box::into_raw(alloc::boxed::Box::new(generator));
+        match GLOBAL.compare_exchange(core::ptr::null_mut(), ptr, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => Ok(()),
+            Err(current) => {
+                // SAFETY: `ptr` was never published, so this is its only owner.
+                drop(unsafe { alloc::boxed::Box::from_raw(ptr) });
+                // SAFETY: as in `global`.
+                let current = unsafe { &*current };
+                Err(AlreadyInitialized {
+                    node_id: current.node_id,
+                    shard_id: current.shard_id,
+                    implicit: core::ptr::eq(current, &GENERATOR),
+                })
+            }
+        }
+    }
+
+    /// Whether the global generator is fixed, by [`AtomicOption::init_global`], by
+    /// [`AtomicOption::reconfigure`], or by the first ID generated, so that a later
+    /// [`AtomicOption::init_global`] would fail.
+    pub fn is_initialized() -> bool {
+        #[cfg(feature = "reconfigure")]
+        if RECONFIGURED.load().is_some() {
+            return true;
+        }
+        !GLOBAL.load(Ordering::Acquire).is_null()
+    }
+
+    /// Replace the global generator used by [`AtomicId`], which starts out as node 1,
+    /// shard 0, with one for `node_id` and `shard_id`. Requires the `reconfigure`
+    /// feature.
+    ///
+    /// For node assignments that arrive after startup, e.g. from a coordinator, once
+    /// some IDs have already been minted. The swap is atomic: every ID is generated
+    /// entirely by the old generator or entirely by the new one, and generation never
+    /// blocks on it. The sequence counters are shared by all generators, so they carry
+    /// on across the swap.
+    ///
+    /// IDs minted before and after the swap stay unique as long as no two processes
+    /// use the same node and shard pair at the same time: the old pair must not be
+    /// handed to another process while this one may still be generating with it.
+    ///
+    /// Called before anything generates an ID, it counts as initializing the global
+    /// generator: [`AtomicOption::is_initialized`] is true afterwards and a later
+    /// [`AtomicOption::init_global`] fails.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, AtomicOption, IdGenerator, x64};
+    /// AtomicOption::reconfigure(42, 7);
+    /// let (id, _) = AtomicId::<x64>::new_with_raw();
+    /// assert_eq!(IdGenerator::decompose64(id).node_id, 42);
+    /// ```
+    #[cfg(feature = "reconfigure")]
+    pub fn reconfigure(node_id: u16, shard_id: u8) {
+        Self::reconfigure_with(IdGenerator::new(node_id, shard_id));
+    }
+
+    /// Replace the global generator with `generator`, e.g. one with its own epoch or
+    /// region from [`IdGenerator::builder`]. See [`AtomicOption::reconfigure`].
+    #[cfg(feature = "reconfigure")]
+    pub fn reconfigure_with(generator: IdGenerator) {
+        RECONFIGURED.store(Some(Arc::new(generator)));
+    }
+
+    /// Set the unit of the timestamp in 64-bit IDs. Defaults to
+    /// [`TimestampPrecision::Millis`].
+    ///
+    /// The precision moves bits between the time fields and the sequence, so it must
+    /// match across every process generating or decoding the IDs, like the layout.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, TimestampPrecision};
+    /// AtomicOption::timestamp_precision(TimestampPrecision::Micros);
+    /// assert_eq!(AtomicOption::get_timestamp_precision(), TimestampPrecision::Micros);
+    /// ```
+    pub fn timestamp_precision(precision: TimestampPrecision) {
+        TIMESTAMP_PRECISION.store(precision as u8, Ordering::Relaxed);
+    }
+
+    /// Get the currently selected 64-bit timestamp precision.
+    pub fn get_timestamp_precision() -> TimestampPrecision {
+        TimestampPrecision::current()
+    }
+
+    /// Set how close to a timestamp wrap, as a percentage of the field's period,
+    /// counts as near the wrap. Clamped to 0..=100; `0` disables the check.
+    ///
+    /// Near the wrap, [`IdGenerator::gen64_checked`] returns an error and
+    /// [`IdGenerator::gen64`] reports [`IdEvent::TimestampNearWrap`]. The default of
+    /// 1% is about 10.5 seconds of the 64-bit field's 17.5-minute period.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::AtomicOption;
+    /// AtomicOption::wrap_threshold(5);
+    /// assert_eq!(AtomicOption::get_wrap_threshold(), 5);
+    /// ```
+    pub fn wrap_threshold(percent: u8) {
+        WRAP_THRESHOLD_PERCENT.store(percent.min(100), Ordering::Relaxed);
+    }
+
+    /// Get the current wrap threshold percentage.
+    pub fn get_wrap_threshold() -> u8 {
+        WRAP_THRESHOLD_PERCENT.load(Ordering::Relaxed)
+    }
+
+    /// Set the width of the thread ID field in 64-bit IDs, clamped to 1..=16 bits.
+    ///
+    /// The thread ID and sequence fields share 24 bits in every [`Layout64`] (22 with
+    /// [`AtomicOption::version_bits`] enabled), so each extra thread bit halves the
+    /// sequence space, which caps how many IDs a node can mint per millisecond before
+    /// the sequence wraps:
+    ///
+    /// | Thread bits | Sequence bits | IDs per ms |
+    /// |-------------|---------------|------------|
+    /// | 8 (default) | 16            | 65,536     |
+    /// | 9           | 15            | 32,768     |
+    /// | 10          | 14            | 16,384     |
+    /// | 12          | 12            | 4,096      |
+    /// | 16          | 8             | 256        |
+    ///
+    /// Hashed thread IDs only use half of the field's values, so with hundreds of
+    /// threads combine this with [`ThreadIdMode::Sequential`]. Like the layout, the
+    /// split must be the same for everyone reading the IDs, so set it once at startup.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, ThreadIdMode};
+    /// AtomicOption::thread_id_bits(10);
+    /// AtomicOption::thread_id_mode(ThreadIdMode::Sequential);
+    /// assert_eq!(AtomicOption::get_thread_id_bits(), 10);
+    /// ```
+    pub fn thread_id_bits(bits: u8) {
+        THREAD_ID_BITS.store(bits.clamp(1, 16), Ordering::Relaxed);
+    }
+
+    /// Get the current width of the 64-bit thread ID field.
+    pub fn get_thread_id_bits() -> u8 {
+        THREAD_ID_BITS.load(Ordering::Relaxed)
+    }
+
+    /// Enable or disable the layout version field in 64- and 128-bit IDs. Disabled by
+    /// default, so IDs keep the layout of earlier releases.
+    ///
+    /// While enabled, 64-bit IDs end in a 2-bit layout version, taken from the
+    /// sequence space, and 128-bit IDs carry it in two of their reserved bits, so
+    /// readers can tell the current layout from future ones with
+    /// [`IdGenerator::layout_version`]. Unversioned (version 0) IDs keep the 2 bits
+    /// for the sequence. The version bits overlap the sequence of unversioned IDs, so
+    /// like the layout this setting must be the same for everyone reading the IDs;
+    /// set it once at startup.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, IdGenerator};
+    /// let generator = IdGenerator::new(7, 3);
+    /// assert_eq!(IdGenerator::layout_version(generator.gen64()), 0);
+    /// AtomicOption::version_bits(true);
+    /// assert_eq!(IdGenerator::layout_version(generator.gen64()), 1);
+    /// ```
+    pub fn version_bits(enabled: bool) {
+        VERSION_BITS_ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Get whether IDs carry a layout version field.
+    pub fn get_version_bits() -> bool {
+        version_bits()
+    }
+
+    /// Select how threads are assigned their thread IDs.
+    ///
+    /// Threads that already generated an ID in sequential mode keep their number.
+    /// Sequential mode requires the `std` feature and is ignored without it.
+    pub fn thread_id_mode(mode: ThreadIdMode) {
+        THREAD_ID_MODE.store(mode as u8, Ordering::Relaxed);
+    }
+
+    /// Get the current thread ID assignment mode.
+    pub fn get_thread_id_mode() -> ThreadIdMode {
+        ThreadIdMode::current()
+    }
+
+    /// Mix `salt` into hashed thread IDs, so processes whose threads hash alike get
+    /// different thread IDs.
+    ///
+    /// Thread IDs in [`ThreadIdMode::Hashed`] come from a hash of the thread's
+    /// `ThreadId` or from the [`AtomicOption::thread_entropy`] provider. `ThreadId`s
+    /// are numbered from the same start in every process, so without a salt the first
+    /// threads of two processes get the same thread IDs, and their IDs differ only in
+    /// the node and shard fields. Any value unique to the process works as a salt,
+    /// such as the node ID or the process ID. The default of 0 keeps thread IDs as in
+    /// earlier releases. Sequential thread IDs are not salted.
+    ///
+    /// Like the provider, the salt is applied when a thread first generates an ID, so
+    /// set it at startup: threads that already generated an ID keep their thread ID.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::AtomicOption;
+    /// AtomicOption::thread_id_salt(std::process::id() as u64);
+    /// assert_eq!(AtomicOption::get_thread_id_salt(), std::process::id() as u64);
+    /// # AtomicOption::thread_id_salt(0);
+    /// ```
+    pub fn thread_id_salt(salt: u64) {
+        THREAD_ID_SALT.store(salt, Ordering::Relaxed);
+    }
+
+    /// Get the salt mixed into hashed thread IDs.
+    pub fn get_thread_id_salt() -> u64 {
+        THREAD_ID_SALT.load(Ordering::Relaxed)
+    }
+
+    /// Select the bit layout used by [`IdGenerator::gen32`] from now on. Like the
+    /// 64-bit layout, it must be the same for everyone reading the IDs.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, Layout32};
+    /// AtomicOption::layout32(Layout32::TimeSalted);
+    /// assert_eq!(AtomicOption::get_layout32(), Layout32::TimeSalted);
+    /// ```
+    pub fn layout32(layout: Layout32) {
+        LAYOUT_32.store(layout as u8, Ordering::Relaxed);
+    }
+
+    /// Get the current 32-bit layout.
+    pub fn get_layout32() -> Layout32 {
+        Layout32::current()
+    }
+
+    /// Select the bit layout used for all 24-bit IDs generated from now on.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, Layout24};
+    /// AtomicOption::layout24(Layout24::TimeSalted);
+    /// assert_eq!(AtomicOption::get_layout24(), Layout24::TimeSalted);
+    /// ```
+    pub fn layout24(layout: Layout24) {
+        LAYOUT_24.store(layout as u8, Ordering::Relaxed);
+    }
+
+    /// Get the current 24-bit layout.
+    pub fn get_layout24() -> Layout24 {
+        Layout24::current()
+    }
+
+    /// Select what [`IdGenerator::try_gen24`] does once the 24-bit sequence is used up.
+    /// Defaults to [`OverflowPolicy::Wrap`].
+    pub fn overflow_policy24(policy: OverflowPolicy) {
+        OVERFLOW_POLICY_24.store(policy as u8, Ordering::Relaxed);
+    }
+
+    /// Get the current 24-bit overflow policy.
+    pub fn get_overflow_policy24() -> OverflowPolicy {
+        OverflowPolicy::current()
+    }
+
+    /// Declare every 24-bit ID generated so far expired, so the whole sequence may be
+    /// handed out again. [`IdGenerator::try_gen24`] stops failing or blocking, and
+    /// the next IDs continue from where the counter is, reusing earlier values.
+    ///
+    /// Only call this once earlier 24-bit IDs are no longer in use, e.g. after the
+    /// cache or session store they key has been flushed.
+    pub fn reset24() {
+        SEQ_24_RESET_AT.store(SEQ_24.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Select what happens when the time since the epoch outgrows a timestamp field.
+    /// Defaults to [`OverflowMode::Wrap`]; see [`OverflowMode`] for what each mode
+    /// means for uniqueness. [`OverflowMode::Error`] only takes effect in
+    /// [`IdGenerator::gen64_checked`]; the other generators wrap in it.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicOption, OverflowMode};
+    /// AtomicOption::on_overflow(OverflowMode::Error);
+    /// assert_eq!(AtomicOption::get_overflow_mode(), OverflowMode::Error);
+    /// ```
+    pub fn on_overflow(mode: OverflowMode) {
+        OVERFLOW_MODE.store(mode as u8, Ordering::Relaxed);
+    }
+
+    /// Get the current timestamp overflow mode.
+    pub fn get_overflow_mode() -> OverflowMode {
+        OverflowMode::current()
+    }
+
+    /// Cap the number of 64-bit IDs generated per millisecond at `limit`, across all
+    /// threads and generators, or lift the cap with 0, the default.
+    ///
+    /// Within one timestamp, 64-bit IDs are only told apart by their sequence field:
+    /// 65,536 values with the default [`AtomicOption::thread_id_bits`]. A runaway
+    /// loop that generates more than that in a millisecond wraps the sequence and
+    /// repeats IDs, reported only as [`IdEvent::SequenceExhausted`]. With a limit,
+    /// [`IdGenerator::gen64`] and the other methods that read the clock for a single
+    /// 64-bit ID wait instead, turning the collision into backpressure. A limit above
+    /// the sequence values a millisecond holds, under the thread ID bits and
+    /// [`TimestampPrecision`] in force when an ID is generated, is clamped to them,
+    /// since it could not prevent a wrap.
+    ///
+    /// Not every 64-bit ID is counted. [`AtomicId::<x64>::lease`](AtomicId::<64>::lease)
+    /// counts its whole block up front. IDs for a given time
+    /// ([`IdGenerator::gen64_at`]) and blocks from [`IdGenerator::reserve64`] and
+    /// [`IdGenerator::gen64_reserved`] are not counted, and still use up sequence
+    /// values that counted IDs in the same millisecond then wrap into.
+    /// [`IdGenerator::gen64_k_sorted`] is not counted either: it has its own sequence,
+    /// which moves on to the next millisecond instead of wrapping.
+    ///
+    /// This costs latency. Every 64-bit ID takes a compare-and-swap on one shared
+    /// counter, which contends between threads like the sequence counter does; and
+    /// once the limit is reached, callers spin, then yield, until the clock reaches
+    /// the next millisecond, so each of them can wait up to a millisecond plus
+    /// scheduling delay. Throughput is capped at `limit * 1000` IDs per second. With
+    /// a [`TimeSource`] whose time stands still, a full millisecond never ends and
+    /// generation waits forever.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::AtomicOption;
+    /// AtomicOption::max_rate_per_ms(10_000);
+    /// assert_eq!(AtomicOption::get_max_rate_per_ms(), 10_000);
+    /// AtomicOption::max_rate_per_ms(0);
+    /// ```
+    pub fn max_rate_per_ms(limit: u32) {
+        MAX_RATE_64.store(limit, Ordering::Relaxed);
+    }
+
+    /// Get the per-millisecond limit on 64-bit IDs as set, before any clamping, or 0
+    /// if there is none.
+    pub fn get_max_rate_per_ms() -> u32 {
+        MAX_RATE_64.load(Ordering::Relaxed)
+    }
+
+    /// Keep the sequence counters from going backwards across restarts by storing
+    /// their high watermarks in the file at `path`.
+    ///
+    /// If the file exists, each counter is first raised to the value stored in it, so
+    /// IDs generated after a restart within the same millisecond as the previous
+    /// run's last IDs take sequence values that run never used. The file is then
+    /// rewritten now and every [`AtomicOption::persist_sequence_interval`] IDs, with
+    /// each counter plus a reserve of twice the interval, so the IDs generated
+    /// between two writes are already covered when the process dies.
+    ///
+    /// Each write creates `path` with `.tmp` appended, syncs it to disk with
+    /// `fsync`, and renames it over `path`, so the file is never half-written. The
+    /// periodic writes run on a background thread, `atomic-id-persist`, started by
+    /// the first call: the thread whose ID completes an interval only asks for a
+    /// write, so generation does not wait for the disk. That is one `fsync` per
+    /// interval; a shorter interval costs more, while a longer one only makes the
+    /// counters jump further on restart.
+    ///
+    /// No sequence value at or above the watermark last written is handed out. If
+    /// writes fall a whole reserve behind, or fail, e.g. on a full disk, every
+    /// generating method waits until a write succeeds. A failed write is reported as
+    /// [`IdEvent::SequencePersistFailed`] and retried every 100 ms while generation
+    /// waits. Call [`AtomicOption::flush_sequence`] before a clean shutdown to write
+    /// at once.
+    ///
+    /// Only the sequence counters are stored: timestamps, thread IDs, and the other
+    /// fields are still needed to tell processes apart.
+    ///
+    /// # Errors
+    /// Any I/O error from reading the existing file or writing the new one, or
+    /// [`std::io::ErrorKind::InvalidData`] if the existing file is malformed.
+    /// Persistence is not started on error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use atomic_id::{AtomicId, AtomicOption, x64};
+    /// use std::path::Path;
+    /// AtomicOption::persist_sequence(Path::new("/var/lib/orders/sequence"))?;
+    /// let id = AtomicId::<x64>::new();
+    /// AtomicOption::flush_sequence()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    pub fn persist_sequence(path: &std::path::Path) -> std::io::Result<()> {
+        persist::start(path)
+    }
+
+    /// Set how many IDs, of all widths together, are generated between writes of
+    /// the [`AtomicOption::persist_sequence`] file. Defaults to 4096; 0 counts as 1.
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    pub fn persist_sequence_interval(ids: u64) {
+        persist::INTERVAL.store(ids.max(1), Ordering::Relaxed);
+    }
+
+    /// Get the number of IDs between writes of the sequence watermarks.
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    pub fn get_persist_sequence_interval() -> u64 {
+        persist::INTERVAL.load(Ordering::Relaxed)
+    }
+
+    /// Write the [`AtomicOption::persist_sequence`] file now, e.g. at shutdown. Does
+    /// nothing if persistence was not started.
+    ///
+    /// # Errors
+    /// Any I/O error from writing the file.
+    #[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+    pub fn flush_sequence() -> std::io::Result<()> {
+        persist::flush()
+    }
+
+    /// Check every 64-bit ID against the recently issued ones, to catch
+    /// misconfigured node IDs or a stopped clock early instead of as unique-constraint
+    /// violations later. Requires the `debug_checks` feature.
+    ///
+    /// Each ID is recorded in a window of the last
+    /// [`AtomicOption::debug_duplicate_capacity`] IDs, and a repeat within the window
+    /// is reported as [`IdEvent::DuplicateId`]; in builds with debug assertions the
+    /// generating thread then panics. Only IDs issued by this process are seen, so
+    /// two processes with the same node ID are caught only when they share one
+    /// generator, e.g. in an integration test. Every ID takes a global lock while the
+    /// check is on; for debugging, not production. Turning it off forgets the window.
+    ///
+    /// # Example
+    /// ```
+    /// use atomic_id::{AtomicId, AtomicOption, x64};
+    /// AtomicOption::debug_duplicate_check(true);
+    /// let id = AtomicId::<x64>::new();
+    /// AtomicOption::debug_duplicate_check(false);
+    /// ```
+    #[cfg(feature = "debug_checks")]
+    pub fn debug_duplicate_check(on: bool) {
+        debug_checks::enable(on);
+    }
+
+    /// Whether 64-bit IDs are checked for repeats.
+    #[cfg(feature = "debug_checks")]
+    pub fn get_debug_duplicate_check() -> bool {
+        debug_checks::enabled()
+    }
+
+    /// Set how many recent IDs [`AtomicOption::debug_duplicate_check`] keeps.
+    /// Defaults to 65,536, under 2 MiB with the lookup set; 0 counts as 1. A smaller
+    /// window takes effect at the next ID.
+    #[cfg(feature = "debug_checks")]
+    pub fn debug_duplicate_capacity(ids: usize) {
+        debug_checks::CAPACITY.store(ids.max(1), Ordering::Relaxed);
+    }
+
+    /// Get how many recent IDs the duplicate check keeps.
+    #[cfg(feature = "debug_checks")]
+    pub fn get_debug_duplicate_capacity() -> usize {
+        debug_checks::CAPACITY.load(Ordering::Relaxed)
+    }
+
+    /// Reset the statistics reported by [`AtomicOption::stats`] to zero.
+    ///
+    /// This does not touch the sequence counters themselves, so uniqueness is unaffected.
+    pub fn reset_stats() {
+        for (i, seq) in [&SEQ_24, &SEQ_32, &SEQ_64, &SEQ_128, &SEQ_256, &SEQ_32_TIMED]
+            .into_iter()
+            .enumerate()
+        {
+            STATS_BASE[i].store(seq.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        LAST_TIMESTAMP.store(0, Ordering::Relaxed);
+        CLOCK_ROLLBACKS.store(0, Ordering::Relaxed);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialize a test that generates IDs against tests that change global state.
+    fn guard() -> testing::TestGuard {
+        testing::TestGuard::scoped(Default::default())
+    }
+
+    /// Test generation of 64-bit IDs in all encodings.
+    #[test]
+    fn test_gen64() {
+        let _guard = guard();
+        let id = AtomicId::<64>::new();
+        println!("Generated ID: {}", id);
+        let id = AtomicId::<64>::base36();
+        println!("Generated ID: {}", id);
+        let id = AtomicId::<64>::base58();
+        println!("Generated ID: {}", id);
+        let id = AtomicId::<64>::base91();
+        println!("Generated ID: {}", id);
+        let id = AtomicId::<64>::hex();
+        println!("Generated ID: {}", id);
+    }
+
+    /// Test generation of 128-bit IDs.
+    #[test]
+    fn test_gen128() {
+        let _guard = guard();
+        let id = AtomicId::<128>::base36();
+        println!("Generated ID: {}", id);
+    }
+
+    /// Test generation of 256-bit IDs.
+    #[test]
+    fn test_gen256() {
+        let _guard = guard();
+        let id = AtomicId::<256>::base36();
+        println!("Generated ID: {}", id);
+    }
+
+    /// Test uniqueness of 64-bit IDs over 10 million generations.
+    #[test]
+    #[ignore] // This test is long-running and should be run manually.
+    fn test_uniqueness_64() {
+        let _guard = guard();
+        let mut ids = std::collections::HashSet::new();
+        for _ in 0..10_000_000 {
+            let id = AtomicId::<64>::new();
+            assert!(ids.insert(id), "Duplicate ID found");
+        }
+        println!("All IDs are unique");
+    }
+
+    /// Check the birthday estimates against hand-computed reference values.
+    #[test]
+    fn test_collision_probability() {
+        let _guard = guard();
+        assert_eq!(birthday_probability(1 << 16, 16, 7), 0.0);
+        assert_eq!(birthday_probability((1 << 24) + 1, 24, 0), 1.0);
+        // One pair shares a sequence value: 1 - e^(-1/128).
+        let p = birthday_probability((1 << 24) + 1, 24, 7);
+        assert!((p - 0.007782061739756488).abs() < 1e-12);
+        // 16 pairs share a sequence value: 1 - e^(-16/128).
+        let p = AtomicId::<64>::collision_probability(65_536 + 16);
+        assert!((p - 0.1175030974154046).abs() < 1e-12);
+        assert_eq!(AtomicId::<64>::collision_probability(1 << 17), 1.0);
+        assert_eq!(AtomicId::<64>::capacity(), 1 << 63);
+        assert_eq!(AtomicId::<128>::capacity(), 1 << 119);
+        AtomicOption::version_bits(true);
+        let p = AtomicId::<64>::collision_probability(16_384 + 16);
+        assert!((p - 0.1175030974154046).abs() < 1e-12);
+        assert_eq!(AtomicId::<64>::capacity(), 1 << 61);
+        assert_eq!(AtomicId::<128>::capacity(), 1 << 117);
+    }
+
+    /// Test runtime-dispatched generation and its error cases.
+    #[test]
+    fn test_generate_dispatch() {
+        let _guard = guard();
+        assert_eq!(AtomicId::generate(Width::X64, Encoding::Base36).unwrap().len(), 13);
+        assert_eq!(AtomicId::generate(Width::X128, Encoding::Base91).unwrap().len(), 20);
+        assert_eq!(AtomicId::generate(Width::X256, Encoding::Hex).unwrap().len(), 64);
+        assert_eq!(Width::try_from(48), Err(IdError::UnsupportedWidth(48)));
+        #[cfg(feature = "short")]
+        assert_eq!(AtomicId::generate(Width::X24, Encoding::Base58).unwrap().len(), 5);
+        #[cfg(not(feature = "short"))]
+        assert_eq!(
+            AtomicId::generate(Width::X32, Encoding::Hex),
+            Err(IdError::FeatureDisabled("short"))
+        );
+
+        for width in Width::ALL {
+            assert_eq!(Width::try_from(width.bits()), Ok(width));
+            match AtomicId::generate(width, Encoding::Hex) {
+                Ok(id) => assert_eq!(Ok(id.len()), Encoding::Hex.encoded_len(width.bits())),
+                Err(e) => assert_eq!((e, width.is_enabled()), (IdError::FeatureDisabled("short"), false)),
+            }
+        }
+        assert_eq!(Width::X24.is_enabled(), cfg!(feature = "short"));
+    }
+
+    /// Test that `AnyId` keeps the width, encodes like the typed generators, and
+    /// parses its `Display` form back.
+    #[test]
+    fn test_any_id() {
+        let _guard = guard();
+        for width in Width::ALL {
+            let id = match AtomicId::generate_any(width) {
+                Ok(id) => id,
+                Err(e) => {
+                    assert_eq!((e, width.is_enabled()), (IdError::FeatureDisabled("short"), false));
+                    continue;
+                }
+            };
+            assert_eq!(id.width(), width.bits());
+            for encoding in [Encoding::Base36, Encoding::Base58, Encoding::Hex] {
+                assert_eq!(Ok(id.encode(encoding).len()), encoding.encoded_len(width.bits()));
+            }
+            let shown = id.to_string();
+            assert_eq!(AnyId::try_from((shown.as_str(), width.bits())), Ok(id));
+        }
+
+        let id = AnyId::X256([1, 2, 3, 4]);
+        assert_eq!(id.encode(Encoding::Base58), AtomicId::<x256>::to_canonical([1, 2, 3, 4], Encoding::Base58));
+        assert_eq!(AnyId::X24(35).to_string(), "0000z");
+        assert_eq!(AnyId::try_from(("0000z", 24)), Ok(AnyId::X24(35)));
+        assert_eq!(AnyId::try_from(("zzzzz", 24)), Err(IdError::Overflow));
+        assert_eq!(AnyId::try_from(("0000z", 32)), Err(IdError::InvalidLength(5)));
+        assert_eq!(AnyId::try_from(("0000z", 48)), Err(IdError::UnsupportedWidth(48)));
+        let max = AnyId::X128(u128::MAX);
+        assert_eq!(AnyId::try_from((max.to_string().as_str(), 128)), Ok(max));
+    }
+
+    /// Test that statistics track generation and can be reset.
+    #[test]
+    fn test_stats() {
+        let _guard = guard();
+        AtomicOption::reset_stats();
+        let _ = AtomicId::<128>::batch(3);
+        let stats = AtomicOption::stats();
+        assert!(stats.generated_128 >= 3);
+        assert!(stats.last_timestamp_ms >= DEFAULT_EPOCH);
+    }
+
+    /// Test that the epoch can be configured before the first ID but not after, and
+    /// that the deprecated setter reports the ignored change through the event hook.
+    #[test]
+    #[cfg(not(feature = "const_epoch"))]
+    #[allow(deprecated)]
+    fn test_epoch_freeze() {
+        static REJECTED: std::sync::Mutex<Vec<IdEvent>> = std::sync::Mutex::new(Vec::new());
+        fn record(event: IdEvent) {
+            if matches!(event, IdEvent::EpochChangeRejected { .. }) {
+                REJECTED.lock().unwrap().push(event);
+            }
+        }
+        let _guard = guard();
+        let epoch = DEFAULT_EPOCH + 1000;
+
+        // Configure, then generate.
+        assert_eq!(AtomicOption::try_epoch(epoch), Ok(()));
+        assert_eq!(AtomicOption::try_epoch(epoch + 1), Ok(()));
+        AtomicOption::epoch(epoch);
+        let first = xgen().gen64();
+        assert_eq!(AtomicOption::get_epoch(), epoch);
+
+        // Generate, then configure.
+        assert_eq!(AtomicOption::try_epoch(DEFAULT_EPOCH), Err(IdError::EpochFrozen));
+        assert_eq!(AtomicOption::epoch_rfc3339("2024-01-01T00:00:00Z"), Err(EpochParseError::Frozen));
+        AtomicOption::on_event(record);
+        AtomicOption::epoch(DEFAULT_EPOCH);
+        AtomicOption::reset_epoch();
+        AtomicOption::clear_on_event();
+        assert_eq!(AtomicOption::get_epoch(), epoch);
+        assert_eq!(
+            *REJECTED.lock().unwrap(),
+            vec![
+                IdEvent::EpochChangeRejected {
+                    current: epoch,
+                    requested: DEFAULT_EPOCH,
+                };
+                2
+            ]
+        );
+        assert!(IdGenerator::decompose64(xgen().gen64()).timestamp >= IdGenerator::decompose64(first).timestamp);
+
+        // The escape hatch still works, and the epoch stays frozen afterwards.
+        AtomicOption::force_epoch_unchecked(DEFAULT_EPOCH);
+        assert_eq!(AtomicOption::get_epoch(), DEFAULT_EPOCH);
+        assert_eq!(AtomicOption::try_epoch(epoch), Err(IdError::EpochFrozen));
+    }
+
+    /// Test that an ID generated while another thread keeps setting the epoch is
+    /// measured from the epoch left in place: no change lands between the ID freezing
+    /// the epoch and reading it.
+    #[test]
+    #[cfg(not(feature = "const_epoch"))]
+    fn test_epoch_freeze_race() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        NOW.store(DEFAULT_EPOCH + 10_000, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        for _ in 0..200 {
+            CUSTOM_EPOCH.store(DEFAULT_EPOCH, Ordering::Relaxed);
+            let id = std::thread::scope(|scope| {
+                scope.spawn(|| {
+                    let mut flip = 0;
+                    while AtomicOption::try_epoch(DEFAULT_EPOCH + flip * 1000).is_ok() {
+                        flip ^= 1;
+                    }
+                });
+                xgen().gen64()
+            });
+            let elapsed = DEFAULT_EPOCH + 10_000 - AtomicOption::get_epoch();
+            assert_eq!(IdGenerator::decompose64(id).timestamp, elapsed);
+        }
+    }
+
+    /// Test that events reach the hook and are never delivered re-entrantly.
+    #[test]
+    #[cfg(not(feature = "const_epoch"))]
+    fn test_event_hook() {
+        static EVENTS: std::sync::Mutex<Vec<IdEvent>> = std::sync::Mutex::new(Vec::new());
+        fn record(event: IdEvent) {
+            EVENTS.lock().unwrap().push(event);
+            // Raising another event from inside the hook must not recurse.
+            emit(IdEvent::SequenceExhausted { width: 0 });
+        }
+
+        let _guard = guard();
+        AtomicOption::on_event(record);
+        AtomicOption::try_epoch(DEFAULT_EPOCH + 1).unwrap();
+        AtomicOption::clear_on_event();
+
+        // Other tests may trigger unrelated events while the hook is installed.
+        let events: Vec<_> = EVENTS
+            .lock()
+            .unwrap()
+            .iter()
+            .copied()
+            .filter(|e| matches!(e, IdEvent::EpochChanged { .. } | IdEvent::SequenceExhausted { width: 0 }))
+            .collect();
+        assert_eq!(
+            events,
+            vec![IdEvent::EpochChanged {
+                old: DEFAULT_EPOCH,
+                new: DEFAULT_EPOCH + 1,
+            }]
+        );
+    }
+
+    /// Test that a 64-bit sequence running out within one tick is reported once, at
+    /// the first ID that repeats a sequence value of the tick, wherever the counter
+    /// stood when the tick began.
+    #[test]
+    fn test_sequence_exhausted() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        static EXHAUSTED: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        fn record(event: IdEvent) {
+            if event == (IdEvent::SequenceExhausted { width: 64 }) {
+                EXHAUSTED.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let _guard = guard();
+        NOW.store(current_epoch() + 1_000, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        SEQ_64.store(12_345, Ordering::Relaxed);
+        AtomicOption::on_event(record);
+        let generator = IdGenerator::new(1, 0);
+        let capacity = 1u64 << seq_bits64(thread_id_bits());
+        for _ in 0..capacity {
+            generator.gen64();
+        }
+        let before = EXHAUSTED.load(Ordering::Relaxed);
+        generator.gen64();
+        let first = EXHAUSTED.load(Ordering::Relaxed);
+        for _ in 0..capacity {
+            generator.gen64();
+        }
+        NOW.fetch_add(1, Ordering::Relaxed);
+        for _ in 0..capacity {
+            generator.gen64();
+        }
+        AtomicOption::clear_on_event();
+        assert_eq!((before, first, EXHAUSTED.load(Ordering::Relaxed)), (0, 1, 1));
+    }
+
+    /// Test that adopted UUIDs keep their value and share the format of new IDs.
+    #[test]
+    fn test_adopt_uuid() {
+        let _guard = guard();
+        let uuid = 0x67e5504410b1426f9247bb680e5fe0c8u128;
+        let adopted = AtomicId::<x128>::adopt_uuid_bytes(uuid.to_be_bytes());
+        assert_eq!(adopted, AtomicId::<x128>::from_u128(uuid));
+        assert_eq!(adopted.len(), AtomicId::<x128>::new().len());
+        assert_eq!(Encoding::Base36.decode(&adopted), Ok(uuid));
+        assert_eq!(adopted.parse::<Id128>(), Ok(Id128(uuid)));
+        assert_eq!(AtomicId::<x128>::from_u128(u128::MAX).len(), 25);
+        assert_eq!(AtomicId::<x128>::from_u128(0), "0".repeat(25));
+    }
+
+    /// Test that UUID bytes round-trip and lead with the timestamp.
+    #[test]
+    fn test_uuid_bytes_round_trip() {
+        let _guard = guard();
+        let bytes = AtomicId::<128>::uuid_bytes();
+        let raw = AtomicId::<128>::from_uuid_bytes(bytes);
+        assert_eq!(raw.to_be_bytes(), bytes);
+
+        // The first four bytes are the 32-bit millisecond timestamp.
+        let ts = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64;
+        let now = xgen().timestamp() & 0xFFFFFFFF;
+        assert!(now.wrapping_sub(ts) < 60_000);
+    }
+
+    /// Test that peeking predicts exactly the next ID of each width without side
+    /// effects, and matches the layout.
+    #[test]
+    fn test_peek64() {
+        let _guard = guard();
+        let generator = IdGenerator::new(0xABC, 0x5D);
+        let peeked = generator.peek64();
+        assert_eq!((peeked >> 32) & 0xFFF, 0xABC);
+        assert_eq!((peeked >> 24) & 0xFF, 0x5D);
+        assert_eq!((peeked >> 16) & 0xFF, generator.thread_id() as u64);
+
+        // A clock behind the last timestamp seen is not reported until an ID is generated.
+        let (last, rollbacks) = (LAST_TIMESTAMP.load(Ordering::Relaxed), CLOCK_ROLLBACKS.load(Ordering::Relaxed));
+        LAST_TIMESTAMP.store(u64::MAX, Ordering::Relaxed);
+        generator.peek24();
+        generator.peek32();
+        generator.peek64();
+        generator.peek128();
+        generator.peek256();
+        assert_eq!(CLOCK_ROLLBACKS.load(Ordering::Relaxed), rollbacks);
+        assert_eq!(LAST_TIMESTAMP.load(Ordering::Relaxed), u64::MAX);
+        LAST_TIMESTAMP.store(last, Ordering::Relaxed);
+
+        AtomicOption::deterministic(42);
+        let check = || {
+            assert_eq!(generator.peek24(), generator.peek24());
+            assert_eq!(generator.peek24(), generator.gen24());
+            assert_eq!(generator.peek32(), generator.gen32());
+            assert_eq!(generator.peek64(), generator.peek64());
+            assert_eq!(generator.peek64(), generator.gen64());
+            assert_eq!(generator.peek128(), generator.peek128());
+            assert_eq!(generator.peek128(), generator.gen128());
+            assert_eq!(generator.peek256(), generator.gen256());
+        };
+        check();
+        AtomicOption::layout24(Layout24::TimeSalted);
+        AtomicOption::layout32(Layout32::TimeSalted);
+        AtomicOption::layout64(Layout64::WideNode);
+        AtomicOption::timestamp_precision(TimestampPrecision::Micros);
+        check();
+    }
+
+    /// Test that a guard applies overrides and restores the previous state on drop.
+    #[test]
+    #[cfg(not(feature = "const_epoch"))]
+    fn test_guard_scoped_overrides() {
+        use testing::{TestConfig, TestGuard};
+
+        let epoch = {
+            let _guard = TestGuard::scoped(TestConfig::default());
+            AtomicOption::get_epoch()
+        };
+        let overriding = {
+            let _guard = TestGuard::scoped(TestConfig {
+                epoch: Some(DEFAULT_EPOCH + 1000),
+                node_id: Some(0x123),
+                shard_id: Some(0x45),
+                reset_counters: true,
+                ..TestConfig::default()
+            });
+            assert_eq!(AtomicOption::get_epoch(), DEFAULT_EPOCH + 1000);
+            let id = xgen().gen64();
+            assert_eq!((id >> 32) & 0xFFF, 0x123);
+            assert_eq!((id >> 24) & 0xFF, 0x45);
+
+            testing::reset_all();
+            assert_eq!(AtomicOption::get_epoch(), DEFAULT_EPOCH);
+            alloc::sync::Arc::downgrade(&testing::generator_override().unwrap())
+        };
+        // The overriding generator is freed with its guard.
+        assert!(overriding.upgrade().is_none());
+        let _guard = TestGuard::scoped(TestConfig::default());
+        assert_eq!(AtomicOption::get_epoch(), epoch);
+        assert_eq!((xgen().gen64() >> 32) & 0xFFF, 1);
+    }
+
+    /// Test that reseeding changes the entropy stream, and that IDs generated while
+    /// other threads reseed concurrently stay unique.
+    #[test]
+    fn test_reseed() {
+        use testing::{TestConfig, TestGuard};
+
+        let run = |reseed: Option<u64>| {
+            let _guard = TestGuard::scoped(TestConfig {
+                deterministic: Some(42),
+                ..TestConfig::default()
+            });
+            let mut ids = AtomicId::<128>::batch(5);
+            if let Some(seed) = reseed {
+                AtomicOption::reseed(seed);
+            }
+            ids.extend(AtomicId::<128>::batch(5));
+            ids
+        };
+        let plain = run(None);
+        let reseeded = run(Some(7));
+        assert_eq!(reseeded, run(Some(7)));
+        assert_eq!(plain[..5], reseeded[..5]);
+        assert_ne!(plain[5..], reseeded[5..]);
+
+        let _guard = TestGuard::scoped(TestConfig {
+            deterministic: Some(42),
+            ..TestConfig::default()
+        });
+        let ids = std::thread::scope(|scope| {
+            scope.spawn(|| (0..1000).for_each(AtomicOption::reseed));
+            let workers: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| (0..2000).map(|_| xgen().gen128()).collect::<Vec<_>>()))
+                .collect();
+            workers.into_iter().flat_map(|w| w.join().unwrap()).collect::<Vec<_>>()
+        });
+        let unique: std::collections::HashSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), ids.len());
+    }
+
+    /// Test that two fresh deterministic runs produce identical IDs.
+    #[test]
+    fn test_deterministic_runs_match() {
+        use testing::{TestConfig, TestGuard};
+
+        let run = |seed| {
+            let _guard = TestGuard::scoped(TestConfig {
+                deterministic: Some(seed),
+                ..TestConfig::default()
+            });
+            let mut ids = AtomicId::<64>::batch(100);
+            ids.extend(AtomicId::<128>::base58_batch(10));
+            ids.extend(AtomicId::<256>::hex_batch(10));
+            ids
+        };
+        let first = run(42);
+        assert_eq!(first, run(42));
+        assert_ne!(first, run(43));
+
+        let unique: std::collections::HashSet<_> = first.iter().collect();
+        assert_eq!(unique.len(), first.len());
+    }
+
+    /// Test that node IDs beyond 4095 round-trip through the wide-node layout.
+    #[test]
+    fn test_wide_node_layout() {
+        let generator = IdGenerator::new(5000, 9);
+        {
+            let _guard = guard();
+            let parts = IdGenerator::decompose64_wide(generator.gen64_wide_node());
+            assert_eq!(parts.node_id, 5000);
+            assert_eq!(parts.shard_id, 9);
+            assert_eq!(parts.thread_id, generator.thread_id());
+        }
+
+        // The standard layout truncates the node to 12 bits.
+        let parts = IdGenerator::decompose64(generator.compose64(1, thread_seq64(2, 3, 8)));
+        assert_eq!(parts.node_id, 5000 & 0xFFF);
+        assert_eq!((parts.timestamp, parts.thread_id, parts.sequence), (1, 2, 3));
+
+        let _guard = testing::TestGuard::scoped(testing::TestConfig {
+            node_id: Some(5000),
+            ..Default::default()
+        });
+        AtomicOption::layout64(Layout64::WideNode);
+        let parts = Layout64::current().decompose(xgen().gen64());
+        assert_eq!(parts.node_id, 5000);
+    }
+
+    /// Test that an installed time source and thread entropy provider are used.
+    #[test]
+    fn test_time_source() {
+        let _guard = guard();
+        fn fixed_ms() -> u64 {
+            current_epoch() + 12_345
+        }
+        fn fixed_ns() -> u64 {
+            7
+        }
+        fn entropy() -> u64 {
+            42
+        }
+        AtomicOption::time_source(TimeSource {
+            now_ms: fixed_ms,
+            now_ns: fixed_ns,
+        });
+        AtomicOption::thread_entropy(entropy);
+
+        assert_eq!(IdGenerator::decompose64(xgen().gen64()).timestamp, 12_345);
+        let id = std::thread::spawn(|| xgen().thread_id()).join().unwrap();
+        assert_eq!(id as u16, fold_thread_id(mix64(42), 8));
+    }
+
+    /// Test that a reader never pairs the milliseconds of one time source with the
+    /// nanoseconds of another while sources are being swapped.
+    #[test]
+    fn test_time_source_swap() {
+        let _guard = guard();
+        fn a_ms() -> u64 {
+            1
+        }
+        fn a_ns() -> u64 {
+            1_000_000
+        }
+        fn b_ms() -> u64 {
+            2
+        }
+        fn b_ns() -> u64 {
+            2_000_000
+        }
+        let stop = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    AtomicOption::time_source(TimeSource { now_ms: a_ms, now_ns: a_ns });
+                    AtomicOption::time_source(TimeSource { now_ms: b_ms, now_ns: b_ns });
+                }
+            });
+            for _ in 0..100_000 {
+                let (ms, ns) = now_ms_ns();
+                assert_eq!(ns / 1_000_000, ms);
+            }
+            stop.store(true, Ordering::Relaxed);
+        });
+    }
+
+    /// Test that time-ordered 32-bit IDs carry the second and sort across seconds.
+    #[test]
+    fn test_gen32_timed() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        NOW.store(current_epoch() + 5_999, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let generator = xgen();
+        let (a, b) = (generator.gen32_timed(), generator.gen32_timed());
+        let (pa, pb) = (IdGenerator::decompose32(a), IdGenerator::decompose32(b));
+        assert_eq!((pa.timestamp, pb.timestamp), (5, 5));
+        assert_eq!(pb.sequence, (pa.sequence + 1) & 0xFFF);
+
+        NOW.fetch_add(1, Ordering::Relaxed);
+        let c = generator.gen32_timed();
+        assert_eq!(IdGenerator::decompose32(c).timestamp, 6);
+        assert!(c > a && c > b);
+
+        // Untimed 32-bit IDs draw from their own counter, so they use up none of
+        // the 4096 timed IDs of the second.
+        for _ in 0..5000 {
+            generator.gen32();
+        }
+        let d = generator.gen32_timed();
+        assert_eq!(IdGenerator::decompose32(d).sequence, (IdGenerator::decompose32(c).sequence + 1) & 0xFFF);
+        #[cfg(feature = "short")]
+        {
+            let id = AtomicId::<32>::timed();
+            assert_eq!(id.len(), 7);
+            let raw = encode::from_base36(&id).unwrap() as u32;
+            assert_eq!(IdGenerator::decompose32(raw).timestamp, 6);
+            assert_eq!(AtomicId::<32>::timed_with(Encoding::Base58).len(), 6);
+        }
+    }
+
+    /// Test that partitions and jump-hash buckets are near-uniform over generated IDs,
+    /// and that growing the bucket count only moves IDs into the new bucket.
+    #[test]
+    fn test_partition_of() {
+        let _guard = guard();
+        let generator = xgen();
+        let ids: Vec<u64> = (0..1_200_000).map(|_| generator.gen64()).collect();
+        let (mut partitions, mut buckets) = ([0u32; 12], [0u32; 12]);
+        let mut moved = 0;
+        for &id in &ids {
+            partitions[AtomicId::<64>::partition_of(id, 12) as usize] += 1;
+            let before = AtomicId::<64>::bucket_of(id, 11);
+            let after = AtomicId::<64>::bucket_of(id, 12);
+            buckets[after as usize] += 1;
+            if before != after {
+                assert_eq!(after, 11);
+                moved += 1;
+            }
+        }
+        // 100,000 per bucket on average; 2% is several standard deviations.
+        for count in partitions.iter().chain(&buckets) {
+            assert!(count.abs_diff(100_000) < 2_000, "{:?} {:?}", partitions, buckets);
+        }
+        assert_eq!(moved, buckets[11]);
+
+        let id = encode::base36(ids[0] as u128, 13);
+        let partition = AtomicId::<64>::partition_of_str(&id, 12);
+        assert_eq!(partition, AtomicId::<64>::partition_of(hash::fnv1a64(&[id.as_bytes()]), 12));
+        assert_eq!(AtomicId::<64>::bucket_of(ids[0], 1), 0);
+    }
+
+    /// Test RFC 3339 parsing, including leap years, offsets, and invalid strings.
+    #[test]
+    fn test_parse_rfc3339() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Ok(0));
+        assert_eq!(parse_rfc3339("2024-01-01T00:00:00Z"), Ok(1704067200000));
+        assert_eq!(parse_rfc3339("2022-05-01T00:00:00Z"), Ok(DEFAULT_EPOCH));
+        assert_eq!(parse_rfc3339("2024-01-01t00:00:00.5z"), Ok(1704067200500));
+        assert_eq!(parse_rfc3339("2024-01-01T00:00:00.123456Z"), Ok(1704067200123));
+        assert_eq!(parse_rfc3339("2024-01-01T02:30:00+02:30"), Ok(1704067200000));
+        assert_eq!(parse_rfc3339("2023-12-31T21:00:00-03:00"), Ok(1704067200000));
+        assert_eq!(parse_rfc3339("2016-12-31T23:59:60Z"), parse_rfc3339("2017-01-01T00:00:00Z"));
+
+        // Leap years: divisible by 4, except centuries not divisible by 400.
+        assert_eq!(parse_rfc3339("2024-02-29T00:00:00Z"), Ok(1709164800000));
+        assert_eq!(parse_rfc3339("2000-02-29T00:00:00Z"), Ok(951782400000));
+        assert_eq!(parse_rfc3339("2023-02-29T00:00:00Z"), Err(EpochParseError::FieldOutOfRange));
+        assert_eq!(parse_rfc3339("2100-02-29T00:00:00Z"), Err(EpochParseError::FieldOutOfRange));
+        assert_eq!(parse_rfc3339("2024-03-01T00:00:00Z"), Ok(1709251200000));
+
+        for invalid in [
+            "",
+            "2024-01-01",
+            "2024-01-01T00:00:00",
+            "2024-01-01 00:00:00Z",
+            "2024/01/01T00:00:00Z",
+            "2024-1-01T00:00:00Z",
+            "2024-01-01T00:00:00.Z",
+            "2024-01-01T00:00:00+0200",
+            "2024-01-01T00:00:00Zjunk",
+            "+2024-01-01T00:00:00Z",
+            "２024-01-01T00:00:00Z",
+        ] {
+            assert_eq!(parse_rfc3339(invalid), Err(EpochParseError::Syntax), "{:?}", invalid);
+        }
+        for out_of_range in [
+            "2024-13-01T00:00:00Z",
+            "2024-00-01T00:00:00Z",
+            "2024-04-31T00:00:00Z",
+            "2024-01-00T00:00:00Z",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:60:00Z",
+            "2024-01-01T00:00:61Z",
+            "2024-01-01T00:00:00+24:00",
+        ] {
+            assert_eq!(parse_rfc3339(out_of_range), Err(EpochParseError::FieldOutOfRange), "{:?}", out_of_range);
+        }
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), Err(EpochParseError::BeforeUnixEpoch));
+        assert_eq!(parse_rfc3339("1970-01-01T00:30:00+01:00"), Err(EpochParseError::BeforeUnixEpoch));
+    }
+
+    /// Test that the epoch setters reject future timestamps and round-trip `SystemTime`.
+    #[test]
+    #[cfg(not(feature = "const_epoch"))]
+    fn test_epoch_time() {
+        let _guard = guard();
+        assert_eq!(AtomicOption::epoch_rfc3339("2024-01-01T00:00:00Z"), Ok(()));
+        assert_eq!(AtomicOption::get_epoch(), 1704067200000);
+        assert_eq!(AtomicOption::epoch_rfc3339("9999-01-01T00:00:00Z"), Err(EpochParseError::InFuture));
+        assert_eq!(AtomicOption::get_epoch(), 1704067200000);
+
+        let t = UNIX_EPOCH + Duration::from_millis(1704067200123);
+        assert_eq!(AtomicOption::epoch_time(t), Ok(()));
+        assert_eq!(AtomicOption::get_epoch_time(), t);
+        let future = SystemTime::now() + Duration::from_secs(3600);
+        assert_eq!(AtomicOption::epoch_time(future), Err(EpochParseError::InFuture));
+        let before = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(AtomicOption::epoch_time(before), Err(EpochParseError::BeforeUnixEpoch));
+        assert_eq!(AtomicOption::get_epoch(), 1704067200123);
+    }
+
+    /// Test time bucketing of raw and encoded 64-bit IDs over a synthetic time range.
+    #[test]
+    fn test_time_bucket64() {
+        let generator = IdGenerator::new(4095, 255);
+        let minute = Duration::from_secs(60);
+        let mut previous = 0;
+        for ts in (0..1 << 20).step_by(997) {
+            let id = generator.compose64(ts, thread_seq64(200, ts, 8));
+            let bucket = IdGenerator::time_bucket64(id, minute);
+            assert_eq!(bucket, ts / 60_000 * 60_000);
+            assert!(bucket >= previous);
+            previous = bucket;
+            assert_eq!(IdGenerator::time_bucket64(id, Duration::from_millis(1)), ts);
+
+            let encoded = encode::base36(id as u128, 13);
+            assert_eq!(IdGenerator::time_bucket64_from_str(&encoded, minute), Ok(bucket));
+        }
+        assert_eq!(previous, 17 * 60_000);
+        assert_eq!(IdGenerator::time_bucket64(u64::MAX, Duration::from_secs(3600)), 0);
+        assert_eq!(IdGenerator::time_bucket64_from_str("", minute), Err(IdError::Empty));
+        assert_eq!(IdGenerator::time_bucket64_from_str("zzzzzzzzzzzzzz", minute), Err(IdError::Overflow));
+        assert_eq!(IdGenerator::time_bucket64_from_str("ABC", minute), Err(IdError::InvalidCharacter('A')));
+    }
+
+    /// Test that a generator built with its own epoch ignores the global one.
+    #[test]
+    fn test_generator_epoch() {
+        fn now_ms() -> u64 {
+            DEFAULT_EPOCH + 600_000
+        }
+        let _guard = guard();
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let tenant = IdGenerator::builder()
+            .node_id(7)
+            .shard_id(3)
+            .epoch_ms(DEFAULT_EPOCH + 100_000)
+            .build();
+        assert_eq!((tenant.node_id, tenant.shard_id), (7, 3));
+        assert_eq!(tenant.epoch_ms(), DEFAULT_EPOCH + 100_000);
+        assert_eq!(IdGenerator::new(1, 0).epoch_ms(), AtomicOption::get_epoch());
+
+        let parts = IdGenerator::decompose64(tenant.gen64());
+        assert_eq!((parts.timestamp, parts.node_id, parts.shard_id), (500_000, 7, 3));
+        assert_eq!(tenant.gen128() >> 96, 500_000);
+        #[cfg(not(feature = "const_epoch"))]
+        {
+            AtomicOption::force_epoch_unchecked(DEFAULT_EPOCH + 200_000);
+            assert_eq!(IdGenerator::decompose64(xgen().gen64()).timestamp, 400_000);
+        }
+        assert_eq!(IdGenerator::decompose64(tenant.gen64()).timestamp, 500_000);
+    }
+
+    /// Test that the era layout keeps IDs increasing across a timestamp wrap.
+    #[test]
+    fn test_era_rollover() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        AtomicOption::layout64(Layout64::Era);
+        let generator = IdGenerator::new(4095, 31);
+        let epoch = AtomicOption::get_epoch();
+        let mut previous = 0;
+        for elapsed in (1 << 20) - 3..(1 << 20) + 3 {
+            NOW.store(epoch + elapsed, Ordering::Relaxed);
+            let id = generator.gen64();
+            assert!(id > previous);
+            previous = id;
+            let parts = Layout64::Era.decompose(id);
+            assert_eq!(parts.era, (elapsed >> 20) as u8);
+            assert_eq!(parts.timestamp, elapsed & 0xFFFFF);
+            assert_eq!((parts.node_id, parts.shard_id), (4095, 31));
+            assert_eq!(generator.created_at64(id), epoch + elapsed);
+            assert_eq!(IdGenerator::time_bucket64(id, Duration::from_millis(1)), elapsed);
+        }
+        assert_eq!(Layout64::Era.timestamp_bits(), 23);
+        assert_eq!(Layout64::Standard.elapsed_ms(previous), ((1 << 20) + 2) >> 3);
+    }
+
+    /// Test that monotonic 128-bit IDs are strictly increasing, within and across threads.
+    #[test]
+    fn test_gen128_monotonic() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        let generator = IdGenerator::new(4095, 15);
+        let mut previous = generator.gen128_monotonic();
+        for _ in 0..10_000 {
+            let id = generator.gen128_monotonic();
+            assert!(id > previous);
+            assert_eq!((id >> 64) & 0xFFFF, 0xFFFF);
+            previous = id;
+        }
+
+        // A clock that steps back does not move the timestamp back.
+        let epoch = AtomicOption::get_epoch();
+        NOW.store(epoch + (previous >> 80) as u64 + 1000, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let ahead = generator.gen128_monotonic();
+        NOW.store(epoch + (previous >> 80) as u64 + 10, Ordering::Relaxed);
+        let behind = generator.gen128_monotonic();
+        assert!(behind > ahead && ahead > previous);
+        assert_eq!(behind >> 80, ahead >> 80);
+        AtomicOption::clear_time_source();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(move || {
+                    let generator = IdGenerator::new(4095, 15);
+                    let ids: Vec<u128> = (0..10_000).map(|_| generator.gen128_monotonic()).collect();
+                    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+                    ids
+                })
+            })
+            .collect();
+        let mut all = std::collections::HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(id > behind);
+                assert!(all.insert(id as u64));
+            }
+        }
+    }
+
+    /// Test that the generation hook sees every width and is reset by the guard.
+    #[test]
+    fn test_on_generate() {
+        static SEEN: AtomicU64 = AtomicU64::new(0);
+        static LAST_64: AtomicU64 = AtomicU64::new(0);
+        static LAST_256: std::sync::Mutex<Option<[u64; 4]>> = std::sync::Mutex::new(None);
+        fn hook(bits: usize, raw: u128) {
+            SEEN.fetch_or(bits as u64, Ordering::Relaxed);
+            if bits == 64 {
+                LAST_64.store(raw as u64, Ordering::Relaxed);
+            }
+        }
+        fn hook256(id: [u64; 4]) {
+            *LAST_256.lock().unwrap() = Some(id);
+        }
+        {
+            let _guard = guard();
+            AtomicOption::on_generate(hook);
+            let generator = xgen();
+            generator.gen24();
+            generator.gen32();
+            let id = generator.gen64();
+            assert_eq!(LAST_64.load(Ordering::Relaxed), id);
+            let child = generator.gen64_child(id);
+            assert_eq!(LAST_64.load(Ordering::Relaxed), child);
+            generator.gen128();
+            generator.gen256();
+            assert_eq!(SEEN.load(Ordering::Relaxed), 24 | 32 | 64 | 128 | 256);
+
+            AtomicOption::on_generate256(hook256);
+            let id = generator.gen256();
+            assert_eq!(LAST_256.lock().unwrap().unwrap(), id);
+        }
+        let _guard = guard();
+        SEEN.store(0, Ordering::Relaxed);
+        *LAST_256.lock().unwrap() = None;
+        xgen().gen64();
+        xgen().gen256();
+        assert_eq!(SEEN.load(Ordering::Relaxed), 0);
+        assert_eq!(*LAST_256.lock().unwrap(), None);
+    }
+
+    /// Test that trace and span IDs are never all zero, even from all-zero inputs.
+    #[test]
+    fn test_trace_ids() {
+        let _guard = guard();
+        for _ in 0..1000 {
+            assert_ne!(trace_id(), [0; 16]);
+            assert_ne!(span_id(), [0; 8]);
+        }
+        let (trace, span) = (trace_id_hex(), span_id_hex());
+        assert_eq!((trace.len(), span.len()), (32, 16));
+        assert!(trace.chars().chain(span.chars()).all(|c| matches!(c, '0'..='9' | 'a'..='f')));
+        assert_eq!(span_id_from(0), [0; 8]);
+
+        // Epoch timestamp, zero nanoseconds, node 0, shard 0, and zero thread entropy:
+        // only the thread ID field keeps the IDs nonzero.
+        fn epoch_ms() -> u64 {
+            current_epoch()
+        }
+        fn zero() -> u64 {
+            0
+        }
+        AtomicOption::time_source(TimeSource { now_ms: epoch_ms, now_ns: zero });
+        AtomicOption::thread_entropy(zero);
+        let (trace, span) = std::thread::spawn(|| {
+            let generator = IdGenerator::new(0, 0);
+            (trace_id_from(generator.gen128()), span_id_from(generator.gen64()))
+        })
+        .join()
+        .unwrap();
+        assert_ne!(trace, [0; 16]);
+        assert_ne!(span, [0; 8]);
+    }
+
+    /// Test that 300 sequentially numbered threads with 9 thread bits never collide,
+    /// and that their IDs decompose at 9 bits after the setting changes.
+    #[test]
+    fn test_sequential_thread_ids() {
+        let _guard = guard();
+        AtomicOption::thread_id_bits(9);
+        AtomicOption::thread_id_mode(ThreadIdMode::Sequential);
+
+        let ids: Vec<u64> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..300)
+                .map(|_| scope.spawn(|| (0..100).map(|_| xgen().gen64()).collect::<Vec<_>>()))
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+
+        let unique: std::collections::HashSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), ids.len());
+        let threads: std::collections::HashSet<_> = ids
+            .iter()
+            .map(|&id| IdGenerator::decompose64(id).wide_thread_id)
+            .collect();
+        assert_eq!(threads.len(), 300);
+        assert_eq!(AtomicOption::get_thread_id_bits(), 9);
+
+        // IDs read back at their own width once the setting has moved on.
+        let parts = IdGenerator::decompose64(ids[299]);
+        assert_eq!(parts.thread_id, parts.wide_thread_id as u8);
+        AtomicOption::thread_id_bits(12);
+        assert_eq!(Layout64::Standard.decompose_with_thread_bits(ids[299], 9), parts);
+        assert_ne!(IdGenerator::decompose64(ids[299]), parts);
+    }
+
+    /// Test that IDs carry a layout version and decompose in either mode.
+    #[test]
+    fn test_version_bits() {
+        let _guard = guard();
+        assert!(!AtomicOption::get_version_bits());
+        AtomicOption::version_bits(true);
+        let generator = IdGenerator::new(4095, 255);
+        let id = generator.gen64();
+        assert_eq!(id & 0b11, 1);
+        assert_eq!(IdGenerator::layout_version(id), 1);
+        assert_eq!(IdGenerator::layout_version128(generator.gen128()), 1);
+        let parts = IdGenerator::decompose64(generator.compose64(9, thread_seq64(100, 0x3FFF, 8)));
+        assert_eq!((parts.timestamp, parts.thread_id, parts.sequence, parts.version), (9, 100, 0x3FFF, 1));
+        let versioned = generator.compose64(9, thread_seq64(100, 0x3FFF, 8));
+        let child = generator.gen64_child(id);
+        assert_eq!(IdGenerator::decompose64(child).version, 1);
+        let first = IdGenerator::decompose64(generator.gen64()).sequence;
+        assert_eq!(IdGenerator::decompose64(generator.gen64()).sequence, (first + 1) & 0x3FFF);
+
+        AtomicOption::version_bits(false);
+        assert!(!AtomicOption::get_version_bits());
+        let parts = IdGenerator::decompose64(generator.compose64(9, thread_seq64(100, 0xFFFF, 8)));
+        assert_eq!((parts.timestamp, parts.thread_id, parts.sequence, parts.version), (9, 100, 0xFFFF, 0));
+        assert_eq!(generator.compose64(9, thread_seq64(100, 0xFFFF, 8)) & 0xFFFFFF, 100 << 16 | 0xFFFF);
+        // A reader without the flag takes a version-1 ID's version bits for sequence bits.
+        let parts = IdGenerator::decompose64(versioned);
+        assert_eq!((parts.timestamp, parts.thread_id, parts.sequence, parts.version), (9, 100, 0x3FFF << 2 | 1, 0));
+        assert_eq!((parts.node_id, parts.shard_id), (4095, 255));
+        assert_eq!(IdGenerator::layout_version(id), 0);
+        assert!((0..64).all(|_| IdGenerator::layout_version(generator.gen64()) == 0));
+        let created = generator.created_at64(id);
+        AtomicOption::version_bits(true);
+        assert_eq!(generator.created_at64(id), created);
+    }
+
+    /// Test each overflow mode at the wrap boundary of every timestamp field.
+    #[test]
+    fn test_overflow_mode() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        static NEAR_WRAP: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        fn on_event(event: IdEvent) {
+            if let IdEvent::TimestampNearWrap { width: 64, .. } = event {
+                NEAR_WRAP.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let _guard = guard();
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let epoch = AtomicOption::get_epoch();
+        let generator = IdGenerator::new(1, 0);
+        let field_at = |elapsed: u64| {
+            NOW.store(epoch + elapsed, Ordering::Relaxed);
+            IdGenerator::decompose64(generator.gen64()).timestamp
+        };
+        let field128_at = |elapsed: u64| {
+            NOW.store(epoch + elapsed, Ordering::Relaxed);
+            (generator.gen128() >> 96) as u64
+        };
+        let field32_at = |elapsed: u64| {
+            NOW.store(epoch + elapsed, Ordering::Relaxed);
+            IdGenerator::decompose32(generator.gen32_timed()).timestamp
+        };
+        let checked_at = |elapsed: u64| {
+            NOW.store(epoch + elapsed, Ordering::Relaxed);
+            generator.gen64_checked().map(|id| Layout64::current().decompose(id).timestamp)
+        };
+
+        assert_eq!(AtomicOption::get_overflow_mode(), OverflowMode::Wrap);
+        assert_eq!(field_at((1 << 20) - 1), (1 << 20) - 1);
+        assert_eq!(field_at((1 << 20) + 5), 5);
+        assert_eq!(field128_at((1 << 32) + 5), 5);
+        assert_eq!(field32_at(((1 << 20) + 5) * 1000), 5);
+        assert!(matches!(checked_at((1 << 20) - 1), Err(IdError::TimestampNearWrap(_))));
+        assert_eq!(checked_at((1 << 20) + 5), Ok(5));
+
+        AtomicOption::on_overflow(OverflowMode::Saturate);
+        assert_eq!(field_at((1 << 20) - 1), (1 << 20) - 1);
+        assert_eq!(field_at((1 << 20) + 5), (1 << 20) - 1);
+        assert_eq!(field128_at((1 << 32) + 5), (1 << 32) - 1);
+        assert_eq!(field32_at(((1 << 20) + 5) * 1000), (1 << 20) - 1);
+        assert_eq!(checked_at((1 << 20) - 1), Ok((1 << 20) - 1));
+        assert_eq!(checked_at((1 << 20) + 5), Ok((1 << 20) - 1));
+
+        AtomicOption::on_overflow(OverflowMode::Error);
+        assert_eq!(checked_at((1 << 20) - 1), Ok((1 << 20) - 1));
+        assert_eq!(checked_at(1 << 20), Err(IdError::TimestampOverflow));
+        let sequence = SEQ_64.load(Ordering::Relaxed);
+        assert_eq!(checked_at((1 << 20) + 5), Err(IdError::TimestampOverflow));
+        assert_eq!(SEQ_64.load(Ordering::Relaxed), sequence);
+        // The infallible generators wrap, and warn first at 64 bits.
+        assert_eq!(field_at((1 << 20) + 5), 5);
+        assert_eq!(field128_at((1 << 32) + 5), 5);
+        assert_eq!(field32_at(((1 << 20) + 5) * 1000), 5);
+        AtomicOption::on_event(on_event);
+        field_at((2 << 20) - 1);
+        AtomicOption::clear_on_event();
+        assert_eq!(NEAR_WRAP.load(Ordering::Relaxed), 1);
+        AtomicOption::layout64(Layout64::Era);
+        assert_eq!(checked_at((1 << 20) + 5), Ok(5));
+        assert_eq!(checked_at(1 << 23), Err(IdError::TimestampOverflow));
+    }
+
+    /// Test that the time-salted 32-bit layout keeps IDs apart across a sequence wrap
+    /// that repeats them in the standard layout, that thread IDs use the full byte, and
+    /// that a generator's own layout overrides the global one.
+    #[test]
+    fn test_layout32() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        let generator = IdGenerator::new(1, 0);
+        let mut thread_ids = std::collections::HashSet::new();
+        for entropy in 0..256u64 {
+            thread_ids.insert(fold_thread_id_full(mix64(entropy), 8));
+        }
+        assert!(thread_ids.iter().any(|&t| t > 128) && thread_ids.contains(&0));
+
+        SEQ_32.store(42, Ordering::Relaxed);
+        let first = generator.gen32();
+        SEQ_32.store(42 + (1 << 24), Ordering::Relaxed);
+        assert_eq!(generator.gen32(), first);
+        let parts = Layout32::Standard.decompose(first);
+        assert_eq!((parts.thread_id, parts.sequence), ((first >> 24) as u8, 42));
+
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        AtomicOption::layout32(Layout32::TimeSalted);
+        let epoch = AtomicOption::get_epoch();
+        NOW.store(epoch + 5_000, Ordering::Relaxed);
+        SEQ_32.store(42, Ordering::Relaxed);
+        let first = generator.gen32();
+        NOW.store(epoch + 6_000, Ordering::Relaxed);
+        SEQ_32.store(42 + (1 << 22), Ordering::Relaxed);
+        let wrapped = generator.gen32();
+        assert_ne!(wrapped, first);
+        let (a, b) = (Layout32::TimeSalted.decompose(first), Layout32::TimeSalted.decompose(wrapped));
+        assert_eq!((a.timestamp, a.sequence), (5, 42));
+        assert_eq!((b.timestamp, b.sequence), (6, 42));
+        NOW.store(epoch + 1_029_000, Ordering::Relaxed);
+        SEQ_32.store(42 + (2 << 22), Ordering::Relaxed);
+        assert_eq!(generator.gen32(), first);
+
+        // A generator built with its own layout ignores the global one.
+        let standard = IdGenerator::builder().node_id(1).layout32(Layout32::Standard).build();
+        assert_eq!(standard.layout32(), Layout32::Standard);
+        SEQ_32.store(42, Ordering::Relaxed);
+        assert_eq!(Layout32::Standard.decompose(standard.gen32()).sequence, 42);
+        AtomicOption::layout32(Layout32::Standard);
+        let salted = IdGenerator::builder().layout32(Layout32::TimeSalted).build();
+        SEQ_32.store(42, Ordering::Relaxed);
+        assert_eq!(salted.gen32(), first);
+    }
+
+    /// Test that generated parts match the decomposition of the packed ID in every layout.
+    #[test]
+    fn test_gen64_parts() {
+        static LAST: AtomicU64 = AtomicU64::new(0);
+        fn hook(_bits: usize, raw: u128) {
+            LAST.store(raw as u64, Ordering::Relaxed);
+        }
+        let _guard = guard();
+        AtomicOption::on_generate(hook);
+        let generator = IdGenerator::new(0xABCD, 0xEF);
+        let mut seen = std::collections::HashSet::new();
+        for layout in [Layout64::Standard, Layout64::WideNode, Layout64::Regional, Layout64::Era] {
+            AtomicOption::layout64(layout);
+            for _ in 0..1000 {
+                let parts = generator.gen64_parts();
+                assert_eq!(layout.decompose(LAST.load(Ordering::Relaxed)), parts);
+                assert!(seen.insert(LAST.load(Ordering::Relaxed)));
+                assert!(seen.insert(generator.gen64()));
+            }
+        }
+        AtomicOption::version_bits(false);
+        AtomicOption::thread_id_bits(12);
+        let parts = generator.gen64_parts();
+        assert_eq!(Layout64::Era.decompose(LAST.load(Ordering::Relaxed)), parts);
+        assert_eq!(parts.version, 0);
+    }
+
+    /// Test that constant-time equality agrees with `==` on equal and unequal IDs,
+    /// including IDs that differ in a single bit of any part.
+    #[test]
+    fn test_ct_eq() {
+        let _guard = guard();
+        let token = Id256::new();
+        assert!(token.ct_eq(&token));
+        assert!(token.ct_eq(&Id256(token.0)));
+        for bit in 0..256 {
+            let mut parts = token.0;
+            parts[bit / 64] ^= 1 << (bit % 64);
+            assert!(!token.ct_eq(&Id256(parts)));
+            assert!(!Id256(parts).ct_eq(&token));
+        }
+        assert!(Id256([0; 4]).ct_eq(&Id256([0; 4])));
+        assert!(!Id256([0, 0, 0, 1]).ct_eq(&Id256([1, 0, 0, 0])));
+
+        let id = Id128::new();
+        assert!(id.ct_eq(&Id128(id.0)));
+        for bit in 0..128 {
+            assert!(!id.ct_eq(&Id128(id.0 ^ (1 << bit))));
+        }
+        for pair in [Id128::new(), Id128::new(), Id128(0), Id128(u128::MAX)].windows(2) {
+            assert_eq!(pair[0].ct_eq(&pair[1]), pair[0] == pair[1]);
+        }
+    }
+
+    /// Test that the typed IDs parse from their `Display` and hex forms, reject other
+    /// lengths, and work with code generic over `FromStr`, as a config loader or a
+    /// `DisplayFromStr` deserializer would be.
+    #[test]
+    fn test_typed_id_from_str() {
+        let _guard = guard();
+        let (id64, id128, id256) = (Id64::new(), Id128::new(), Id256::new());
+        assert_eq!(id64.to_string().parse(), Ok(id64));
+        assert_eq!(id128.to_string().parse(), Ok(id128));
+        assert_eq!(id256.to_string().parse(), Ok(id256));
+        assert_eq!(encode::hex(id64.0 as u128, 16).parse(), Ok(id64));
+        assert_eq!(encode::hex(id128.0, 32).parse(), Ok(id128));
+        let hex256: String = id256.0.iter().map(|&p| encode::hex(p as u128, 16)).collect();
+        assert_eq!(hex256.parse(), Ok(id256));
+
+        assert_eq!(Id64::try_from("0000000000001"), Ok(Id64(1)));
+        assert_eq!(Id64::try_from(String::from("000000000000z")), Ok(Id64(35)));
+        assert_eq!(Id128::try_from("00000000000000000000000ff"), Ok(Id128(36 * 15 + 15)));
+        assert_eq!("123".parse::<Id64>(), Err(IdError::InvalidLength(3)));
+        assert_eq!("".parse::<Id256>(), Err(IdError::InvalidLength(0)));
+        assert_eq!("zzzzzzzzzzzzz".parse::<Id64>(), Err(IdError::Overflow));
+        assert_eq!("000000000000A".parse::<Id64>(), Err(IdError::InvalidCharacter('A')));
+        assert_eq!("00000000000000g0".parse::<Id64>(), Err(IdError::InvalidCharacter('g')));
+        let straddling = format!("{}é{}", "0".repeat(12), "0".repeat(38));
+        assert_eq!(straddling.parse::<Id256>(), Err(IdError::InvalidCharacter('é')));
+
+        assert_eq!(u64::from(id64), id64.0);
+        assert_eq!(Id128::from(id128.0), id128);
+        assert_eq!(<[u64; 4]>::from(id256), id256.0);
+
+        // A hand-rolled deserializer for `key = value` lines, generic over `FromStr`.
+        fn field<T: core::str::FromStr>(input: &str, key: &str) -> Result<T, String>
+        where
+            T::Err: core::fmt::Display,
+        {
+            let line = input
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.trim_start().strip_prefix('='))
+                .ok_or_else(|| format!("missing field `{}`", key))?;
+            line.trim().parse().map_err(|e: T::Err| format!("invalid `{}`: {}", key, e))
+        }
+        let config = format!("tenant = {}\nsession = {}\nbad = 12345\n", id64, id256);
+        assert_eq!(field::<Id64>(&config, "tenant"), Ok(id64));
+        assert_eq!(field::<Id256>(&config, "session"), Ok(id256));
+        assert_eq!(
+            field::<Id128>(&config, "bad"),
+            Err(String::from("invalid `bad`: wrong length for an ID: 5 bytes"))
+        );
+    }
+
+    /// Test that constant-time string comparison agrees with `==`, including on
+    /// prefixes, differing lengths, and a single differing byte at any position.
+    #[test]
+    fn test_constant_eq() {
+        let _guard = guard();
+        let token = AtomicId::<x256>::base58();
+        assert!(constant_eq(&token, &token.clone()));
+        assert!(constant_eq("", ""));
+        assert!(!constant_eq(&token, ""));
+        assert!(!constant_eq("", &token));
+        assert!(!constant_eq(&token[..43], &token));
+        assert!(!constant_eq(&token, &token[..43]));
+        assert!(!constant_eq("a", "a\0"));
+        for i in 0..token.len() {
+            let mut bytes = token.clone().into_bytes();
+            bytes[i] ^= 1;
+            let changed = String::from_utf8(bytes).unwrap();
+            assert!(!constant_eq(&changed, &token));
+        }
+        for pair in AtomicId::<x128>::batch(8).windows(2) {
+            assert_eq!(constant_eq(&pair[0], &pair[1]), pair[0] == pair[1]);
+        }
+    }
+
+    /// Test that compact 256-bit IDs round-trip, agree with the 128-bit encoder, and
+    /// drop leading zeros.
+    #[test]
+    fn test_compact256() {
+        let _guard = guard();
+        let max = [u64::MAX; 4];
+        let longest = encode::to_base_256(max, encode::BASE58, 0);
+        assert_eq!(longest.len(), 44);
+        assert_eq!(AtomicId::<x256>::from_compact(&longest), Ok(max));
+        assert_eq!(encode::to_base_256([0; 4], encode::BASE58, 0), "1");
+        assert_eq!(AtomicId::<x256>::from_compact("1"), Ok([0; 4]));
+        assert_eq!(AtomicId::<x256>::from_compact(""), Err(IdError::Empty));
+        assert_eq!(AtomicId::<x256>::from_compact("0"), Err(IdError::InvalidCharacter('0')));
+        assert_eq!(AtomicId::<x256>::from_compact(&"z".repeat(44)), Err(IdError::Overflow));
+
+        let raw = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210_u128;
+        let small = [0, 0, (raw >> 64) as u64, raw as u64];
+        let short = encode::to_base_256(small, encode::BASE58, 0);
+        assert_eq!(short, encode::base58(raw, 0));
+        assert!(short.len() < 44);
+        assert_eq!(AtomicId::<x256>::from_compact(&short), Ok(small));
+
+        let wide = [1 << 63 | 1, 2, 3, 4];
+        let encoded = encode::to_base_256(wide, encode::BASE58, 0);
+        assert_eq!(AtomicId::<x256>::from_compact(&encoded), Ok(wide));
+        for id in AtomicId::<x256>::compact_batch(100) {
+            let parts = AtomicId::<x256>::from_compact(&id).unwrap();
+            assert_eq!(encode::to_base_256(parts, encode::BASE58, 0), id);
+        }
+    }
+
+    /// Test grouped formatting, its validation against each alphabet, and that the
+    /// grouped hex of every typed ID parses back.
+    #[test]
+    fn test_format_grouped() {
+        let _guard = guard();
+        assert_eq!(format_grouped("0123456789", 4, '-'), "0123-4567-89");
+        assert_eq!(format_grouped("01234567", 4, ' '), "0123 4567");
+        assert_eq!(format_grouped("0123", 0, '-'), "0123");
+        assert_eq!(format_grouped("", 4, '-'), "");
+
+        let raw = 0x0123_4567_89ab_cdef_u128;
+        for encoding in [Encoding::Base36, Encoding::Base58, Encoding::Base91, Encoding::Hex] {
+            let id = encoding.encode(raw, 0);
+            let grouped = encoding.format_grouped(&id, 4, ' ').unwrap();
+            assert_eq!(encoding.parse_lenient(&grouped, &[' ']), Ok(raw));
+        }
+        assert_eq!(Encoding::Base91UrlSafe.format_grouped("ab", 1, '-'), Err(IdError::SeparatorInAlphabet('-')));
+        assert_eq!(Encoding::Base91.format_grouped("ab", 1, '.'), Err(IdError::SeparatorInAlphabet('.')));
+        assert_eq!(Encoding::Hex.format_grouped("ab", 1, 'F'), Err(IdError::SeparatorInAlphabet('F')));
+        assert_eq!(Encoding::Base91UrlSafe.parse_lenient("ab", &['-']), Err(IdError::SeparatorInAlphabet('-')));
+
+        let id = Id64(0x0a1b_2c3d_4e5f_6071);
+        assert_eq!(id.to_grouped_hex(), "0a1b2c3d-4e5f6071");
+        assert_eq!(Id64::from_grouped_hex(" 0A1B2C3D-4E5F6071 "), Ok(id));
+        assert_eq!(Id64::from_grouped_hex("1-00000000-00000000"), Err(IdError::Overflow));
+
+        let id = Id128(raw << 64 | raw);
+        assert_eq!(id.to_grouped_hex(), "01234567-89abcdef-01234567-89abcdef");
+        assert_eq!(Id128::from_grouped_hex(&id.to_grouped_hex().to_uppercase()), Ok(id));
+
+        let id = Id256([1, 2, u64::MAX, 0x0a1b_2c3d_4e5f_6071]);
+        let grouped = id.to_grouped_hex();
+        assert_eq!(grouped.matches('-').count(), 7);
+        assert!(grouped.starts_with("00000000-00000001-00000000-00000002-ffffffff-ffffffff-"));
+        assert_eq!(Id256::from_grouped_hex(&grouped), Ok(id));
+        assert_eq!(Id256::from_grouped_hex(&format!("\n{}\t", grouped.to_uppercase())), Ok(id));
+        assert_eq!(Id256::from_grouped_hex("ff"), Ok(Id256([0, 0, 0, 0xff])));
+        assert_eq!(Id256::from_grouped_hex(&"1".repeat(65)), Err(IdError::Overflow));
+        assert_eq!(Id256::from_grouped_hex("00é0"), Err(IdError::InvalidCharacter('é')));
+        let fresh = Id256::new();
+        assert_eq!(Id256::from_grouped_hex(&fresh.to_grouped_hex()), Ok(fresh));
+    }
+
+    /// Test that lenient decoding accepts mixed case, padding, and separators, and
+    /// agrees with strict decoding of the canonical string.
+    #[test]
+    fn test_parse_lenient() {
+        let raw = 0x0123_4567_89ab_cdef_u128;
+        let canonical = Encoding::Base36.encode(raw, 13);
+        let shouted = canonical.to_ascii_uppercase();
+        for input in [
+            canonical.clone(),
+            shouted.clone(),
+            format!("\t {}\r\n", shouted),
+            format!("{}-{}-{}", &canonical[..4], &shouted[4..8], &canonical[8..]),
+            format!(" {} {} ", &canonical[..6], &canonical[6..]),
+        ] {
+            assert_eq!(Encoding::Base36.parse_lenient(&input, &['-', ' ']), Ok(raw));
+        }
+        assert_eq!(Encoding::Hex.parse_lenient("0123 4567 89AB CDEF", &[' ']), Ok(raw));
+        assert_eq!(Encoding::Hex.parse_lenient("01234567_89ABcdef", &['_']), Ok(raw));
+        assert_eq!(Encoding::Hex.decode("0123456789ABCDEF"), Err(IdError::InvalidCharacter('A')));
+        assert_eq!(Encoding::Hex.parse_lenient("01-23", &[]), Err(IdError::InvalidCharacter('-')));
+        assert_eq!(Encoding::Hex.parse_lenient("  ", &[]), Err(IdError::Empty));
+
+        let base58 = Encoding::Base58.encode(raw, 11);
+        let grouped = format!(" {}-{} ", &base58[..5], &base58[5..]);
+        assert_eq!(Encoding::Base58.parse_lenient(&grouped, &['-']), Ok(raw));
+        assert_ne!(Encoding::Base58.parse_lenient(&base58.to_ascii_lowercase(), &[]), Ok(raw));
+    }
+
+    /// Test that encoded IDs compare and canonicalize by value, across case and
+    /// padding, and that strings in another encoding are refused.
+    #[test]
+    fn test_encoded_eq_canonicalize() {
+        let raw = 0x0123_4567_89ab_cdef_u128;
+        let padded = Encoding::Base36.encode(raw, 13);
+        let compact = padded.trim_start_matches('0');
+        assert_ne!(compact, padded);
+        for other in [compact.to_string(), padded.to_ascii_uppercase(), format!(" {}\n", compact)] {
+            assert_eq!(Encoding::Base36.encoded_eq(&padded, &other), Ok(true));
+            assert_eq!(Encoding::Base36.canonicalize(&other, 64), Ok(padded.clone()));
+        }
+        assert_eq!(Encoding::Base36.encoded_eq(&padded, "1"), Ok(false));
+        assert_eq!(Encoding::Hex.encoded_eq("00000000000000000000000000000000ff", "FF"), Ok(true));
+        assert_eq!(Encoding::Hex.canonicalize("0FF", 128), Ok(format!("{:032x}", 0xff)));
+
+        // Base58 tells case apart, so case differences are different IDs.
+        let base58 = Encoding::Base58.encode(raw, 11);
+        assert_eq!(Encoding::Base58.encoded_eq(&base58, &base58[1..]), Ok(base58.starts_with('1')));
+        assert_ne!(Encoding::Base58.encoded_eq(&base58, &base58.to_ascii_lowercase()), Ok(true));
+
+        // Cross-encoding mismatches.
+        assert_eq!(Encoding::Hex.encoded_eq("0n3x8q", "ff"), Err(IdError::InvalidCharacter('n')));
+        assert_eq!(Encoding::Base58.canonicalize("0x1f", 64), Err(IdError::InvalidCharacter('0')));
+        assert_eq!(Encoding::Hex.canonicalize("", 64), Err(IdError::Empty));
+
+        // Widths.
+        assert_eq!(Encoding::Hex.canonicalize("1ffffffff", 32), Err(IdError::Overflow));
+        assert_eq!(Encoding::Hex.canonicalize(&"f".repeat(33), 128), Err(IdError::Overflow));
+        assert_eq!(Encoding::Hex.canonicalize("ff", 48), Err(IdError::UnsupportedWidth(48)));
+        let parts = [1, 2, 3, 4];
+        let canonical = AtomicId::<x256>::to_canonical(parts, Encoding::Base58);
+        let compact = canonical.trim_start_matches('1');
+        assert_eq!(Encoding::Base58.canonicalize(compact, 256), Ok(canonical.clone()));
+        assert_eq!(Encoding::Base58.encoded_eq(compact, &canonical), Ok(true));
+    }
+
+    /// Test the 24-bit overflow policies at the exact wrap boundary, and the time-salted layout.
+    #[cfg(feature = "short")]
+    #[test]
+    fn test_overflow_policy24() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        SEQ_24.store((1 << 24) - 2, Ordering::Relaxed);
+        assert_eq!(AtomicId::<24>::remaining_before_wrap(), 2);
+        assert_eq!(AtomicId::<24>::try_new().unwrap(), encode::base36((1 << 24) - 2, 5));
+        assert_eq!(AtomicId::<24>::try_new().unwrap(), encode::base36((1 << 24) - 1, 5));
+        assert_eq!(AtomicId::<24>::remaining_before_wrap(), 1 << 24);
+        assert_eq!(AtomicId::<24>::try_new().unwrap(), "00000");
+
+        AtomicOption::overflow_policy24(OverflowPolicy::Error);
+        SEQ_24.store((1 << 24) - 1, Ordering::Relaxed);
+        assert_eq!(AtomicId::<24>::remaining_before_wrap(), 1);
+        assert_eq!(AtomicId::<24>::try_new().unwrap(), encode::base36((1 << 24) - 1, 5));
+        assert_eq!(AtomicId::<24>::remaining_before_wrap(), 0);
+        assert_eq!(AtomicId::<24>::try_new(), Err(SequenceExhausted));
+        assert_eq!(SEQ_24.load(Ordering::Relaxed), 1 << 24);
+        AtomicOption::reset24();
+        assert_eq!(AtomicId::<24>::remaining_before_wrap(), 1 << 24);
+        assert_eq!(AtomicId::<24>::try_new().unwrap(), "00000");
+
+        AtomicOption::overflow_policy24(OverflowPolicy::Block);
+        SEQ_24.store((2 << 24) - 1, Ordering::Relaxed);
+        assert_eq!(xgen().try_gen24(), Ok((1 << 24) - 1));
+        let waiter = std::thread::spawn(|| xgen().try_gen24());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        AtomicOption::reset24();
+        assert_eq!(waiter.join().unwrap(), Ok(0));
+
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        NOW.store(AtomicOption::get_epoch() + 300_000, Ordering::Relaxed);
+        AtomicOption::layout24(Layout24::TimeSalted);
+        AtomicOption::overflow_policy24(OverflowPolicy::Wrap);
+        SEQ_24.store((1 << 16) - 1, Ordering::Relaxed);
+        assert_eq!(AtomicId::<24>::remaining_before_wrap(), 1);
+        assert_eq!(xgen().gen24(), (300 % 256) << 16 | 0xFFFF);
+        assert_eq!(xgen().gen24(), (300 % 256) << 16);
     }
-    /// Generate a batch of 32-bit IDs as hexadecimal strings.
-    pub fn hex_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::hex()).collect()
+
+    /// Test that the thread ID salt changes hashed thread IDs by a fixed mask, keeps
+    /// them when it is 0, and tells apart processes whose threads hash alike.
+    #[test]
+    fn test_thread_id_salt() {
+        let _guard = guard();
+        let generator = IdGenerator::new(1, 1);
+        AtomicOption::__reset_thread_ids();
+        let unsalted = generator.thread_id_in(16);
+
+        AtomicOption::thread_id_salt(42);
+        assert_eq!(AtomicOption::get_thread_id_salt(), 42);
+        assert_eq!(generator.thread_id_in(16), unsalted, "cached IDs keep their salt");
+        AtomicOption::__reset_thread_ids();
+        let salted = generator.thread_id_in(16);
+        assert_eq!(salted - 1, (unsalted - 1) ^ (mix64(42) & 0x7fff) as u16);
+        AtomicOption::thread_id_salt(0);
+        AtomicOption::__reset_thread_ids();
+        assert_eq!(generator.thread_id_in(16), unsalted);
+
+        // Two processes whose threads report the same entropy.
+        fn same_core() -> u64 {
+            3
+        }
+        AtomicOption::thread_entropy(same_core);
+        let ids: Vec<u16> = [0, 1, 2, 1]
+            .iter()
+            .map(|&salt| {
+                AtomicOption::thread_id_salt(salt);
+                AtomicOption::__reset_thread_ids();
+                generator.thread_id_in(16)
+            })
+            .collect();
+        assert_eq!(ids[0], fold_thread_id(mix64(3), 16));
+        assert_ne!(ids[1], ids[0]);
+        assert_ne!(ids[2], ids[1]);
+        assert_eq!(ids[3], ids[1]);
+
+        AtomicOption::thread_id_mode(ThreadIdMode::Sequential);
+        AtomicOption::__reset_thread_ids();
+        assert_eq!(generator.thread_id_in(16), 1);
     }
-}
 
-impl AtomicId<64> {
-    /// Generate a new 64-bit ID, encoded as a 13-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x64};
-    /// let id = AtomicId::<x64>::new();
-    /// assert_eq!(id.len(), 13);
-    /// ```
-    pub fn new() -> String {
-        encode::base36(xgen().gen64() as u128, 13)
+    /// Test that anchored clock readings track the wall clock, never go back when the
+    /// wall clock falls behind, and catch up with it when re-anchored.
+    #[test]
+    fn test_clock_mode() {
+        let _guard = guard();
+        assert_eq!(AtomicOption::get_clock_mode(), ClockMode::Wall);
+        AtomicOption::clock_mode(ClockMode::MonotonicAnchored);
+        let (before, anchored) = (wall_ns(), now_ns());
+        assert!(anchored.abs_diff(before) < 1_000_000_000);
+
+        // Pretend the wall clock read an hour ahead at anchoring and then stepped back.
+        let hour = 3_600_000_000_000;
+        CLOCK_ANCHOR_NS.fetch_add(hour, Ordering::Relaxed);
+        let mut last = now_ns();
+        assert!(last >= wall_ns() + hour - 1_000_000_000);
+        for _ in 0..1000 {
+            let now = now_ns();
+            assert!(now >= last);
+            last = now;
+        }
+        assert_eq!(now_ms(), now_ns() / 1_000_000);
+
+        // Re-anchoring does not follow the wall clock back.
+        AtomicOption::clock_reanchor_interval(Duration::from_nanos(1));
+        assert_eq!(AtomicOption::get_clock_reanchor_interval(), Duration::from_nanos(1));
+        assert!(now_ns() >= last);
+
+        // But it catches up with a wall clock that moved ahead.
+        CLOCK_ANCHOR_NS.store(1, Ordering::Relaxed);
+        assert!(now_ns() >= wall_ns() - 1_000_000_000);
+        assert!(now_ns() < wall_ns() + 1_000_000_000);
+
+        AtomicOption::clock_mode(ClockMode::Wall);
+        let wall = wall_ns();
+        assert!(now_ns().abs_diff(wall) < 1_000_000_000);
     }
-    /// Generate a new 64-bit ID, encoded as an 11-character base58 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x64};
-    /// let id = AtomicId::<x64>::base58();
-    /// assert_eq!(id.len(), 11);
-    /// ```
-    pub fn base58() -> String {
-        encode::base58(xgen().gen64() as u128, 11)
+
+    /// Test that each timestamp precision moves bits between the time fields and the
+    /// sequence, and that IDs read back the time and sequence they were generated
+    /// with, including at the wrap of the time fields and of the sequence.
+    #[test]
+    fn test_timestamp_precision() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ns() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        fn now_ms() -> u64 {
+            now_ns() / 1_000_000
+        }
+        let _guard = guard();
+        AtomicOption::time_source(TimeSource { now_ms, now_ns });
+        let epoch = AtomicOption::get_epoch();
+        let set = |ms: u64, us: u64| NOW.store((epoch + ms) * 1_000_000 + us * 1000, Ordering::Relaxed);
+        use TimestampPrecision::{Micros, Millis, Seconds};
+        assert_eq!(AtomicOption::get_timestamp_precision(), Millis);
+
+        for (precision, ts_bits, seq_bits) in [(Millis, 20, 16), (Micros, 30, 6), (Seconds, 10, 26)] {
+            assert_eq!(precision.timestamp_bits(Layout64::Standard), ts_bits);
+            assert_eq!(precision.timestamp_bits(Layout64::Era), ts_bits + 3);
+            assert_eq!(precision.sequence_bits(), seq_bits);
+        }
+        // The shift leaves at least one sequence bit and at most 32.
+        AtomicOption::thread_id_bits(16);
+        assert_eq!((Micros.timestamp_bits(Layout64::Standard), Micros.sequence_bits()), (27, 1));
+        AtomicOption::thread_id_bits(1);
+        assert_eq!((Seconds.timestamp_bits(Layout64::Standard), Seconds.sequence_bits()), (11, 32));
+        AtomicOption::thread_id_bits(DEFAULT_THREAD_ID_BITS);
+
+        let generator = IdGenerator::new(300, 7);
+        for (precision, ticks, created) in [(Millis, 123_456, 123_456), (Micros, 123_456_789, 123_456), (Seconds, 123, 123_000)] {
+            AtomicOption::timestamp_precision(precision);
+            set(123_456, 789);
+            let parts = generator.gen64_parts();
+            let id = generator.gen64();
+            let decoded = IdGenerator::decompose64(id);
+            assert_eq!((parts.timestamp, decoded.timestamp), (ticks, ticks));
+            assert_eq!((decoded.node_id, decoded.shard_id, decoded.era), (300, 7, 0));
+            assert_eq!(decoded.sequence, (parts.sequence + 1) & ((1 << precision.sequence_bits()) - 1));
+            assert_eq!(generator.created_at64(id), epoch + created);
+            assert_eq!(IdGenerator::decompose64(generator.gen64_child(id)).timestamp, ticks);
+
+            let (start, end) = Layout64::Standard.id_range(123_456, 123_456);
+            assert!((start..=end).contains(&id));
+            set(123_457, 0);
+            let next = generator.gen64();
+            assert!(next > id);
+            assert!(Layout64::Standard.id_range(123_457, 123_457).0 <= next);
+            if precision == Millis {
+                assert!(next > end);
+            }
+        }
+
+        // The 6-bit sequence wraps within a microsecond without touching the time.
+        AtomicOption::timestamp_precision(Micros);
+        set(5, 6);
+        let first = IdGenerator::decompose64(generator.gen64());
+        for i in 1..=64 {
+            let parts = IdGenerator::decompose64(generator.gen64());
+            assert_eq!(parts.timestamp, 5006);
+            assert_eq!(parts.sequence, (first.sequence + i) & 0x3F);
+        }
+
+        // Every timestamp bit sorts above the node: an earlier microsecond of the same
+        // millisecond sorts first even from a higher node.
+        set(7, 1);
+        let early = IdGenerator::new(4095, 255).gen64();
+        set(7, 2);
+        assert!(early < IdGenerator::new(0, 0).gen64());
+
+        // The last microsecond of the 30-bit field, then the wrap.
+        let last = (1u64 << 30) - 1;
+        set(last / 1000, last % 1000);
+        assert_eq!(IdGenerator::decompose64(generator.gen64()).timestamp, last);
+        assert_eq!(AtomicId::<x64>::time_remaining(), Duration::from_micros(1));
+        set((last + 1) / 1000, (last + 1) % 1000);
+        assert_eq!(IdGenerator::decompose64(generator.gen64()).timestamp, 0);
+        AtomicOption::on_overflow(OverflowMode::Saturate);
+        assert_eq!(IdGenerator::decompose64(generator.gen64()).timestamp, last);
+        AtomicOption::on_overflow(OverflowMode::Error);
+        assert_eq!(generator.gen64_checked(), Err(IdError::TimestampOverflow));
+        AtomicOption::on_overflow(OverflowMode::Wrap);
+
+        // Seconds wrap after 1023 s, with the sequence spilling into the time fields.
+        AtomicOption::timestamp_precision(Seconds);
+        set(1_023_999, 0);
+        let parts = IdGenerator::decompose64(generator.gen64());
+        assert_eq!(parts.timestamp, 1023);
+        assert_eq!(AtomicId::<x64>::time_remaining(), Duration::from_secs(1));
+        set(1_024_000, 0);
+        assert_eq!(IdGenerator::decompose64(generator.gen64()).timestamp, 0);
     }
-    /// Generate a new 64-bit ID, encoded as a 10-character base91 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x64};
-    /// let id = AtomicId::<x64>::base91();
-    /// assert_eq!(id.len(), 10);
-    /// ```
-    pub fn base91() -> String {
-        encode::base91(xgen().gen64() as u128, 10)
+
+    /// Test that mixed 256-bit IDs have every bit set about half the time, in a
+    /// chi-square test over all 256 bits, where the raw parts fail badly.
+    #[test]
+    fn test_gen256_bytes_distribution() {
+        let _guard = guard();
+        let generator = IdGenerator::new(5, 9);
+        const N: u32 = 20_000;
+        let chi_square = |ids: &[[u8; 32]]| {
+            let mut ones = [0u32; 256];
+            for id in ids {
+                for (bit, count) in ones.iter_mut().enumerate() {
+                    *count += (id[bit / 8] >> (bit % 8) & 1) as u32;
+                }
+            }
+            let expected = N as f64 / 2.0;
+            // Each bit count has variance N/4, so each term is (ones - N/2)^2 / (N/4),
+            // summed over the ones and zeros of each bit.
+            ones.iter().map(|&c| 2.0 * (c as f64 - expected).powi(2) / expected).sum::<f64>()
+        };
+        let mixed: Vec<[u8; 32]> = (0..N).map(|_| generator.gen256_bytes()).collect();
+        // 256 degrees of freedom: mean 256, standard deviation about 22.6.
+        let mixed_chi = chi_square(&mixed);
+        assert!(mixed_chi < 256.0 + 6.0 * 22.6, "chi-square {}", mixed_chi);
+        let raw: Vec<[u8; 32]> = (0..N).map(|_| Id256(generator.gen256()).to_be_bytes()).collect();
+        assert!(chi_square(&raw) > 10_000.0);
+        assert_eq!(mixed.iter().collect::<std::collections::HashSet<_>>().len(), N as usize);
+
+        let parts = [1, 2, 3, 4];
+        assert_ne!(mix256(parts), mix256([1, 2, 3, 5]));
+        assert_eq!(mix256(parts), mix256(parts));
     }
-    /// Generate a new 64-bit ID, encoded as a 13-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x64};
-    /// let id = AtomicId::<x64>::base36();
-    /// assert_eq!(id.len(), 13);
-    /// ```
-    pub fn base36() -> String {
-        encode::base36(xgen().gen64() as u128, 13)
+
+    /// Test that padded base36 IDs keep their value, and that widths too small for
+    /// the largest ID are raised instead of truncating it.
+    #[test]
+    fn test_base36_width() {
+        let _guard = guard();
+        let id = AtomicId::<x64>::base36_width(20);
+        assert_eq!(id.len(), 20);
+        assert!(id.starts_with("0000000"));
+        assert!(Encoding::Base36.decode(&id).unwrap() <= u64::MAX as u128);
+        assert_eq!(AtomicId::<x64>::base36_width(0).len(), 13);
+        assert_eq!(AtomicId::<x128>::base36_width(30).len(), 30);
+        assert_eq!(AtomicId::<x128>::base36_width(1).len(), 25);
+        assert_eq!(encode::base36(u64::MAX as u128, 13).len(), 13);
+        assert_eq!(encode::base36(u128::MAX, 25).len(), 25);
     }
-    /// Generate a new 64-bit ID, encoded as a 16-character hexadecimal string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x64};
-    /// let id = AtomicId::<x64>::hex();
-    /// assert_eq!(id.len(), 16);
-    /// ```
-    pub fn hex() -> String {
-        encode::hex(xgen().gen64() as u128, 16)
+
+    /// Test that IDs for a given time carry that time, up to the first and last
+    /// millisecond their timestamp fields hold, and that other times are refused.
+    #[test]
+    fn test_gen_at() {
+        let _guard = guard();
+        let epoch_ms = 1_700_000_000_000;
+        let generator = IdGenerator::builder().epoch_ms(epoch_ms).build();
+        let epoch = UNIX_EPOCH + Duration::from_millis(epoch_ms);
+        let ms = Duration::from_millis;
+
+        let first = generator.gen64_at(epoch).unwrap();
+        let second = generator.gen64_at(epoch).unwrap();
+        assert_eq!(generator.created_at64(first), epoch_ms);
+        let (a, b) = (IdGenerator::decompose64(first), IdGenerator::decompose64(second));
+        assert_eq!((a.timestamp, b.timestamp), (0, 0));
+        assert_eq!(b.sequence, (a.sequence + 1) & 0x3FFF);
+        assert_eq!(generator.gen64_at(epoch - Duration::from_nanos(1)), Err(IdError::BeforeEpoch));
+
+        let last = generator.gen64_at(epoch + ms((1 << 20) - 1)).unwrap();
+        assert_eq!(IdGenerator::decompose64(last).timestamp, (1 << 20) - 1);
+        assert_eq!(generator.gen64_at(epoch + ms(1 << 20)), Err(IdError::TimestampOverflow));
+        AtomicOption::layout64(Layout64::Era);
+        let era = generator.gen64_at(epoch + ms(1 << 20)).unwrap();
+        assert_eq!(generator.created_at64(era), epoch_ms + (1 << 20));
+        assert_eq!(generator.gen64_at(epoch + ms(1 << 23)), Err(IdError::TimestampOverflow));
+        AtomicOption::layout64(Layout64::Standard);
+
+        AtomicOption::timestamp_precision(TimestampPrecision::Micros);
+        let id = generator.gen64_at(epoch + Duration::from_micros(1_234_567)).unwrap();
+        assert_eq!(IdGenerator::decompose64(id).timestamp, 1_234_567);
+        let overflow = generator.gen64_at(epoch + Duration::from_micros(1 << 30));
+        assert_eq!(overflow, Err(IdError::TimestampOverflow));
+        AtomicOption::timestamp_precision(TimestampPrecision::Millis);
+
+        let id = generator.gen128_at(epoch + ms(u32::MAX as u64)).unwrap();
+        assert_eq!((id >> 96) as u32, u32::MAX);
+        assert_eq!(generator.gen128_at(epoch + ms(1 << 32)), Err(IdError::TimestampOverflow));
+        assert_eq!(generator.gen128_at(epoch - ms(1)), Err(IdError::BeforeEpoch));
+
+        let global = AtomicOption::get_epoch_time() + ms(5);
+        let id = AtomicId::<x64>::new_at(global).unwrap();
+        assert_eq!(u64::from_str_radix(&id, 36).unwrap() >> 44, 5);
+        assert_eq!(AtomicId::<x64>::hex_at(global).unwrap().len(), 16);
+        assert_eq!(AtomicId::<x128>::base58_at(global).unwrap().len(), 22);
+        assert_eq!(AtomicId::<x128>::new_at(UNIX_EPOCH), Err(IdError::BeforeEpoch));
     }
 
-    /// Generate a batch of 64-bit IDs, encoded as base36 strings.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x64};
-    /// let ids = AtomicId::<x64>::batch(3);
-    /// assert_eq!(ids.len(), 3);
-    /// ```
-    pub fn batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::new()).collect()
+    /// Test that every ID generated at a time falls within the pagination bounds for
+    /// that time, in every layout and precision, under a mock clock.
+    #[test]
+    fn test_id64_bounds_for() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ns() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        fn now_ms() -> u64 {
+            now_ns() / 1_000_000
+        }
+        let _guard = guard();
+        AtomicOption::time_source(TimeSource { now_ms, now_ns });
+        let epoch = AtomicOption::get_epoch_time();
+        let generator = xgen();
+        let layouts = [Layout64::Standard, Layout64::WideNode, Layout64::Era, Layout64::Regional];
+        let precisions = [TimestampPrecision::Millis, TimestampPrecision::Micros, TimestampPrecision::Seconds];
+        for (layout, precision) in layouts.into_iter().flat_map(|l| precisions.map(|p| (l, p))) {
+            AtomicOption::layout64(layout);
+            AtomicOption::timestamp_precision(precision);
+            for elapsed in [Duration::ZERO, Duration::from_micros(123_456_789), Duration::from_millis(999_999)] {
+                let time = epoch + elapsed;
+                NOW.store(time.duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64, Ordering::Relaxed);
+                let (min, max) = (Id64::min_for(time).unwrap(), Id64::max_for(time).unwrap());
+                for _ in 0..100 {
+                    let id = Id64(generator.gen64());
+                    assert!(min <= id && id <= max, "{:?} {:?} {:?}", layout, precision, elapsed);
+                }
+                // The next tick's bounds start past these, except in `Micros`, whose
+                // bounds widen to 1024 µs steps.
+                if precision != TimestampPrecision::Micros {
+                    assert!(Id64::min_for(time + precision.ticks_to_duration(1)).unwrap() > max);
+                }
+            }
+        }
+        AtomicOption::layout64(Layout64::Standard);
+        AtomicOption::timestamp_precision(TimestampPrecision::Millis);
+
+        let time = epoch + Duration::from_millis(5);
+        assert_eq!(Id64::min_for_encoded(time, Encoding::Hex), Ok(format!("{:016x}", 5u64 << 44)));
+        assert_eq!(Id64::max_for_encoded(time, Encoding::Hex), Ok(format!("{:016x}", (6u64 << 44) - 1)));
+        assert_eq!(Id64::max_for_encoded(time, Encoding::Base36).unwrap().len(), 13);
+        assert_eq!(Id64::min_for(epoch - Duration::from_nanos(1)), Err(IdError::BeforeEpoch));
+        let overflowed = epoch + Duration::from_millis((1 << 20) + 5);
+        assert_eq!(Id64::min_for(overflowed), Ok(Id64(5 << 44)));
+        AtomicOption::on_overflow(OverflowMode::Saturate);
+        assert_eq!(Id64::min_for(overflowed), Ok(Id64(((1 << 20) - 1) << 44)));
+        assert_eq!(Id64::max_for(overflowed), Ok(Id64(u64::MAX)));
+        AtomicOption::on_overflow(OverflowMode::Error);
+        assert_eq!(Id64::max_for(overflowed), Err(IdError::TimestampOverflow));
+        assert_eq!(Id64::min_for(time), Ok(Id64(5 << 44)));
     }
-    /// Generate a batch of 64-bit IDs as base58 strings.
-    pub fn base58_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base58()).collect()
+
+    /// Test that IDs generated on the system clock fall within the bounds for the
+    /// times around them, with the default epoch, whose time fields wrapped long ago,
+    /// and in every overflow mode.
+    #[test]
+    fn test_id64_bounds_for_now() {
+        let _guard = guard();
+        let generator = xgen();
+        for mode in [OverflowMode::Wrap, OverflowMode::Saturate] {
+            AtomicOption::on_overflow(mode);
+            let mut checked = 0;
+            while checked < 100 {
+                let before = SystemTime::now();
+                let id = Id64(generator.gen64());
+                let after = SystemTime::now();
+                let (min, max) = (Id64::min_for(before).unwrap(), Id64::max_for(after).unwrap());
+                // Skip the rare pair that straddles a wrap of the time fields.
+                if min <= max {
+                    assert!(min <= id && id <= max, "{:?} {:?} {:?} {:?}", mode, min, id, max);
+                    checked += 1;
+                }
+            }
+        }
+        AtomicOption::on_overflow(OverflowMode::Error);
+        assert_eq!(Id64::min_for(SystemTime::now()), Err(IdError::TimestampOverflow));
     }
-    /// Generate a batch of 64-bit IDs as base91 strings.
-    pub fn base91_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base91()).collect()
+
+    /// Test that ID comparisons read the node, shard, and time in the current layout,
+    /// decode strings first, and refuse reserved layout versions.
+    #[test]
+    fn test_same_node_and_time_between() {
+        let _guard = guard();
+        let a = IdGenerator::new(4000, 3).compose64(100, thread_seq64(1, 0, 8));
+        let b = IdGenerator::new(4000, 3).compose64(350, thread_seq64(2, 9, 8));
+        let c = IdGenerator::new(4000, 19).compose64(90, thread_seq64(1, 0, 8));
+        // Unversioned IDs are read whatever their low sequence bits.
+        for low in 0..4 {
+            assert_eq!(AtomicId::<x64>::same_shard(a | low, b), Ok(true));
+            assert_eq!(AtomicId::<x64>::time_between(a | low, b), Ok(Duration::from_millis(250)));
+        }
+        assert_eq!(AtomicId::<x64>::same_node(a, b), Ok(true));
+        assert_eq!(AtomicId::<x64>::same_shard(a, b), Ok(true));
+        assert_eq!(AtomicId::<x64>::same_node(a, c), Ok(true));
+        assert_eq!(AtomicId::<x64>::same_shard(a, c), Ok(false));
+        assert_eq!(AtomicId::<x64>::time_between(a, b), Ok(Duration::from_millis(250)));
+        assert_eq!(AtomicId::<x64>::time_between(a, c), Ok(Duration::from_millis(10)));
+
+        let (sa, sb) = (encode::base36(a as u128, 13), encode::base36(c as u128, 13));
+        assert_eq!(AtomicId::<x64>::same_shard_str(&sa, &sb), Ok(false));
+        assert_eq!(AtomicId::<x64>::time_between_str(&sa, &sb), Ok(Duration::from_millis(10)));
+        assert_eq!(AtomicId::<x64>::same_node_str(&sa, "!"), Err(IdError::InvalidCharacter('!')));
+        assert_eq!(AtomicId::<x64>::same_node_str(&sa, "zzzzzzzzzzzzzz"), Err(IdError::Overflow));
+
+        // Read in the wide-node layout, the high bits of shard 19 belong to the node.
+        AtomicOption::layout64(Layout64::WideNode);
+        assert_eq!(AtomicId::<x64>::same_node(a, c), Ok(false));
+        AtomicOption::layout64(Layout64::Standard);
+
+        AtomicOption::timestamp_precision(TimestampPrecision::Micros);
+        // All 30 bits of microseconds sit above the node, which moves down 10 bits.
+        let generator = IdGenerator::new(1, 0);
+        let d = generator.compose64_ticks(Layout64::Standard, (1 << 10) | 3, 0, 0, 8);
+        let e = generator.compose64_ticks(Layout64::Standard, 1 << 10, 0, 0, 8);
+        assert_eq!((d >> 34, (d >> 22) & 0xFFF), ((1 << 10) | 3, 1));
+        assert_eq!(AtomicId::<x64>::time_between(d, e), Ok(Duration::from_micros(3)));
+        assert_eq!(AtomicId::<x64>::same_node(d, IdGenerator::new(1, 9).gen64()), Ok(true));
+        AtomicOption::timestamp_precision(TimestampPrecision::Millis);
+
+        AtomicOption::version_bits(true);
+        let a = IdGenerator::new(4000, 3).compose64(100, thread_seq64(1, 0, 8));
+        let reserved = a | 3;
+        assert_eq!(AtomicId::<x64>::same_node(a, reserved), Err(IdError::UnknownLayoutVersion(3)));
+        assert_eq!(AtomicId::<x64>::time_between(reserved, a), Err(IdError::UnknownLayoutVersion(3)));
+        AtomicOption::version_bits(false);
+        assert_eq!(AtomicId::<x64>::same_node(a, reserved), Ok(true));
     }
-    /// Generate a batch of 64-bit IDs as base36 strings.
-    pub fn base36_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base36()).collect()
+
+    /// Test that the hexadecimal halves of 128-bit IDs split the full hex form at
+    /// the boundary between the high and low 64 bits.
+    #[test]
+    fn test_hex_halves() {
+        let _guard = guard();
+        for id in [0, 1, 1 << 64, u128::MAX, xgen().gen128()] {
+            let (high, low) = hex_halves(id);
+            assert_eq!((high.len(), low.len()), (16, 16));
+            assert_eq!(high + &low, encode::hex(id, 32));
+        }
+        let (high, low) = AtomicId::<x128>::hex_halves();
+        // The node ID of the global generator is in the high half.
+        let high = u64::from_str_radix(&high, 16).unwrap();
+        assert_eq!((high >> 20) & 0xFFF, 1);
+        assert_eq!(low.len(), 16);
     }
-    /// Generate a batch of 64-bit IDs as hexadecimal strings.
-    pub fn hex_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::hex()).collect()
+
+    /// Test that batches appended to reused vectors stay unique and allocate only
+    /// what they need.
+    #[test]
+    fn test_batch_extend() {
+        let _guard = guard();
+        let mut ids = Vec::new();
+        AtomicId::<x64>::batch_extend(100, &mut ids);
+        let capacity = ids.capacity();
+        ids.clear();
+        AtomicId::<x64>::batch_extend(100, &mut ids);
+        AtomicId::<x64>::batch_extend(0, &mut ids);
+        assert_eq!((ids.len(), ids.capacity()), (100, capacity));
+
+        let mut raw = vec![0];
+        AtomicId::<x64>::batch_raw_extend(1000, &mut raw);
+        assert_eq!(raw.len(), 1001);
+        let unique: std::collections::HashSet<_> = raw[1..].iter().collect();
+        assert_eq!(unique.len(), 1000);
+
+        let strings = AtomicId::<x128>::batch_map(1000, |id| encode::base36(id, 25));
+        assert_eq!(strings.iter().collect::<std::collections::HashSet<_>>().len(), 1000);
+        let mut wide = Vec::new();
+        AtomicId::<x256>::batch_raw_extend(2, &mut wide);
+        assert_ne!(wide[0], wide[1]);
     }
 
-    /// Generate a sequential 64-bit ID as a base36 string.
-    ///
-    /// This method uses a simple atomic counter, making the IDs sequential but not
-    /// time-sortable. It is useful for scenarios where strict ordering is more
-    /// important than distributed uniqueness.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x64};
-    /// let id1 = AtomicId::<x64>::sequential();
-    /// let id2 = AtomicId::<x64>::sequential();
-    /// // id2 will be lexicographically greater than id1
-    /// assert!(id2 > id1);
-    /// ```
-    pub fn sequential() -> String {
-        static COUNTER: AtomicU64 = AtomicU64::new(0);
-        let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
-        encode::base36(seq as u128, 13)
+    /// Test that the hex encoder writes exactly what the formatting machinery does,
+    /// for random values of every width and for padding narrower and wider than them.
+    #[test]
+    fn test_hex_matches_format() {
+        let _guard = guard();
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..2000 {
+            state = mix64(state);
+            let n = ((state as u128) << 64) | mix64(!state) as u128;
+            for bits in [24, 32, 64, 128] {
+                let n = if bits == 128 { n } else { n & ((1 << bits) - 1) };
+                for width in [0, 1, bits / 4, 16, 32, 40] {
+                    assert_eq!(encode::hex(n, width), format!("{:0width$x}", n, width = width));
+                }
+            }
+        }
+        for n in [0, 1, 0xF, 0x10, u64::MAX as u128, u128::MAX] {
+            assert_eq!(encode::hex(n, 0), format!("{:x}", n));
+        }
+        let parts = [state, 0, u64::MAX, 1];
+        let expected = format!("{:016x}{:016x}{:016x}{:016x}", parts[0], parts[1], parts[2], parts[3]);
+        assert_eq!(Id256(parts).to_grouped_hex().replace('-', ""), expected);
+        assert_eq!(AtomicId::<x256>::hex().len(), 64);
     }
 
-    /// Generate a sequential 64-bit ID as a base58 string.
-    pub fn sequential_base58() -> String {
-        static COUNTER: AtomicU64 = AtomicU64::new(0);
-        let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
-        encode::base58(seq as u128, 11)
+    /// Test that persisted sequence watermarks stay ahead of every handed-out value,
+    /// so counters resume above them after a simulated restart, and that generation
+    /// waits at the reserve while writes fail.
+    #[test]
+    fn test_persist_sequence() {
+        let _guard = guard();
+        let path = std::env::temp_dir().join(format!("atomic-id-sequence-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        fn stored_in(path: &std::path::Path, width: &str) -> u64 {
+            let text = std::fs::read_to_string(path).unwrap();
+            let line = text.lines().find(|l| l.split(' ').next() == Some(width)).unwrap();
+            line.split(' ').nth(1).unwrap().parse().unwrap()
+        }
+        let stored = |width: &str| stored_in(&path, width);
+
+        AtomicOption::persist_sequence_interval(10);
+        assert_eq!(AtomicOption::get_persist_sequence_interval(), 10);
+        SEQ_64.store(500, Ordering::Relaxed);
+        AtomicOption::persist_sequence(&path).unwrap();
+        assert_eq!(stored("64"), 520);
+        let generator = IdGenerator::new(1, 0);
+        // The writer thread may lag behind, so only the bounds are exact.
+        for _ in 0..25 {
+            generator.gen64();
+            assert!(stored("64") >= SEQ_64.load(Ordering::Relaxed));
+        }
+        AtomicOption::flush_sequence().unwrap();
+        assert!(stored("64") >= 545);
+        let watermark = stored("64");
+        assert!(watermark > SEQ_64.load(Ordering::Relaxed));
+
+        // A restart starts from zero, then resumes at the watermark.
+        SEQ_64.store(0, Ordering::Relaxed);
+        AtomicOption::persist_sequence(&path).unwrap();
+        assert!(SEQ_64.load(Ordering::Relaxed) >= watermark);
+        assert!(stored("64") >= watermark + 20);
+
+        // Stop the writer thread first, so a late write cannot replace the bad file.
+        persist::reset();
+        AtomicOption::persist_sequence_interval(10);
+        std::fs::write(&path, "64 twelve\n").unwrap();
+        let err = AtomicOption::persist_sequence(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+
+        // Once writes fail, generation stops at the reserve, and resumes when a write
+        // succeeds again.
+        let dir = std::env::temp_dir().join(format!("atomic-id-sequence-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sequence");
+        SEQ_64.store(0, Ordering::Relaxed);
+        AtomicOption::persist_sequence(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let worker = std::thread::spawn(|| {
+            let generator = IdGenerator::new(1, 0);
+            for _ in 0..30 {
+                generator.gen64_parts();
+            }
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!worker.is_finished());
+        assert!(SEQ_64.load(Ordering::Relaxed) <= 21);
+        std::fs::create_dir_all(&dir).unwrap();
+        worker.join().unwrap();
+        assert!(stored_in(&path, "64") >= SEQ_64.load(Ordering::Relaxed));
+        persist::reset();
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
-    /// Generate a sequential 64-bit ID as a base91 string.
-    pub fn sequential_base91() -> String {
-        static COUNTER: AtomicU64 = AtomicU64::new(0);
-        let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
-        encode::base91(seq as u128, 10)
+    /// Test that the canonical 256-bit encodings are the big-integer form of the whole
+    /// value, and convert to and from the legacy word format.
+    #[test]
+    fn test_canonical_256() {
+        let _guard = guard();
+        let parts = [1, 0, 0, 0];
+        let canonical = AtomicId::<x256>::to_canonical(parts, Encoding::Hex);
+        assert_eq!(canonical, AtomicId::<x256>::to_words(parts, Encoding::Hex));
+        // 2^192 in base36, where the word format would end in "...01" and 39 zeros.
+        let base36 = AtomicId::<x256>::to_canonical(parts, Encoding::Base36);
+        assert_eq!(base36, format!("{:0>50}", "1n030ke8hj7nszs89yaz14ivfb2owprougrchs"));
+        for encoding in [Encoding::Base36, Encoding::Base58, Encoding::Base91, Encoding::Base91UrlSafe] {
+            for parts in [[0; 4], [u64::MAX; 4], [1, 2, 3, 4], xgen().gen256()] {
+                let canonical = AtomicId::<x256>::to_canonical(parts, encoding);
+                assert_eq!(AtomicId::<x256>::from_canonical(&canonical, encoding), Ok(parts));
+                let words = AtomicId::<x256>::to_words(parts, encoding);
+                assert_eq!(Ok(canonical.len()), encoding.encoded_len(256));
+                assert_eq!(AnyId::X256(parts).encode(encoding), canonical);
+                assert_eq!(AnyId::parse(&canonical, Width::X256, encoding), Ok(AnyId::X256(parts)));
+                assert_eq!(encoding.canonicalize(&canonical, 256).as_ref(), Ok(&canonical));
+                assert_eq!(AtomicId::<x256>::from_words(&words, encoding), Ok(parts));
+                assert_eq!(AtomicId::<x256>::words_to_canonical(&words, encoding), Ok(canonical.clone()));
+                assert_eq!(AtomicId::<x256>::canonical_to_words(&canonical, encoding), Ok(words));
+            }
+        }
+        // Canonical strings sort like the values they encode.
+        let (low, high) = ([0, u64::MAX, u64::MAX, u64::MAX], [1, 0, 0, 0]);
+        assert!(AtomicId::<x256>::to_canonical(low, Encoding::Base58) < AtomicId::<x256>::to_canonical(high, Encoding::Base58));
+        assert!(AtomicId::<x256>::new().parse::<Id256>().is_ok());
+        assert_eq!(AtomicId::<x256>::from_canonical("1", Encoding::Base58), Err(IdError::InvalidLength(1)));
+        assert_eq!(AtomicId::<x256>::from_words(&"z".repeat(52), Encoding::Base36), Err(IdError::Overflow));
+        let max = AtomicId::<x256>::to_canonical([u64::MAX; 4], Encoding::Base36);
+        assert!(max.starts_with('6'), "{}", max);
+        assert_eq!(AtomicId::<x256>::from_canonical(&"z".repeat(50), Encoding::Base36), Err(IdError::Overflow));
+        // Base36 takes the 50-character canonical form on every path.
+        let canonical = AtomicId::<x256>::to_canonical([1, 2, 3, 4], Encoding::Base36);
+        assert_eq!(Encoding::Base36.canonicalize(&canonical.to_uppercase(), 256), Ok(canonical.clone()));
+        assert_eq!(AnyId::parse(&canonical, Width::X256, Encoding::Base36), Ok(AnyId::X256([1, 2, 3, 4])));
+        assert_eq!(AnyId::X256([1, 2, 3, 4]).encode(Encoding::Base36), canonical);
+        assert_eq!(Id256([1, 2, 3, 4]).to_string(), canonical);
+        assert_eq!((AtomicId::<x256>::new().len(), AtomicId::<x256>::base36().len()), (50, 50));
+        assert!(AtomicId::<x256>::from_canonical(&AtomicId::<x256>::with_encoding(Encoding::Base36), Encoding::Base36).is_ok());
+        // The legacy word format is still produced on request and parsed as `Id256`.
+        let words = AtomicId::<x256>::to_words([1, 2, 3, 4], Encoding::Base36);
+        assert_eq!(AtomicId::<x256>::base36_words().len(), 52);
+        assert_eq!(words.parse::<Id256>(), Ok(Id256([1, 2, 3, 4])));
+        assert_eq!(AnyId::parse(&words, Width::X256, Encoding::Base36), Err(IdError::InvalidLength(52)));
     }
 
-    /// Generate a sequential 64-bit ID as a base36 string.
-    pub fn sequential_base36() -> String {
-        static COUNTER: AtomicU64 = AtomicU64::new(0);
-        let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
-        encode::base36(seq as u128, 13)
+    /// Test that batch encoding matches encoding one value at a time, on both sides
+    /// of the size where the vector path takes over and at every padding width.
+    #[test]
+    fn test_encode_batch() {
+        let _guard = guard();
+        let mut values = vec![0, 1, 35, 36, 36u64.pow(6) - 1, 36u64.pow(6), 36u64.pow(12), u64::MAX];
+        values.extend((0..100).map(|i| mix64(i) >> (i % 64)));
+        for len in [0, 3, encode::SIMD_MIN_BATCH - 1, encode::SIMD_MIN_BATCH, values.len()] {
+            for width in [0, 1, 5, 13, 20] {
+                for encoding in [Encoding::Base36, Encoding::Hex] {
+                    let expected: Vec<_> = values[..len].iter().map(|&n| encoding.encode(n as u128, width)).collect();
+                    assert_eq!(encoding.encode_batch(&values[..len], width), expected, "{} {}", len, width);
+                }
+            }
+        }
+        let ids = AtomicId::<x64>::base36_batch(100);
+        assert!(ids.iter().all(|id| id.len() == 13 && encode::from_base36(id).is_ok()));
     }
 
-    /// Generate a sequential 64-bit ID as a hexadecimal string.
-    pub fn sequential_hex() -> String {
-        static COUNTER: AtomicU64 = AtomicU64::new(0);
-        let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
-        encode::hex(seq as u128, 16)
+    /// Test that the encodings shared through [`BitWidth`] give the strings the
+    /// per-width methods gave before they were unified, and that every generating
+    /// method uses them.
+    #[test]
+    fn test_bit_width_golden() {
+        let _guard = guard();
+        let encodings = [Encoding::Base36, Encoding::Base58, Encoding::Base91, Encoding::Base91UrlSafe, Encoding::Hex];
+        #[cfg(feature = "short")]
+        {
+            let golden = ["6pbsf", "1zi2A", "O`8k", "O]8k", "abcdef"];
+            for (encoding, expected) in encodings.iter().zip(golden) {
+                assert_eq!(AtomicId::<x24>::encode_raw(0xabcdef, *encoding), expected);
+            }
+            let golden = ["1ps9wxb", "6h8cQN", "2r53\"", "2r53}", "deadbeef"];
+            for (encoding, expected) in encodings.iter().zip(golden) {
+                assert_eq!(AtomicId::<x32>::encode_raw(0xdeadbeef, *encoding), expected);
+            }
+        }
+        let golden = ["00mf9g063v08f", "1C3CPq7c8PY", "ARnv{/X;P\"", "ARnv^+X;P}", "0123456789abcdef"];
+        for (encoding, expected) in encodings.iter().zip(golden) {
+            assert_eq!(AtomicId::<x64>::encode_raw(0x0123_4567_89ab_cdef, *encoding), expected);
+        }
+        let golden = [
+            "02fapl4n1azs9fhghwz46aryo",
+            "199dn6s7bZpEGBoKc7YnPh",
+            "AIXxddHz,[LR|y]J{g6A",
+            "AIXxddHz)<LR`y>J^g6A",
+            "0123456789abcdeffedcba9876543210",
+        ];
+        for (encoding, expected) in encodings.iter().zip(golden) {
+            assert_eq!(AtomicId::<x128>::encode_raw(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210, *encoding), expected);
+        }
+        let golden = [
+            "0000000000001n030ke8hj7ntu3g5seyzfy6taklxr2xi9iltr",
+            "11111111111QLbz7JHiBTtuf8Rwv9VfjBjb5DgnoSDxr",
+            "AAAAAAAAAAJ9V9D)$q.%}5iM]XFI.Cdm(R@TVDIe",
+            "AAAAAAAAAAJ9V9D&_q*~{5iM>XFI*Cdm$R\"TVDIe",
+            "000000000000000100000000000000020000000000000003ffffffffffffffff",
+        ];
+        let parts = [1, 2, 3, u64::MAX];
+        for (encoding, expected) in encodings.iter().zip(golden) {
+            assert_eq!(AtomicId::<x256>::encode_raw(parts, *encoding), expected);
+        }
+        assert_eq!(AtomicId::<x256>::encode_default(parts), "0000000000001n030ke8hj7ntu3g5seyzfy6taklxr2xi9iltr");
+
+        fn check<const BITS: usize>(bits: usize)
+        where
+            AtomicId<BITS>: BitWidth,
+        {
+            let generated = [
+                (Encoding::Base36, AtomicId::<BITS>::base36()),
+                (Encoding::Base58, AtomicId::<BITS>::base58()),
+                (Encoding::Base91, AtomicId::<BITS>::base91()),
+                (Encoding::Hex, AtomicId::<BITS>::hex()),
+                (Encoding::Base91UrlSafe, AtomicId::<BITS>::with_encoding(Encoding::Base91UrlSafe)),
+                (Encoding::Base58, AtomicId::<BITS>::base58_batch(1).remove(0)),
+                (Encoding::Base36, AtomicId::<BITS>::base36_batch(40).remove(39)),
+            ];
+            for (encoding, id) in generated {
+                assert_eq!(Ok(id.len()), encoding.encoded_len(bits), "{} {:?}", bits, encoding);
+                assert!(id.chars().all(|c| encoding.contains(c)), "{} {:?} {}", bits, encoding, id);
+            }
+            let raw = AtomicId::<BITS>::batch_map(1, |raw| raw).remove(0);
+            assert_eq!(AtomicId::<BITS>::encode_default(raw).len(), AtomicId::<BITS>::new().len());
+        }
+        #[cfg(feature = "short")]
+        {
+            check::<x24>(24);
+            check::<x32>(32);
+        }
+        check::<x64>(64);
+        check::<x128>(128);
+        check::<x256>(256);
     }
 
-    /// Generate a batch of sequential 64-bit IDs as base36 strings.
-    pub fn sequential_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::sequential()).collect()
+    /// Test that every layout's fields are disjoint and cover exactly its width, and
+    /// that the fields are where the generators put them.
+    #[test]
+    fn test_layout_fields() {
+        let _guard = guard();
+        let layouts = [Layout64::Standard, Layout64::WideNode, Layout64::Era, Layout64::Regional];
+        let precisions = [TimestampPrecision::Millis, TimestampPrecision::Micros, TimestampPrecision::Seconds];
+        for layout in layouts {
+            for precision in precisions {
+                for thread_bits in 1..=16 {
+                    for version in [false, true] {
+                        let fields = layout64(layout, precision, thread_bits, version);
+                        assert!(fields.is_exact(), "{:?} {:?} {} {}\n{}", layout, precision, thread_bits, version, fields);
+                        let width = |name| fields.field(name).map_or(0, |field| field.bit_width);
+                        let field_bits = THREAD_SEQ_BITS_64 - thread_bits - if version { VERSION_BITS } else { 0 };
+                        assert_eq!(width("sequence") + width("sequence_high"), precision.sequence_bits_for(field_bits));
+                        assert_eq!(width("era") + width("timestamp") + width("timestamp_low"), precision.timestamp_bits_for(layout, field_bits));
+                    }
+                }
+            }
+        }
+
+        AtomicOption::version_bits(true);
+        let generator = IdGenerator::new(0xABC, 0x5);
+        for layout in layouts {
+            AtomicOption::layout64(layout);
+            for precision in precisions {
+                AtomicOption::timestamp_precision(precision);
+                let fields = AtomicId::<x64>::layout();
+                let id = generator.gen64() as u128;
+                let parts = layout.decompose(id as u64);
+                assert_eq!(fields.field("node").unwrap().extract(id), parts.node_id as u128);
+                assert_eq!(fields.field("shard").unwrap().extract(id), parts.shard_id as u128);
+                assert_eq!(fields.field("version").unwrap().extract(id), LAYOUT_VERSION as u128);
+            }
+        }
+        AtomicOption::version_bits(false);
+        assert!(AtomicId::<x64>::layout().field("version").is_none());
+        assert!(AtomicId::<x128>::layout().is_exact());
+        AtomicOption::version_bits(true);
+
+        let fields = AtomicId::<x128>::layout();
+        assert!(fields.is_exact(), "{}", fields);
+        let id = generator.gen128();
+        assert_eq!(fields.field("node").unwrap().extract(id), 0xABC);
+        assert_eq!(fields.field("shard").unwrap().extract(id), 0x5);
+        assert_eq!(fields.field("version").unwrap().extract(id), LAYOUT_VERSION as u128);
+        let thread = fields.field("thread").unwrap().extract(id) as u8;
+        assert_eq!(fields.field("thread_rotated").unwrap().extract(id), thread.rotate_left(3) as u128);
+
+        #[cfg(feature = "short")]
+        {
+            for layout in [Layout24::Standard, Layout24::TimeSalted] {
+                AtomicOption::layout24(layout);
+                assert!(AtomicId::<x24>::layout().is_exact());
+            }
+            for layout in [Layout32::Standard, Layout32::TimeSalted] {
+                AtomicOption::layout32(layout);
+                assert!(AtomicId::<x32>::layout().is_exact());
+            }
+        }
+        assert!(AtomicId::<x64>::layout().to_string().contains(" 12 bits  node"));
     }
-    /// Generate a batch of sequential 64-bit IDs as base58 strings.
-    pub fn sequential_base58_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::sequential_base58()).collect()
+
+    /// Test sequences: starting offsets, reservations, moving the start, named
+    /// lookups, and the `sequential*` wrappers sharing the global sequence.
+    #[test]
+    fn test_sequence() {
+        let _guard = guard();
+        let sequence = Sequence::new(1_000_000);
+        assert_eq!(sequence.peek(), 1_000_000);
+        assert_eq!(sequence.next(), 1_000_000);
+        assert_eq!(sequence.reserve(10), 1_000_001..1_000_011);
+        assert_eq!(sequence.reserve(0), 1_000_011..1_000_011);
+        assert_eq!(sequence.next_base36(), encode::base36(1_000_011, 13));
+        assert_eq!(sequence.set_start(5), Err(IdError::SequenceAhead(1_000_012)));
+        assert_eq!(sequence.set_start(1_000_012), Ok(()));
+        assert_eq!(sequence.set_start(2_000_000), Ok(()));
+        assert_eq!(sequence.next_encoded(Encoding::Hex), encode::hex(2_000_000, 16));
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(|| (0..1000).map(|_| Sequence::named("test_sequence", 7).next()).collect::<Vec<_>>()))
+            .collect();
+        let mut values: Vec<u64> = threads.into_iter().flat_map(|t| t.join().unwrap()).collect();
+        values.sort_unstable();
+        assert_eq!(values, (7..4007).collect::<Vec<_>>());
+        assert_eq!(Sequence::named("test_sequence", 0).name(), Some("test_sequence"));
+        assert_eq!(Sequence::named("test_sequence_other", 3).peek(), 3);
+
+        let before = Sequence::global().peek();
+        let id = AtomicId::<x64>::sequential_hex();
+        assert!(u64::from_str_radix(&id, 16).unwrap() >= before);
+        assert!(Sequence::global().peek() > before);
     }
-    /// Generate a batch of sequential 64-bit IDs as base91 strings.
-    pub fn sequential_base91_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::sequential_base91()).collect()
+
+    /// Test that scattering is reversible and spreads consecutive IDs evenly.
+    #[test]
+    fn test_gen64_scattered() {
+        let _guard = guard();
+        for id in [0, 1, u64::MAX, 0x0123_4567_89ab_cdef] {
+            assert_eq!(unscatter64(scatter64(id)), id);
+            assert_eq!(scatter64(unscatter64(id)), id);
+        }
+        let generator = IdGenerator::new(7, 3);
+        let ids: Vec<u64> = (0..4096).map(|_| generator.gen64_scattered()).collect();
+        let mut originals: Vec<u64> = ids.iter().map(|&id| IdGenerator::unscatter64(id)).collect();
+        assert!(originals.iter().all(|&id| Layout64::current().decompose(id).node_id == 7));
+        originals.sort_unstable();
+        originals.dedup();
+        assert_eq!(originals.len(), ids.len());
+
+        // Consecutive IDs differ only in their low bits, but land evenly on 16 shards
+        // by their top bits.
+        let mut shards = [0; 16];
+        for id in (0..4096u64).map(scatter64) {
+            shards[(id >> 60) as usize] += 1;
+        }
+        assert!(shards.iter().all(|&n| (192..=320).contains(&n)), "{:?}", shards);
     }
-    /// Generate a batch of sequential 64-bit IDs as base36 strings.
-    pub fn sequential_base36_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::sequential_base36()).collect()
+
+    /// Test that k-sorted IDs sort in generation order as a simulated clock jumps
+    /// forward, stalls, and steps back, and that order-preserving encodings keep the
+    /// numeric order of random values.
+    #[test]
+    fn test_k_sorted() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        NOW.store(current_epoch() + 1_000, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let generator = xgen();
+        let mut last = AtomicId::<64>::k_sorted();
+        for i in 0..20_000u64 {
+            match mix64(i) % 8 {
+                0 => NOW.fetch_sub(mix64(!i) % 5_000, Ordering::Relaxed),
+                1..=3 => NOW.fetch_add(mix64(!i) % 40_000_000, Ordering::Relaxed),
+                _ => 0,
+            };
+            let id = AtomicId::<64>::k_sorted();
+            assert_eq!(id.len(), 13);
+            assert!(id > last, "{id} after {last}");
+            last = id;
+        }
+        let raw = generator.gen64_k_sorted();
+        assert_eq!(raw >> 63, 0);
+        assert!(raw >> 22 >= NOW.load(Ordering::Relaxed) - current_epoch() - 5_000);
+        assert_eq!((raw >> 12) & 0x3FF, (generator.node_id & 0x3FF) as u64);
+
+        // Order holds per generator, even with another node's IDs in between.
+        let other = IdGenerator::new(generator.node_id ^ 1, 0);
+        let mut mixed = (generator.gen64_k_sorted(), other.gen64_k_sorted());
+        for _ in 0..1_000 {
+            let next = (generator.gen64_k_sorted(), other.gen64_k_sorted());
+            assert!(next.0 > mixed.0 && next.1 > mixed.1);
+            mixed = next;
+        }
+
+        // A burst past the 12-bit sequence borrows the next millisecond.
+        let first = generator.gen64_k_sorted();
+        let burst: Vec<u64> = (0..10_000).map(|_| generator.gen64_k_sorted()).collect();
+        assert!(burst.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(burst[9_999] >> 22 > first >> 22);
+
+        for encoding in [Encoding::Base36, Encoding::Base58, Encoding::Base91, Encoding::Base91UrlSafe, Encoding::Hex] {
+            let width = encoding.encoded_len(64).unwrap();
+            let mut mismatches = 0;
+            for i in 0..10_000u64 {
+                let (a, b) = (mix64(i), mix64(!i));
+                let (sa, sb) = (encoding.encode(a as u128, width), encoding.encode(b as u128, width));
+                if (a < b) != (sa < sb) {
+                    mismatches += 1;
+                }
+            }
+            assert_eq!(mismatches == 0, encoding.is_sort_preserving(), "{encoding:?}");
+        }
     }
-    /// Generate a batch of sequential 64-bit IDs as hexadecimal strings.
-    pub fn sequential_hex_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::sequential_hex()).collect()
+
+    /// Test that hashed thread IDs of 1000 threads spread roughly uniformly over
+    /// 1..=128, with either thread hash.
+    #[test]
+    fn test_thread_id_distribution() {
+        let _guard = guard();
+        let mut counts = [0u32; 129];
+        for chunk in 0..10 {
+            let ids: Vec<u8> = std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..100).map(|_| scope.spawn(|| xgen().thread_id())).collect();
+                handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+            });
+            for id in ids {
+                assert!((1..=128).contains(&id), "thread ID {id} in chunk {chunk}");
+                counts[id as usize] += 1;
+            }
+        }
+        // 1000 threads over 128 values average 7.8 each. The chi-squared statistic
+        // has 127 degrees of freedom; 200 is beyond its 99.99th percentile.
+        let expected = 1000.0 / 128.0;
+        let chi_squared: f64 = counts[1..].iter().map(|&n| (n as f64 - expected).powi(2) / expected).sum();
+        assert!(chi_squared < 200.0, "chi-squared {chi_squared}");
+        assert!(counts[1..].iter().filter(|&&n| n == 0).count() <= 3);
+        assert!(counts[1..].iter().all(|&n| n <= 24), "{counts:?}");
     }
-}
 
-impl AtomicId<128> {
-    /// Generate a new 128-bit ID, encoded as a 25-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x128};
-    /// let id = AtomicId::<x128>::new();
-    /// assert_eq!(id.len(), 25);
-    /// ```
-    pub fn new() -> String {
-        encode::base36(xgen().gen128(), 25)
+    /// Test that 128-bit IDs decompose into the generator's fields, that tags land in
+    /// the reserved field without touching the others, and that wide tags are refused.
+    #[test]
+    fn test_gen128_tagged() {
+        let _guard = guard();
+        AtomicOption::version_bits(true);
+        let generator = IdGenerator::new(0xABC, 0x5D);
+        let parts = IdGenerator::decompose128(generator.gen128());
+        assert_eq!((parts.node_id, parts.shard_id), (0xABC, 0x5D));
+        assert_eq!(parts.thread_id, generator.thread_id());
+        assert_eq!(parts.reserved & 0b11, LAYOUT_VERSION);
+        let layout = AtomicId::<128>::layout();
+        let id = generator.gen128();
+        assert_eq!(layout.field("node").unwrap().extract(id), 0xABC);
+        assert_eq!(layout.field("sequence").unwrap().extract(id), IdGenerator::decompose128(id).sequence as u128);
+
+        // With version bits the tag takes only the 2 bits above the version.
+        for tag in 0..=3 {
+            let id = generator.gen128_tagged(tag).unwrap();
+            let parts = IdGenerator::decompose128(id);
+            assert_eq!(IdGenerator::tag128(id), tag);
+            assert_eq!(parts.reserved, tag << 2 | LAYOUT_VERSION);
+            assert_eq!((parts.node_id, parts.shard_id), (0xABC, 0x5D));
+            assert_eq!(IdGenerator::layout_version128(id), LAYOUT_VERSION);
+        }
+        assert_eq!(generator.gen128_tagged(4), Err(IdError::InvalidTag(4)));
+
+        AtomicOption::version_bits(false);
+        for tag in 0..=15 {
+            let id = generator.gen128_tagged(tag).unwrap();
+            assert_eq!(IdGenerator::tag128(id), tag);
+            assert_eq!(IdGenerator::decompose128(id).reserved, tag);
+        }
+        let (a, b) = (generator.gen128_tagged(3).unwrap(), generator.gen128_tagged(3).unwrap());
+        assert_eq!(IdGenerator::decompose128(b).sequence, (IdGenerator::decompose128(a).sequence + 1) & 0xFF_FFFF);
+        assert_eq!(generator.gen128_tagged(16), Err(IdError::InvalidTag(16)));
+        assert_eq!(generator.gen128_tagged(u8::MAX), Err(IdError::InvalidTag(u8::MAX)));
     }
-    /// Generate a new 128-bit ID, encoded as a 22-character base58 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x128};
-    /// let id = AtomicId::<x128>::base58();
-    /// assert_eq!(id.len(), 22);
-    /// ```
-    pub fn base58() -> String {
-        encode::base58(xgen().gen128(), 22)
+
+    /// Test that 128-bit IDs take their timestamp and nanoseconds from one clock
+    /// reading: with a time source the fields decompose to exactly its values, and on
+    /// the system clock the nanoseconds always fall within the timestamp's millisecond.
+    #[test]
+    fn test_gen128_single_clock_read() {
+        static NOW_NS: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW_NS.load(Ordering::Relaxed) / 1_000_000
+        }
+        fn now_ns() -> u64 {
+            NOW_NS.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        let generator = IdGenerator::new(0x123, 0x45);
+        let epoch = generator.epoch_ms();
+        for _ in 0..10_000 {
+            let parts = IdGenerator::decompose128(generator.gen128());
+            let ms = epoch.wrapping_add(parts.timestamp);
+            let offset = (parts.nanos as u64).wrapping_sub(ms.wrapping_mul(1_000_000)) & 0xFFFF_FFFF;
+            assert!(offset < 1_000_000, "{:?}", parts);
+        }
+
+        let ns = (epoch + 1_000) * 1_000_000 + 456_789;
+        NOW_NS.store(ns, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns });
+        let seq = SEQ_128.load(Ordering::Relaxed);
+        let parts = IdGenerator::decompose128(generator.gen128());
+        assert_eq!(parts.timestamp, 1_000);
+        assert_eq!(parts.nanos, ns as u32);
+        assert_eq!(parts.reserved, (ns.rotate_right(16) & 0xF) as u8);
+        assert_eq!((parts.node_id, parts.shard_id, parts.thread_id), (0x123, 0x45, generator.thread_id()));
+        assert_eq!(parts.sequence, seq as u32 & 0xFF_FFFF);
+        // The two-read path kept for the benchmarks builds the same ID from the same clock.
+        let (one, two) = (generator.gen128(), generator.__gen128_two_reads());
+        assert_eq!(two - one, 1 << 8);
     }
-    /// Generate a new 128-bit ID, encoded as a 20-character base91 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x128};
-    /// let id = AtomicId::<x128>::base91();
-    /// assert_eq!(id.len(), 20);
-    /// ```
-    pub fn base91() -> String {
-        encode::base91(xgen().gen128(), 20)
+
+    /// Test that folds of wide IDs match values pinned from the documented algorithm,
+    /// and that IDs differing in a single part fold apart.
+    #[test]
+    fn test_fold() {
+        assert_eq!(Id128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210).fold64(), 0xa0e1_eaaa_e134_deaa);
+        assert_eq!(Id128(1 << 64).fold64(), 0x7ab4_0e09_0f36_3a7d);
+        let id = Id256([1, 2, 3, u64::MAX]);
+        assert_eq!(id.fold64(), 0x02eb_1186_5b25_fd8a);
+        assert_eq!(id.fold128(), 0x02eb_1186_5b25_fd8a_304b_bab3_7dda_c0ff);
+        assert_eq!(Id256::from_be_bytes(id.to_be_bytes()).fold64(), id.fold64());
+
+        let _guard = guard();
+        let base = Id256::new();
+        let mut folds = std::collections::HashSet::new();
+        for part in 0..4 {
+            for bit in 0..64 {
+                let mut flipped = base;
+                flipped.0[part] ^= 1 << bit;
+                assert!(folds.insert(flipped.fold64()));
+                assert_ne!(flipped.fold128() as u64, base.fold128() as u64);
+            }
+        }
+        assert!(!folds.contains(&base.fold64()));
+        let wide = Id128::new();
+        assert_ne!(Id128(wide.0 ^ 1).fold64(), wide.fold64());
+        assert_ne!(Id128(wide.0 ^ 1 << 64).fold64(), wide.fold64());
     }
-    /// Generate a new 128-bit ID, encoded as a 25-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x128};
-    /// let id = AtomicId::<x128>::base36();
-    /// assert_eq!(id.len(), 25);
-    /// ```
-    pub fn base36() -> String {
-        encode::base36(xgen().gen128(), 25)
+
+    /// Test that every raw and encoded pair describes the same ID, in every encoding.
+    #[test]
+    fn test_batch_pairs() {
+        let _guard = guard();
+        for encoding in [Encoding::Base36, Encoding::Base58, Encoding::Base91, Encoding::Base91UrlSafe, Encoding::Hex] {
+            let pairs = AtomicId::<64>::batch_pairs(500, encoding);
+            assert_eq!(pairs.len(), 500);
+            for (raw, id) in pairs.iter().chain([AtomicId::<64>::new_pair(encoding)].iter()) {
+                assert_eq!(id.len(), encoding.encoded_len(64).unwrap());
+                assert_eq!(encoding.decode(id), Ok(*raw as u128));
+            }
+            assert!(pairs.windows(2).all(|pair| pair[0].0 != pair[1].0));
+
+            for (raw, id) in AtomicId::<128>::batch_pairs(500, encoding).iter().chain([AtomicId::<128>::new_pair(encoding)].iter()) {
+                assert_eq!(id.len(), encoding.encoded_len(128).unwrap());
+                assert_eq!(encoding.decode(id), Ok(*raw));
+            }
+            let (raw, id) = AtomicId::<256>::new_pair(encoding);
+            assert_eq!(AnyId::parse(&id, Width::X256, encoding), Ok(AnyId::X256(raw)));
+        }
     }
-    /// Generate a new 128-bit ID, encoded as a 32-character hexadecimal string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x128};
-    /// let id = AtomicId::<x128>::hex();
-    /// assert_eq!(id.len(), 32);
-    /// ```
-    pub fn hex() -> String {
-        encode::hex(xgen().gen128(), 32)
+
+    /// Test that the self-test accounts for every ID, finds no duplicates from one
+    /// thread in the default configuration, and counts them when the settings do
+    /// collide.
+    #[test]
+    fn test_self_test() {
+        let _guard = guard();
+        let report = AtomicId::<64>::self_test(100_003, 7);
+        assert_eq!(report.generated, 100_003);
+        assert_eq!(report.unique + report.duplicates, 100_003);
+        let report = AtomicId::<64>::self_test(50_000, 1);
+        assert_eq!((report.unique, report.duplicates), (50_000, 0));
+        assert_eq!(AtomicId::<64>::self_test(10, 0).unique, 10);
+        assert_eq!(AtomicId::<64>::self_test(0, 4).generated, 0);
+
+        // With the clock stopped and one thread ID, the 16-bit sequence wraps.
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        NOW.store(current_epoch() + 1_000, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        AtomicOption::thread_entropy(|| 0);
+        let report = AtomicId::<64>::self_test(70_000, 1);
+        assert_eq!(report.unique, 1 << 16);
+        assert_eq!(report.duplicates, 70_000 - (1 << 16));
     }
 
-    /// Generate a batch of 128-bit IDs, encoded as base36 strings.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x128};
-    /// let ids = AtomicId::<x128>::batch(3);
-    /// assert_eq!(ids.len(), 3);
-    /// ```
-    pub fn batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::new()).collect()
+    /// Test that the duplicate check reports a repeat forced by wrapping the sequence
+    /// within one millisecond, and misses it once the window is too small.
+    #[cfg(feature = "debug_checks")]
+    #[test]
+    fn test_debug_duplicate_check() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        static REPEATED: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        fn on_event(event: IdEvent) {
+            if let IdEvent::DuplicateId { width: 64, id } = event {
+                REPEATED.store(id as u64, Ordering::Relaxed);
+            }
+        }
+        let _guard = guard();
+        NOW.store(current_epoch() + 1_000, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        AtomicOption::on_event(on_event);
+        let generator = xgen();
+        let wrap = 1 << seq_bits64(thread_id_bits());
+
+        AtomicOption::debug_duplicate_check(true);
+        let first = generator.gen64();
+        for _ in 1..wrap {
+            generator.gen64();
+        }
+        let result = std::panic::catch_unwind(|| generator.gen64());
+        assert_eq!(result.is_err(), cfg!(debug_assertions));
+        assert_eq!(REPEATED.load(Ordering::Relaxed), first);
+
+        AtomicOption::debug_duplicate_check(false);
+        AtomicOption::debug_duplicate_check(true);
+        AtomicOption::debug_duplicate_capacity(wrap / 2);
+        REPEATED.store(0, Ordering::Relaxed);
+        for _ in 0..=wrap {
+            generator.gen64();
+        }
+        assert_eq!(REPEATED.load(Ordering::Relaxed), 0);
+        AtomicOption::debug_duplicate_check(false);
+        AtomicOption::debug_duplicate_capacity(0);
+        assert_eq!(AtomicOption::get_debug_duplicate_capacity(), 1);
+        AtomicOption::debug_duplicate_capacity(65_536);
     }
-    /// Generate a batch of 128-bit IDs as base58 strings.
-    pub fn base58_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base58()).collect()
+
+    /// Test that regional IDs round-trip the region of the generator or, without one,
+    /// the global region, and that out-of-range regions are refused.
+    #[test]
+    fn test_gen64_regional() {
+        let _guard = guard();
+        for region in 0..=15 {
+            let generator = IdGenerator::builder().node_id(0xABC).shard_id(0x15).region_id(region).build();
+            let parts = IdGenerator::decompose64_regional(generator.gen64_regional());
+            assert_eq!((parts.region, parts.node_id, parts.shard_id), (region, 0xABC, 0x5));
+            assert_eq!(AtomicId::<64>::layout().field("region"), None);
+        }
+
+        let generator = IdGenerator::new(7, 3);
+        assert_eq!(generator.region_id(), 0);
+        AtomicOption::region_id(9).unwrap();
+        assert_eq!(generator.region_id(), 9);
+        assert_eq!(IdGenerator::decompose64_regional(generator.gen64_regional()).region, 9);
+        assert_eq!(IdGenerator::decompose64(generator.gen64()).region, 0);
+        assert_eq!(AtomicOption::region_id(16), Err(IdError::InvalidRegion(16)));
+        assert_eq!(AtomicOption::get_region_id(), 9);
+
+        AtomicOption::layout64(Layout64::Regional);
+        let id = generator.gen64();
+        assert_eq!(AtomicId::<64>::layout().field("region").unwrap().extract(id as u128), 9);
+        assert_eq!(Layout64::current().decompose(id), IdGenerator::decompose64_regional(id));
+        assert_eq!(id >> 40 & 0xF, 9);
+        assert!(std::panic::catch_unwind(|| IdGenerator::builder().region_id(16)).is_err());
     }
-    /// Generate a batch of 128-bit IDs as base91 strings.
-    pub fn base91_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base91()).collect()
+
+    /// Test that versioned IDs minted under every layout decode in their own layout,
+    /// whatever the current one, with the full time fields, and that IDs recording no
+    /// layout are refused rather than guessed at.
+    #[test]
+    fn test_gen64_versioned() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        AtomicOption::version_bits(true);
+        NOW.store(current_epoch() + (5 << 20) + 1234, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let generator = IdGenerator::builder().node_id(0xABC).shard_id(0x15).region_id(9).build();
+        let layouts = [Layout64::Standard, Layout64::WideNode, Layout64::Era, Layout64::Regional];
+        let ids: Vec<_> = layouts
+            .iter()
+            .map(|&layout| {
+                AtomicOption::layout64(layout);
+                (layout, generator.gen64_versioned().unwrap())
+            })
+            .collect();
+        AtomicOption::layout64(Layout64::Standard);
+        for (version, &(layout, id)) in (1..).zip(&ids) {
+            assert_eq!(IdGenerator::format_version(id), version);
+            assert_eq!(Layout64::from_format_version(version), Some(layout));
+            assert_eq!(IdGenerator::layout_version(id), FORMAT_LAYOUT_VERSION);
+            let parts = IdGenerator::decompose_auto(id).unwrap();
+            let era = if layout == Layout64::Era { 5 } else { 0 };
+            assert_eq!((parts.era, parts.timestamp, parts.node_id), (era, 1234, 0xABC));
+            let shard_mask = if matches!(layout, Layout64::WideNode | Layout64::Regional) { 0xF } else { 0x1F };
+            assert_eq!(parts.shard_id, 0x15 & shard_mask);
+            assert_eq!(parts.region, if layout == Layout64::Regional { 9 } else { 0 });
+            assert_eq!((parts.wide_thread_id, parts.version), (generator.thread_id_in(8), FORMAT_LAYOUT_VERSION));
+            assert_eq!(AtomicId::<x64>::same_node(id, generator.gen64()), Ok(true));
+        }
+        // The sequence keeps counting, 4 bits narrower.
+        let (a, b) = (generator.gen64_versioned().unwrap(), generator.gen64_versioned().unwrap());
+        let (a, b) = (IdGenerator::decompose_auto(a).unwrap(), IdGenerator::decompose_auto(b).unwrap());
+        assert_eq!(b.sequence, (a.sequence + 1) & 0xFFF);
+        let (_, era_id) = ids[2];
+        let standard_id = generator.gen64();
+        assert_eq!(AtomicId::<x64>::time_between(era_id, standard_id), Ok(Duration::from_millis(5 << 20)));
+        // Later versioned IDs sort after earlier ones.
+        NOW.fetch_add(1, Ordering::Relaxed);
+        assert!(generator.gen64_versioned().unwrap() > ids[0].1);
+
+        // IDs with layout version 1 or without version bits record no layout.
+        assert_eq!(IdGenerator::decompose_auto(generator.gen64()), Err(IdError::UnknownFormatVersion(0)));
+        assert_eq!(IdGenerator::decompose_auto(generator.gen64() | 3), Err(IdError::UnknownFormatVersion(0)));
+        AtomicOption::version_bits(false);
+        let unversioned = generator.gen64() & !0b11;
+        assert_eq!(IdGenerator::decompose_auto(unversioned), Err(IdError::UnknownFormatVersion(0)));
+        let id = ids[3].1;
+        assert_eq!(IdGenerator::decompose_auto(id).unwrap().region, 9);
+        // Versioned IDs still decode without the flag, but are no longer generated.
+        assert_eq!(IdGenerator::layout_version(id), 0);
+        assert_eq!(IdGenerator::decompose_auto(ids[1].1).unwrap().node_id, 0xABC);
+        assert_eq!(generator.gen64_versioned(), Err(IdError::VersionBitsDisabled));
     }
-    /// Generate a batch of 128-bit IDs as base36 strings.
-    pub fn base36_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base36()).collect()
+
+    /// Test that versioned and plain IDs interleaved within one millisecond never
+    /// collide, and that versioned IDs are refused while version bits are disabled.
+    #[test]
+    fn test_gen64_versioned_interleaved() {
+        fn now_ms() -> u64 {
+            current_epoch() + 1234
+        }
+        let _guard = guard();
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let generator = IdGenerator::new(7, 3);
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..1000 {
+            assert!(seen.insert(generator.gen64()));
+            assert_eq!(generator.gen64_versioned(), Err(IdError::VersionBitsDisabled));
+        }
+        AtomicOption::version_bits(true);
+        for _ in 0..1000 {
+            assert!(seen.insert(generator.gen64()));
+            assert!(seen.insert(generator.gen64_versioned().unwrap()));
+        }
+        assert!(seen.iter().all(|&id| IdGenerator::decompose64(id).timestamp == 1234));
     }
-    /// Generate a batch of 128-bit IDs as hexadecimal strings.
-    pub fn hex_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::hex()).collect()
+
+    /// Test that reconfiguring swaps the node and shard of every later global ID, that
+    /// a generator in use is kept alive across a swap, and that guards restore it.
+    #[cfg(feature = "reconfigure")]
+    #[test]
+    fn test_reconfigure() {
+        {
+            let _guard = guard();
+            let before = IdGenerator::decompose64(AtomicId::<64>::new_with_raw().0);
+            assert_eq!((before.node_id, before.shard_id), (1, 0));
+            let held = xgen();
+            AtomicOption::reconfigure(42, 7);
+            assert_eq!((held.node_id, IdGenerator::decompose64(held.gen64()).node_id), (1, 1));
+            let after = IdGenerator::decompose64(AtomicId::<64>::new_with_raw().0);
+            assert_eq!((after.node_id, after.shard_id), (42, 7));
+
+            AtomicOption::reconfigure_with(IdGenerator::builder().node_id(9).region_id(3).build());
+            assert_eq!(IdGenerator::decompose64_regional(xgen().gen64_regional()).region, 3);
+            let ids: Vec<_> = (0..1000).map(|_| AtomicId::<64>::new_with_raw().0).collect();
+            assert!(ids.iter().all(|&id| IdGenerator::decompose64(id).node_id == 9));
+
+            // A reconfigured generator counts as initialized, even before first use.
+            GLOBAL.store(core::ptr::null_mut(), Ordering::Release);
+            assert!(AtomicOption::is_initialized());
+            assert_eq!(
+                AtomicOption::init_global(IdGenerator::new(3, 0)),
+                Err(AlreadyInitialized { node_id: 9, shard_id: 0, implicit: false })
+            );
+        }
+        let _guard = guard();
+        assert_eq!(xgen().node_id, 1);
     }
-}
 
-impl AtomicId<256> {
-    /// Generate a new 256-bit ID, encoded as a 52-character base36 string.
-    ///
-    /// The raw 256-bit ID is composed of four 64-bit parts, each of which is
-    /// encoded into a 13-character base36 string and then joined.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x256};
-    /// let id = AtomicId::<x256>::new();
-    /// assert_eq!(id.len(), 52);
-    /// ```
-    pub fn new() -> String {
-        let parts = xgen().gen256();
-        parts
-            .iter()
-            .map(|&p| encode::base36(p as u128, 13))
-            .collect::<Vec<_>>()
-            .join("")
+    /// Test that an ID distinct from `prev` skips the one ID that would repeat it, once
+    /// the sequence wraps back within the same millisecond or when `prev` is the next
+    /// ID, and is the next ID otherwise.
+    #[test]
+    fn test_new_distinct_from() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        NOW.store(current_epoch() + 1_000, Ordering::Relaxed);
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        let generator = xgen();
+        let wrap = 1u64 << seq_bits64(thread_id_bits());
+
+        let prev = generator.gen64();
+        let start = SEQ_64.load(Ordering::Relaxed);
+        SEQ_64.fetch_add(wrap - 1, Ordering::Relaxed);
+        assert_eq!(generator.peek64(), prev);
+        let id = AtomicId::<64>::new_distinct_from(prev);
+        assert_ne!(id, prev);
+        assert_eq!(IdGenerator::decompose64(id).sequence, (IdGenerator::decompose64(prev).sequence + 1) % wrap as u32);
+        assert_eq!(SEQ_64.load(Ordering::Relaxed) - start, wrap + 1);
+
+        // Any other `prev` leaves the next ID as it is.
+        let next = generator.peek64();
+        assert_eq!(AtomicId::<64>::new_distinct_from(prev), next);
+
+        // The next ID itself is skipped, taking one more sequence value.
+        let next = generator.peek64();
+        let start = SEQ_64.load(Ordering::Relaxed);
+        assert_ne!(AtomicId::<64>::new_distinct_from(next), next);
+        assert_eq!(SEQ_64.load(Ordering::Relaxed) - start, 2);
     }
 
-    /// Generate a new 256-bit ID, encoded as a 44-character base58 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x256};
-    /// let id = AtomicId::<x256>::base58();
-    /// assert_eq!(id.len(), 44);
-    /// ```
-    pub fn base58() -> String {
-        let parts = xgen().gen256();
-        parts
-            .iter()
-            .map(|&p| encode::base58(p as u128, 11))
-            .collect::<Vec<_>>()
-            .join("")
+    /// Test that resetting thread IDs restarts sequential numbering on every thread.
+    #[test]
+    fn test_reset_thread_ids() {
+        let _guard = guard();
+        AtomicOption::thread_id_mode(ThreadIdMode::Sequential);
+        fn thread_id() -> u16 {
+            IdGenerator::decompose64(xgen().gen64()).wide_thread_id
+        }
+        let spawned = || std::thread::spawn(thread_id).join().unwrap();
+        let first = thread_id();
+        assert_eq!(spawned(), first + 1);
+
+        AtomicOption::__reset_thread_ids();
+        assert_eq!(spawned(), 1);
+        assert_eq!(thread_id(), 2);
+        assert_eq!(thread_id(), 2);
+        AtomicOption::__reset_thread_ids();
+        assert_eq!(thread_id(), 1);
+        assert_eq!(spawned(), 2);
     }
 
-    /// Generate a new 256-bit ID, encoded as a 40-character base91 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x256};
-    /// let id = AtomicId::<x256>::base91();
-    /// assert_eq!(id.len(), 40);
-    /// ```
-    pub fn base91() -> String {
-        let parts = xgen().gen256();
-        parts
-            .iter()
-            .map(|&p| encode::base91(p as u128, 10))
-            .collect::<Vec<_>>()
-            .join("")
+    /// Test decimal encoding of 64-bit IDs and its decoder.
+    #[test]
+    fn test_decimal() {
+        let _guard = guard();
+        let ids = AtomicId::<64>::decimal_batch(100);
+        assert_eq!(ids.iter().collect::<std::collections::HashSet<_>>().len(), 100);
+        for id in &ids {
+            let raw: u64 = id.parse().unwrap();
+            assert_eq!(AtomicId::<64>::from_decimal(id), Ok(raw));
+            assert_eq!(encode::decimal(raw as u128), *id);
+        }
+        assert_eq!(encode::decimal(u64::MAX as u128), "18446744073709551615");
+        assert_eq!(encode::decimal(0), "0");
+        assert_eq!(AtomicId::<64>::from_decimal("007"), Ok(7));
+        assert_eq!(AtomicId::<64>::from_decimal(""), Err(IdError::Empty));
+        assert_eq!(AtomicId::<64>::from_decimal("-1"), Err(IdError::InvalidCharacter('-')));
+        assert_eq!(AtomicId::<64>::from_decimal("18446744073709551616"), Err(IdError::Overflow));
+        assert_eq!(AtomicId::<64>::from_decimal(&"9".repeat(40)), Err(IdError::Overflow));
     }
 
-    /// Generate a new 256-bit ID, encoded as a 52-character base36 string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x256};
-    /// let id = AtomicId::<x256>::base36();
-    /// assert_eq!(id.len(), 52);
-    /// ```
-    pub fn base36() -> String {
-        let parts = xgen().gen256();
-        parts
-            .iter()
-            .map(|&p| encode::base36(p as u128, 13))
-            .collect::<Vec<_>>()
-            .join("")
+    /// Test that the raw value and encoding returned together describe the same ID.
+    #[test]
+    fn test_new_with_raw() {
+        let _guard = guard();
+        let (raw, id) = AtomicId::<64>::new_with_raw();
+        assert_eq!(id, encode::base36(raw as u128, 13));
+        let (raw, id) = AtomicId::<64>::base58_with_raw();
+        assert_eq!(id, encode::base58(raw as u128, 11));
+        let (raw, id) = AtomicId::<64>::hex_with_raw();
+        assert_eq!(u64::from_str_radix(&id, 16).unwrap(), raw);
     }
 
-    /// Generate a new 256-bit ID, encoded as a 64-character hexadecimal string.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x256};
-    /// let id = AtomicId::<x256>::hex();
-    /// assert_eq!(id.len(), 64);
-    /// ```
-    pub fn hex() -> String {
-        let parts = xgen().gen256();
-        format!(
-            "{:016x}{:016x}{:016x}{:016x}",
-            parts[0], parts[1], parts[2], parts[3]
-        )
+    /// Test the time left before the timestamp wraps and the checked generator.
+    #[test]
+    fn test_timestamp_wrap() {
+        assert_eq!(ms_until_wrap(0, TS_BITS_64), 1 << 20);
+        assert_eq!(ms_until_wrap((1 << 20) - 1, TS_BITS_64), 1);
+        assert_eq!(ms_until_wrap(3 << 20, TS_BITS_64), 1 << 20);
+
+        let _guard = testing::TestGuard::scoped(testing::TestConfig {
+            deterministic: Some((1 << 20) - 100),
+            ..Default::default()
+        });
+        // The logical clock starts 100 ms before the wrap; 1% is 10,485 ms.
+        assert_eq!(
+            AtomicId::<64>::time_remaining(),
+            std::time::Duration::from_millis(100)
+        );
+        let before = SEQ_64.load(Ordering::Relaxed);
+        assert_eq!(
+            xgen().gen64_checked(),
+            Err(IdError::TimestampNearWrap(std::time::Duration::from_millis(99)))
+        );
+        assert_eq!(SEQ_64.load(Ordering::Relaxed), before);
+
+        AtomicOption::wrap_threshold(0);
+        assert!(xgen().gen64_checked().is_ok());
     }
 
-    /// Generate a batch of 256-bit IDs, encoded as base36 strings.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::{AtomicId, x256};
-    /// let ids = AtomicId::<x256>::batch(3);
-    /// assert_eq!(ids.len(), 3);
-    /// ```
-    pub fn batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::new()).collect()
+    /// Test that every encoding decodes its own output, including padding.
+    #[test]
+    fn test_encoding_round_trip() {
+        let encodings = [
+            Encoding::Base36,
+            Encoding::Base58,
+            Encoding::Base91,
+            Encoding::Base91UrlSafe,
+            Encoding::Hex,
+        ];
+        for encoding in encodings {
+            for n in [0, 1, 12345, u64::MAX as u128, u128::MAX] {
+                let s = encoding.encode(n, encoding.encoded_len(128).unwrap());
+                assert_eq!(encoding.decode(&s), Ok(n), "{:?} {}", encoding, s);
+            }
+        }
+        assert_eq!(Encoding::Hex.decode("FF"), Err(IdError::InvalidCharacter('F')));
+        assert_eq!(Encoding::Base58.decode("0"), Err(IdError::InvalidCharacter('0')));
+        assert_eq!(Encoding::Hex.decode(&"f".repeat(33)), Err(IdError::Overflow));
+        assert_eq!(Encoding::Base91.decode(""), Err(IdError::Empty));
+        assert_eq!(Encoding::Base36.encode(0, 0), "0");
     }
-    /// Generate a batch of 256-bit IDs as base58 strings.
-    pub fn base58_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base58()).collect()
+
+    /// Test that the base always follows the alphabet, from binary up to 256 symbols.
+    #[test]
+    fn test_to_base_alphabets() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for n in [0, 1, 255, 256, 0xDEADBEEF, u128::MAX] {
+            let s = encode::to_base(n, &bytes, 16);
+            assert_eq!(encode::from_base(&s, &bytes), Ok(n), "{:?}", s);
+            assert_eq!(s.chars().count(), 16);
+
+            let binary = encode::to_base(n, b"01", 0);
+            assert_eq!(binary, format!("{:b}", n));
+            assert_eq!(encode::from_base(&binary, b"01"), Ok(n));
+        }
+        assert_eq!(encode::to_base(0x1FF, &bytes, 0), "\u{1}\u{ff}");
+        assert_eq!(encode::from_base("\u{100}", &bytes), Err(IdError::InvalidCharacter('\u{100}')));
+
+        for alphabet in [&b"0"[..], &[]] {
+            assert!(std::panic::catch_unwind(|| encode::to_base(1, alphabet, 0)).is_err());
+        }
+        let too_long: Vec<u8> = bytes.iter().chain(b"x").copied().collect();
+        assert!(std::panic::catch_unwind(|| encode::from_base("x", &too_long)).is_err());
+        #[cfg(debug_assertions)]
+        assert!(std::panic::catch_unwind(|| encode::to_base(1, b"0120", 0)).is_err());
     }
-    /// Generate a batch of 256-bit IDs as base91 strings.
-    pub fn base91_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base91()).collect()
+
+    /// Test that `to_base` writes the same bytes as the push-and-reverse encoder it
+    /// replaced, for every encoding and width.
+    #[test]
+    fn test_to_base_matches_reversed() {
+        fn reversed(mut n: u128, alphabet: &[u8], min_width: usize) -> String {
+            let base = alphabet.len() as u128;
+            let mut chars = Vec::new();
+            while n > 0 {
+                chars.push(alphabet[(n % base) as usize]);
+                n /= base;
+            }
+            while chars.len() < min_width.max(1) {
+                chars.push(alphabet[0]);
+            }
+            chars.iter().rev().map(|&b| char::from(b)).collect()
+        }
+
+        const STEP: u128 = 0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835;
+        for encoding in [
+            Encoding::Base36,
+            Encoding::Base58,
+            Encoding::Base91,
+            Encoding::Base91UrlSafe,
+            Encoding::Hex,
+        ] {
+            let alphabet = encoding.alphabet();
+            for bits in [24, 32, 64, 128] {
+                let len = encoding.encoded_len(bits).unwrap();
+                let max = u128::MAX >> (128 - bits);
+                let samples = (1..=64u128).map(|i| i.wrapping_mul(STEP) & max);
+                for n in [0, 1, max - 1, max].into_iter().chain(samples) {
+                    for width in [0, len, len + 3] {
+                        let s = encode::to_base(n, alphabet, width);
+                        assert_eq!(s, reversed(n, alphabet, width), "{:?} {}", encoding, n);
+                        assert_eq!(encode::from_base(&s, alphabet), Ok(n));
+                    }
+                }
+            }
+        }
+        let bytes: Vec<u8> = (0..=255).collect();
+        for n in [0, 0xFF, 0x100, u128::MAX] {
+            assert_eq!(encode::to_base(n, &bytes, 20), reversed(n, &bytes, 20));
+            assert_eq!(encode::to_base(n, b"01", 130), reversed(n, b"01", 130));
+        }
     }
-    /// Generate a batch of 256-bit IDs as base36 strings.
-    pub fn base36_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::base36()).collect()
+
+    /// Test that child IDs share the parent's time bucket and are distinct.
+    #[test]
+    fn test_gen64_child() {
+        let _guard = guard();
+        let generator = IdGenerator::new(300, 7);
+        let parent = IdGenerator::new(9, 2).gen64();
+        let children: Vec<u64> = (0..1000).map(|_| generator.gen64_child(parent)).collect();
+
+        let expected = IdGenerator::decompose64(parent);
+        for &child in &children {
+            let parts = IdGenerator::decompose64(child);
+            assert_eq!(
+                (parts.timestamp, parts.node_id, parts.shard_id),
+                (expected.timestamp, expected.node_id, expected.shard_id)
+            );
+        }
+        let unique: std::collections::HashSet<u64> = children.iter().copied().chain([parent]).collect();
+        assert_eq!(unique.len(), 1001);
+
+        AtomicOption::layout64(Layout64::WideNode);
+        let parent = generator.gen64();
+        let child = generator.gen64_child(parent);
+        assert_eq!(IdGenerator::decompose64_wide(child).node_id, 300);
+        assert_eq!(child >> 24, parent >> 24);
     }
-    /// Generate a batch of 256-bit IDs as hexadecimal strings.
-    pub fn hex_batch(n: usize) -> Vec<String> {
-        (0..n).map(|_| Self::hex()).collect()
+
+    /// Test that a reserved block is contiguous despite concurrent `gen64` calls, and
+    /// that its IDs share a timestamp and are unique.
+    #[test]
+    fn test_reserve64() {
+        let _guard = guard();
+        let generator = IdGenerator::new(300, 7);
+        let (block, singles) = std::thread::scope(|s| {
+            let singles = s.spawn(|| (0..10_000).map(|_| generator.gen64()).collect::<Vec<u64>>());
+            let block = generator.reserve64(1000);
+            (block, singles.join().unwrap())
+        });
+        assert_eq!(*block.end() - *block.start(), 999);
+        let ids: Vec<u64> = generator.gen64_reserved(block.clone()).collect();
+        assert_eq!(ids.len(), 1000);
+
+        let first = IdGenerator::decompose64(ids[0]);
+        for (&id, seq) in ids.iter().zip(block) {
+            let parts = IdGenerator::decompose64(id);
+            assert_eq!((parts.timestamp, parts.thread_id), (first.timestamp, first.thread_id));
+            assert_eq!(parts.sequence as u64, seq & 0x3FFF);
+        }
+        let unique: std::collections::HashSet<u64> = ids.iter().chain(&singles).copied().collect();
+        assert_eq!(unique.len(), 11_000);
+
+        assert!(generator.reserve64(0).is_empty());
+        assert_eq!(generator.gen64_reserved(generator.reserve64(0)).count(), 0);
+
+        // The counter wraps instead of overflowing.
+        SEQ_64.store(u64::MAX - 1, Ordering::Relaxed);
+        assert_eq!(generator.reserve64(2), u64::MAX - 1..=u64::MAX);
+        SEQ_64.store(u64::MAX - 1, Ordering::Relaxed);
+        assert!(generator.reserve64(4).is_empty());
+        assert_eq!(SEQ_64.load(Ordering::Relaxed), 2);
     }
-}
 
-/// Provides methods for configuring global settings for `atomic-id`.
-///
-/// Use this struct to manage the global epoch for timestamp-based ID generation.
-pub struct AtomicOption;
+    /// Test that grouped IDs of every width and encoding decode to the raw value once
+    /// ungrouped, and that separators from the alphabet are refused.
+    #[test]
+    fn test_grouped() {
+        fn round_trip<const BITS: usize>(width: Width)
+        where
+            AtomicId<BITS>: BitWidth,
+        {
+            for encoding in [
+                Encoding::Base36,
+                Encoding::Base58,
+                Encoding::Base91,
+                Encoding::Base91UrlSafe,
+                Encoding::Hex,
+            ] {
+                let len = encoding.encoded_len(BITS).unwrap();
+                for group in [0, 1, 4, 7, len, len + 1] {
+                    let grouped = AtomicId::<BITS>::grouped_with(encoding, group, '·').unwrap();
+                    let seps = (len - 1).checked_div(group).unwrap_or(0);
+                    assert_eq!(grouped.matches('·').count(), seps, "{}", grouped);
+                    let body = ungroup(&grouped, '·');
+                    assert_eq!(body.len(), len);
+                    let parsed = AnyId::parse(&body, width, encoding).unwrap();
+                    assert_eq!(parsed.encode(encoding), body);
+                }
+                assert_eq!(
+                    AtomicId::<BITS>::grouped_with(encoding, 4, '1'),
+                    Err(IdError::SeparatorInAlphabet('1'))
+                );
+            }
+            let grouped = AtomicId::<BITS>::grouped(4, '-');
+            assert_eq!(AtomicId::<BITS>::new().len(), ungroup(&grouped, '-').len());
+            assert!(std::panic::catch_unwind(|| AtomicId::<BITS>::grouped(4, 'A')).is_err());
+        }
 
-impl AtomicOption {
-    /// Set the global custom epoch for timestamp-based IDs.
-    ///
-    /// The epoch is the point in time from which the timestamp portion of an ID is measured.
-    /// Setting a more recent epoch can extend the lifespan of the generator.
-    ///
-    /// # Arguments
-    /// * `ms` - The epoch timestamp in milliseconds since the UNIX epoch.
-    ///
-    /// # Example
-    /// ```
-    /// use atomic_id::AtomicOption;
-    /// // Set the epoch to January 1, 2024
-    /// AtomicOption::epoch(1704067200000);
-    /// ```
-    pub fn epoch(ms: u64) {
-        CUSTOM_EPOCH.store(ms, Ordering::Relaxed);
+        let _guard = guard();
+        #[cfg(feature = "short")]
+        {
+            round_trip::<24>(Width::X24);
+            round_trip::<32>(Width::X32);
+        }
+        round_trip::<64>(Width::X64);
+        round_trip::<128>(Width::X128);
+        round_trip::<256>(Width::X256);
+
+        let grouped = AtomicId::<x256>::grouped(13, '-');
+        assert_eq!(grouped.matches('-').count(), 3);
+        assert!(AtomicId::<x256>::from_canonical(&ungroup(&grouped, '-'), Encoding::Base36).is_ok());
     }
 
-    /// Get the current global epoch value.
-    ///
-    /// # Returns
-    /// The current epoch in milliseconds since the UNIX epoch.
-    pub fn get_epoch() -> u64 {
-        CUSTOM_EPOCH.load(Ordering::Relaxed)
+    /// Test that re-encoding matches decoding and encoding again, for every pair of
+    /// encodings and every width, and refuses strings of the wrong length, in the
+    /// wrong alphabet, or out of range for the width.
+    #[test]
+    fn test_reencode() {
+        let encodings = [
+            Encoding::Base36,
+            Encoding::Base58,
+            Encoding::Base91,
+            Encoding::Base91UrlSafe,
+            Encoding::Hex,
+        ];
+        let _guard = guard();
+        let values = [
+            AnyId::X24(0xABCDEF),
+            AnyId::X32(0),
+            AnyId::X64(xgen().gen64()),
+            AnyId::X128(xgen().gen128()),
+            AnyId::X256(xgen().gen256()),
+        ];
+        for id in values {
+            let width = Width::try_from(id.width()).unwrap();
+            for from in encodings {
+                for to in encodings {
+                    assert_eq!(reencode(&id.encode(from), from, to, width), Ok(id.encode(to)));
+                }
+            }
+        }
+        assert_eq!(reencode("", Encoding::Hex, Encoding::Base36, Width::X32), Err(IdError::InvalidLength(0)));
+        assert_eq!(
+            reencode("000000FF", Encoding::Hex, Encoding::Base36, Width::X32),
+            Err(IdError::InvalidCharacter('F'))
+        );
+        assert_eq!(reencode("1ffffff", Encoding::Hex, Encoding::Base36, Width::X24), Err(IdError::InvalidLength(7)));
+        assert_eq!(reencode("zzzzz", Encoding::Base36, Encoding::Hex, Width::X24), Err(IdError::Overflow));
     }
 
-    /// Reset the global epoch to its default value (`2022-05-01 00:00:00 UTC`).
-    pub fn reset_epoch() {
-        CUSTOM_EPOCH.store(DEFAULT_EPOCH, Ordering::Relaxed);
+    /// Test that `init_global` fixes the global generator once, that the error names
+    /// the defaults locked in by generating first, and that reading the generator's
+    /// settings without generating locks nothing in.
+    #[test]
+    fn test_init_global() {
+        static EVENTS: std::sync::Mutex<Vec<IdEvent>> = std::sync::Mutex::new(Vec::new());
+        fn record(event: IdEvent) {
+            // Unguarded tests may raise other events in the meantime.
+            if matches!(event, IdEvent::ImplicitInit { .. }) {
+                EVENTS.lock().unwrap().push(event);
+            }
+        }
+        let _guard = guard();
+        AtomicOption::on_event(record);
+
+        GLOBAL.store(core::ptr::null_mut(), Ordering::Release);
+        AtomicId::<x64>::time_remaining();
+        AtomicId::<x128>::time_remaining();
+        Id64::min_for(SystemTime::now()).unwrap();
+        assert!(!AtomicOption::is_initialized());
+        assert_eq!(AtomicOption::init_global(IdGenerator::new(42, 7)), Ok(()));
+        assert!(AtomicOption::is_initialized());
+        let (id, _) = AtomicId::<x64>::new_with_raw();
+        assert_eq!(IdGenerator::decompose64(id).node_id, 42);
+        let err = AtomicOption::init_global(IdGenerator::new(3, 0)).unwrap_err();
+        assert_eq!(err, AlreadyInitialized { node_id: 42, shard_id: 7, implicit: false });
+        assert_eq!(xgen().node_id, 42);
+        assert!(EVENTS.lock().unwrap().is_empty());
+
+        GLOBAL.store(core::ptr::null_mut(), Ordering::Release);
+        AtomicId::<x64>::new();
+        assert!(AtomicOption::is_initialized());
+        let err = AtomicOption::init_global(IdGenerator::new(42, 7)).unwrap_err();
+        assert_eq!(err, AlreadyInitialized { node_id: 1, shard_id: 0, implicit: true });
+        assert!(err.to_string().contains("defaults node_id=1, shard_id=0"), "{}", err);
+        AtomicOption::clear_on_event();
+        let expected = if cfg!(debug_assertions) {
+            vec![IdEvent::ImplicitInit { node_id: 1, shard_id: 0 }]
+        } else {
+            vec![]
+        };
+        assert_eq!(*EVENTS.lock().unwrap(), expected);
     }
-}
 
+    /// Test that concurrent threads generating flat out stay under the rate limit in
+    /// every millisecond and never repeat an ID, that a full millisecond waits, and
+    /// that a limit past the sequence field is clamped to it.
+    #[test]
+    fn test_max_rate_per_ms() {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn now_ms() -> u64 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let _guard = guard();
+        AtomicOption::max_rate_per_ms(200);
+        let generator = IdGenerator::new(1, 0);
+        let ids: Vec<u64> = std::thread::scope(|s| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| s.spawn(|| (0..1000).map(|_| generator.gen64()).collect::<Vec<u64>>()))
+                .collect();
+            workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+        });
+        let unique: std::collections::HashSet<u64> = ids.iter().copied().collect();
+        assert_eq!(unique.len(), 4000);
+        let mut per_ms = std::collections::HashMap::new();
+        for &id in &ids {
+            *per_ms.entry(IdGenerator::decompose64(id).timestamp).or_insert(0) += 1;
+        }
+        assert!(per_ms.values().all(|&n| n <= 200), "{:?}", per_ms.values().max());
+        assert!(per_ms.len() >= 20);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        AtomicOption::time_source(TimeSource { now_ms, now_ns: now_ms });
+        NOW.store(AtomicOption::get_epoch() + 1000, Ordering::Relaxed);
+        AtomicOption::max_rate_per_ms(2);
+        generator.gen64();
+        generator.gen64();
+        let waiter = std::thread::spawn(move || IdGenerator::new(1, 0).gen64());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        NOW.fetch_add(1, Ordering::Relaxed);
+        let id = waiter.join().unwrap();
+        assert_eq!(IdGenerator::decompose64(id).timestamp, 1001);
 
-    /// Test generation of 64-bit IDs in all encodings.
+        // A limit past the sequence field is clamped to it.
+        NOW.store(AtomicOption::get_epoch() + 2000, Ordering::Relaxed);
+        AtomicOption::max_rate_per_ms(u32::MAX);
+        assert_eq!(AtomicOption::get_max_rate_per_ms(), u32::MAX);
+        for _ in 0..1 << 16 {
+            generator.gen64();
+        }
+        let waiter = std::thread::spawn(move || IdGenerator::new(1, 0).gen64());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        NOW.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(IdGenerator::decompose64(waiter.join().unwrap()).timestamp, 2001);
+    }
+
+    /// Test that concurrent leases hand out unique IDs across re-leases, and that the
+    /// block size is kept within the sequence field.
     #[test]
-    fn test_gen64() {
-        let id = AtomicId::<64>::new();
-        println!("Generated ID: {}", id);
-        let id = AtomicId::<64>::base36();
-        println!("Generated ID: {}", id);
-        let id = AtomicId::<64>::base58();
-        println!("Generated ID: {}", id);
-        let id = AtomicId::<64>::base91();
-        println!("Generated ID: {}", id);
-        let id = AtomicId::<64>::hex();
-        println!("Generated ID: {}", id);
+    fn test_lease() {
+        fn assert_send<T: Send>(_: &T) {}
+        let _guard = guard();
+        let ids: Vec<u64> = std::thread::scope(|s| {
+            let workers: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut lease = AtomicId::<x64>::lease(1000);
+                        assert_send(&lease);
+                        lease.by_ref().take(3000).collect::<Vec<u64>>()
+                    })
+                })
+                .collect();
+            workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+        });
+        let unique: std::collections::HashSet<u64> = ids.iter().copied().collect();
+        assert_eq!(unique.len(), 12_000);
+
+        let mut lease = AtomicId::<x64>::lease(2);
+        assert_eq!((lease.size(), lease.remaining()), (2, 2));
+        let block: Vec<u64> = lease.by_ref().take(3).collect();
+        assert_eq!(lease.remaining(), 1);
+        assert_eq!(IdGenerator::decompose64(block[0]).timestamp, IdGenerator::decompose64(block[1]).timestamp);
+        assert_eq!(AtomicId::<x64>::lease(0).size(), 1);
+        assert_eq!(AtomicId::<x64>::lease(usize::MAX).size(), 1 << 12);
+        AtomicOption::max_rate_per_ms(1000);
+        assert_eq!(AtomicId::<x64>::lease(usize::MAX).size(), 1000);
+        AtomicOption::max_rate_per_ms(0);
+
+        // Back-to-back full-size leases on one thread outrun the sequence field within
+        // a millisecond, and wait for the next one instead of repeating.
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..64 {
+            for id in AtomicId::<x64>::lease(usize::MAX).take(1 << 12) {
+                assert!(seen.insert(id), "{:#x}", id);
+            }
+        }
+        let ids: Vec<u64> = AtomicId::<x64>::lease(usize::MAX).take(200_000).collect();
+        assert!(ids.iter().all(|&id| seen.insert(id)));
     }
 
-    /// Test generation of 128-bit IDs.
+    /// Test that `new_unique` skips IDs already seen and gives up after its retries.
     #[test]
-    fn test_gen128() {
-        let id = AtomicId::<128>::base36();
-        println!("Generated ID: {}", id);
+    fn test_new_unique() {
+        let _guard = guard();
+        let mut seen = std::collections::HashSet::new();
+        let ids: Vec<u64> = (0..100)
+            .map(|_| AtomicId::<64>::new_unique(&mut seen, 0).unwrap())
+            .collect();
+        assert_eq!(seen.len(), 100);
+        assert!(ids.iter().all(|id| seen.contains(id)));
+
+        let mut seen = std::collections::HashSet::from([7u8]);
+        let mut calls = 0;
+        let result = new_unique_with(&mut seen, 3, || {
+            calls += 1;
+            if calls < 3 { 7 } else { 8 }
+        });
+        assert_eq!(result, Ok(8));
+        assert_eq!(new_unique_with(&mut seen, 3, || 8), Err(IdError::RetriesExhausted(4)));
+
+        let mut seen = std::collections::HashSet::new();
+        let id = AtomicId::<256>::new_unique(&mut seen, 0).unwrap();
+        assert!(seen.contains(&id));
     }
 
-    /// Test generation of 256-bit IDs.
+    /// Test which URL-safe base91 characters need percent-encoding under RFC 3986, and
+    /// that the reserved characters in it are exactly the documented ones.
     #[test]
-    fn test_gen256() {
-        let id = AtomicId::<256>::base36();
-        println!("Generated ID: {}", id);
+    fn test_base91_urlsafe() {
+        let alphabet = encode::BASE91_URLSAFE;
+        let unique: std::collections::HashSet<_> = alphabet.iter().collect();
+        assert_eq!((alphabet.len(), unique.len()), (91, 91));
+
+        // RFC 3986: unreserved, and the other characters allowed in a query.
+        let unreserved = |c: u8| c.is_ascii_alphanumeric() || b"-._~".contains(&c);
+        let query = |c: u8| unreserved(c) || b"!$&'()*+,;=:@/?".contains(&c);
+        let needs_encoding: Vec<u8> = alphabet.iter().copied().filter(|&c| !query(c)).collect();
+        assert_eq!(needs_encoding, b"\"<>[\\]^`{|}");
+        assert_eq!(alphabet.iter().filter(|&&c| unreserved(c)).count(), 66);
+        assert!(!alphabet.iter().any(|c| b"%#?".contains(c)));
+
+        // RFC 3986 reserved characters: every one but `?` and `#`, and of those that
+        // end a path segment, only `/`, as there is no unreserved character to spare.
+        let reserved: &[u8] = b":/?#[]@!$&'()*+,;=";
+        let in_alphabet: Vec<u8> = reserved.iter().copied().filter(|c| alphabet.contains(c)).collect();
+        assert_eq!(in_alphabet, b":/[]@!$&'()*+,;=");
+        let outside: Vec<u8> = (b' '..=b'~').filter(|c| !alphabet.contains(c)).collect();
+        assert_eq!(outside, b" #%?");
+
+        let _guard = guard();
+        let id = AtomicId::<64>::base91_urlsafe();
+        let raw = Encoding::Base91UrlSafe.decode(&id).unwrap();
+        assert_eq!(Encoding::Base91UrlSafe.encode(raw, 10), id);
+        assert_eq!(AtomicId::generate(Width::X256, Encoding::Base91UrlSafe).unwrap().len(), 40);
     }
 
-    /// Test uniqueness of 64-bit IDs over 10 million generations.
+    /// Test typed ID display and byte conversions.
     #[test]
-    #[ignore] // This test is long-running and should be run manually.
-    fn test_uniqueness_64(){
-        // test uniqueness of 64-bit IDs
-        let mut ids = std::collections::HashSet::new();
-        for _ in 0..10_000_000 {
-            let id = AtomicId::<64>::new();
-            assert!(ids.insert(id), "Duplicate ID found");  
+    fn test_typed_ids() {
+        let _guard = guard();
+        assert_eq!(Id64(35).to_string(), "000000000000z");
+        assert_eq!(Id64::from_i64(-1), Id64(u64::MAX));
+        assert!((0..100).all(|_| Id64::new_i64_safe().to_i64() >= 0));
+        assert!((0..100).all(|_| AtomicId::<x64>::positive() >= 0));
+
+        let id = Id128::new();
+        assert_eq!(Id128::from_be_bytes(id.to_be_bytes()), id);
+        assert_eq!(id.to_string().len(), 25);
+
+        let id = Id256([1, 2, 3, u64::MAX]);
+        let bytes = id.to_be_bytes();
+        assert_eq!(bytes[7], 1);
+        assert_eq!(bytes[24..], [0xFF; 8]);
+        assert_eq!(Id256::from_be_bytes(bytes), id);
+        assert_eq!(id.to_string().len(), 50);
+        assert_eq!(id.to_string().parse(), Ok(id));
+    }
+
+    /// Test that `Arbitrary` reaches every encoding.
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_encoding() {
+        use arbitrary::{Arbitrary, Unstructured};
+        let bytes: Vec<u8> = (0..=255).collect();
+        let mut u = Unstructured::new(&bytes);
+        let mut seen = std::collections::HashSet::new();
+        while let Ok(encoding) = Encoding::arbitrary(&mut u) {
+            seen.insert(encoding);
+            if u.is_empty() {
+                break;
+            }
         }
-        println!("All IDs are unique");
+        assert_eq!(seen.len(), 5);
+    }
+
+    /// Pin derived IDs to golden values so the hash can never silently change.
+    #[test]
+    fn test_derive_golden() {
+        assert_eq!(IdGenerator::derive64("", b""), 0xa8c7f832281a39c5);
+        assert_eq!(IdGenerator::derive64("", b"hello"), 0x8fe9535b8a55c92b);
+        assert_eq!(IdGenerator::derive64("orders", b"hello"), 0x2ba5030e91562cea);
+        assert_eq!(
+            IdGenerator::derive128("orders", b"hello"),
+            0xa602bd65f2a7e48124b373545e53965a
+        );
+        assert_eq!(AtomicId::<64>::derive(b"hello", Encoding::Base36), "26saar39k7qcr");
+        assert_eq!(AtomicId::<64>::derive_in("orders", b"hello", Encoding::Base36), "0nw669bu0qpre");
+        assert_eq!(AtomicId::<64>::derive(b"hello", Encoding::Hex), "8fe9535b8a55c92b");
+        assert_eq!(AtomicId::<128>::derive(b"hello", Encoding::Base36), "9ubapyy0e2s2ehpije15knpub");
+        assert_eq!(
+            AtomicId::<128>::derive_in("orders", b"hello", Encoding::Base36),
+            "9tte5doigpszzodvf8ga46jx6"
+        );
+        assert_eq!(
+            AtomicId::<128>::derive_in("orders", b"hello", Encoding::Hex),
+            "a602bd65f2a7e48124b373545e53965a"
+        );
+        // Reference FNV-1a test vector for "a".
+        assert_eq!(hash::fnv1a64(&[b"a"]), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash::fnv1a128(&[b"a"]), 0xd228cb696f1a8caf78912b704e4a8964);
     }
 }
\ No newline at end of file
@@ -0,0 +1,24 @@
+//! [OpenTelemetry](https://docs.rs/opentelemetry) conversions, so request IDs can double
+//! as trace and span IDs.
+//!
+//! `From<Id128>` for `TraceId` and `From<Id64>` for `SpanId` keep the raw bits, so the
+//! trace ID in a `traceparent` header is the request ID in hex. IDs from the generator
+//! are never zero, so they always convert to valid OpenTelemetry IDs; only a
+//! hand-built `Id128(0)` or `Id64(0)` becomes `TraceId::INVALID` or `SpanId::INVALID`.
+//! For scrambled IDs, use `TraceId::from_bytes(atomic_id::trace_id())` and
+//! `SpanId::from_bytes(atomic_id::span_id())`.
+
+use crate::{Id64, Id128};
+use opentelemetry::trace::{SpanId, TraceId};
+
+impl From<Id128> for TraceId {
+    fn from(id: Id128) -> TraceId {
+        TraceId::from_bytes(id.to_be_bytes())
+    }
+}
+
+impl From<Id64> for SpanId {
+    fn from(id: Id64) -> SpanId {
+        SpanId::from_bytes(id.0.to_be_bytes())
+    }
+}
@@ -0,0 +1,244 @@
+//! Sequence high watermarks kept in a file across restarts, see
+//! [`AtomicOption::persist_sequence`](crate::AtomicOption::persist_sequence).
+//!
+//! The file holds one `width value` line per sequence counter. Each value is the
+//! counter at the time of writing plus a reserve of twice the flush interval, so a
+//! process that stops between flushes has used less than what was written, and the
+//! next one starts above every sequence value it handed out.
+//!
+//! Generating threads never write the file. Every interval they ask a writer thread
+//! to, and they only wait for it once a counter has used up the reserve last
+//! written, which keeps every handed-out value below the watermark on disk.
+
+use crate::{IdEvent, SEQ_24, SEQ_32, SEQ_64, SEQ_128, SEQ_256, emit};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use core::time::Duration;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread;
+
+/// Default number of IDs between flushes.
+pub const DEFAULT_INTERVAL: u64 = 4096;
+
+/// The widths of the counters, in file order.
+const WIDTHS: [usize; 5] = [24, 32, 64, 128, 256];
+
+/// Name of the writer thread.
+const WRITER: &str = "atomic-id-persist";
+
+/// How long the writer thread waits before retrying a failed write that generation
+/// is waiting for.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// What the writer thread works from.
+struct State {
+    /// The file being written, if any.
+    path: Option<PathBuf>,
+    /// Whether a write has been asked for since the writer thread last started one.
+    pending: bool,
+    /// Whether the writer thread is running.
+    writer: bool,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    path: None,
+    pending: false,
+    writer: false,
+});
+
+/// Wakes the writer thread once a write is pending.
+static WANTED: Condvar = Condvar::new();
+
+/// Wakes threads waiting for the reserve once a write has finished.
+static WRITTEN: Condvar = Condvar::new();
+
+/// Held for the whole of each write, so writes never share the temporary file, and
+/// taken before [`STATE`] when both are.
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Whether a file is set, so generation skips the count when it is not.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// IDs between flushes, set with [`AtomicOption::persist_sequence_interval`](crate::AtomicOption::persist_sequence_interval).
+pub static INTERVAL: AtomicU64 = AtomicU64::new(DEFAULT_INTERVAL);
+
+/// IDs generated since persistence started, of every width.
+static GENERATED: AtomicU64 = AtomicU64::new(0);
+
+/// The watermarks last written, in [`WIDTHS`] order. Each counter may hand out
+/// values below its own.
+static RESERVED: [AtomicU64; 5] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+fn counters() -> [&'static AtomicU64; 5] {
+    [&SEQ_24, &SEQ_32, &SEQ_64, &SEQ_128, &SEQ_256]
+}
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn writing() -> MutexGuard<'static, ()> {
+    WRITING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Raise the counters to the watermarks in `path`, if it exists, then write fresh
+/// watermarks and flush to it from now on.
+pub fn start(path: &Path) -> io::Result<()> {
+    let _writing = writing();
+    match fs::read_to_string(path) {
+        Ok(text) => {
+            for (counter, value) in counters().iter().zip(parse(&text)?) {
+                counter.fetch_max(value, Ordering::Relaxed);
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let written = write(path)?;
+    for (reserved, value) in RESERVED.iter().zip(written) {
+        reserved.store(value, Ordering::Relaxed);
+    }
+    let mut state = state();
+    if !state.writer {
+        thread::Builder::new().name(WRITER.into()).spawn(run)?;
+        state.writer = true;
+    }
+    state.path = Some(path.to_path_buf());
+    GENERATED.store(0, Ordering::Relaxed);
+    ENABLED.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Stop writing watermarks and restore the default interval. A write already under
+/// way finishes first.
+#[cfg(any(test, feature = "testing"))]
+pub fn reset() {
+    let _writing = writing();
+    let mut state = state();
+    ENABLED.store(false, Ordering::Release);
+    INTERVAL.store(DEFAULT_INTERVAL, Ordering::Relaxed);
+    state.path = None;
+    WRITTEN.notify_all();
+}
+
+/// Count a generated ID, asking for a write every [`INTERVAL`] IDs, and wait for
+/// one if a counter has used up its reserve.
+#[inline]
+pub fn tick() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let interval = INTERVAL.load(Ordering::Relaxed);
+    if GENERATED.fetch_add(1, Ordering::Relaxed) % interval == interval - 1 {
+        state().pending = true;
+        WANTED.notify_one();
+    }
+    if !within_reserve() {
+        wait_for_reserve();
+    }
+}
+
+/// Whether every counter is at or below its watermark on disk, so every value
+/// handed out so far is below it.
+fn within_reserve() -> bool {
+    counters()
+        .iter()
+        .zip(&RESERVED)
+        .all(|(counter, reserved)| counter.load(Ordering::Relaxed) <= reserved.load(Ordering::Relaxed))
+}
+
+/// Ask for writes until the counters are back within their reserve. The writer
+/// thread itself does not wait, as IDs generated from an event hook it calls
+/// would never be written for.
+#[cold]
+fn wait_for_reserve() {
+    if thread::current().name() == Some(WRITER) {
+        return;
+    }
+    let mut state = state();
+    while ENABLED.load(Ordering::Relaxed) && !within_reserve() {
+        state.pending = true;
+        WANTED.notify_one();
+        state = WRITTEN.wait(state).unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+/// The writer thread: write the watermarks whenever asked to. A failed write is
+/// reported as [`IdEvent::SequencePersistFailed`], and retried after
+/// [`RETRY_DELAY`] if generation is waiting for it, or at the next interval if not.
+fn run() {
+    let mut state = state();
+    loop {
+        while !state.pending {
+            state = WANTED.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.pending = false;
+        drop(state);
+        if flush().is_err() {
+            emit(IdEvent::SequencePersistFailed);
+            thread::sleep(RETRY_DELAY);
+        }
+        state = self::state();
+        WRITTEN.notify_all();
+    }
+}
+
+/// Write the watermarks now, if a file is set, and raise the reserve to them.
+pub fn flush() -> io::Result<()> {
+    let _writing = writing();
+    let path = state().path.clone();
+    if let Some(path) = path {
+        let written = write(&path)?;
+        for (reserved, value) in RESERVED.iter().zip(written) {
+            reserved.fetch_max(value, Ordering::Relaxed);
+        }
+        let _state = state();
+        WRITTEN.notify_all();
+    }
+    Ok(())
+}
+
+/// Write the watermarks to a temporary file next to `path`, sync it, and rename it
+/// over `path`, so a crash leaves either the old file or the new one. Returns the
+/// watermarks written. Callers hold [`WRITING`].
+fn write(path: &Path) -> io::Result<[u64; 5]> {
+    let reserve = INTERVAL.load(Ordering::Relaxed).saturating_mul(2);
+    let mut values = [0; 5];
+    let mut text = String::new();
+    for ((width, counter), value) in WIDTHS.iter().zip(counters()).zip(&mut values) {
+        *value = counter.load(Ordering::Relaxed).saturating_add(reserve);
+        text.push_str(&format!("{} {}\n", width, value));
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file = File::create(&tmp)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+    Ok(values)
+}
+
+/// Read the watermarks written by [`write`], in [`WIDTHS`] order. Missing widths
+/// read as 0.
+fn parse(text: &str) -> io::Result<[u64; 5]> {
+    let invalid = |line: &str| io::Error::new(io::ErrorKind::InvalidData, format!("invalid sequence line {:?}", line));
+    let mut values = [0; 5];
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (width, value) = line.trim().split_once(' ').ok_or_else(|| invalid(line))?;
+        let slot = WIDTHS
+            .iter()
+            .position(|w| width.parse() == Ok(*w))
+            .ok_or_else(|| invalid(line))?;
+        values[slot] = value.trim().parse().map_err(|_| invalid(line))?;
+    }
+    Ok(values)
+}
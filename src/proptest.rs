@@ -0,0 +1,113 @@
+//! [proptest](https://docs.rs/proptest) strategies for the typed IDs.
+//!
+//! Generated IDs are composed with the same code as real ones, so they decompose into
+//! plausible fields: a timestamp in a chosen window, node and shard IDs within the
+//! current [`Layout64`], a nonzero thread ID within [`AtomicOption::thread_id_bits`](crate::AtomicOption::thread_id_bits),
+//! and any sequence value. Failing cases shrink toward the start of the time window,
+//! sequence 0, node 0, and shard 0, so they stay readable.
+//!
+//! ```
+//! use atomic_id::IdGenerator;
+//! use atomic_id::proptest::id64_for_node;
+//! use proptest::test_runner::TestRunner;
+//!
+//! // Or `fn f(id in id64_for_node(42))` inside `proptest! { ... }`.
+//! TestRunner::default()
+//!     .run(&id64_for_node(42), |id| {
+//!         assert_eq!(IdGenerator::decompose64(id.0).node_id, 42);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use crate::{Id64, Id128, IdGenerator, Layout64, seq_bits64, thread_id_bits, thread_seq64};
+use core::ops::{Range, RangeInclusive};
+use proptest::prelude::*;
+
+/// Parameters for the `Arbitrary` impls of [`Id64`] and [`Id128`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdParams {
+    /// Timestamps to draw from, in milliseconds since the epoch. Values beyond the
+    /// timestamp field wrap, as they do for real IDs.
+    pub time_ms: Range<u64>,
+    /// Fix the node ID instead of drawing it.
+    pub node_id: Option<u16>,
+}
+
+impl Default for IdParams {
+    /// One full period of the 64-bit timestamp field (2^20 ms) from the epoch, any node.
+    fn default() -> Self {
+        IdParams { time_ms: 0..1 << 20, node_id: None }
+    }
+}
+
+/// Node IDs that fit `layout`, or just `node_id` when it is given.
+fn nodes(layout: Layout64, node_id: Option<u16>) -> RangeInclusive<u16> {
+    match (node_id, layout) {
+        (Some(node), _) => node..=node,
+        (None, Layout64::WideNode) => 0..=u16::MAX,
+        (None, _) => 0..=0xFFF,
+    }
+}
+
+/// Strategy for 64-bit IDs with the given parameters, in the current [`Layout64`].
+pub fn id64_with(params: IdParams) -> impl Strategy<Value = Id64> {
+    let layout = Layout64::current();
+    let shards = match layout {
+        Layout64::WideNode | Layout64::Regional => 1 << 4,
+        Layout64::Era => 1 << 5,
+        Layout64::Standard => 1 << 8,
+    };
+    let thread_bits = thread_id_bits();
+    let threads = 1..1u32 << thread_bits;
+    let sequences = 0..1u64 << seq_bits64(thread_bits);
+    (params.time_ms, sequences, nodes(layout, params.node_id), 0..shards as u16, threads).prop_map(
+        move |(ts, seq, node, shard, thread)| {
+            let thread_seq = thread_seq64(thread as u16, seq, thread_bits);
+            Id64(IdGenerator::new(node, shard as u8).compose64_as(layout, ts, thread_seq))
+        },
+    )
+}
+
+/// Strategy for 64-bit IDs with timestamps in `time_ms`, in milliseconds since the epoch.
+pub fn any_id64_in(time_ms: Range<u64>) -> impl Strategy<Value = Id64> {
+    id64_with(IdParams { time_ms, ..IdParams::default() })
+}
+
+/// Strategy for 64-bit IDs minted by node `node`.
+///
+/// Under [`Layout64::Standard`] only the low 12 bits of `node` are kept, as with
+/// [`IdGenerator::new`].
+pub fn id64_for_node(node: u16) -> impl Strategy<Value = Id64> {
+    id64_with(IdParams { node_id: Some(node), ..IdParams::default() })
+}
+
+/// Strategy for 128-bit IDs with the given parameters. The timestamp field is 32 bits.
+pub fn id128_with(params: IdParams) -> impl Strategy<Value = Id128> {
+    let node = nodes(Layout64::Standard, params.node_id.map(|n| n & 0xFFF));
+    (params.time_ms, 0..1u64 << 24, node, any::<u8>(), 1..=u8::MAX, any::<u32>()).prop_map(
+        |(ts, seq, node, shard, thread, nanos)| {
+            Id128(IdGenerator::new(node, shard).compose128(ts, thread, nanos as u64, seq))
+        },
+    )
+}
+
+impl Arbitrary for Id64 {
+    type Parameters = IdParams;
+    type Strategy = BoxedStrategy<Id64>;
+
+    /// Draws from [`id64_with`]. The layout and thread ID width are read when the
+    /// strategy is built, so set them with [`AtomicOption`](crate::AtomicOption) beforehand.
+    fn arbitrary_with(params: IdParams) -> Self::Strategy {
+        id64_with(params).boxed()
+    }
+}
+
+impl Arbitrary for Id128 {
+    type Parameters = IdParams;
+    type Strategy = BoxedStrategy<Id128>;
+
+    fn arbitrary_with(params: IdParams) -> Self::Strategy {
+        id128_with(params).boxed()
+    }
+}
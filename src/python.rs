@@ -0,0 +1,89 @@
+//! Python bindings, built with [PyO3](https://pyo3.rs).
+//!
+//! The module calls into the same global generator as the Rust API, so IDs minted from
+//! Python share sequences with any Rust code in the same process and match Rust services
+//! byte for byte. Build it with [maturin](https://www.maturin.rs) from the crate root,
+//! which reads `pyproject.toml`:
+//! ```sh
+//! maturin develop
+//! pytest python/tests
+//! ```
+//!
+//! ```python
+//! import atomic_id
+//!
+//! atomic_id.new64()                  # '0n3x8q9a7k2fc'
+//! atomic_id.decompose64(raw)         # {'timestamp': ..., 'node_id': 1, ...}
+//! ```
+
+use crate::{AtomicId, AtomicOption, IdGenerator};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Generate a 64-bit ID as a 13-character base36 string, like `AtomicId::<x64>::new`.
+#[pyfunction]
+fn new64() -> String {
+    AtomicId::<64>::new()
+}
+
+/// Generate a 128-bit ID as a 25-character base36 string, like `AtomicId::<x128>::new`.
+#[pyfunction]
+fn new128() -> String {
+    AtomicId::<128>::new()
+}
+
+/// Generate a 64-bit ID as a 16-character hexadecimal string.
+#[pyfunction]
+fn hex64() -> String {
+    AtomicId::<64>::hex()
+}
+
+/// Generate a 128-bit ID as a 22-character base58 string.
+#[pyfunction]
+fn base58_128() -> String {
+    AtomicId::<128>::base58()
+}
+
+/// Set the global epoch in milliseconds since the UNIX epoch.
+///
+/// Call it before generating any ID. Raises `RuntimeError` once IDs have been generated
+/// or if the library was built with the `const_epoch` feature, and `OverflowError` for
+/// values outside the `u64` range.
+#[pyfunction]
+fn set_epoch(ms: u64) -> PyResult<()> {
+    if cfg!(feature = "const_epoch") {
+        return Err(PyRuntimeError::new_err(
+            "the epoch is fixed at build time by the `const_epoch` feature",
+        ));
+    }
+    AtomicOption::try_epoch(ms).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
+/// Split a raw 64-bit ID in the standard layout into a dict of its fields.
+///
+/// Raises `OverflowError` for values outside the `u64` range.
+#[pyfunction]
+fn decompose64(py: Python<'_>, id: u64) -> PyResult<Bound<'_, PyDict>> {
+    let parts = IdGenerator::decompose64(id);
+    let dict = PyDict::new(py);
+    dict.set_item("timestamp", parts.timestamp)?;
+    dict.set_item("node_id", parts.node_id)?;
+    dict.set_item("shard_id", parts.shard_id)?;
+    dict.set_item("thread_id", parts.wide_thread_id)?;
+    dict.set_item("sequence", parts.sequence)?;
+    Ok(dict)
+}
+
+/// The `atomic_id` Python module.
+#[pymodule]
+#[pyo3(name = "atomic_id")]
+fn python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(new64, m)?)?;
+    m.add_function(wrap_pyfunction!(new128, m)?)?;
+    m.add_function(wrap_pyfunction!(hex64, m)?)?;
+    m.add_function(wrap_pyfunction!(base58_128, m)?)?;
+    m.add_function(wrap_pyfunction!(set_epoch, m)?)?;
+    m.add_function(wrap_pyfunction!(decompose64, m)?)?;
+    Ok(())
+}
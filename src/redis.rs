@@ -0,0 +1,143 @@
+//! [redis](https://docs.rs/redis) conversions for the typed IDs.
+//!
+//! [`Id64`], [`Id128`], and [`Id256`] are written as their canonical base36 strings
+//! (the same text as their `Display` output and [`AtomicId::new`](crate::AtomicId::new))
+//! and read back from string replies. [`Id256`] also reads the 52-character word
+//! format its `Display` wrote before the canonical form. Wrap an [`Id64`] in [`RawId64`] to store the raw
+//! integer instead, e.g. for sorted-set members that must compare numerically.
+//!
+//! ```no_run
+//! # fn main() -> redis::RedisResult<()> {
+//! use atomic_id::Id64;
+//! use atomic_id::redis::RawId64;
+//! use redis::Commands;
+//!
+//! let mut con = redis::Client::open("redis://127.0.0.1/")?.get_connection()?;
+//! let id = Id64::new();
+//! let _: () = con.set("order:last", id)?;
+//! let _: () = con.set("order:last:raw", RawId64(id))?;
+//! let read: Id64 = con.get("order:last")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{AtomicId, Encoding, Id64, Id128, Id256};
+use redis::{
+    ErrorKind, FromRedisValue, NumericBehavior, RedisError, RedisResult, RedisWrite, ToRedisArgs,
+    Value,
+};
+
+/// An [`Id64`] stored in Redis as its raw value in decimal rather than as base36.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawId64(pub Id64);
+
+/// Read a string reply as text.
+///
+/// Integer replies are rejected rather than read as text, since a 13-digit integer is
+/// also valid base36.
+fn text(v: &Value) -> RedisResult<String> {
+    match v {
+        Value::BulkString(_) | Value::SimpleString(_) | Value::VerbatimString { .. } => {
+            String::from_redis_value(v)
+        }
+        _ => Err(RedisError::from((
+            ErrorKind::TypeError,
+            "expected a base36 ID string",
+            format!("{:?}", v),
+        ))),
+    }
+}
+
+/// Fail unless `s` is exactly `len` bytes long.
+fn check_len(s: &str, len: usize) -> RedisResult<()> {
+    if s.len() == len {
+        return Ok(());
+    }
+    Err(RedisError::from((
+        ErrorKind::TypeError,
+        "wrong length for a base36 ID",
+        format!("expected {} characters, got {:?}", len, s),
+    )))
+}
+
+/// Decode a base36 ID of exactly `len` characters.
+fn base36(s: &str, len: usize) -> RedisResult<u128> {
+    check_len(s, len)?;
+    Encoding::Base36
+        .decode(s)
+        .map_err(|e| RedisError::from((ErrorKind::TypeError, "invalid base36 ID", e.to_string())))
+}
+
+/// Decode a 13-character base36 ID that must fit in 64 bits.
+fn base36_u64(s: &str) -> RedisResult<u64> {
+    u64::try_from(base36(s, 13)?)
+        .map_err(|_| RedisError::from((ErrorKind::TypeError, "base36 ID exceeds 64 bits")))
+}
+
+impl ToRedisArgs for Id64 {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        out.write_arg(self.to_string().as_bytes());
+    }
+}
+
+impl FromRedisValue for Id64 {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        base36_u64(&text(v)?).map(Id64)
+    }
+}
+
+impl ToRedisArgs for Id128 {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        out.write_arg(self.to_string().as_bytes());
+    }
+}
+
+impl FromRedisValue for Id128 {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        base36(&text(v)?, 25).map(Id128)
+    }
+}
+
+impl ToRedisArgs for Id256 {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        out.write_arg(self.to_string().as_bytes());
+    }
+}
+
+impl FromRedisValue for Id256 {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let s = text(v)?;
+        let parts = if s.len() == 52 {
+            AtomicId::<256>::from_words(&s, Encoding::Base36)
+        } else {
+            check_len(&s, 50)?;
+            AtomicId::<256>::from_canonical(&s, Encoding::Base36)
+        };
+        parts
+            .map(Id256)
+            .map_err(|e| RedisError::from((ErrorKind::TypeError, "invalid base36 ID", e.to_string())))
+    }
+}
+
+impl ToRedisArgs for RawId64 {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        self.0.0.write_redis_args(out);
+    }
+
+    fn describe_numeric_behavior(&self) -> NumericBehavior {
+        NumericBehavior::NumberIsInteger
+    }
+}
+
+impl FromRedisValue for RawId64 {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let n = match *v {
+            // redis-rs casts negative integers to u64; reject them instead.
+            Value::Int(n) => u64::try_from(n).map_err(|_| {
+                RedisError::from((ErrorKind::TypeError, "negative raw ID", n.to_string()))
+            })?,
+            _ => u64::from_redis_value(v)?,
+        };
+        Ok(RawId64(Id64(n)))
+    }
+}
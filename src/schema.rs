@@ -0,0 +1,109 @@
+//! [schemars](https://docs.rs/schemars) schemas for the typed IDs.
+//!
+//! Each ID is described as a string of exactly its canonical base36 length, with a
+//! `pattern` built from the encoder's own alphabet (so the two cannot drift apart) and
+//! a `format` hint such as `atomic-id-64`. Pass the schemas to utoipa, aide, or any
+//! other schemars-based OpenAPI generator.
+
+use crate::{Id64, Id128, Id256, encode};
+use std::borrow::Cow;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+
+/// Build a regex matching exactly `len` characters of `alphabet`, collapsing runs of
+/// three or more consecutive characters into ranges, e.g. `^[0-9a-z]{13}$`.
+fn pattern(alphabet: &[u8], len: usize) -> String {
+    let mut sorted = alphabet.to_vec();
+    sorted.sort_unstable();
+    let escape = |c: u8| match c {
+        b'\\' | b']' | b'[' | b'^' | b'-' => format!("\\{}", c as char),
+        _ => (c as char).to_string(),
+    };
+
+    let mut class = String::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let mut end = i;
+        while end + 1 < sorted.len() && sorted[end + 1] == sorted[end] + 1 {
+            end += 1;
+        }
+        if end - i >= 2 {
+            class += &format!("{}-{}", escape(sorted[i]), escape(sorted[end]));
+        } else {
+            sorted[i..=end].iter().for_each(|&c| class += &escape(c));
+        }
+        i = end + 1;
+    }
+    format!("^[{}]{{{}}}$", class, len)
+}
+
+/// The schema of a base36 ID of `len` characters.
+fn id_schema(bits: usize, len: usize) -> Schema {
+    json_schema!({
+        "type": "string",
+        "minLength": len,
+        "maxLength": len,
+        "pattern": pattern(encode::BASE36, len),
+        "format": format!("atomic-id-{}", bits),
+    })
+}
+
+impl JsonSchema for Id64 {
+    fn schema_name() -> Cow<'static, str> {
+        "Id64".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "atomic_id::Id64".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        id_schema(64, 13)
+    }
+}
+
+impl JsonSchema for Id128 {
+    fn schema_name() -> Cow<'static, str> {
+        "Id128".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "atomic_id::Id128".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        id_schema(128, 25)
+    }
+}
+
+impl JsonSchema for Id256 {
+    fn schema_name() -> Cow<'static, str> {
+        "Id256".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "atomic_id::Id256".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        id_schema(256, 50)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that patterns collapse ranges and escape class metacharacters.
+    #[test]
+    fn test_pattern() {
+        assert_eq!(pattern(encode::BASE36, 13), "^[0-9a-z]{13}$");
+        // `\`, `]`, and `^` are consecutive, so they collapse into an escaped range.
+        let escaped = pattern(b"ab-^]\\", 2);
+        assert_eq!(escaped, r"^[\-\\-\^ab]{2}$");
+        let escaped = regex::Regex::new(&escaped).unwrap();
+        assert!(["-]", "\\^", "ab"].iter().all(|s| escaped.is_match(s)));
+        assert!(!escaped.is_match("[a"));
+        let base91 = regex::Regex::new(&pattern(encode::BASE91_URLSAFE, 10)).unwrap();
+        assert!(base91.is_match(&crate::AtomicId::<64>::base91_urlsafe()));
+    }
+}
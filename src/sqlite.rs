@@ -0,0 +1,52 @@
+//! [rusqlite](https://docs.rs/rusqlite) conversions for the typed IDs.
+//!
+//! | Type      | SQLite type | Stored as                                         |
+//! |-----------|-------------|---------------------------------------------------|
+//! | [`Id64`]  | `INTEGER`   | The raw bits as an `i64` ([`Id64::to_i64`])       |
+//! | [`Id128`] | `BLOB`      | 16 big-endian bytes                               |
+//! | [`Id256`] | `BLOB`      | 32 big-endian bytes                               |
+//!
+//! Blobs compare bytewise, so `ORDER BY` on `Id128` and `Id256` columns follows the
+//! numeric order of the IDs. Reading a blob of the wrong length fails with
+//! [`FromSqlError::InvalidBlobSize`](rusqlite::types::FromSqlError::InvalidBlobSize). `INTEGER` is signed, so `Id64` values with the top bit set
+//! sort before the rest; generate them with [`Id64::new_i64_safe`] when the column's
+//! order matters.
+
+use crate::{Id64, Id128, Id256};
+use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+impl ToSql for Id64 {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_i64()))
+    }
+}
+
+impl FromSql for Id64 {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        i64::column_result(value).map(Id64::from_i64)
+    }
+}
+
+impl ToSql for Id128 {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_be_bytes().to_vec()))
+    }
+}
+
+impl FromSql for Id128 {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        <[u8; 16]>::column_result(value).map(Id128::from_be_bytes)
+    }
+}
+
+impl ToSql for Id256 {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_be_bytes().to_vec()))
+    }
+}
+
+impl FromSql for Id256 {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        <[u8; 32]>::column_result(value).map(Id256::from_be_bytes)
+    }
+}
@@ -0,0 +1,386 @@
+//! Helpers for isolating `atomic-id`'s global state in tests.
+//!
+//! The epoch, the global generator, and the sequence counters are process-wide, so a
+//! test that calls [`AtomicOption::try_epoch`](crate::AtomicOption::try_epoch) changes the
+//! behavior of every other test in the same binary. [`TestGuard`] snapshots that state,
+//! applies per-test overrides, and restores the snapshot when dropped.
+//!
+//! Enable the `testing` feature in your dev-dependencies to use this module:
+//! ```toml
+//! [dev-dependencies]
+//! atomic-id = { version = "0.1", features = ["testing"] }
+//! ```
+//!
+//! Guards serialize on an internal mutex, so two tests holding guards never run their
+//! guarded sections at the same time, even under the parallel test runner. Tests that
+//! do not take a guard are not serialized and may still observe overridden state.
+
+use crate::{
+    AtomicOption, CLOCK_MODE, CLOCK_REANCHOR_NS, CLOCK_ROLLBACKS, CUSTOM_EPOCH, ClockMode,
+    DEFAULT_EPOCH, DEFAULT_THREAD_ID_BITS, DEFAULT_WRAP_THRESHOLD_PERCENT, EPOCH_FROZEN,
+    GENERATE_HOOK, GENERATE_HOOK_256, GLOBAL, IdGenerator, LAST_TIMESTAMP, LAYOUT_24, LAYOUT_32,
+    LAYOUT_64, LEASE_WINDOW_64, Layout24, Layout32, Layout64, MAX_RATE_64, OVERFLOW_MODE,
+    OVERFLOW_POLICY_24, OverflowMode, OverflowPolicy, RATE_WINDOW_64, REGION_ID, SEQ_24,
+    SEQ_24_RESET_AT, SEQ_32, SEQ_32_TIMED, SEQ_64, SEQ_128, SEQ_256, STATS_BASE, THREAD_ENTROPY,
+    THREAD_ID_BITS, THREAD_ID_MODE, THREAD_ID_SALT, TICK_START_32, TICK_START_64, TICK_START_128,
+    TIME_MS, TIME_NS, TIMESTAMP_PRECISION, ThreadIdMode, TimestampPrecision, VERSION_BITS_ENABLED,
+    WRAP_THRESHOLD_PERCENT, WRAP_WARNED_PERIOD_64, reset_clock_anchor,
+};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Serializes all guards.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Generator used in place of the global one while a guard overrides node or shard.
+/// `None` when no override is active.
+static OVERRIDE: Mutex<Option<Arc<IdGenerator>>> = Mutex::new(None);
+
+/// Whether [`OVERRIDE`] is set, so that generating IDs skips its lock when it is not.
+static OVERRIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// Whether deterministic mode is active.
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// Logical clock used in place of the wall clock in deterministic mode, in ms since the epoch.
+static LOGICAL_CLOCK: AtomicU64 = AtomicU64::new(0);
+
+/// State of the SplitMix64 generator used for entropy in deterministic mode.
+static PRNG_STATE: AtomicU64 = AtomicU64::new(0);
+
+/// Bumped whenever deterministic mode is entered or left, invalidating thread IDs
+/// assigned under the previous mode.
+static THREAD_ID_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// The deterministic thread entropy of the current thread and the generation it belongs to.
+thread_local! {
+    static DETERMINISTIC_THREAD_ENTROPY: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+/// Increment of the SplitMix64 state per draw.
+const GAMMA: u64 = 0x9E3779B97F4A7C15;
+
+/// Draw the next value from the seeded SplitMix64 generator.
+fn next_random() -> u64 {
+    split_mix(PRNG_STATE.fetch_add(GAMMA, Ordering::Relaxed).wrapping_add(GAMMA))
+}
+
+/// The SplitMix64 output for the state `z`.
+fn split_mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Enter deterministic mode with the given seed. See [`AtomicOption::deterministic`](crate::AtomicOption::deterministic).
+pub(crate) fn enable_deterministic(seed: u64) {
+    LOGICAL_CLOCK.store(seed, Ordering::Relaxed);
+    PRNG_STATE.store(seed, Ordering::Relaxed);
+    counters().iter().for_each(|c| c.store(0, Ordering::Relaxed));
+    SEQ_24_RESET_AT.store(0, Ordering::Relaxed);
+    THREAD_ID_GENERATION.fetch_add(1, Ordering::Relaxed);
+    DETERMINISTIC.store(true, Ordering::Release);
+}
+
+/// Replace the PRNG state and invalidate seeded thread entropy. See
+/// [`AtomicOption::reseed`](crate::AtomicOption::reseed).
+pub(crate) fn reseed(seed: u64) {
+    PRNG_STATE.store(seed, Ordering::Relaxed);
+    THREAD_ID_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The next logical timestamp, if deterministic mode is active.
+pub(crate) fn deterministic_timestamp() -> Option<u64> {
+    DETERMINISTIC
+        .load(Ordering::Acquire)
+        .then(|| LOGICAL_CLOCK.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Seeded nanosecond entropy, if deterministic mode is active.
+pub(crate) fn deterministic_nanos() -> Option<u64> {
+    DETERMINISTIC.load(Ordering::Acquire).then(next_random)
+}
+
+/// The logical timestamp [`deterministic_timestamp`] returns next, without
+/// advancing the clock.
+pub(crate) fn peek_deterministic_timestamp() -> Option<u64> {
+    DETERMINISTIC
+        .load(Ordering::Acquire)
+        .then(|| LOGICAL_CLOCK.load(Ordering::Relaxed))
+}
+
+/// The nanosecond entropy [`deterministic_nanos`] returns next, without drawing it.
+pub(crate) fn peek_deterministic_nanos() -> Option<u64> {
+    DETERMINISTIC
+        .load(Ordering::Acquire)
+        .then(|| split_mix(PRNG_STATE.load(Ordering::Relaxed).wrapping_add(GAMMA)))
+}
+
+/// The current thread's seeded thread entropy, if deterministic mode is active.
+pub(crate) fn deterministic_thread_entropy() -> Option<u64> {
+    if !DETERMINISTIC.load(Ordering::Acquire) {
+        return None;
+    }
+    let generation = THREAD_ID_GENERATION.load(Ordering::Relaxed);
+    let entropy = DETERMINISTIC_THREAD_ENTROPY.with(|slot| {
+        let (assigned_in, entropy) = slot.get();
+        if assigned_in == generation {
+            return entropy;
+        }
+        let entropy = next_random();
+        slot.set((generation, entropy));
+        entropy
+    });
+    Some(entropy)
+}
+
+/// The sequence counters, in width order 24, 32, timed 32, 64, 128, 256.
+fn counters() -> [&'static AtomicU64; 6] {
+    [&SEQ_24, &SEQ_32, &SEQ_32_TIMED, &SEQ_64, &SEQ_128, &SEQ_256]
+}
+
+/// The time source, thread entropy, and generation hook callback slots.
+fn callbacks() -> [&'static AtomicPtr<()>; 5] {
+    [&TIME_MS, &TIME_NS, &THREAD_ENTROPY, &GENERATE_HOOK, &GENERATE_HOOK_256]
+}
+
+/// Return the overriding generator, if a guard installed one.
+pub(crate) fn generator_override() -> Option<Arc<IdGenerator>> {
+    if !OVERRIDDEN.load(Ordering::Acquire) {
+        return None;
+    }
+    OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Install `generator` as the overriding generator, returning the one it replaces.
+fn set_override(generator: Option<Arc<IdGenerator>>) -> Option<Arc<IdGenerator>> {
+    let mut slot = OVERRIDE.lock().unwrap_or_else(|e| e.into_inner());
+    OVERRIDDEN.store(generator.is_some(), Ordering::Release);
+    std::mem::replace(&mut *slot, generator)
+}
+
+/// Overrides applied by [`TestGuard::scoped`].
+///
+/// Fields left as `None` keep their current value.
+#[derive(Debug, Clone, Default)]
+pub struct TestConfig {
+    /// Epoch to use for the duration of the guard, in milliseconds since UNIX_EPOCH.
+    ///
+    /// Has no effect with the `const_epoch` feature.
+    pub epoch: Option<u64>,
+    /// Node ID for the global generator used by [`AtomicId`](crate::AtomicId).
+    pub node_id: Option<u16>,
+    /// Shard ID for the global generator used by [`AtomicId`](crate::AtomicId).
+    pub shard_id: Option<u8>,
+    /// Zero the sequence counters when the guard is created.
+    pub reset_counters: bool,
+    /// Enter deterministic mode with this seed for the duration of the guard.
+    ///
+    /// See [`AtomicOption::deterministic`](crate::AtomicOption::deterministic).
+    pub deterministic: Option<u64>,
+}
+
+/// Restores the global state captured by [`TestGuard::scoped`] when dropped.
+///
+/// # Example
+/// ```
+/// use atomic_id::testing::{TestConfig, TestGuard};
+/// use atomic_id::{AtomicId, AtomicOption, x64};
+///
+/// let before = AtomicOption::get_epoch();
+/// {
+///     let _guard = TestGuard::scoped(TestConfig {
+///         epoch: Some(1704067200000),
+///         node_id: Some(42),
+///         ..TestConfig::default()
+///     });
+///     let _id = AtomicId::<x64>::new(); // minted by node 42
+/// }
+/// // Everything is back to how it was.
+/// assert_eq!(AtomicOption::get_epoch(), before);
+/// ```
+pub struct TestGuard {
+    epoch: u64,
+    generator: (Option<Arc<IdGenerator>>, *mut IdGenerator),
+    #[cfg(feature = "reconfigure")]
+    reconfigured: Option<std::sync::Arc<IdGenerator>>,
+    counters: [u64; 6],
+    layout64: (u8, bool, u8, u8),
+    x24: (u8, u8, u64),
+    layout32: u8,
+    thread_ids: (u8, u8, u64),
+    wrap_threshold: u8,
+    overflow_mode: u8,
+    max_rate: u32,
+    clock: (u8, u64),
+    callbacks: [*mut (); 5],
+    deterministic: (bool, u64, u64),
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestGuard {
+    /// Take the global test lock, snapshot the global state, and apply `config`.
+    ///
+    /// The epoch starts out unfrozen, as if no ID had been generated yet, so the test
+    /// can set it with [`AtomicOption::try_epoch`](crate::AtomicOption::try_epoch).
+    ///
+    /// Blocks until any other guard is dropped.
+    pub fn scoped(config: TestConfig) -> TestGuard {
+        // A test that panicked while holding a guard has already been restored by
+        // `Drop`, so a poisoned lock is safe to reuse.
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let guard = TestGuard {
+            epoch: CUSTOM_EPOCH.fetch_and(!EPOCH_FROZEN, Ordering::Relaxed),
+            generator: (generator_override(), GLOBAL.load(Ordering::Acquire)),
+            #[cfg(feature = "reconfigure")]
+            reconfigured: crate::RECONFIGURED.load_full(),
+            counters: counters().map(|c| c.load(Ordering::Relaxed)),
+            layout64: (
+                LAYOUT_64.load(Ordering::Relaxed),
+                VERSION_BITS_ENABLED.load(Ordering::Relaxed),
+                TIMESTAMP_PRECISION.load(Ordering::Relaxed),
+                REGION_ID.load(Ordering::Relaxed),
+            ),
+            x24: (
+                LAYOUT_24.load(Ordering::Relaxed),
+                OVERFLOW_POLICY_24.load(Ordering::Relaxed),
+                SEQ_24_RESET_AT.load(Ordering::Relaxed),
+            ),
+            layout32: LAYOUT_32.load(Ordering::Relaxed),
+            thread_ids: (
+                THREAD_ID_BITS.load(Ordering::Relaxed),
+                THREAD_ID_MODE.load(Ordering::Relaxed),
+                THREAD_ID_SALT.load(Ordering::Relaxed),
+            ),
+            wrap_threshold: WRAP_THRESHOLD_PERCENT.load(Ordering::Relaxed),
+            overflow_mode: OVERFLOW_MODE.load(Ordering::Relaxed),
+            max_rate: MAX_RATE_64.load(Ordering::Relaxed),
+            clock: (
+                CLOCK_MODE.load(Ordering::Relaxed),
+                CLOCK_REANCHOR_NS.load(Ordering::Relaxed),
+            ),
+            callbacks: callbacks().map(|c| c.load(Ordering::Acquire)),
+            deterministic: (
+                DETERMINISTIC.load(Ordering::Acquire),
+                LOGICAL_CLOCK.load(Ordering::Relaxed),
+                PRNG_STATE.load(Ordering::Relaxed),
+            ),
+            _lock: lock,
+        };
+
+        if let Some(epoch) = config.epoch {
+            CUSTOM_EPOCH.store(epoch, Ordering::Relaxed);
+        }
+        if config.node_id.is_some() || config.shard_id.is_some() {
+            let current = crate::xgen_read();
+            let generator = IdGenerator::new(
+                config.node_id.unwrap_or(current.node_id),
+                config.shard_id.unwrap_or(current.shard_id),
+            );
+            set_override(Some(Arc::new(generator)));
+        }
+        if config.reset_counters {
+            counters().iter().for_each(|c| c.store(0, Ordering::Relaxed));
+            SEQ_24_RESET_AT.store(0, Ordering::Relaxed);
+        }
+        if let Some(seed) = config.deterministic {
+            enable_deterministic(seed);
+        }
+        // Sequence exhaustion is counted from the first ID of a tick, and tests often
+        // reuse the same fake times, so start every guarded test on a fresh tick.
+        for tick in [&TICK_START_32, &TICK_START_64, &TICK_START_128] {
+            tick.ts.store(u64::MAX, Ordering::Relaxed);
+        }
+        guard
+    }
+}
+
+impl Drop for TestGuard {
+    fn drop(&mut self) {
+        CUSTOM_EPOCH.store(self.epoch, Ordering::Relaxed);
+        // Frees this guard's generator, unless an ID is being generated with it.
+        set_override(self.generator.0.take());
+        GLOBAL.store(self.generator.1, Ordering::Release);
+        #[cfg(feature = "reconfigure")]
+        crate::RECONFIGURED.store(self.reconfigured.take());
+        for (counter, value) in counters().iter().zip(self.counters) {
+            counter.store(value, Ordering::Relaxed);
+        }
+        LAYOUT_64.store(self.layout64.0, Ordering::Relaxed);
+        VERSION_BITS_ENABLED.store(self.layout64.1, Ordering::Relaxed);
+        TIMESTAMP_PRECISION.store(self.layout64.2, Ordering::Relaxed);
+        REGION_ID.store(self.layout64.3, Ordering::Relaxed);
+        LAYOUT_24.store(self.x24.0, Ordering::Relaxed);
+        OVERFLOW_POLICY_24.store(self.x24.1, Ordering::Relaxed);
+        SEQ_24_RESET_AT.store(self.x24.2, Ordering::Relaxed);
+        LAYOUT_32.store(self.layout32, Ordering::Relaxed);
+        THREAD_ID_BITS.store(self.thread_ids.0, Ordering::Relaxed);
+        THREAD_ID_MODE.store(self.thread_ids.1, Ordering::Relaxed);
+        THREAD_ID_SALT.store(self.thread_ids.2, Ordering::Relaxed);
+        WRAP_THRESHOLD_PERCENT.store(self.wrap_threshold, Ordering::Relaxed);
+        OVERFLOW_MODE.store(self.overflow_mode, Ordering::Relaxed);
+        MAX_RATE_64.store(self.max_rate, Ordering::Relaxed);
+        RATE_WINDOW_64.store(0, Ordering::Relaxed);
+        LEASE_WINDOW_64.store(0, Ordering::Relaxed);
+        CLOCK_MODE.store(self.clock.0, Ordering::Relaxed);
+        CLOCK_REANCHOR_NS.store(self.clock.1, Ordering::Relaxed);
+        reset_clock_anchor();
+        #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+        crate::persist::reset();
+        for (slot, value) in callbacks().iter().zip(self.callbacks) {
+            slot.store(value, Ordering::Release);
+        }
+        let (enabled, clock, prng) = self.deterministic;
+        LOGICAL_CLOCK.store(clock, Ordering::Relaxed);
+        PRNG_STATE.store(prng, Ordering::Relaxed);
+        THREAD_ID_GENERATION.fetch_add(1, Ordering::Relaxed);
+        DETERMINISTIC.store(enabled, Ordering::Release);
+    }
+}
+
+/// Reset all global state to its initial values.
+///
+/// Zeroes the sequence counters, restores and unfreezes the default epoch, restores
+/// the global generator (unset, so [`AtomicOption::init_global`] works again), the layouts, region, version bits, and 24-bit overflow policy, thread ID settings and assignments, wrap threshold, overflow mode, rate limit, and clock mode, stops persisting sequences, clears the statistics, removes
+/// any time source, thread entropy provider, or generation hooks, and leaves
+/// deterministic mode. This makes previously generated IDs repeatable, so only call it
+/// from tests, ideally while holding a [`TestGuard`].
+pub fn reset_all() {
+    counters().iter().for_each(|c| c.store(0, Ordering::Relaxed));
+    STATS_BASE.iter().for_each(|c| c.store(0, Ordering::Relaxed));
+    CUSTOM_EPOCH.store(DEFAULT_EPOCH, Ordering::Relaxed);
+    GLOBAL.store(std::ptr::null_mut(), Ordering::Release);
+    #[cfg(feature = "reconfigure")]
+    crate::RECONFIGURED.store(None);
+    LAYOUT_64.store(Layout64::Standard as u8, Ordering::Relaxed);
+    VERSION_BITS_ENABLED.store(false, Ordering::Relaxed);
+    TIMESTAMP_PRECISION.store(TimestampPrecision::Millis as u8, Ordering::Relaxed);
+    REGION_ID.store(0, Ordering::Relaxed);
+    LAYOUT_24.store(Layout24::Standard as u8, Ordering::Relaxed);
+    LAYOUT_32.store(Layout32::Standard as u8, Ordering::Relaxed);
+    OVERFLOW_POLICY_24.store(OverflowPolicy::Wrap as u8, Ordering::Relaxed);
+    SEQ_24_RESET_AT.store(0, Ordering::Relaxed);
+    THREAD_ID_BITS.store(DEFAULT_THREAD_ID_BITS, Ordering::Relaxed);
+    THREAD_ID_MODE.store(ThreadIdMode::Hashed as u8, Ordering::Relaxed);
+    THREAD_ID_SALT.store(0, Ordering::Relaxed);
+    AtomicOption::__reset_thread_ids();
+    WRAP_THRESHOLD_PERCENT.store(DEFAULT_WRAP_THRESHOLD_PERCENT, Ordering::Relaxed);
+    OVERFLOW_MODE.store(OverflowMode::Wrap as u8, Ordering::Relaxed);
+    MAX_RATE_64.store(0, Ordering::Relaxed);
+    RATE_WINDOW_64.store(0, Ordering::Relaxed);
+    LEASE_WINDOW_64.store(0, Ordering::Relaxed);
+    CLOCK_MODE.store(ClockMode::Wall as u8, Ordering::Relaxed);
+    CLOCK_REANCHOR_NS.store(0, Ordering::Relaxed);
+    reset_clock_anchor();
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    crate::persist::reset();
+    WRAP_WARNED_PERIOD_64.store(u64::MAX, Ordering::Relaxed);
+    callbacks().iter().for_each(|c| c.store(std::ptr::null_mut(), Ordering::Release));
+    LAST_TIMESTAMP.store(0, Ordering::Relaxed);
+    CLOCK_ROLLBACKS.store(0, Ordering::Relaxed);
+    for tick in [&TICK_START_32, &TICK_START_64, &TICK_START_128] {
+        tick.ts.store(u64::MAX, Ordering::Relaxed);
+    }
+    DETERMINISTIC.store(false, Ordering::Release);
+}
@@ -0,0 +1,199 @@
+//! [tower](https://docs.rs/tower) middleware that tags every HTTP request with an ID.
+//!
+//! [`RequestIdLayer`] reads the `x-request-id` header of each request. If it holds an
+//! ID that [`Id64`]'s parser accepts, that ID is kept, so a request keeps its ID across
+//! services; otherwise, when the header is missing or malformed, a new [`Id64`] is
+//! generated. The ID is stored in the request extensions as a [`RequestId`], the
+//! request header is set to its canonical base36 form for the inner service, and the
+//! response carries the same header back to the caller.
+//!
+//! ```
+//! use atomic_id::tower::{RequestId, RequestIdLayer};
+//! use http::{Request, Response};
+//! use tower::ServiceBuilder;
+//!
+//! let service = ServiceBuilder::new()
+//!     .layer(RequestIdLayer::new())
+//!     .service_fn(|req: Request<()>| async move {
+//!         let id = req.extensions().get::<RequestId>().copied();
+//!         // e.g. tracing::info_span!("request", request_id = %id.unwrap())
+//!         Ok::<_, std::convert::Infallible>(Response::new(id.unwrap().to_string()))
+//!     });
+//! ```
+
+use crate::Id64;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, ready};
+use http::{HeaderName, HeaderValue, Request, Response};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The header read and written by [`RequestIdLayer`].
+pub const HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The ID of the current request, stored in the request extensions by
+/// [`RequestIdService`].
+///
+/// Displays as the base36 form of the [`Id64`], the same text as the header, so it
+/// can be recorded directly as a `tracing` field with `%request_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestId(pub Id64);
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl RequestId {
+    /// Take the ID from `header`, or generate a new one if it is absent or does not
+    /// parse as an [`Id64`].
+    fn from_header(header: Option<&HeaderValue>) -> RequestId {
+        let parsed = header.and_then(|v| v.to_str().ok()).and_then(|s| s.parse().ok());
+        // `Id64::default()` is a new ID.
+        RequestId(parsed.unwrap_or_default())
+    }
+
+    /// The ID as a header value.
+    fn header_value(&self) -> HeaderValue {
+        HeaderValue::try_from(self.to_string()).expect("base36 is a valid header value")
+    }
+}
+
+/// A [`Layer`] that wraps services in a [`RequestIdService`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestIdLayer;
+
+impl RequestIdLayer {
+    /// Create the layer.
+    pub fn new() -> RequestIdLayer {
+        RequestIdLayer
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> RequestIdService<S> {
+        RequestIdService { inner }
+    }
+}
+
+/// Middleware that assigns each request a [`RequestId`]; see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> ResponseFuture<S::Future> {
+        let id = RequestId::from_header(req.headers().get(&HEADER));
+        let value = id.header_value();
+        req.headers_mut().insert(HEADER, value.clone());
+        req.extensions_mut().insert(id);
+        ResponseFuture { inner: self.inner.call(req), value: Some(value) }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// The response future of [`RequestIdService`], which sets the `x-request-id`
+    /// header of the response.
+    pub struct ResponseFuture<F> {
+        #[pin]
+        inner: F,
+        value: Option<HeaderValue>,
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = Result<Response<ResBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.inner.poll(cx))?;
+        if let Some(value) = this.value.take() {
+            res.headers_mut().insert(HEADER, value);
+        }
+        Poll::Ready(Ok(res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use core::task::Waker;
+    use tower::{ServiceBuilder, ServiceExt};
+
+    /// Run a future that completes without waiting, as the dummy service's does.
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut cx = Context::from_waker(Waker::noop());
+        match core::pin::pin!(fut).poll(&mut cx) {
+            Poll::Ready(out) => out,
+            Poll::Pending => panic!("future not ready"),
+        }
+    }
+
+    /// Send a request with the given `x-request-id` header, returning the header and
+    /// extension seen by the inner service and the header of the response.
+    fn roundtrip(header: Option<&str>) -> (String, RequestId, String) {
+        let service = ServiceBuilder::new().layer(RequestIdLayer::new()).service_fn(
+            |req: Request<()>| async move {
+                let seen = req.headers()[HEADER].to_str().unwrap().to_string();
+                let id = *req.extensions().get::<RequestId>().unwrap();
+                Ok::<_, Infallible>(Response::new((seen, id)))
+            },
+        );
+        let mut req = Request::builder();
+        if let Some(header) = header {
+            req = req.header(HEADER, header);
+        }
+        let res = block_on(service.oneshot(req.body(()).unwrap())).unwrap();
+        let echoed = res.headers()[HEADER].to_str().unwrap().to_string();
+        let (seen, id) = res.into_body();
+        (seen, id, echoed)
+    }
+
+    /// Test that requests without a valid ID get a new one, and that valid incoming
+    /// IDs are passed through to the inner service and echoed in the response.
+    #[test]
+    fn test_request_id_layer() {
+        let (seen, id, echoed) = roundtrip(None);
+        assert_eq!(seen.parse::<Id64>().unwrap(), id.0);
+        assert_eq!(echoed, id.to_string());
+        assert_ne!(roundtrip(None).1, id);
+
+        let incoming = Id64::new();
+        let (seen, id, echoed) = roundtrip(Some(&incoming.to_string()));
+        assert_eq!(id, RequestId(incoming));
+        assert_eq!(seen, incoming.to_string());
+        assert_eq!(echoed, incoming.to_string());
+        // The hex form is accepted too, and passed on in base36.
+        let (_, id, echoed) = roundtrip(Some(&format!("{:016x}", incoming.0)));
+        assert_eq!((id, echoed), (RequestId(incoming), incoming.to_string()));
+
+        for malformed in ["", "not-an-id", "0000000000000000000", "zzzzzzzzzzzzz", "ABCDEFGHIJKLM"] {
+            let (seen, id, echoed) = roundtrip(Some(malformed));
+            assert_ne!(id, RequestId(incoming), "{:?}", malformed);
+            assert_ne!(seen, malformed);
+            assert_eq!(seen, id.to_string());
+            assert_eq!(echoed, id.to_string());
+        }
+    }
+}
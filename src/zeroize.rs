@@ -0,0 +1,67 @@
+//! [zeroize](https://docs.rs/zeroize) support, for IDs used as secrets such as API
+//! tokens.
+//!
+//! [`Id64`], [`Id128`], and [`Id256`] implement `Zeroize`, which overwrites the ID
+//! with zeros. The typed IDs are `Copy`, so they cannot implement `ZeroizeOnDrop`,
+//! and every copy made along the way is a separate value that is not wiped. Keep
+//! secret IDs in `Zeroizing<Id256>` (which is `ZeroizeOnDrop`), pass them by
+//! reference, and compare them with [`Id256::ct_eq`].
+//!
+//! With this feature the encoders and decoders also wipe their scratch buffers
+//! before returning. The strings they return belong to the caller; hold those in
+//! `Zeroizing<String>` as well.
+//!
+//! ```
+//! use atomic_id::Id256;
+//! use zeroize::Zeroizing;
+//!
+//! let token = Zeroizing::new(Id256::new());
+//! let presented = Id256::from_grouped_hex(&token.to_grouped_hex()).unwrap();
+//! assert!(token.ct_eq(&presented));
+//! ```
+
+use crate::{Id64, Id128, Id256};
+use zeroize::Zeroize;
+
+impl Zeroize for Id64 {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Zeroize for Id128 {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Zeroize for Id256 {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zeroize::Zeroizing;
+
+    /// Test that zeroizing clears every part, also through `Zeroizing`.
+    #[test]
+    fn test_zeroize() {
+        let mut id = Id256([1, 2, 3, u64::MAX]);
+        id.zeroize();
+        assert_eq!(id, Id256([0; 4]));
+        let mut id = Id128(u128::MAX);
+        id.zeroize();
+        assert_eq!(id, Id128(0));
+        let mut id = Id64(u64::MAX);
+        id.zeroize();
+        assert_eq!(id, Id64(0));
+
+        let mut token = Zeroizing::new(Id256([7; 4]));
+        assert!(token.ct_eq(&Id256([7; 4])));
+        token.zeroize();
+        assert_eq!(*token, Id256([0; 4]));
+    }
+}
@@ -0,0 +1,102 @@
+//! Calls the `ffi` functions through their C ABI declarations, as C code would.
+
+#![cfg(feature = "ffi")]
+// Without `testing`, `guard()` returns `()` but the tests still bind it.
+#![cfg_attr(not(feature = "testing"), allow(clippy::let_unit_value))]
+
+#[cfg(all(feature = "testing", not(feature = "const_epoch")))]
+use atomic_id::ffi::ATOMIC_ID_ERR_EPOCH_FROZEN;
+use atomic_id::ffi::{
+    ATOMIC_ID_ERR_BUFFER_TOO_SMALL, ATOMIC_ID_ERR_NULL, ATOMIC_ID_ERR_UNSUPPORTED, ATOMIC_ID_ERR_WIDTH,
+    ATOMIC_ID_OK,
+};
+use atomic_id::{AtomicId, Encoding, IdGenerator, Width, x128};
+use std::ffi::{CStr, c_char};
+
+unsafe extern "C" {
+    fn atomic_id_new64() -> u64;
+    fn atomic_id_new128(out: *mut u8) -> i32;
+    fn atomic_id_generate(bits: usize, out: *mut c_char, cap: usize) -> i32;
+    fn atomic_id_encode_base36(value: u64, out: *mut c_char, cap: usize) -> i32;
+    #[cfg(all(feature = "testing", not(feature = "const_epoch")))]
+    fn atomic_id_set_epoch(ms: u64) -> i32;
+}
+
+/// Serialize ID generation against `set_epoch_through_c_abi`, which needs the epoch
+/// to stay unfrozen until it generates an ID itself.
+#[cfg(feature = "testing")]
+fn guard() -> atomic_id::testing::TestGuard {
+    atomic_id::testing::TestGuard::scoped(Default::default())
+}
+#[cfg(not(feature = "testing"))]
+fn guard() {}
+
+/// Test that 64-bit IDs from C are unique and in the standard layout.
+#[test]
+fn new64_through_c_abi() {
+    let _guard = guard();
+    let a = unsafe { atomic_id_new64() };
+    let b = unsafe { atomic_id_new64() };
+    assert_ne!(a, b);
+    assert_eq!(IdGenerator::decompose64(a).node_id, 1);
+}
+
+/// Test that 128-bit IDs are written as 16 big-endian bytes.
+#[test]
+fn new128_through_c_abi() {
+    let _guard = guard();
+    let mut out = [0u8; 16];
+    assert_eq!(unsafe { atomic_id_new128(out.as_mut_ptr()) }, ATOMIC_ID_OK);
+    assert_ne!(out, [0; 16]);
+    assert_eq!(AtomicId::<x128>::from_uuid_bytes(out).to_be_bytes(), out);
+    assert_eq!(unsafe { atomic_id_new128(std::ptr::null_mut()) }, ATOMIC_ID_ERR_NULL);
+}
+
+/// Test that base36 encoding matches the Rust API and checks the buffer size.
+#[test]
+fn encode_base36_through_c_abi() {
+    let mut out = [0 as c_char; 14];
+    let len = unsafe { atomic_id_encode_base36(u64::MAX, out.as_mut_ptr(), out.len()) };
+    assert_eq!(len, 13);
+    let encoded = unsafe { CStr::from_ptr(out.as_ptr()) };
+    assert_eq!(encoded.to_str().unwrap(), "3w5e11264sgsf");
+
+    let len = unsafe { atomic_id_encode_base36(1, out.as_mut_ptr(), 13) };
+    assert_eq!(len, ATOMIC_ID_ERR_BUFFER_TOO_SMALL);
+    let len = unsafe { atomic_id_encode_base36(1, std::ptr::null_mut(), 14) };
+    assert_eq!(len, ATOMIC_ID_ERR_NULL);
+}
+
+/// Test that IDs of a runtime width have the base36 length of that width.
+#[test]
+fn generate_through_c_abi() {
+    let _guard = guard();
+    let mut out = [0 as c_char; 64];
+    for width in Width::ALL {
+        let len = unsafe { atomic_id_generate(width.bits(), out.as_mut_ptr(), out.len()) };
+        if width.is_enabled() {
+            assert_eq!(Ok(len as usize), Encoding::Base36.encoded_len(width.bits()));
+        } else {
+            assert_eq!(len, ATOMIC_ID_ERR_UNSUPPORTED);
+        }
+    }
+    assert_eq!(unsafe { atomic_id_generate(48, out.as_mut_ptr(), out.len()) }, ATOMIC_ID_ERR_WIDTH);
+    let len = unsafe { atomic_id_generate(64, out.as_mut_ptr(), 13) };
+    assert_eq!(len, ATOMIC_ID_ERR_BUFFER_TOO_SMALL);
+    let len = unsafe { atomic_id_generate(64, std::ptr::null_mut(), 14) };
+    assert_eq!(len, ATOMIC_ID_ERR_NULL);
+}
+
+/// Test that the epoch set from C is the one the Rust API sees, until it is frozen.
+///
+/// The guard starts with an unfrozen epoch, even if other tests generated IDs.
+#[test]
+#[cfg(all(feature = "testing", not(feature = "const_epoch")))]
+fn set_epoch_through_c_abi() {
+    let _guard = guard();
+    assert_eq!(unsafe { atomic_id_set_epoch(1704067200000) }, ATOMIC_ID_OK);
+    assert_eq!(atomic_id::AtomicOption::get_epoch(), 1704067200000);
+    unsafe { atomic_id_new64() };
+    assert_eq!(unsafe { atomic_id_set_epoch(1) }, ATOMIC_ID_ERR_EPOCH_FROZEN);
+    assert_eq!(atomic_id::AtomicOption::get_epoch(), 1704067200000);
+}
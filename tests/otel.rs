@@ -0,0 +1,27 @@
+//! Converts typed IDs and trace context IDs into OpenTelemetry IDs.
+
+#![cfg(feature = "otel")]
+
+use atomic_id::{Id64, Id128, span_id, trace_id};
+use opentelemetry::trace::{SpanId, TraceId};
+
+/// Test that generated IDs never convert to the invalid all-zero IDs.
+#[test]
+fn never_invalid() {
+    for _ in 0..10_000 {
+        assert_ne!(TraceId::from(Id128::new()), TraceId::INVALID);
+        assert_ne!(SpanId::from(Id64::new()), SpanId::INVALID);
+        assert_ne!(TraceId::from_bytes(trace_id()), TraceId::INVALID);
+        assert_ne!(SpanId::from_bytes(span_id()), SpanId::INVALID);
+    }
+}
+
+/// Test that conversions keep the raw bits, so the hex forms match.
+#[test]
+fn keeps_raw_bits() {
+    let id = Id128::new();
+    assert_eq!(TraceId::from(id).to_string(), format!("{:032x}", id.0));
+    let id = Id64::new();
+    assert_eq!(SpanId::from(id).to_string(), format!("{:016x}", id.0));
+    assert_eq!(TraceId::from(Id128(0)), TraceId::INVALID);
+}
@@ -0,0 +1,69 @@
+//! Properties of the proptest strategies for typed IDs.
+
+#![cfg(feature = "proptest")]
+
+use atomic_id::proptest::{IdParams, any_id64_in, id64_for_node, id128_with};
+use atomic_id::{Id64, Id128, IdGenerator};
+use proptest::prelude::*;
+use proptest::test_runner::{TestError, TestRunner};
+
+proptest! {
+    /// Test that generated IDs decompose into fields within range.
+    #[test]
+    fn fields_in_range(id in any::<Id64>()) {
+        let parts = IdGenerator::decompose64(id.0);
+        prop_assert!(parts.timestamp < 1 << 20);
+        prop_assert!(parts.node_id < 1 << 12);
+        prop_assert!(parts.thread_id > 0);
+    }
+
+    /// Test that timestamps stay inside the requested window.
+    #[test]
+    fn time_window(id in any_id64_in(1000..2000)) {
+        let ts = IdGenerator::decompose64(id.0).timestamp;
+        prop_assert!((1000..2000).contains(&ts));
+    }
+
+    /// Test that the node strategy fixes the node and nothing else.
+    #[test]
+    fn fixed_node(id in id64_for_node(4095)) {
+        prop_assert_eq!(IdGenerator::decompose64(id.0).node_id, 4095);
+    }
+
+    /// Test 128-bit IDs against their high-word layout.
+    #[test]
+    fn id128_fields(id in id128_with(IdParams { time_ms: 7..8, node_id: Some(9) })) {
+        let high = (id.0 >> 64) as u64;
+        prop_assert_eq!(high >> 32, 7);
+        prop_assert_eq!((high >> 20) & 0xFFF, 9);
+        prop_assert!((high >> 4) & 0xFF > 0);
+    }
+}
+
+/// Test that failures shrink to the smallest timestamp and fields.
+#[test]
+fn shrinks_toward_small_values() {
+    let mut runner = TestRunner::default();
+    let result = runner.run(&any_id64_in(0..1 << 20), |id| {
+        prop_assert!(IdGenerator::decompose64(id.0).timestamp < 5000);
+        Ok(())
+    });
+    let Err(TestError::Fail(_, id)) = result else {
+        panic!("expected a failure, got {:?}", result);
+    };
+    let parts = IdGenerator::decompose64(id.0);
+    assert_eq!(
+        (parts.timestamp, parts.node_id, parts.shard_id, parts.thread_id, parts.sequence),
+        (5000, 0, 0, 1, 0)
+    );
+
+    let mut runner = TestRunner::default();
+    let result = runner.run(&any::<Id128>(), |id| {
+        prop_assert!(id.0 >> 96 < 100);
+        Ok(())
+    });
+    let Err(TestError::Fail(_, id)) = result else {
+        panic!("expected a failure, got {:?}", result);
+    };
+    assert_eq!(id.0 >> 96, 100);
+}
@@ -0,0 +1,60 @@
+//! Converts typed IDs to and from redis-rs values, without a server.
+
+#![cfg(feature = "redis")]
+
+use atomic_id::redis::RawId64;
+use atomic_id::{AtomicId, Encoding, Id64, Id128, Id256};
+use redis::{FromRedisValue, ToRedisArgs, Value};
+
+fn bulk(s: &str) -> Value {
+    Value::BulkString(s.as_bytes().to_vec())
+}
+
+/// Test that IDs are written as their base36 text and read back from string replies.
+#[test]
+fn round_trip() {
+    let id = Id64::new();
+    assert_eq!(id.to_redis_args(), vec![id.to_string().into_bytes()]);
+    assert_eq!(Id64::from_redis_value(&bulk(&id.to_string())).unwrap(), id);
+    let simple = Value::SimpleString(id.to_string());
+    assert_eq!(Id64::from_redis_value(&simple).unwrap(), id);
+
+    let id = Id128::new();
+    assert_eq!(id.to_redis_args(), vec![id.to_string().into_bytes()]);
+    assert_eq!(Id128::from_redis_value(&bulk(&id.to_string())).unwrap(), id);
+
+    let id = Id256([1, 2, 3, u64::MAX]);
+    let args = id.to_redis_args();
+    assert_eq!(args[0].len(), 50);
+    let read = Id256::from_redis_value(&Value::BulkString(args[0].clone())).unwrap();
+    assert_eq!(read, id);
+    let legacy = AtomicId::<256>::to_words(id.0, Encoding::Base36);
+    assert_eq!(Id256::from_redis_value(&bulk(&legacy)).unwrap(), id);
+}
+
+/// Test that `RawId64` is stored as a decimal integer.
+#[test]
+fn raw_integer() {
+    let id = Id64(u64::MAX);
+    assert_eq!(RawId64(id).to_redis_args(), vec![b"18446744073709551615".to_vec()]);
+    assert_eq!(RawId64::from_redis_value(&bulk("18446744073709551615")).unwrap(), RawId64(id));
+    assert_eq!(RawId64::from_redis_value(&Value::Int(42)).unwrap(), RawId64(Id64(42)));
+    assert!(RawId64::from_redis_value(&Value::Int(-1)).is_err());
+    assert!(RawId64::from_redis_value(&bulk("0n3x8q9a7k2fc")).is_err());
+}
+
+/// Test that malformed values are rejected with errors.
+#[test]
+fn rejects_malformed() {
+    assert!(Id64::from_redis_value(&Value::Nil).is_err());
+    assert!(Id64::from_redis_value(&Value::Int(1_234_567_890_123)).is_err());
+    assert!(Id64::from_redis_value(&bulk("abc")).is_err());
+    assert!(Id64::from_redis_value(&bulk("0n3x8q9a7k2f!")).is_err());
+    assert!(Id64::from_redis_value(&bulk("zzzzzzzzzzzzz")).is_err());
+    assert!(Id64::from_redis_value(&Value::BulkString(vec![0xFF; 13])).is_err());
+    assert!(Id128::from_redis_value(&bulk(&Id64::new().to_string())).is_err());
+    assert!(Id256::from_redis_value(&bulk(&"z".repeat(50))).is_err());
+    assert!(Id256::from_redis_value(&bulk(&"z".repeat(52))).is_err());
+    assert!(Id256::from_redis_value(&bulk(&format!("{}é{}", "0".repeat(12), "0".repeat(36)))).is_err());
+    assert!(Id256::from_redis_value(&bulk(&format!("{}é{}", "0".repeat(12), "0".repeat(38)))).is_err());
+}
@@ -0,0 +1,35 @@
+//! Validates generated IDs against the JSON schemas of the typed IDs.
+
+#![cfg(feature = "schemars")]
+
+use atomic_id::{Id64, Id128, Id256};
+use regex::Regex;
+use schemars::{JsonSchema, schema_for};
+
+/// Check that `ids` all match `T`'s schema and that its fixed length is right.
+fn check<T: JsonSchema>(ids: &[String], len: u64, format: &str) {
+    let schema = schema_for!(T);
+    assert_eq!(schema.get("type").unwrap(), "string");
+    assert_eq!(schema.get("minLength").unwrap(), len);
+    assert_eq!(schema.get("maxLength").unwrap(), len);
+    assert_eq!(schema.get("format").unwrap(), format);
+
+    let pattern = Regex::new(schema.get("pattern").unwrap().as_str().unwrap()).unwrap();
+    for id in ids {
+        assert!(pattern.is_match(id), "{} does not match {}", id, pattern);
+        assert!(!pattern.is_match(&id[1..]));
+        assert!(!pattern.is_match(&format!("{}0", id)));
+        assert!(!pattern.is_match(&id.to_uppercase()));
+    }
+}
+
+/// Test that generated IDs of every typed width match their schema.
+#[test]
+fn generated_ids_match() {
+    let ids: Vec<_> = (0..1000).map(|_| Id64::new().to_string()).collect();
+    check::<Id64>(&ids, 13, "atomic-id-64");
+    let ids: Vec<_> = (0..1000).map(|_| Id128::new().to_string()).collect();
+    check::<Id128>(&ids, 25, "atomic-id-128");
+    let ids: Vec<_> = (0..1000).map(|_| Id256::new().to_string()).collect();
+    check::<Id256>(&ids, 50, "atomic-id-256");
+}
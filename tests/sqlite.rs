@@ -0,0 +1,79 @@
+//! Stores typed IDs in an in-memory SQLite database through rusqlite.
+
+#![cfg(feature = "rusqlite")]
+
+use atomic_id::{Id64, Id128, Id256};
+use rusqlite::Connection;
+use rusqlite::types::FromSqlError;
+
+fn db() -> Connection {
+    let db = Connection::open_in_memory().unwrap();
+    db.execute_batch(
+        "CREATE TABLE ids (id64 INTEGER, id128 BLOB, id256 BLOB);
+         CREATE TABLE ordered (id INTEGER PRIMARY KEY);",
+    )
+    .unwrap();
+    db
+}
+
+/// Test that every typed ID reads back exactly as written.
+#[test]
+fn round_trip() {
+    let db = db();
+    let ids = (Id64(u64::MAX), Id128::new(), Id256::new());
+    db.execute("INSERT INTO ids VALUES (?1, ?2, ?3)", (ids.0, ids.1, ids.2))
+        .unwrap();
+    let read: (Id64, Id128, Id256) = db
+        .query_row("SELECT id64, id128, id256 FROM ids", [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .unwrap();
+    assert_eq!(read, ids);
+
+    let (kind, len): (String, i64) = db
+        .query_row("SELECT typeof(id64), length(id256) FROM ids", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!((kind.as_str(), len), ("integer", 32));
+}
+
+/// Test that i64-safe IDs come back from the database in generation order.
+#[test]
+fn i64_safe_order() {
+    let db = db();
+    let generated: Vec<Id64> = (0..1000).map(|_| Id64::new_i64_safe()).collect();
+    for id in generated.iter().rev() {
+        db.execute("INSERT INTO ordered VALUES (?1)", [id]).unwrap();
+    }
+    let mut stmt = db.prepare("SELECT id FROM ordered ORDER BY id").unwrap();
+    let stored: Vec<Id64> = stmt
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let mut sorted = generated.clone();
+    sorted.sort();
+    assert_eq!(stored, sorted);
+    assert!(stored.iter().all(|id| id.to_i64() >= 0));
+}
+
+/// Test that blobs of the wrong size and wrong column types are rejected.
+#[test]
+fn rejects_bad_values() {
+    let db = db();
+    db.execute("INSERT INTO ids VALUES ('text', x'0102', ?1)", [Id128::new()])
+        .unwrap();
+    let err = db
+        .query_row("SELECT id128 FROM ids", [], |row| row.get::<_, Id128>(0))
+        .unwrap_err();
+    let rusqlite::Error::FromSqlConversionFailure(_, _, err) = err else {
+        panic!("{:?}", err);
+    };
+    assert!(matches!(
+        err.downcast_ref::<FromSqlError>(),
+        Some(FromSqlError::InvalidBlobSize { expected_size: 16, blob_size: 2 })
+    ));
+    assert!(db.query_row("SELECT id256 FROM ids", [], |row| row.get::<_, Id256>(0)).is_err());
+    assert!(db.query_row("SELECT id64 FROM ids", [], |row| row.get::<_, Id64>(0)).is_err());
+}
@@ -0,0 +1,34 @@
+//! Tests for the `wasm` feature on `wasm32-unknown-unknown`.
+//!
+//! Run with `wasm-pack test --node -- --features wasm` (or `--headless --firefox`).
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use atomic_id::{AtomicId, IdGenerator, x64, x128};
+use std::collections::HashSet;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// Test that generation works without `SystemTime` and yields unique IDs.
+#[wasm_bindgen_test]
+fn generates_unique_ids() {
+    let ids = AtomicId::<x64>::batch(1000);
+    let unique: HashSet<_> = ids.iter().collect();
+    assert_eq!(unique.len(), ids.len());
+    assert!(!AtomicId::<x128>::new().is_empty());
+    assert!(!AtomicId::<256>::hex().is_empty());
+}
+
+/// Test that raw IDs decompose back into the fields they were built from.
+#[wasm_bindgen_test]
+fn round_trips_ids() {
+    let generator = IdGenerator::new(7, 3);
+    let parts = IdGenerator::decompose64(generator.gen64());
+    assert_eq!(parts.node_id, 7);
+    assert_eq!(parts.shard_id, 3);
+    // The browser is single-threaded, so every ID carries thread ID 1.
+    assert_eq!(parts.thread_id, 1);
+    assert!(parts.timestamp > 0);
+
+    let bytes = AtomicId::<x128>::uuid_bytes();
+    assert_eq!(AtomicId::<x128>::from_uuid_bytes(bytes).to_be_bytes(), bytes);
+}